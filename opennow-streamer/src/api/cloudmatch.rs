@@ -224,11 +224,8 @@ impl GfnApiClient {
                 session_error.unified_error_code
             );
 
-            return Err(anyhow::anyhow!(
-                "{}: {}",
-                session_error.title,
-                session_error.description
-            ));
+            // Displays as "title: description"; callers downcast it for recovery
+            return Err(session_error.into());
         }
 
         let api_response: CloudMatchResponse =
@@ -245,11 +242,7 @@ impl GfnApiClient {
                 api_response.request_status.unified_error_code
             );
 
-            return Err(anyhow::anyhow!(
-                "{}: {}",
-                session_error.title,
-                session_error.description
-            ));
+            return Err(session_error.into());
         }
 
         let session_data = api_response.session;
@@ -258,43 +251,6 @@ impl GfnApiClient {
             session_data.session_id, session_data.status
         );
 
-        // Determine session state
-        let state = Self::parse_session_state(&session_data);
-
-        // Extract connection info
-        let server_ip = session_data.streaming_server_ip().unwrap_or_default();
-        let signaling_path = session_data.signaling_url();
-
-        // Build full signaling URL
-        let signaling_url = signaling_path
-            .map(|path| {
-                if path.starts_with("wss://") || path.starts_with("rtsps://") {
-                    // Already a full URL
-                    Self::build_signaling_url(&path, &server_ip)
-                } else if path.starts_with('/') {
-                    // Path like /nvst/
-                    format!("wss://{}:443{}", server_ip, path)
-                } else {
-                    format!("wss://{}:443/nvst/", server_ip)
-                }
-            })
-            .or_else(|| {
-                if !server_ip.is_empty() {
-                    Some(format!("wss://{}:443/nvst/", server_ip))
-                } else {
-                    None
-                }
-            });
-
-        info!(
-            "Stream server: {}, signaling: {:?}",
-            server_ip, signaling_url
-        );
-
-        // Extract ICE servers and media info before moving other fields
-        let ice_servers = session_data.ice_servers();
-        let media_connection_info = session_data.media_connection_info();
-
         // Debug: log connection info
         if let Some(ref conns) = session_data.connection_info {
             for conn in conns {
@@ -306,77 +262,18 @@ impl GfnApiClient {
         } else {
             info!("No connection_info in session response");
         }
-        info!("Media connection info: {:?}", media_connection_info);
-
-        // Extract ads info if present
-        let ads_required = session_data.session_ads_required;
-        let ads_info = session_data.session_ads.as_ref().map(|ads| SessionAdsInfo {
-            video_url: ads
-                .get("videoUrl")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            duration_secs: ads.get("duration").and_then(|v| v.as_u64()).unwrap_or(120) as u32,
-            completion_url: ads
-                .get("completionUrl")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            raw_config: Some(ads.clone()),
-        });
-
-        // If ads are required and we're in queue, switch to WatchingAds state
-        let final_state = if ads_required {
+        if session_data.session_ads_required {
             info!("Session requires ads (free tier user)");
-            match state {
-                SessionState::InQueue { .. } | SessionState::Launching => {
-                    let duration = ads_info.as_ref().map(|a| a.duration_secs).unwrap_or(120);
-                    SessionState::WatchingAds {
-                        remaining_secs: duration,
-                        total_secs: duration,
-                    }
-                }
-                other => other,
-            }
-        } else {
-            state
-        };
-
-        Ok(SessionInfo {
-            session_id: session_data.session_id,
-            server_ip,
-            zone: zone.to_string(),
-            state: final_state,
-            gpu_type: session_data.gpu_type,
-            signaling_url,
-            ice_servers,
-            media_connection_info,
-            ads_required,
-            ads_info,
-        })
-    }
-
-    /// Build signaling WebSocket URL from raw path/URL
-    fn build_signaling_url(raw: &str, server_ip: &str) -> String {
-        if raw.starts_with("rtsps://") || raw.starts_with("rtsp://") {
-            // Extract hostname from RTSP URL
-            let host = raw
-                .strip_prefix("rtsps://")
-                .or_else(|| raw.strip_prefix("rtsp://"))
-                .and_then(|s| s.split(':').next())
-                .filter(|h| !h.is_empty() && !h.starts_with('.'));
-
-            if let Some(h) = host {
-                format!("wss://{}/nvst/", h)
-            } else {
-                // Malformed URL, use server IP
-                format!("wss://{}:443/nvst/", server_ip)
-            }
-        } else if raw.starts_with("wss://") {
-            raw.to_string()
-        } else if raw.starts_with('/') {
-            format!("wss://{}:443{}", server_ip, raw)
-        } else {
-            format!("wss://{}:443/nvst/", server_ip)
         }
+
+        let session =
+            session_data.into_session_info(zone, api_response.request_status.server_id, None);
+        info!(
+            "Stream server: {}, signaling: {:?}",
+            session.server_ip, session.signaling_url
+        );
+        info!("Media connection info: {:?}", session.media_connection_info);
+        Ok(session)
     }
 
     /// Poll session status until ready
@@ -450,33 +347,12 @@ impl GfnApiClient {
                 poll_response.request_status.status_code,
                 poll_response.request_status.unified_error_code
             );
-            return Err(anyhow::anyhow!(
-                "Poll failed: {} - {}",
-                session_error.title,
-                session_error.description
-            ));
+            // CloudMatch ended the session (e.g. removed from the queue for
+            // maintenance); callers downcast it for recovery
+            return Err(session_error.into());
         }
 
         let session_data = poll_response.session;
-        let state = Self::parse_session_state(&session_data);
-
-        let server_ip = session_data.streaming_server_ip().unwrap_or_default();
-        let signaling_path = session_data.signaling_url();
-
-        // Build full signaling URL
-        let signaling_url = signaling_path
-            .map(|path| Self::build_signaling_url(&path, &server_ip))
-            .or_else(|| {
-                if !server_ip.is_empty() {
-                    Some(format!("wss://{}:443/nvst/", server_ip))
-                } else {
-                    None
-                }
-            });
-
-        // Extract ICE servers and media info before moving other fields
-        let ice_servers = session_data.ice_servers();
-        let media_connection_info = session_data.media_connection_info();
 
         // Debug: log connection info in poll response
         if let Some(ref conns) = session_data.connection_info {
@@ -487,53 +363,16 @@ impl GfnApiClient {
                 );
             }
         }
-        if media_connection_info.is_some() {
-            info!("Poll media connection info: {:?}", media_connection_info);
-        }
 
-        // Extract ads info if present
-        let ads_required = session_data.session_ads_required;
-        let ads_info = session_data.session_ads.as_ref().map(|ads| SessionAdsInfo {
-            video_url: ads
-                .get("videoUrl")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            duration_secs: ads.get("duration").and_then(|v| v.as_u64()).unwrap_or(120) as u32,
-            completion_url: ads
-                .get("completionUrl")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            raw_config: Some(ads.clone()),
-        });
-
-        // If ads are required and we're in queue, switch to WatchingAds state
-        let final_state = if ads_required {
-            match state {
-                SessionState::InQueue { .. } | SessionState::Launching => {
-                    let duration = ads_info.as_ref().map(|a| a.duration_secs).unwrap_or(120);
-                    SessionState::WatchingAds {
-                        remaining_secs: duration,
-                        total_secs: duration,
-                    }
-                }
-                other => other,
-            }
-        } else {
-            state
-        };
-
-        Ok(SessionInfo {
-            session_id: session_data.session_id,
-            server_ip,
-            zone: zone.to_string(),
-            state: final_state,
-            gpu_type: session_data.gpu_type,
-            signaling_url,
-            ice_servers,
-            media_connection_info,
-            ads_required,
-            ads_info,
-        })
+        let session =
+            session_data.into_session_info(zone, poll_response.request_status.server_id, None);
+        if session.media_connection_info.is_some() {
+            info!(
+                "Poll media connection info: {:?}",
+                session.media_connection_info
+            );
+        }
+        Ok(session)
     }
 
     /// Stop a streaming session
@@ -585,56 +424,6 @@ impl GfnApiClient {
         Ok(())
     }
 
-    /// Parse session state from CloudMatch response
-    fn parse_session_state(session_data: &CloudMatchSession) -> SessionState {
-        // Status 2 = ready for streaming
-        if session_data.status == 2 {
-            return SessionState::Ready;
-        }
-
-        // Status 3 = already streaming
-        if session_data.status == 3 {
-            return SessionState::Streaming;
-        }
-
-        // Check seat setup info for detailed states
-        if let Some(ref seat_info) = session_data.seat_setup_info {
-            match seat_info.seat_setup_step {
-                0 => return SessionState::Connecting,
-                1 => {
-                    // In queue - show position
-                    return SessionState::InQueue {
-                        position: seat_info.queue_position.max(0) as u32,
-                        eta_secs: (seat_info.seat_setup_eta / 1000).max(0) as u32,
-                    };
-                }
-                5 => return SessionState::CleaningUp,
-                6 => return SessionState::WaitingForStorage,
-                _ => {
-                    // Other steps = general launching/configuring
-                    if seat_info.seat_setup_step > 0 {
-                        return SessionState::Launching;
-                    }
-                }
-            }
-        }
-
-        // Status 1 = setting up
-        if session_data.status == 1 {
-            return SessionState::Launching;
-        }
-
-        // Error states
-        if session_data.status <= 0 || session_data.error_code != 0 {
-            return SessionState::Error(format!(
-                "Error code: {} (status: {})",
-                session_data.error_code, session_data.status
-            ));
-        }
-
-        SessionState::Launching
-    }
-
     /// Get active sessions
     /// Returns list of sessions with status 2 (Ready) or 3 (Streaming)
     pub async fn get_active_sessions(&self) -> Result<Vec<ActiveSessionInfo>> {
@@ -984,64 +773,12 @@ impl GfnApiClient {
             if session_data.status == 2 || session_data.status == 3 {
                 info!("Session ready after claim! Status: {}", session_data.status);
 
-                let state = Self::parse_session_state(&session_data);
-                let server_ip_final = session_data
-                    .streaming_server_ip()
-                    .unwrap_or_else(|| server_ip.to_string());
-                let signaling_path = session_data.signaling_url();
-
-                let signaling_url = signaling_path
-                    .map(|path| Self::build_signaling_url(&path, &server_ip_final))
-                    .or_else(|| Some(format!("wss://{}:443/nvst/", server_ip_final)));
-
-                let ice_servers = session_data.ice_servers();
-                let media_connection_info = session_data.media_connection_info();
-
-                // Extract ads info if present
-                let ads_required = session_data.session_ads_required;
-                let ads_info = session_data.session_ads.as_ref().map(|ads| SessionAdsInfo {
-                    video_url: ads
-                        .get("videoUrl")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    duration_secs: ads.get("duration").and_then(|v| v.as_u64()).unwrap_or(120)
-                        as u32,
-                    completion_url: ads
-                        .get("completionUrl")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    raw_config: Some(ads.clone()),
-                });
-
-                // If ads are required and we're in queue, switch to WatchingAds state
-                let final_state = if ads_required {
-                    match state {
-                        SessionState::InQueue { .. } | SessionState::Launching => {
-                            let duration =
-                                ads_info.as_ref().map(|a| a.duration_secs).unwrap_or(120);
-                            SessionState::WatchingAds {
-                                remaining_secs: duration,
-                                total_secs: duration,
-                            }
-                        }
-                        other => other,
-                    }
-                } else {
-                    state
-                };
-
-                return Ok(SessionInfo {
-                    session_id: session_data.session_id,
-                    server_ip: server_ip_final,
-                    zone: String::new(),
-                    state: final_state,
-                    gpu_type: session_data.gpu_type,
-                    signaling_url,
-                    ice_servers,
-                    media_connection_info,
-                    ads_required,
-                    ads_info,
-                });
+                // Claim responses don't carry the zone
+                return Ok(session_data.into_session_info(
+                    "",
+                    poll_api_response.request_status.server_id,
+                    Some(server_ip),
+                ));
             }
 
             if session_data.status != 6 {
@@ -4,7 +4,9 @@
 //! These provide user-friendly error messages for session failures.
 
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// GFN Session Error Codes from official client
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -545,8 +547,25 @@ static ERROR_MESSAGES: Lazy<HashMap<i64, (&'static str, &'static str)>> = Lazy::
     m
 });
 
+/// What kind of failure an error is, which decides how to recover from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Temporary server or network problem, worth retrying shortly
+    Transient,
+    /// Login expired or was rejected, refresh it and relaunch
+    Auth,
+    /// Steam needs Steam Guard approval or the account linked
+    SteamLink,
+    /// Service, zone or game under maintenance, retry later
+    Maintenance,
+    /// Another session is running on the account
+    SessionConflict,
+    /// Needs the user to act (entitlement, region, ...)
+    Fatal,
+}
+
 /// Parsed error information from CloudMatch response
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionError {
     /// HTTP status code (e.g., 403)
     pub http_status: u16,
@@ -762,8 +781,97 @@ impl SessionError {
             3237093670 // InvalidAuthenticationCredentials
         ) || self.http_status == 401
     }
+
+    /// Check if Steam needs Steam Guard approval or account linking
+    pub fn needs_steam_link(&self) -> bool {
+        // SteamGuardRequired, SteamLoginRequired, SteamGuardInvalid
+        matches!(self.gfn_error_code, 3237093658..=3237093660)
+    }
+
+    /// Check if the service, zone or game is under maintenance
+    pub fn is_maintenance(&self) -> bool {
+        matches!(
+            self.gfn_error_code,
+            3237093656 | // MaintenanceStatus
+            3237093673 | // AppPatching
+            3237093688 | // AppMaintenanceStatus
+            3237093703 | // SessionRemovedFromQueueMaintenance
+            3237093704 // ZoneMaintenanceStatus
+        ) || self
+            .status_description
+            .as_ref()
+            .map(|d| d.to_uppercase().contains("MAINTENANCE"))
+            .unwrap_or(false)
+    }
+
+    /// Recovery category, most specific check first
+    pub fn category(&self) -> ErrorCategory {
+        if self.is_session_conflict() {
+            ErrorCategory::SessionConflict
+        } else if self.needs_steam_link() {
+            ErrorCategory::SteamLink
+        } else if self.is_maintenance() {
+            ErrorCategory::Maintenance
+        } else if self.needs_reauth() {
+            ErrorCategory::Auth
+        } else if self.is_retryable() {
+            ErrorCategory::Transient
+        } else {
+            ErrorCategory::Fatal
+        }
+    }
+
+    /// Error for a request that never got a CloudMatch response
+    pub fn network(detail: &str) -> Self {
+        let gfn_error_code = GfnErrorCode::NetworkError as i64;
+        let (title, description) = Self::get_error_message(gfn_error_code, &None, 0);
+        SessionError {
+            http_status: 0,
+            status_code: 0,
+            status_description: Some(detail.to_string()),
+            unified_error_code: None,
+            session_error_code: None,
+            gfn_error_code,
+            title,
+            description,
+        }
+    }
+
+    /// Error codes and server status, for bug reports
+    pub fn diagnostics(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+        format!(
+            "Error: {}\n\
+             Description: {}\n\
+             GFN error code: {} (0x{:08X})\n\
+             Unified error code: {}\n\
+             CloudMatch status: {} ({})\n\
+             Session error code: {}\n\
+             HTTP status: {}",
+            self.title,
+            self.description,
+            self.gfn_error_code,
+            self.gfn_error_code,
+            optional(
+                self.unified_error_code
+                    .map(|c| format!("{} (0x{:08X})", c, c as u32))
+            ),
+            self.status_code,
+            optional(self.status_description.clone()),
+            optional(self.session_error_code.map(|c| c.to_string())),
+            self.http_status
+        )
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.description)
+    }
 }
 
+impl std::error::Error for SessionError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -790,4 +898,51 @@ mod tests {
         assert_eq!(error.gfn_error_code, 3237093643); // 3237093632 + 11
         assert!(error.is_session_conflict());
     }
+
+    #[test]
+    fn test_recovery_categories() {
+        let error = |status: i32, description: &str| {
+            SessionError::from_response(
+                403,
+                &format!(
+                    r#"{{"requestStatus":{{"statusCode":{},"statusDescription":"{}","unifiedErrorCode":-2121088763}}}}"#,
+                    status, description
+                ),
+            )
+        };
+
+        assert_eq!(
+            error(26, "STEAM_GUARD_REQUIRED").category(),
+            ErrorCategory::SteamLink
+        );
+        assert_eq!(
+            error(27, "STEAM_LOGIN_REQUIRED").category(),
+            ErrorCategory::SteamLink
+        );
+        assert_eq!(
+            error(72, "ZONE_MAINTENANCE_STATUS").category(),
+            ErrorCategory::Maintenance
+        );
+        assert_eq!(
+            error(16, "INVALID_AUTHENTICATION_EXPIRED").category(),
+            ErrorCategory::Auth
+        );
+        assert_eq!(
+            error(85, "SESSION_REJECTED_NO_CAPACITY").category(),
+            ErrorCategory::Transient
+        );
+        assert_eq!(
+            error(19, "INVALID_APP_ID_NOT_AVAILABLE").category(),
+            ErrorCategory::Fatal
+        );
+        assert_eq!(
+            SessionError::network("connection refused").category(),
+            ErrorCategory::Transient
+        );
+
+        let diagnostics = error(72, "ZONE_MAINTENANCE_STATUS").diagnostics();
+        assert!(diagnostics.contains("GFN error code: 3237093704 (0xC0F22148)"));
+        assert!(diagnostics.contains("Unified error code: -2121088763 (0x8192C105)"));
+        assert!(diagnostics.contains("CloudMatch status: 72 (ZONE_MAINTENANCE_STATUS)"));
+    }
 }
@@ -673,7 +673,7 @@ pub async fn fetch_server_info(access_token: Option<&str>) -> Result<ServerInfo>
 
     info!("Fetching server info from: {}", url);
 
-    let client = reqwest::Client::builder()
+    let client = super::http::client_builder()
         .user_agent(GFN_USER_AGENT)
         .build()?;
 
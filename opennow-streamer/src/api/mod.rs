@@ -5,13 +5,14 @@
 mod cloudmatch;
 mod games;
 pub mod error_codes;
+pub mod http;
 pub mod queue;
 
 #[allow(unused_imports)]
 pub use cloudmatch::*;
 pub use games::*;
 pub use error_codes::SessionError;
-pub use queue::{QueueServerInfo, fetch_queue_servers, format_queue_eta, calculate_server_score, get_auto_selected_server, get_unique_regions, sort_servers};
+pub use queue::{QueueServerInfo, QueueDataProvider, queue_data_provider, format_queue_eta, calculate_server_score, get_auto_selected_server, get_unique_regions, sort_servers};
 
 use reqwest::Client;
 use parking_lot::RwLock;
@@ -230,7 +231,7 @@ pub struct GfnApiClient {
 impl GfnApiClient {
     /// Create a new API client
     pub fn new() -> Self {
-        let client = Client::builder()
+        let client = http::client_builder()
             .danger_accept_invalid_certs(true) // GFN servers may have self-signed certs
             .gzip(true)
             .build()
@@ -342,7 +343,7 @@ struct AddonAttribute {
 pub async fn fetch_subscription(token: &str, user_id: &str) -> Result<crate::app::SubscriptionInfo, String> {
     use crate::auth;
     
-    let client = Client::builder()
+    let client = http::client_builder()
         .gzip(true)
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
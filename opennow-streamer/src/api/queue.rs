@@ -1,12 +1,19 @@
-//! PrintedWaste Queue Times API
+//! Queue Times API
 //!
-//! Fetches queue time information from PrintedWaste API for GeForce NOW servers.
+//! Queue time information for GeForce NOW servers. Comes from the
+//! PrintedWaste API by default; see [`QueueDataProvider`] for the others.
 
+use futures_util::future::BoxFuture;
 use log::{info, warn, debug};
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
 
+use crate::app::config::{QueueProvider, Settings};
+use crate::app::queue_watch::{self, QueueRun};
+use crate::app::server_scoring::{ServerScore, ServerScorer};
+use crate::utils::LatencyStats;
+
 /// Server mapping data from PrintedWaste
 #[derive(Debug, Clone)]
 pub struct ServerMapping {
@@ -83,6 +90,8 @@ pub struct QueueServerInfo {
     pub display_name: String,
     pub region: String,
     pub ping_ms: Option<u32>,
+    /// Full probe result behind `ping_ms`
+    pub latency: Option<LatencyStats>,
     pub queue_position: i32,
     pub eta_seconds: Option<i64>,
     pub is_4080_server: bool,
@@ -97,14 +106,213 @@ const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 const QUEUE_API_URL: &str = "https://api.printedwaste.com/gfn/queue/";
 const MAPPING_API_URL: &str = "https://remote.printedwaste.com/config/GFN_SERVERID_TO_REGION_MAPPING";
 
+/// Local observations older than this are left out
+const LOCAL_MAX_AGE_SECS: i64 = 7 * 24 * 3600;
+
+/// Source of queue times
+pub trait QueueDataProvider: Send + Sync {
+    /// Name for logs
+    fn name(&self) -> &'static str;
+
+    /// Fetch queue info for all servers, shortest queue first
+    fn fetch_servers<'a>(
+        &'a self,
+        client: &'a Client,
+    ) -> BoxFuture<'a, Result<Vec<QueueServerInfo>, String>>;
+}
+
+/// Provider selected in settings
+pub fn queue_data_provider(settings: &Settings) -> Box<dyn QueueDataProvider> {
+    match settings.queue_provider {
+        QueueProvider::PrintedWaste => Box::new(PrintedWasteProvider::new()),
+        QueueProvider::Local => Box::new(LocalQueueProvider::new()),
+        QueueProvider::Custom => {
+            let url = settings
+                .queue_custom_url
+                .as_deref()
+                .map(str::trim)
+                .filter(|u| !u.is_empty());
+            let Some(url) = url else {
+                warn!("Custom queue provider has no URL, using local observations");
+                return Box::new(LocalQueueProvider::new());
+            };
+            let mapping_url = settings
+                .queue_custom_mapping_url
+                .as_deref()
+                .map(str::trim)
+                .filter(|u| !u.is_empty());
+            Box::new(PrintedWasteProvider::with_urls(url, mapping_url))
+        }
+    }
+}
+
+/// PrintedWaste API, or anything serving the same format
+pub struct PrintedWasteProvider {
+    queue_url: String,
+    /// Without a mapping, servers are listed by ID with no GPU filtering
+    mapping_url: Option<String>,
+}
+
+impl Default for PrintedWasteProvider {
+    fn default() -> Self {
+        Self::with_urls(QUEUE_API_URL, Some(MAPPING_API_URL))
+    }
+}
+
+impl PrintedWasteProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_urls(queue_url: &str, mapping_url: Option<&str>) -> Self {
+        Self {
+            queue_url: queue_url.to_string(),
+            mapping_url: mapping_url.map(str::to_string),
+        }
+    }
+}
+
+impl QueueDataProvider for PrintedWasteProvider {
+    fn name(&self) -> &'static str {
+        if self.queue_url == QUEUE_API_URL {
+            "PrintedWaste"
+        } else {
+            "custom queue API"
+        }
+    }
+
+    fn fetch_servers<'a>(
+        &'a self,
+        client: &'a Client,
+    ) -> BoxFuture<'a, Result<Vec<QueueServerInfo>, String>> {
+        Box::pin(async move {
+            let Some(mapping_url) = &self.mapping_url else {
+                let queue = fetch_queue_data(client, &self.queue_url).await?;
+                return Ok(unmapped_servers(queue));
+            };
+
+            // Fetch both mapping and queue data
+            let (mapping_result, queue_result) = tokio::join!(
+                fetch_server_mapping(client, mapping_url),
+                fetch_queue_data(client, &self.queue_url)
+            );
+            Ok(merge_queue_data(&mapping_result?, &queue_result?))
+        })
+    }
+}
+
+/// Queue times from our own recorded queues (see `app::queue_watch`)
+#[derive(Default)]
+pub struct LocalQueueProvider {
+    /// Loaded from disk on fetch when None
+    history: Option<Vec<QueueRun>>,
+}
+
+impl LocalQueueProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_history(history: Vec<QueueRun>) -> Self {
+        Self { history: Some(history) }
+    }
+}
+
+impl QueueDataProvider for LocalQueueProvider {
+    fn name(&self) -> &'static str {
+        "local observations"
+    }
+
+    fn fetch_servers<'a>(
+        &'a self,
+        _client: &'a Client,
+    ) -> BoxFuture<'a, Result<Vec<QueueServerInfo>, String>> {
+        Box::pin(async move {
+            let loaded;
+            let history = match &self.history {
+                Some(history) => history,
+                None => {
+                    loaded = queue_watch::load_history();
+                    &loaded
+                }
+            };
+            Ok(observed_servers(history, chrono::Utc::now().timestamp()))
+        })
+    }
+}
+
+/// Latest observation per zone: the queue length when we joined, and how
+/// long it took (or an estimate from other runs if we left early)
+fn observed_servers(history: &[QueueRun], now_secs: i64) -> Vec<QueueServerInfo> {
+    let mut latest: HashMap<&str, (&QueueRun, i64)> = HashMap::new();
+    for run in history {
+        let Some(started) = chrono::DateTime::parse_from_rfc3339(&run.started_at).ok() else {
+            continue;
+        };
+        let started = started.timestamp();
+        if run.samples.is_empty() || now_secs - started > LOCAL_MAX_AGE_SECS {
+            continue;
+        }
+        match latest.get(run.zone.as_str()) {
+            Some((_, seen)) if *seen > started => {}
+            _ => {
+                latest.insert(&run.zone, (run, started));
+            }
+        }
+    }
+
+    let mut servers: Vec<QueueServerInfo> = latest
+        .into_values()
+        .map(|(run, started)| {
+            let first = run.samples[0];
+            let eta_seconds = if run.reached_seat {
+                Some(run.wait_secs.saturating_sub(first.secs) as i64)
+            } else {
+                queue_watch::estimate_wait_secs(history, &run.zone, first.position)
+                    .map(|s| s as i64)
+            };
+            QueueServerInfo {
+                server_id: run.zone.clone(),
+                display_name: zone_display_name(&run.zone),
+                region: run.zone.split('-').next().unwrap_or_default().to_uppercase(),
+                ping_ms: None,
+                latency: None,
+                queue_position: first.position as i32,
+                eta_seconds,
+                is_4080_server: false,
+                is_5080_server: false,
+                last_updated: (started + first.secs as i64) * 1000,
+            }
+        })
+        .collect();
+
+    servers.sort_by_key(|s| s.queue_position);
+    servers
+}
+
+/// "eu-germany-frankfurt-1" -> "Germany Frankfurt 1"
+fn zone_display_name(zone: &str) -> String {
+    zone.split('-')
+        .skip(1)
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Fetch server mapping from PrintedWaste
-pub async fn fetch_server_mapping(client: &Client) -> Result<HashMap<String, ServerMapping>, String> {
+pub async fn fetch_server_mapping(client: &Client, url: &str) -> Result<HashMap<String, ServerMapping>, String> {
     let user_agent = format!("OpenNOW/{}", APP_VERSION);
 
-    debug!("Fetching server mapping from PrintedWaste...");
+    debug!("Fetching server mapping from {}...", url);
 
     let response = client
-        .get(MAPPING_API_URL)
+        .get(url)
         .header("User-Agent", &user_agent)
         .send()
         .await
@@ -130,18 +338,18 @@ pub async fn fetch_server_mapping(client: &Client) -> Result<HashMap<String, Ser
         .map(|(k, v)| (k, ServerMapping::from_raw(v)))
         .collect();
 
-    info!("Fetched {} server mappings from PrintedWaste", mappings.len());
+    info!("Fetched {} server mappings", mappings.len());
     Ok(mappings)
 }
 
 /// Fetch queue data from PrintedWaste
-pub async fn fetch_queue_data(client: &Client) -> Result<QueueResponse, String> {
+pub async fn fetch_queue_data(client: &Client, url: &str) -> Result<QueueResponse, String> {
     let user_agent = format!("OpenNOW/{}", APP_VERSION);
 
-    debug!("Fetching queue data from PrintedWaste...");
+    debug!("Fetching queue data from {}...", url);
 
     let response = client
-        .get(QUEUE_API_URL)
+        .get(url)
         .header("User-Agent", &user_agent)
         .send()
         .await
@@ -161,25 +369,19 @@ pub async fn fetch_queue_data(client: &Client) -> Result<QueueResponse, String>
         return Err(format!("Queue API returned errors: {:?}", queue.errors));
     }
 
-    info!("Fetched queue data for {} servers from PrintedWaste", queue.data.len());
+    info!("Fetched queue data for {} servers", queue.data.len());
     Ok(queue)
 }
 
-/// Fetch combined queue server info
-pub async fn fetch_queue_servers(client: &Client) -> Result<Vec<QueueServerInfo>, String> {
-    // Fetch both mapping and queue data
-    let (mapping_result, queue_result) = tokio::join!(
-        fetch_server_mapping(client),
-        fetch_queue_data(client)
-    );
-
-    let mapping = mapping_result?;
-    let queue = queue_result?;
-
+/// Combine server mapping and queue data
+fn merge_queue_data(
+    mapping: &HashMap<String, ServerMapping>,
+    queue: &QueueResponse,
+) -> Vec<QueueServerInfo> {
     let mut servers: Vec<QueueServerInfo> = Vec::new();
     let mut servers_missing_queue_data = 0;
 
-    for (server_id, server_mapping) in &mapping {
+    for (server_id, server_mapping) in mapping {
         // Skip nuked servers
         if server_mapping.nuked {
             continue;
@@ -199,6 +401,7 @@ pub async fn fetch_queue_servers(client: &Client) -> Result<Vec<QueueServerInfo>
                 // NOT the detailed region from mapping (e.g., "US Central")
                 region: queue_data.region.clone(),
                 ping_ms: None, // Will be filled in by caller if needed
+                latency: None,
                 queue_position: queue_data.queue_position,
                 eta_seconds: queue_data.eta.map(|ms| ms / 1000), // Convert ms to seconds
                 is_4080_server: server_mapping.is4080_server,
@@ -220,9 +423,30 @@ pub async fn fetch_queue_servers(client: &Client) -> Result<Vec<QueueServerInfo>
     }
 
     // Sort by queue position (shortest first)
-    servers.sort_by(|a, b| a.queue_position.cmp(&b.queue_position));
+    servers.sort_by_key(|s| s.queue_position);
+    servers
+}
 
-    Ok(servers)
+/// Queue data without a mapping: every server, named by ID
+fn unmapped_servers(queue: QueueResponse) -> Vec<QueueServerInfo> {
+    let mut servers: Vec<QueueServerInfo> = queue
+        .data
+        .into_iter()
+        .map(|(server_id, queue_data)| QueueServerInfo {
+            display_name: server_id.clone(),
+            server_id,
+            region: queue_data.region,
+            ping_ms: None,
+            latency: None,
+            queue_position: queue_data.queue_position,
+            eta_seconds: queue_data.eta.map(|ms| ms / 1000),
+            is_4080_server: false,
+            is_5080_server: false,
+            last_updated: queue_data.last_updated,
+        })
+        .collect();
+    servers.sort_by_key(|s| s.queue_position);
+    servers
 }
 
 /// Format ETA in a human-readable format
@@ -258,28 +482,17 @@ pub fn format_queue_eta(eta_seconds: Option<i64>) -> String {
 
 /// Calculate server score for "best value" sorting
 /// Lower score = better server
-/// Balances ping (important for gameplay) with queue time
-pub fn calculate_server_score(server: &QueueServerInfo) -> f64 {
-    // Ping weight: 1.0 per ms
-    // ETA weight: 0.5 per minute (capped at 100 to prevent extremely long queues from dominating)
-    let ping_score = server.ping_ms.unwrap_or(500) as f64; // High penalty for unknown ping
-    let eta_minutes = (server.eta_seconds.unwrap_or(0) as f64) / 60.0;
-    let eta_score = (eta_minutes * 0.5).min(100.0); // 0.5 per minute, capped at 100
-
-    ping_score + eta_score
+/// See [`ServerScorer`] for the terms and their weights
+pub fn calculate_server_score(server: &QueueServerInfo, scorer: &ServerScorer) -> f64 {
+    scorer.score(server.into()).total
 }
 
-/// Get the best auto-selected server based on ping and queue time balance
-pub fn get_auto_selected_server(servers: &[QueueServerInfo]) -> Option<&QueueServerInfo> {
-    if servers.is_empty() {
-        return None;
-    }
-
-    servers.iter().min_by(|a, b| {
-        let score_a = calculate_server_score(a);
-        let score_b = calculate_server_score(b);
-        score_a.partial_cmp(&score_b).unwrap_or(std::cmp::Ordering::Equal)
-    })
+/// Get the best auto-selected server (lowest score within the max ping limit)
+pub fn get_auto_selected_server<'s>(
+    servers: &'s [QueueServerInfo],
+    scorer: &ServerScorer,
+) -> Option<(&'s QueueServerInfo, ServerScore)> {
+    scorer.best(servers)
 }
 
 /// Get unique regions from server list
@@ -295,14 +508,18 @@ pub fn get_unique_regions(servers: &[QueueServerInfo]) -> Vec<String> {
 }
 
 /// Sort servers by the specified mode
-pub fn sort_servers(servers: &mut [QueueServerInfo], mode: crate::app::QueueSortMode) {
+pub fn sort_servers(
+    servers: &mut [QueueServerInfo],
+    mode: crate::app::QueueSortMode,
+    scorer: &ServerScorer,
+) {
     use crate::app::QueueSortMode;
 
     match mode {
         QueueSortMode::BestValue => {
             servers.sort_by(|a, b| {
-                let score_a = calculate_server_score(a);
-                let score_b = calculate_server_score(b);
+                let score_a = calculate_server_score(a, scorer);
+                let score_b = calculate_server_score(b, scorer);
                 score_a.partial_cmp(&score_b).unwrap_or(std::cmp::Ordering::Equal)
             });
         }
@@ -325,3 +542,140 @@ pub fn sort_servers(servers: &mut [QueueServerInfo], mode: crate::app::QueueSort
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::queue_watch::QueueSample;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const QUEUE_JSON: &str = r#"{"status": true, "errors": [], "data": {
+        "NP-AMS-08": {"QueuePosition": 42, "Last Updated": 1700000000, "Region": "EU", "eta": 600000},
+        "NP-FRK-02": {"QueuePosition": 3, "Last Updated": 1700000000, "Region": "EU", "eta": 30000},
+        "NP-OLD-01": {"QueuePosition": 0, "Last Updated": 1700000000, "Region": "EU"},
+        "NP-GONE-01": {"QueuePosition": 1, "Last Updated": 1700000000, "Region": "US"}
+    }}"#;
+
+    const MAPPING_JSON: &str = r#"{"status": true, "errors": [], "data": {
+        "NP-AMS-08": {"title": "Amsterdam", "region": "EU West", "is4080Server": true},
+        "NP-FRK-02": {"title": "Frankfurt", "region": "EU Central", "is5080Server": true},
+        "NP-OLD-01": {"title": "Old Rig", "region": "EU West"},
+        "NP-GONE-01": {"title": "Gone", "region": "US East", "is4080Server": true, "nuked": true},
+        "NP-NODATA-01": {"title": "No Data", "region": "EU West", "is4080Server": true}
+    }}"#;
+
+    /// Minimal HTTP server standing in for the queue API
+    async fn stand_in() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let response = match request.split_whitespace().nth(1) {
+                    Some(path @ ("/queue" | "/mapping")) => {
+                        let body = if path == "/queue" { QUEUE_JSON } else { MAPPING_JSON };
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                    }
+                    _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn ids(servers: &[QueueServerInfo]) -> Vec<&str> {
+        servers.iter().map(|s| s.server_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_printedwaste_format_from_stand_in() {
+        let base = stand_in().await;
+        let provider = PrintedWasteProvider::with_urls(
+            &format!("{}/queue", base),
+            Some(&format!("{}/mapping", base)),
+        );
+        let servers = provider.fetch_servers(&Client::new()).await.unwrap();
+
+        // Nuked, non-RTX and unlisted servers are dropped; shortest queue first
+        assert_eq!(ids(&servers), vec!["NP-FRK-02", "NP-AMS-08"]);
+        assert_eq!(servers[0].display_name, "Frankfurt");
+        assert_eq!(servers[0].eta_seconds, Some(30));
+        assert!(servers[0].is_5080_server);
+        assert_eq!(provider.name(), "custom queue API");
+    }
+
+    #[tokio::test]
+    async fn test_custom_url_without_mapping() {
+        let base = stand_in().await;
+        let provider = PrintedWasteProvider::with_urls(&format!("{}/queue", base), None);
+        let servers = provider.fetch_servers(&Client::new()).await.unwrap();
+
+        assert_eq!(
+            ids(&servers),
+            vec!["NP-OLD-01", "NP-GONE-01", "NP-FRK-02", "NP-AMS-08"]
+        );
+        assert_eq!(servers[3].display_name, "NP-AMS-08");
+    }
+
+    #[tokio::test]
+    async fn test_stand_in_errors_are_reported() {
+        let base = stand_in().await;
+        let provider = PrintedWasteProvider::with_urls(&format!("{}/missing", base), None);
+        let err = provider.fetch_servers(&Client::new()).await.unwrap_err();
+        assert!(err.contains("404"), "{}", err);
+    }
+
+    fn observed(
+        zone: &str,
+        started_at: &str,
+        position: u32,
+        wait_secs: u32,
+        reached_seat: bool,
+    ) -> QueueRun {
+        QueueRun {
+            game_id: "1".to_string(),
+            game_title: "Test Game".to_string(),
+            zone: zone.to_string(),
+            started_at: started_at.to_string(),
+            samples: vec![QueueSample { secs: 10, position, eta_secs: 0 }],
+            wait_secs,
+            reached_seat,
+        }
+    }
+
+    #[test]
+    fn test_local_observations() {
+        // 2023-11-14T22:13:20Z
+        let now = 1_700_000_000;
+        let history = vec![
+            observed("eu-germany-frankfurt-1", "2023-11-14T10:00:00Z", 80, 1610, true),
+            // Newer run in the same zone wins
+            observed("eu-germany-frankfurt-1", "2023-11-14T20:00:00Z", 20, 410, true),
+            // Left early: estimated from the zone's drain rate
+            observed("eu-netherlands-south", "2023-11-14T21:00:00+01:00", 50, 100, false),
+            observed("eu-netherlands-south", "2023-11-13T21:00:00Z", 100, 1010, true),
+            // Too old
+            observed("us-california-north", "2023-10-01T12:00:00Z", 5, 100, true),
+        ];
+        let servers = observed_servers(&history, now);
+
+        assert_eq!(
+            ids(&servers),
+            vec!["eu-germany-frankfurt-1", "eu-netherlands-south"]
+        );
+        assert_eq!(servers[0].display_name, "Germany Frankfurt 1");
+        assert_eq!(servers[0].region, "EU");
+        assert_eq!(servers[0].queue_position, 20);
+        assert_eq!(servers[0].eta_seconds, Some(400));
+        // 100 positions in 1000s -> 50 positions in ~500s
+        assert_eq!(servers[1].eta_seconds, Some(500));
+    }
+}
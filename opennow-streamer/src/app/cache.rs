@@ -8,6 +8,7 @@ use std::path::PathBuf;
 use super::{
     ActiveSessionInfo, GameInfo, GameSection, SessionInfo, SessionState, SubscriptionInfo,
 };
+use crate::api::SessionError;
 use crate::app::session::MediaConnectionInfo;
 use crate::auth::AuthTokens;
 
@@ -374,6 +375,10 @@ fn session_error_path() -> Option<PathBuf> {
     get_app_data_dir().map(|p| p.join("session_error.txt"))
 }
 
+fn session_error_details_path() -> Option<PathBuf> {
+    get_app_data_dir().map(|p| p.join("session_error.json"))
+}
+
 pub fn save_session_cache(session: &SessionInfo) {
     if let Some(path) = session_cache_path() {
         if let Some(parent) = path.parent() {
@@ -384,7 +389,8 @@ pub fn save_session_cache(session: &SessionInfo) {
             "session_id": session.session_id,
             "server_ip": session.server_ip,
             "zone": session.zone,
-            "state": format!("{:?}", session.state),
+            "server_id": session.server_id,
+            "state": session.state,
             "gpu_type": session.gpu_type,
             "signaling_url": session.signaling_url,
             "is_ready": session.is_ready(),
@@ -409,28 +415,11 @@ pub fn load_session_cache() -> Option<SessionInfo> {
     let content = std::fs::read_to_string(path).ok()?;
     let cache: serde_json::Value = serde_json::from_str(&content).ok()?;
 
-    let state_str = cache.get("state")?.as_str()?;
-    let state = if state_str.contains("Ready") {
-        SessionState::Ready
-    } else if state_str.contains("Streaming") {
-        SessionState::Streaming
-    } else if state_str.contains("InQueue") {
-        // Parse queue position and eta from state string
-        let position = cache
-            .get("queue_position")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as u32;
-        SessionState::InQueue {
-            position,
-            eta_secs: 0,
-        }
-    } else if state_str.contains("Error") {
-        SessionState::Error(state_str.to_string())
-    } else if state_str.contains("Launching") {
-        SessionState::Launching
-    } else {
-        SessionState::Requesting
-    };
+    // Caches from older versions (Debug strings) fall back to Requesting
+    let state = cache
+        .get("state")
+        .and_then(|v| serde_json::from_value::<SessionState>(v.clone()).ok())
+        .unwrap_or(SessionState::Requesting);
 
     // Parse media_connection_info if present
     let media_connection_info = cache
@@ -446,6 +435,10 @@ pub fn load_session_cache() -> Option<SessionInfo> {
         session_id: cache.get("session_id")?.as_str()?.to_string(),
         server_ip: cache.get("server_ip")?.as_str()?.to_string(),
         zone: cache.get("zone")?.as_str()?.to_string(),
+        server_id: cache
+            .get("server_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
         state,
         gpu_type: cache
             .get("gpu_type")
@@ -489,6 +482,27 @@ pub fn clear_session_error() {
     if let Some(path) = session_error_path() {
         let _ = std::fs::remove_file(path);
     }
+    if let Some(path) = session_error_details_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Error codes behind the session error, saved before the message
+pub fn save_session_error_details(error: &SessionError) {
+    if let Some(path) = session_error_details_path() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(error) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+pub fn load_session_error_details() -> Option<SessionError> {
+    let path = session_error_details_path()?;
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
 }
 
 // ============================================================
@@ -573,15 +587,17 @@ pub fn check_launch_proceed_flag() -> bool {
 // ============================================================
 
 use super::types::ServerStatus;
+use crate::utils::LatencyStats;
 
-pub fn save_ping_results(results: &[(String, Option<u32>, ServerStatus)]) {
+pub fn save_ping_results(results: &[(String, Option<LatencyStats>, ServerStatus)]) {
     if let Some(path) = get_app_data_dir().map(|p| p.join("ping_results.json")) {
         let cache: Vec<serde_json::Value> = results
             .iter()
-            .map(|(id, ping, status)| {
+            .map(|(id, latency, status)| {
                 serde_json::json!({
                     "id": id,
-                    "ping_ms": ping,
+                    "ping_ms": latency.map(|l| l.ping_ms()),
+                    "latency": latency,
                     "status": format!("{:?}", status),
                 })
             })
@@ -606,14 +622,14 @@ pub fn load_ping_results() -> Option<Vec<serde_json::Value>> {
 // Queue Server Ping Results Cache
 // ============================================================
 
-pub fn save_queue_ping_results(results: &[(String, Option<u32>)]) {
+pub fn save_queue_ping_results(results: &[(String, Option<LatencyStats>)]) {
     if let Some(path) = get_app_data_dir().map(|p| p.join("queue_ping_results.json")) {
         let cache: Vec<serde_json::Value> = results
             .iter()
-            .map(|(id, ping)| {
+            .map(|(id, latency)| {
                 serde_json::json!({
                     "server_id": id,
-                    "ping_ms": ping,
+                    "latency": latency,
                 })
             })
             .collect();
@@ -624,19 +640,21 @@ pub fn save_queue_ping_results(results: &[(String, Option<u32>)]) {
     }
 }
 
-pub fn load_queue_ping_results() -> Option<Vec<(String, Option<u32>)>> {
+pub fn load_queue_ping_results() -> Option<Vec<(String, Option<LatencyStats>)>> {
     let path = get_app_data_dir()?.join("queue_ping_results.json");
     let content = std::fs::read_to_string(&path).ok()?;
     let results: Vec<serde_json::Value> = serde_json::from_str(&content).ok()?;
     // Clear the ping file after loading
     let _ = std::fs::remove_file(&path);
 
-    let parsed: Vec<(String, Option<u32>)> = results
+    let parsed: Vec<(String, Option<LatencyStats>)> = results
         .iter()
         .filter_map(|v| {
             let server_id = v.get("server_id")?.as_str()?.to_string();
-            let ping_ms = v.get("ping_ms").and_then(|p| p.as_u64()).map(|p| p as u32);
-            Some((server_id, ping_ms))
+            let latency = v
+                .get("latency")
+                .and_then(|l| serde_json::from_value(l.clone()).ok());
+            Some((server_id, latency))
         })
         .collect();
 
@@ -698,6 +716,7 @@ pub fn save_queue_cache(servers: &[QueueServerInfo]) {
                     "display_name": s.display_name,
                     "region": s.region,
                     "ping_ms": s.ping_ms,
+                    "latency": s.latency,
                     "queue_position": s.queue_position,
                     "eta_seconds": s.eta_seconds,
                     "is_4080_server": s.is_4080_server,
@@ -727,6 +746,9 @@ pub fn load_queue_cache() -> Option<Vec<QueueServerInfo>> {
                     display_name: v.get("display_name")?.as_str()?.to_string(),
                     region: v.get("region")?.as_str()?.to_string(),
                     ping_ms: v.get("ping_ms").and_then(|v| v.as_u64()).map(|v| v as u32),
+                    latency: v
+                        .get("latency")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok()),
                     queue_position: v.get("queue_position")?.as_i64()? as i32,
                     eta_seconds: v.get("eta_seconds").and_then(|v| v.as_i64()),
                     is_4080_server: v
@@ -772,3 +794,35 @@ pub fn mark_welcome_shown() {
         }
     }
 }
+
+// ============================================================
+// Decoder Capabilities Cache
+// ============================================================
+
+use crate::media::DecoderCapabilities;
+
+fn decoder_capabilities_path() -> Option<PathBuf> {
+    get_app_data_dir().map(|p| p.join("decoder_capabilities.json"))
+}
+
+pub fn save_decoder_capabilities(capabilities: &DecoderCapabilities) {
+    if let Some(path) = decoder_capabilities_path() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(capabilities) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(&path, json) {
+                    warn!("Failed to save decoder capabilities: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to serialize decoder capabilities: {}", e),
+        }
+    }
+}
+
+pub fn load_decoder_capabilities() -> Option<DecoderCapabilities> {
+    let path = decoder_capabilities_path()?;
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
@@ -4,6 +4,7 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Application settings
@@ -35,6 +36,28 @@ pub struct Settings {
     /// HDR mode enabled
     pub hdr_enabled: bool,
 
+    /// Tone mapping operator for HDR content on SDR output
+    pub tone_mapper: ToneMapper,
+
+    /// Brightness (nits) that maps to SDR white when tone mapping
+    pub hdr_target_nits: f32,
+
+    /// Assumed peak brightness (nits) of HDR content
+    pub hdr_peak_nits: f32,
+
+    /// Saturation applied after tone mapping (1.0 = unchanged)
+    pub hdr_saturation: f32,
+
+    /// Use an HDR (scRGB) swapchain when the display supports it (applies on restart).
+    /// DX12 only; there is no HDR10 (PQ) swapchain path
+    pub hdr_output: bool,
+
+    /// Colour adjustments for games without their own
+    pub color_adjustments: ColorAdjustments,
+
+    /// Per-game colour adjustments, keyed by game ID
+    pub game_color_adjustments: HashMap<String, ColorAdjustments>,
+
     // === Audio Settings ===
     /// Audio codec
     pub audio_codec: AudioCodec,
@@ -42,6 +65,9 @@ pub struct Settings {
     /// Enable surround sound
     pub surround: bool,
 
+    /// Opus decoder backend
+    pub audio_decoder: AudioDecoderBackend,
+
     // === Performance ===
     /// Enable VSync
     pub vsync: bool,
@@ -49,6 +75,25 @@ pub struct Settings {
     /// Low latency mode (reduces buffer)
     pub low_latency_mode: bool,
 
+    /// Pace frames to the display refresh (smoother, up to one frame more latency)
+    pub frame_pacing: bool,
+
+    /// Filter used to scale the video to the window
+    pub upscale_filter: UpscaleFilter,
+
+    /// Upscale filter strength (0.0 - 1.0): sharpening for FSR,
+    /// kernel sharpness for bicubic, ringing allowed for Lanczos
+    pub upscale_strength: f32,
+
+    /// How the stream is fitted to the window
+    pub scaling_mode: ScalingMode,
+
+    /// Zoom for custom scaling (1.0 = fit)
+    pub custom_zoom: f32,
+
+    /// Pan for custom scaling (-1.0 to 1.0 per axis, 0.0 = centred)
+    pub custom_pan: (f32, f32),
+
     /// NVIDIA Reflex (auto-enabled for 120+ FPS)
     pub nvidia_reflex: bool,
 
@@ -89,14 +134,90 @@ pub struct Settings {
     /// Selected server ID (zone ID)
     pub selected_server: Option<String>,
 
-    /// Auto server selection (picks best ping)
+    /// Auto server selection (picks the best-scoring server)
     pub auto_server_selection: bool,
 
-    /// Proxy URL
+    /// Weights for automatic server selection
+    pub server_scoring: ServerScoring,
+
+    /// How server latency is measured
+    pub ping_mode: PingMode,
+
+    /// Latency samples per server (1-20)
+    pub ping_samples: u32,
+
+    /// Proxy URL (http://, https://, socks5:// or socks5h://, credentials allowed)
     pub proxy: Option<String>,
 
+    /// Extra trusted CA certificates (PEM file paths), e.g. for a TLS-inspecting proxy
+    pub ca_certificates: Vec<String>,
+
+    /// Timeout for HTTP requests in seconds (0 = none)
+    pub http_timeout_secs: u32,
+
     /// Disable telemetry
     pub disable_telemetry: bool,
+
+    /// Desktop notifications while queued (thresholds and seat ready)
+    pub queue_notifications: bool,
+
+    /// Queue positions that trigger a notification when reached
+    pub queue_notify_thresholds: Vec<u32>,
+
+    /// Play a sound with queue notifications
+    pub queue_notify_sound: bool,
+
+    /// Desktop notifications before a session is cut off
+    pub playtime_warnings: bool,
+
+    /// Minutes left in the session that trigger a warning
+    pub playtime_warning_minutes: Vec<u32>,
+
+    /// Session length limit in minutes (0 = tier default)
+    pub session_limit_minutes: u32,
+
+    /// Where queue times come from
+    pub queue_provider: QueueProvider,
+
+    /// Queue data URL for the custom provider (PrintedWaste format)
+    pub queue_custom_url: Option<String>,
+
+    /// Server mapping URL for the custom provider (optional)
+    pub queue_custom_mapping_url: Option<String>,
+
+    /// Request the session in several zones at once and keep the first seat
+    pub queue_race_enabled: bool,
+
+    /// Number of zones to race (2-3)
+    pub queue_race_zones: u32,
+
+    /// Seconds before the race settles for the best-scoring queue
+    pub queue_race_timeout_secs: u32,
+
+    // === Capture ===
+    /// Directory for session recordings (None = Videos/OpenNOW)
+    pub recordings_dir: Option<String>,
+
+    /// Keep a rolling in-memory buffer for instant replay
+    pub replay_buffer_enabled: bool,
+
+    /// Instant replay length in seconds
+    pub replay_buffer_secs: u32,
+
+    /// Directory for screenshots (None = Pictures/OpenNOW)
+    pub screenshots_dir: Option<String>,
+
+    /// Save HDR screenshots as 16-bit BT.2020 PNG instead of tone-mapping
+    pub screenshot_hdr_16bit: bool,
+
+    /// Write a local per-session performance log (never uploaded)
+    pub session_log_enabled: bool,
+
+    /// Session log file format
+    pub session_log_format: SessionLogFormat,
+
+    /// Directory for session logs (None = Documents/OpenNOW/Session Logs)
+    pub session_logs_dir: Option<String>,
 }
 
 impl Default for Settings {
@@ -111,14 +232,28 @@ impl Default for Settings {
             decoder_backend: VideoDecoderBackend::Auto, // Auto-select best decoder
             color_quality: ColorQuality::Bit10Yuv420,
             hdr_enabled: false,
+            tone_mapper: ToneMapper::Aces,
+            hdr_target_nits: 203.0, // BT.2408 reference white
+            hdr_peak_nits: 1000.0,
+            hdr_saturation: 1.0,
+            hdr_output: false,
+            color_adjustments: ColorAdjustments::default(),
+            game_color_adjustments: HashMap::new(),
 
             // Audio
             audio_codec: AudioCodec::Opus,
+            audio_decoder: AudioDecoderBackend::Auto,
             surround: false,
 
             // Performance
             vsync: false,
             low_latency_mode: true,
+            frame_pacing: false,
+            upscale_filter: UpscaleFilter::Bilinear,
+            upscale_strength: 0.5,
+            scaling_mode: ScalingMode::Fit,
+            custom_zoom: 1.0,
+            custom_pan: (0.0, 0.0),
             nvidia_reflex: true,
 
             // Input
@@ -138,8 +273,35 @@ impl Default for Settings {
             preferred_region: None,
             selected_server: None,
             auto_server_selection: true, // Default to auto
+            server_scoring: ServerScoring::default(),
+            ping_mode: PingMode::Tcp,
+            ping_samples: 5,
             proxy: None,
+            ca_certificates: Vec::new(),
+            http_timeout_secs: 30,
             disable_telemetry: true,
+            queue_notifications: true,
+            queue_notify_thresholds: vec![50, 10, 3],
+            queue_notify_sound: false,
+            playtime_warnings: true,
+            playtime_warning_minutes: vec![15, 5, 1],
+            session_limit_minutes: 0,
+            queue_provider: QueueProvider::PrintedWaste,
+            queue_custom_url: None,
+            queue_custom_mapping_url: None,
+            queue_race_enabled: false,
+            queue_race_zones: 2,
+            queue_race_timeout_secs: 180,
+
+            // Capture
+            recordings_dir: None,
+            replay_buffer_enabled: false,
+            replay_buffer_secs: 30,
+            screenshots_dir: None,
+            screenshot_hdr_16bit: false,
+            session_log_enabled: false,
+            session_log_format: SessionLogFormat::Csv,
+            session_logs_dir: None,
         }
     }
 }
@@ -194,6 +356,151 @@ impl Settings {
     pub fn max_bitrate_kbps(&self) -> u32 {
         self.max_bitrate_mbps * 1000
     }
+
+    /// Get the directory recordings are saved to
+    pub fn recordings_path(&self) -> PathBuf {
+        if let Some(dir) = self.recordings_dir.as_ref().filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir);
+        }
+        dirs::video_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("OpenNOW")
+    }
+
+    /// Get the directory screenshots are saved to
+    pub fn screenshots_path(&self) -> PathBuf {
+        if let Some(dir) = self.screenshots_dir.as_ref().filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir);
+        }
+        dirs::picture_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("OpenNOW")
+    }
+
+    /// Get the directory session performance logs are written to
+    pub fn session_logs_path(&self) -> PathBuf {
+        if let Some(dir) = self.session_logs_dir.as_ref().filter(|d| !d.is_empty()) {
+            return PathBuf::from(dir);
+        }
+        dirs::document_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("OpenNOW")
+            .join("Session Logs")
+    }
+
+    /// Colour adjustments for a game, falling back to the global ones
+    pub fn color_adjustments_for(&self, game_id: Option<&str>) -> ColorAdjustments {
+        game_id
+            .and_then(|id| self.game_color_adjustments.get(id))
+            .copied()
+            .unwrap_or(self.color_adjustments)
+    }
+}
+
+/// Colour correction applied to the video in the shaders
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorAdjustments {
+    /// Offset added to every channel (-0.5 to 0.5)
+    pub brightness: f32,
+    /// Contrast around mid grey (0.5 to 1.5)
+    pub contrast: f32,
+    /// Gamma (0.5 to 2.0, above 1.0 brightens midtones)
+    pub gamma: f32,
+    /// Saturation (0.0 = greyscale to 2.0)
+    pub saturation: f32,
+    /// Saturation boost for muted colours only (-1.0 to 1.0)
+    pub vibrance: f32,
+    /// Colour temperature (-1.0 = cool to 1.0 = warm)
+    pub temperature: f32,
+}
+
+impl Default for ColorAdjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            vibrance: 0.0,
+            temperature: 0.0,
+        }
+    }
+}
+
+impl ColorAdjustments {
+    /// Whether these leave the picture unchanged
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Values limited to the slider ranges (hand-edited settings files)
+    pub fn clamped(&self) -> Self {
+        Self {
+            brightness: self.brightness.clamp(-0.5, 0.5),
+            contrast: self.contrast.clamp(0.5, 1.5),
+            gamma: self.gamma.clamp(0.5, 2.0),
+            saturation: self.saturation.clamp(0.0, 2.0),
+            vibrance: self.vibrance.clamp(-1.0, 1.0),
+            temperature: self.temperature.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+/// Weights for automatic server selection. Lower scores win.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerScoring {
+    /// Points per ms of ping
+    pub ping_weight: f32,
+    /// Points per minute of expected queue wait
+    pub eta_weight: f32,
+    /// Most points the queue wait can add
+    pub eta_cap: f32,
+    /// Points per ms of jitter seen in past sessions on the zone
+    pub jitter_weight: f32,
+    /// Points per percent of packet loss seen in past sessions on the zone
+    pub packet_loss_weight: f32,
+    /// Points taken off RTX 5080 servers (half for RTX 4080)
+    pub gpu_tier_bonus: f32,
+    /// Points taken off servers in the preferred region
+    pub region_bonus: f32,
+    /// Servers above this ping are never auto-selected (0 = no limit)
+    pub max_ping_ms: u32,
+}
+
+impl Default for ServerScoring {
+    fn default() -> Self {
+        Self {
+            ping_weight: 1.0,
+            eta_weight: 0.5,
+            eta_cap: 100.0,
+            jitter_weight: 1.0,
+            packet_loss_weight: 20.0,
+            gpu_tier_bonus: 0.0,
+            region_bonus: 25.0,
+            max_ping_ms: 0,
+        }
+    }
+}
+
+impl ServerScoring {
+    /// Values limited to the slider ranges (hand-edited settings files)
+    pub fn clamped(&self) -> Self {
+        Self {
+            ping_weight: self.ping_weight.clamp(0.0, 5.0),
+            eta_weight: self.eta_weight.clamp(0.0, 5.0),
+            eta_cap: self.eta_cap.clamp(0.0, 1000.0),
+            jitter_weight: self.jitter_weight.clamp(0.0, 10.0),
+            packet_loss_weight: self.packet_loss_weight.clamp(0.0, 100.0),
+            gpu_tier_bonus: self.gpu_tier_bonus.clamp(0.0, 200.0),
+            region_bonus: self.region_bonus.clamp(0.0, 200.0),
+            max_ping_ms: self.max_ping_ms.min(500),
+        }
+    }
 }
 
 /// Stream quality presets
@@ -287,6 +594,9 @@ pub const RESOLUTIONS: &[(&str, &str)] = &[
 /// Available FPS options
 pub const FPS_OPTIONS: &[u32] = &[30, 60, 90, 120, 144, 165, 240, 360];
 
+/// Available instant replay lengths in seconds
+pub const REPLAY_DURATIONS: &[u32] = &[15, 30, 60, 120, 300];
+
 /// Video codec options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -477,6 +787,239 @@ pub enum AudioCodec {
     OpusStereo,
 }
 
+/// HDR to SDR tone mapping operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ToneMapper {
+    /// ACES filmic approximation (Narkowicz)
+    #[default]
+    Aces,
+    /// Extended Reinhard on luminance
+    Reinhard,
+    /// ITU-R BT.2390 EETF
+    Bt2390,
+    /// Hable / Uncharted 2 filmic
+    Hable,
+}
+
+impl ToneMapper {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToneMapper::Aces => "ACES",
+            ToneMapper::Reinhard => "Reinhard",
+            ToneMapper::Bt2390 => "BT.2390",
+            ToneMapper::Hable => "Hable",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            ToneMapper::Aces => "Filmic contrast with punchy colors. Ignores the peak setting.",
+            ToneMapper::Reinhard => "Soft, natural roll-off. Keeps hues accurate.",
+            ToneMapper::Bt2390 => {
+                "Broadcast standard. Leaves midtones untouched and compresses only highlights."
+            }
+            ToneMapper::Hable => "Filmic curve with lifted shadows and gentle highlights.",
+        }
+    }
+
+    /// Operator index used by the HDR shader
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            ToneMapper::Aces => 0,
+            ToneMapper::Reinhard => 1,
+            ToneMapper::Bt2390 => 2,
+            ToneMapper::Hable => 3,
+        }
+    }
+
+    pub fn all() -> &'static [ToneMapper] {
+        &[
+            ToneMapper::Aces,
+            ToneMapper::Reinhard,
+            ToneMapper::Bt2390,
+            ToneMapper::Hable,
+        ]
+    }
+}
+
+/// How the stream is fitted to the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScalingMode {
+    /// Keep aspect ratio, letterbox/pillarbox
+    #[default]
+    Fit,
+    /// Largest whole-number multiple that fits (pixel-exact)
+    Integer,
+    /// Fill the window, ignoring aspect ratio
+    Stretch,
+    /// Keep aspect ratio and crop to fill the window
+    Fill,
+    /// Fit with user zoom and pan
+    Custom,
+}
+
+impl ScalingMode {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScalingMode::Fit => "Fit (letterbox)",
+            ScalingMode::Integer => "Integer",
+            ScalingMode::Stretch => "Stretch",
+            ScalingMode::Fill => "Fill (crop)",
+            ScalingMode::Custom => "Custom zoom",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            ScalingMode::Fit => "Whole picture at the correct aspect ratio, black bars where needed.",
+            ScalingMode::Integer => "Scales by whole multiples only for pixel-perfect output.",
+            ScalingMode::Stretch => "Fills the window. Distorts if aspect ratios differ.",
+            ScalingMode::Fill => "Fills the window at the correct aspect ratio, cropping edges.",
+            ScalingMode::Custom => "Manual zoom and pan.",
+        }
+    }
+
+    /// Next mode for the cycle hotkey
+    pub fn next(&self) -> ScalingMode {
+        let all = Self::all();
+        let index = all.iter().position(|m| m == self).unwrap_or(0);
+        all[(index + 1) % all.len()]
+    }
+
+    pub fn all() -> &'static [ScalingMode] {
+        &[
+            ScalingMode::Fit,
+            ScalingMode::Integer,
+            ScalingMode::Stretch,
+            ScalingMode::Fill,
+            ScalingMode::Custom,
+        ]
+    }
+}
+
+/// Video scaling filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpscaleFilter {
+    /// Hardware bilinear sampling, no extra passes
+    #[default]
+    Bilinear,
+    /// Mitchell to Catmull-Rom cubic
+    Bicubic,
+    /// Lanczos-3 with anti-ringing
+    Lanczos,
+    /// FidelityFX FSR1-style edge-adaptive upscale (EASU) + sharpening (RCAS)
+    Fsr,
+}
+
+impl UpscaleFilter {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpscaleFilter::Bilinear => "Bilinear",
+            UpscaleFilter::Bicubic => "Bicubic",
+            UpscaleFilter::Lanczos => "Lanczos",
+            UpscaleFilter::Fsr => "FSR 1 (EASU + RCAS)",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            UpscaleFilter::Bilinear => "Fastest, slightly soft when upscaling.",
+            UpscaleFilter::Bicubic => {
+                "Smoother edges than bilinear.\n\
+                 Strength moves from Mitchell (soft) to Catmull-Rom (sharp)."
+            }
+            UpscaleFilter::Lanczos => {
+                "Sharpest classic filter.\n\
+                 Strength allows more ringing around edges in exchange for detail."
+            }
+            UpscaleFilter::Fsr => {
+                "Edge-adaptive upscale followed by contrast-adaptive sharpening.\n\
+                 Strength sets the amount of sharpening."
+            }
+        }
+    }
+
+    /// Whether the filter takes a strength value
+    pub fn uses_strength(&self) -> bool {
+        !matches!(self, UpscaleFilter::Bilinear)
+    }
+
+    pub fn all() -> &'static [UpscaleFilter] {
+        &[
+            UpscaleFilter::Bilinear,
+            UpscaleFilter::Bicubic,
+            UpscaleFilter::Lanczos,
+            UpscaleFilter::Fsr,
+        ]
+    }
+}
+
+/// Audio decoder backend preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioDecoderBackend {
+    /// Platform decoder, falling back to built-in if unavailable
+    #[default]
+    Auto,
+    /// GStreamer (Linux/Windows x64) or FFmpeg (macOS)
+    Platform,
+    /// In-process libopus, no external plugins
+    Builtin,
+}
+
+impl AudioDecoderBackend {
+    /// Short display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioDecoderBackend::Auto => "Auto",
+            AudioDecoderBackend::Platform => {
+                if cfg!(target_os = "macos") {
+                    "FFmpeg"
+                } else {
+                    "GStreamer"
+                }
+            }
+            AudioDecoderBackend::Builtin => "Built-in",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            AudioDecoderBackend::Auto => {
+                "Uses the platform decoder when available,\n\
+                 otherwise falls back to the built-in decoder."
+            }
+            AudioDecoderBackend::Platform => {
+                "Linux/Windows: GStreamer opusparse + opusdec plugins\n\
+                 macOS: FFmpeg Opus decoder"
+            }
+            AudioDecoderBackend::Builtin => {
+                "In-process libopus decoder.\n\
+                 Works without GStreamer plugins, with native\n\
+                 packet loss concealment and in-band FEC."
+            }
+        }
+    }
+
+    pub fn all() -> &'static [AudioDecoderBackend] {
+        &[
+            AudioDecoderBackend::Auto,
+            AudioDecoderBackend::Platform,
+            AudioDecoderBackend::Builtin,
+        ]
+    }
+}
+
 /// Color quality options (bit depth + chroma subsampling)
 /// Matches NVIDIA GFN client options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -565,3 +1108,118 @@ pub enum StatsPosition {
     BottomLeft,
     BottomRight,
 }
+
+/// Session performance log format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionLogFormat {
+    /// One row per sample or event, opens in any spreadsheet
+    #[default]
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl SessionLogFormat {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionLogFormat::Csv => "CSV",
+            SessionLogFormat::JsonLines => "JSON Lines",
+        }
+    }
+
+    /// File extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SessionLogFormat::Csv => "csv",
+            SessionLogFormat::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn all() -> &'static [SessionLogFormat] {
+        &[SessionLogFormat::Csv, SessionLogFormat::JsonLines]
+    }
+}
+
+/// Latency probe method for the server ping test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PingMode {
+    /// TCP connect to port 443, works everywhere
+    #[default]
+    Tcp,
+    /// ICMP echo over an unprivileged socket (Linux/macOS, else TCP)
+    Icmp,
+    /// STUN binding requests over UDP, the stream's own transport
+    Udp,
+}
+
+impl PingMode {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PingMode::Tcp => "TCP connect",
+            PingMode::Icmp => "ICMP echo",
+            PingMode::Udp => "UDP (STUN)",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            PingMode::Tcp => "Times a TCP handshake to each server. Works on every network and platform.",
+            PingMode::Icmp => "Classic ping. Needs unprivileged ICMP (Linux ping_group_range, macOS); falls back to TCP elsewhere.",
+            PingMode::Udp => "Times STUN requests over UDP, closest to what the stream sees. Some networks block it.",
+        }
+    }
+
+    pub fn all() -> &'static [PingMode] {
+        &[PingMode::Tcp, PingMode::Icmp, PingMode::Udp]
+    }
+}
+
+/// Source of the queue times shown in the Queue tab and server picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueProvider {
+    /// PrintedWaste community API
+    #[default]
+    PrintedWaste,
+    /// Built from our own queue history (no third party)
+    Local,
+    /// PrintedWaste-compatible API at a custom URL
+    Custom,
+}
+
+impl QueueProvider {
+    /// Display name for dropdown
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueueProvider::PrintedWaste => "PrintedWaste",
+            QueueProvider::Local => "Local observations",
+            QueueProvider::Custom => "Custom URL",
+        }
+    }
+
+    /// Detailed description for tooltip
+    pub fn description(&self) -> &'static str {
+        match self {
+            QueueProvider::PrintedWaste => "Live community queue data for every server.",
+            QueueProvider::Local => {
+                "Your own recent queues only. Works offline, covers the zones you've played in."
+            }
+            QueueProvider::Custom => {
+                "A mirror or self-hosted service using the PrintedWaste format."
+            }
+        }
+    }
+
+    pub fn all() -> &'static [QueueProvider] {
+        &[
+            QueueProvider::PrintedWaste,
+            QueueProvider::Local,
+            QueueProvider::Custom,
+        ]
+    }
+}
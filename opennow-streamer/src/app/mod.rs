@@ -4,11 +4,24 @@
 
 pub mod cache;
 pub mod config;
+pub mod playtime;
+pub mod queue_race;
+pub mod queue_watch;
+pub mod recovery;
+pub mod server_scoring;
 pub mod session;
+pub mod session_controller;
+pub mod session_history;
 pub mod types;
+pub mod viewport;
 
 pub use config::{AudioCodec, ColorQuality, Settings, StatsPosition, StreamQuality, VideoCodec};
+pub use queue_race::QueueRace;
+pub use queue_watch::{QueueRun, QueueWatch};
+pub use server_scoring::{ServerScore, ServerScorer, ZoneQualityMap};
 pub use session::{ActiveSessionInfo, SessionInfo, SessionState};
+pub use session_controller::{LifecycleEvent, SessionController};
+pub use session_history::SessionRecord;
 pub use types::{
     parse_resolution, AppState, GameInfo, GameSection, GameVariant, GamesTab, QueueRegionFilter,
     QueueSortMode, ServerInfo, ServerStatus, SettingChange, SharedFrame, SubscriptionInfo,
@@ -26,7 +39,10 @@ use crate::auth::{self, AuthTokens, LoginProvider, PkceChallenge, UserInfo};
 
 use crate::input::InputHandler;
 
-use crate::media::StreamStats;
+use crate::media::{
+    FramePacer, SessionEvent, SessionLog, StatsHistory, StatsMetric, StreamRecorder, StreamStats,
+};
+use crate::utils::LatencyStats;
 use crate::webrtc::StreamingSession;
 
 /// Cache for dynamic regions fetched from serverInfo API
@@ -67,12 +83,24 @@ pub struct App {
     /// Shared frame holder for zero-latency frame delivery
     pub shared_frame: Option<Arc<SharedFrame>>,
 
+    /// Presentation scheduler (only when frame pacing is enabled)
+    pub frame_pacer: Option<FramePacer>,
+
     /// Stream statistics
     pub stats: StreamStats,
 
     /// Whether to show stats overlay
     pub show_stats: bool,
 
+    /// Rolling stats history for the overlay graphs
+    pub stats_history: StatsHistory,
+
+    /// Whether to show graphs in the stats overlay
+    pub show_stats_graphs: bool,
+
+    /// Whether to show the in-stream colour adjustment overlay
+    pub show_color_overlay: bool,
+
     /// Status message for UI
     pub status_message: String,
 
@@ -172,8 +200,56 @@ pub struct App {
     last_render_fps_time: std::time::Instant,
     last_render_frame_count: u64,
 
-    /// Number of times we've polled after session became ready (to ensure candidates)
-    session_ready_poll_count: u32,
+    /// Session lifecycle (request, queue, ready, streaming)
+    session_controller: SessionController,
+
+    /// Multi-zone queue race in progress (until a winner is handed over)
+    queue_race: Option<QueueRace>,
+
+    /// Queue position tracking and notifications for the current session
+    queue_watch: QueueWatch,
+
+    /// Past queues, for wait estimates
+    queue_history: Vec<QueueRun>,
+
+    /// Ask the window manager for attention (queue notification fired)
+    attention_requested: bool,
+
+    /// Jitter and packet loss from past sessions, by zone (server scoring)
+    pub zone_quality: ZoneQualityMap,
+
+    /// Network quality of the current session so far
+    session_quality: server_scoring::SessionQuality,
+
+    /// Finished sessions, oldest first (History tab)
+    pub session_history: Vec<SessionRecord>,
+
+    /// History record of the session being streamed
+    active_session: Option<session_history::ActiveSession>,
+
+    /// How streaming tasks ended, by session ID
+    stream_exit_tx: std::sync::mpsc::Sender<(String, session_history::SessionExit)>,
+    stream_exit_rx: std::sync::mpsc::Receiver<(String, session_history::SessionExit)>,
+
+    /// Countdown to the session being cut off (while streaming)
+    playtime: Option<playtime::PlaytimeTracker>,
+
+    /// Subscription info from background fetches
+    subscription_tx: std::sync::mpsc::Sender<SubscriptionInfo>,
+    subscription_rx: std::sync::mpsc::Receiver<SubscriptionInfo>,
+
+    /// Last subscription fetch, for refreshes while streaming
+    subscription_fetched_at: std::time::Instant,
+
+    /// Recovery for the game being launched (retries, login refresh, ...)
+    pub launch_recovery: Option<recovery::LaunchRecovery>,
+
+    /// Error codes behind the last session failure, for recovery and diagnostics
+    session_error: Option<api::SessionError>,
+
+    /// Login refreshes started by launch recovery
+    login_refresh_tx: std::sync::mpsc::Sender<Result<AuthTokens, String>>,
+    login_refresh_rx: std::sync::mpsc::Receiver<Result<AuthTokens, String>>,
 
     /// Anti-AFK mode enabled (Ctrl+Shift+F10 to toggle)
     pub anti_afk_enabled: bool,
@@ -184,7 +260,7 @@ pub struct App {
     /// Whether a token refresh is currently in progress
     token_refresh_in_progress: bool,
 
-    /// Queue times data from PrintedWaste API
+    /// Queue times from the configured provider
     pub queue_servers: Vec<crate::api::QueueServerInfo>,
 
     /// Whether queue data is loading
@@ -216,6 +292,19 @@ pub struct App {
 
     /// Ads total duration in seconds
     pub ads_total_secs: u32,
+
+    /// Session recorder and instant replay (Ctrl+Shift+R / Ctrl+Shift+S)
+    pub recorder: Arc<StreamRecorder>,
+
+    /// Last capture notice shown in the stream overlay
+    pub capture_notice: Option<(String, std::time::Instant)>,
+
+    /// Notices from background capture work (screenshot encoding)
+    capture_notice_tx: std::sync::mpsc::Sender<String>,
+    capture_notice_rx: std::sync::mpsc::Receiver<String>,
+
+    /// Screenshot requested, taken from the next decoded frame
+    screenshot_pending: bool,
 }
 
 /// Poll interval for session status (2 seconds)
@@ -230,10 +319,17 @@ use crate::media::VideoFrame;
 impl App {
     /// Create new application instance
     pub fn new(runtime: Handle) -> Self {
+        let (capture_notice_tx, capture_notice_rx) = std::sync::mpsc::channel();
+        let (stream_exit_tx, stream_exit_rx) = std::sync::mpsc::channel();
+        let (subscription_tx, subscription_rx) = std::sync::mpsc::channel();
+        let (login_refresh_tx, login_refresh_rx) = std::sync::mpsc::channel();
         // Load settings
         let settings = Settings::load().unwrap_or_default();
         let auto_server = settings.auto_server_selection; // Save before move
 
+        // Proxy / CA certificates / timeouts for every outbound connection
+        api::http::configure(&settings);
+
         // Try to load saved tokens
         let auth_tokens = cache::load_tokens();
         let has_token = auth_tokens
@@ -260,6 +356,9 @@ impl App {
             }
         });
 
+        // Test which decoder backends actually work (cached per GPU driver)
+        crate::media::start_decoder_probe(&runtime, false);
+
         // Start checking active sessions if we have a token
         // Clear stale cache first to ensure we always use fresh data from API
         cache::clear_active_sessions_cache();
@@ -270,6 +369,8 @@ impl App {
             rt.spawn(async move {
                 let mut api_client = GfnApiClient::new();
                 api_client.set_access_token(token);
+                // Before listing, so leftover race sessions don't show up as conflicts
+                queue_race::stop_orphaned_sessions(&api_client).await;
                 match api_client.get_active_sessions().await {
                     Ok(sessions) => {
                         info!(
@@ -316,8 +417,12 @@ impl App {
             cursor_captured: false,
             current_frame: None,
             shared_frame: None,
+            frame_pacer: None,
             stats: StreamStats::default(),
             show_stats: true,
+            stats_history: StatsHistory::new(),
+            show_stats_graphs: false,
+            show_color_overlay: false,
             status_message: "Welcome to OpenNOW".to_string(),
             error_message: None,
             games: Vec::new(),
@@ -353,7 +458,25 @@ impl App {
             render_frame_count: 0,
             last_render_fps_time: std::time::Instant::now(),
             last_render_frame_count: 0,
-            session_ready_poll_count: 0,
+            session_controller: SessionController::new(),
+            queue_race: None,
+            queue_watch: QueueWatch::new(),
+            queue_history: queue_watch::load_history(),
+            attention_requested: false,
+            zone_quality: server_scoring::load_zone_quality(),
+            session_quality: Default::default(),
+            session_history: session_history::load_history(),
+            active_session: None,
+            stream_exit_tx,
+            stream_exit_rx,
+            playtime: None,
+            subscription_tx,
+            subscription_rx,
+            subscription_fetched_at: std::time::Instant::now(),
+            launch_recovery: None,
+            session_error: None,
+            login_refresh_tx,
+            login_refresh_rx,
             anti_afk_enabled: false,
             anti_afk_last_send: std::time::Instant::now(),
             token_refresh_in_progress: false,
@@ -368,6 +491,11 @@ impl App {
             ads_required: false,
             ads_remaining_secs: 0,
             ads_total_secs: 0,
+            recorder: Arc::new(StreamRecorder::new()),
+            capture_notice: None,
+            capture_notice_tx,
+            capture_notice_rx,
+            screenshot_pending: false,
         }
     }
 
@@ -382,6 +510,107 @@ impl App {
         }
     }
 
+    /// Start or stop recording the stream to disk
+    pub fn toggle_recording(&mut self) {
+        if self.state != AppState::Streaming {
+            return;
+        }
+
+        if self.recorder.is_recording() {
+            if self.recorder.stop().is_some() {
+                self.show_capture_notice("Recording saved");
+            }
+            return;
+        }
+
+        let title = self.selected_game.as_ref().map(|g| g.title.as_str());
+        let path =
+            crate::media::capture_file_path(&self.settings.recordings_path(), title, "mkv");
+        match self.recorder.start(path) {
+            Ok(()) => {
+                // Recording starts at the next keyframe - ask for one now
+                self.runtime.spawn(crate::webrtc::request_keyframe());
+            }
+            Err(e) => {
+                error!("Failed to start recording: {}", e);
+                self.show_capture_notice("Failed to start recording");
+            }
+        }
+    }
+
+    /// Save the instant replay buffer to disk
+    pub fn save_replay(&mut self) {
+        if self.state != AppState::Streaming {
+            return;
+        }
+
+        if !self.recorder.is_replay_active() {
+            self.show_capture_notice("Instant replay is disabled in settings");
+            return;
+        }
+
+        let title = self
+            .selected_game
+            .as_ref()
+            .map(|g| format!("{} replay", g.title));
+        let path = crate::media::capture_file_path(
+            &self.settings.recordings_path(),
+            title.as_deref(),
+            "mkv",
+        );
+        match self.recorder.save_replay(path) {
+            Ok(()) => self.show_capture_notice("Replay saved"),
+            Err(e) => {
+                warn!("Failed to save replay: {}", e);
+                self.show_capture_notice(&format!("Replay not saved: {}", e));
+            }
+        }
+    }
+
+    /// Take a screenshot of the next decoded frame
+    pub fn request_screenshot(&mut self) {
+        if self.state == AppState::Streaming {
+            self.screenshot_pending = true;
+        }
+    }
+
+    /// Copy the frame and encode it to PNG on a background thread
+    fn capture_screenshot(&mut self, frame: &VideoFrame) {
+        let image = match crate::media::ScreenshotImage::from_frame(frame) {
+            Ok(image) => image,
+            Err(e) => {
+                error!("Failed to capture screenshot: {}", e);
+                self.show_capture_notice("Screenshot failed");
+                return;
+            }
+        };
+
+        let title = self.selected_game.as_ref().map(|g| g.title.as_str());
+        let path =
+            crate::media::capture_file_path(&self.settings.screenshots_path(), title, "png");
+        let hdr_16bit = self.settings.screenshot_hdr_16bit;
+        let notice_tx = self.capture_notice_tx.clone();
+
+        std::thread::spawn(move || {
+            let notice = match image.save_png(&path, hdr_16bit) {
+                Ok(()) => {
+                    info!("Screenshot saved: {}", path.display());
+                    "Screenshot saved".to_string()
+                }
+                Err(e) => {
+                    error!("Failed to save screenshot: {:#}", e);
+                    "Screenshot failed".to_string()
+                }
+            };
+            let _ = notice_tx.send(notice);
+        });
+    }
+
+    /// Show a short notice in the stream overlay
+    fn show_capture_notice(&mut self, message: &str) {
+        self.capture_notice = Some((message.to_string(), std::time::Instant::now()));
+    }
+
     /// Send anti-AFK key press (F13) if enabled and interval elapsed
     pub fn update_anti_afk(&mut self) {
         if !self.anti_afk_enabled || self.state != AppState::Streaming {
@@ -422,7 +651,7 @@ impl App {
                     GamesTab::Home => self.games.get(index).cloned(), // Use flat list for Home too
                     GamesTab::AllGames => self.games.get(index).cloned(),
                     GamesTab::MyLibrary => self.library_games.get(index).cloned(),
-                    GamesTab::QueueTimes => None, // Queue Times tab doesn't launch games
+                    GamesTab::QueueTimes | GamesTab::History => None, // Neither tab launches games
                 };
                 if let Some(game) = game {
                     self.launch_game(&game);
@@ -434,9 +663,15 @@ impl App {
             UiAction::StopStreaming => {
                 self.stop_streaming();
             }
+            UiAction::RetryLaunch => {
+                self.relaunch();
+            }
             UiAction::ToggleStats => {
                 self.toggle_stats();
             }
+            UiAction::ToggleColorOverlay => {
+                self.toggle_color_overlay();
+            }
             UiAction::UpdateSearch(query) => {
                 self.search_query = query;
             }
@@ -454,9 +689,24 @@ impl App {
                     SettingChange::Fullscreen(fs) => self.settings.fullscreen = fs,
                     SettingChange::VSync(vsync) => self.settings.vsync = vsync,
                     SettingChange::LowLatency(ll) => self.settings.low_latency_mode = ll,
+                    SettingChange::FramePacing(enabled) => self.settings.frame_pacing = enabled,
+                    SettingChange::UpscaleFilter(filter) => self.settings.upscale_filter = filter,
+                    SettingChange::UpscaleStrength(strength) => {
+                        self.settings.upscale_strength = strength.clamp(0.0, 1.0)
+                    }
+                    SettingChange::ScalingMode(mode) => self.settings.scaling_mode = mode,
+                    SettingChange::CustomZoom(zoom) => {
+                        self.settings.custom_zoom = zoom.clamp(0.25, 4.0)
+                    }
+                    SettingChange::CustomPan(x, y) => {
+                        self.settings.custom_pan = (x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0))
+                    }
                     SettingChange::DecoderBackend(backend) => {
                         self.settings.decoder_backend = backend
                     }
+                    SettingChange::AudioDecoder(backend) => {
+                        self.settings.audio_decoder = backend;
+                    }
                     SettingChange::ColorQuality(quality) => {
                         self.settings.color_quality = quality;
                         // Auto-switch codec based on color quality requirements
@@ -479,9 +729,125 @@ impl App {
                             }
                         }
                     }
+                    SettingChange::ToneMapper(mapper) => self.settings.tone_mapper = mapper,
+                    SettingChange::HdrTargetNits(nits) => {
+                        self.settings.hdr_target_nits = nits.clamp(80.0, 500.0)
+                    }
+                    SettingChange::HdrPeakNits(nits) => {
+                        self.settings.hdr_peak_nits = nits.clamp(400.0, 4000.0)
+                    }
+                    SettingChange::HdrSaturation(saturation) => {
+                        self.settings.hdr_saturation = saturation.clamp(0.0, 2.0)
+                    }
+                    SettingChange::HdrOutput(enabled) => self.settings.hdr_output = enabled,
+                    SettingChange::ColorAdjustments(adjustments) => {
+                        self.settings.color_adjustments = adjustments.clamped();
+                    }
+                    SettingChange::GameColorAdjustments(game_id, adjustments) => {
+                        self.settings
+                            .game_color_adjustments
+                            .insert(game_id, adjustments.clamped());
+                    }
+                    SettingChange::ResetGameColorAdjustments(game_id) => {
+                        self.settings.game_color_adjustments.remove(&game_id);
+                    }
                     SettingChange::ClipboardPasteEnabled(enabled) => {
                         self.settings.clipboard_paste_enabled = enabled;
                     }
+                    SettingChange::QueueNotifications(enabled) => {
+                        self.settings.queue_notifications = enabled;
+                    }
+                    SettingChange::QueueNotifySound(enabled) => {
+                        self.settings.queue_notify_sound = enabled;
+                    }
+                    SettingChange::QueueNotifyThresholds(mut thresholds) => {
+                        thresholds.sort_unstable_by(|a, b| b.cmp(a));
+                        thresholds.dedup();
+                        self.settings.queue_notify_thresholds = thresholds;
+                    }
+                    SettingChange::PlaytimeWarnings(enabled) => {
+                        self.settings.playtime_warnings = enabled;
+                    }
+                    SettingChange::PlaytimeWarningMinutes(mut minutes) => {
+                        minutes.sort_unstable_by(|a, b| b.cmp(a));
+                        minutes.dedup();
+                        self.settings.playtime_warning_minutes = minutes;
+                    }
+                    SettingChange::SessionLimit(minutes) => {
+                        self.settings.session_limit_minutes = minutes;
+                    }
+                    SettingChange::QueueProvider(provider) => {
+                        self.settings.queue_provider = provider;
+                        // Next fetch goes to the new source
+                        self.queue_servers.clear();
+                        self.queue_last_fetch =
+                            std::time::Instant::now() - std::time::Duration::from_secs(60);
+                    }
+                    SettingChange::QueueCustomUrl(url) => {
+                        self.settings.queue_custom_url = Some(url).filter(|u| !u.is_empty());
+                        self.queue_last_fetch =
+                            std::time::Instant::now() - std::time::Duration::from_secs(60);
+                    }
+                    SettingChange::QueueCustomMappingUrl(url) => {
+                        self.settings.queue_custom_mapping_url =
+                            Some(url).filter(|u| !u.is_empty());
+                        self.queue_last_fetch =
+                            std::time::Instant::now() - std::time::Duration::from_secs(60);
+                    }
+                    SettingChange::QueueRace(enabled) => {
+                        self.settings.queue_race_enabled = enabled;
+                    }
+                    SettingChange::QueueRaceZones(zones) => {
+                        self.settings.queue_race_zones =
+                            zones.clamp(2, queue_race::MAX_RACE_ZONES as u32);
+                    }
+                    SettingChange::QueueRaceTimeout(secs) => {
+                        self.settings.queue_race_timeout_secs = secs.clamp(30, 900);
+                    }
+                    SettingChange::ReplayBuffer(enabled) => {
+                        self.settings.replay_buffer_enabled = enabled;
+                    }
+                    SettingChange::ReplayDuration(secs) => {
+                        self.settings.replay_buffer_secs = secs;
+                    }
+                    SettingChange::ScreenshotHdr16Bit(enabled) => {
+                        self.settings.screenshot_hdr_16bit = enabled;
+                    }
+                    SettingChange::SessionLog(enabled) => {
+                        self.settings.session_log_enabled = enabled;
+                    }
+                    SettingChange::SessionLogFormat(format) => {
+                        self.settings.session_log_format = format;
+                    }
+                    SettingChange::ServerScoring(scoring) => {
+                        self.settings.server_scoring = scoring.clamped();
+                        if self.auto_server_selection {
+                            self.select_best_server();
+                        }
+                    }
+                    SettingChange::PreferredRegion(region) => {
+                        self.settings.preferred_region = region;
+                        if self.auto_server_selection {
+                            self.select_best_server();
+                        }
+                    }
+                    SettingChange::PingMode(mode) => self.settings.ping_mode = mode,
+                    SettingChange::PingSamples(samples) => {
+                        self.settings.ping_samples = samples.clamp(1, 20);
+                    }
+                    SettingChange::Proxy(proxy) => {
+                        let proxy = proxy.trim();
+                        self.settings.proxy = (!proxy.is_empty()).then(|| proxy.to_string());
+                        api::http::configure(&self.settings);
+                    }
+                    SettingChange::CaCertificates(paths) => {
+                        self.settings.ca_certificates = paths;
+                        api::http::configure(&self.settings);
+                    }
+                    SettingChange::HttpTimeout(secs) => {
+                        self.settings.http_timeout_secs = secs;
+                        api::http::configure(&self.settings);
+                    }
                 }
                 self.save_settings();
             }
@@ -577,13 +943,16 @@ impl App {
                 self.settings.auto_server_selection = enabled;
                 self.save_settings();
                 if enabled {
-                    // Auto-select best server based on ping
+                    // Auto-select the best-scoring server
                     self.select_best_server();
                 }
             }
             UiAction::StartPingTest => {
                 self.start_ping_test();
             }
+            UiAction::ProbeDecoders => {
+                crate::media::start_decoder_probe(&self.runtime, true);
+            }
             UiAction::ToggleSettingsModal => {
                 self.show_settings_modal = !self.show_settings_modal;
                 // Load servers when opening settings if not loaded
@@ -770,6 +1139,18 @@ impl App {
         });
     }
 
+    /// Called after a frame was presented (frame pacing tracks vsync phase)
+    pub fn on_frame_presented(&mut self, render_time: std::time::Duration) {
+        let now = std::time::Instant::now();
+        if let Some(ref mut pacer) = self.frame_pacer {
+            pacer.on_present(now);
+        }
+
+        let render_time_ms = render_time.as_secs_f32() * 1000.0;
+        self.stats.render_time_ms = render_time_ms;
+        self.stats_history.record(StatsMetric::RenderTime, now, render_time_ms);
+    }
+
     /// Update application state (called each frame)
     pub fn update(&mut self) {
         // Track render FPS - we'll increment frame count only when we get a new video frame
@@ -829,7 +1210,18 @@ impl App {
 
         // Check for new video frames from shared frame holder
         if let Some(ref shared) = self.shared_frame {
-            if let Some(frame) = shared.read() {
+            // Paced mode queues frames and releases them on their refresh slot
+            let next_frame = match self.frame_pacer {
+                Some(ref mut pacer) => {
+                    let now = std::time::Instant::now();
+                    if let Some(frame) = shared.read() {
+                        pacer.push(frame, now);
+                    }
+                    pacer.next_frame(now)
+                }
+                None => shared.read(),
+            };
+            if let Some(frame) = next_frame {
                 // Only log the first frame (when current_frame is None)
                 if self.current_frame.is_none() {
                     log::info!(
@@ -866,6 +1258,11 @@ impl App {
                     self.stats.resolution = new_res;
                 }
 
+                if self.screenshot_pending {
+                    self.screenshot_pending = false;
+                    self.capture_screenshot(&frame);
+                }
+
                 self.current_frame = Some(frame);
                 // Increment render frame count only when we get a new video frame
                 // This ensures render FPS matches decode FPS
@@ -873,19 +1270,61 @@ impl App {
             }
         }
 
+        // Check for notices from background capture work
+        while let Ok(notice) = self.capture_notice_rx.try_recv() {
+            self.show_capture_notice(&notice);
+        }
+
+        // Subscription info from a background fetch (also refreshes the playtime countdown)
+        while let Ok(sub) = self.subscription_rx.try_recv() {
+            if self.auth_tokens.is_none() {
+                continue; // Logged out meanwhile
+            }
+            if let Some(tracker) = self.playtime.as_mut() {
+                tracker.set_account_remaining(playtime::account_remaining(&sub), now);
+            }
+            self.subscription = Some(sub);
+        }
+        self.update_playtime(now);
+        self.update_launch_recovery(now);
+
+        // Note how the stream ended for the session history
+        while let Ok((session_id, exit)) = self.stream_exit_rx.try_recv() {
+            if let Some(active) = self
+                .active_session
+                .as_mut()
+                .filter(|a| a.session_id() == session_id)
+            {
+                active.set_exit(exit);
+            }
+        }
+
         // Check for stats updates
         if let Some(ref mut rx) = self.stats_rx {
             while let Ok(mut stats) = rx.try_recv() {
                 // Preserve render_fps from our local tracking
                 stats.render_fps = self.stats.render_fps;
                 stats.frames_rendered = self.stats.frames_rendered;
+                stats.render_time_ms = self.stats.render_time_ms;
                 // Preserve resolution from actual decoded frames (more accurate than SDP)
                 if !self.stats.resolution.is_empty() {
                     stats.resolution = self.stats.resolution.clone();
                 }
+                self.stats_history.record_stats(&stats, now);
+                self.session_quality.add(&stats);
+                if let Some(active) = self.active_session.as_mut() {
+                    active.add_stats(&stats);
+                }
                 self.stats = stats;
             }
         }
+        if let Some(ref pacer) = self.frame_pacer {
+            let pacing = pacer.stats();
+            self.stats.frame_pacing = true;
+            self.stats.frames_presented = pacing.presented;
+            self.stats.frames_repeated = pacing.repeated;
+            self.stats.frames_skipped = pacing.skipped;
+        }
 
         // Update cached providers
         let cached = auth::get_cached_providers();
@@ -1165,9 +1604,18 @@ impl App {
 
         // Clear current subscription so update loop will reload from cache after fetch completes
         self.subscription = None;
+        self.spawn_subscription_fetch();
+    }
 
-        let token = self.auth_tokens.as_ref().unwrap().jwt().to_string();
-        let user_id = self.auth_tokens.as_ref().unwrap().user_id().to_string();
+    /// Fetch subscription info in the background; `update` picks up the result
+    fn spawn_subscription_fetch(&mut self) {
+        let Some(tokens) = &self.auth_tokens else {
+            return;
+        };
+        let token = tokens.jwt().to_string();
+        let user_id = tokens.user_id().to_string();
+        let subscription_tx = self.subscription_tx.clone();
+        self.subscription_fetched_at = std::time::Instant::now();
 
         let runtime = self.runtime.clone();
         runtime.spawn(async move {
@@ -1181,6 +1629,7 @@ impl App {
                         sub.is_unlimited
                     );
                     cache::save_subscription_cache(&sub);
+                    let _ = subscription_tx.send(sub);
                 }
                 Err(e) => {
                     warn!("Failed to fetch subscription: {}", e);
@@ -1189,7 +1638,7 @@ impl App {
         });
     }
 
-    /// Fetch queue times from PrintedWaste API
+    /// Fetch queue times from the configured provider (PrintedWaste by default)
     pub fn fetch_queue_times(&mut self) {
         // Rate limit: only fetch if more than 30 seconds since last fetch
         const QUEUE_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(30);
@@ -1200,14 +1649,16 @@ impl App {
         self.queue_loading = true;
         self.queue_last_fetch = std::time::Instant::now();
 
+        let provider = crate::api::queue_data_provider(&self.settings);
         let runtime = self.runtime.clone();
         runtime.spawn(async move {
-            let client = reqwest::Client::new();
-            match crate::api::fetch_queue_servers(&client).await {
+            let client = api::http::client_builder().build().unwrap_or_default();
+            match provider.fetch_servers(&client).await {
                 Ok(servers) => {
                     info!(
-                        "Fetched queue times for {} servers from PrintedWaste",
-                        servers.len()
+                        "Fetched queue times for {} servers from {}",
+                        servers.len(),
+                        provider.name()
                     );
                     cache::save_queue_cache(&servers);
                 }
@@ -1227,7 +1678,7 @@ impl App {
 
         // Spawn async task to fetch dynamic regions
         runtime.spawn(async move {
-            let client = reqwest::Client::new();
+            let client = api::http::client_builder().build().unwrap_or_default();
             let regions = api::fetch_dynamic_regions(&client, token.as_deref()).await;
 
             // Store the results for the main thread to pick up
@@ -1279,6 +1730,7 @@ impl App {
                 region: region.to_string(),
                 url: None,
                 ping_ms: None,
+                latency: None,
                 status: ServerStatus::Unknown,
             })
             .collect();
@@ -1351,6 +1803,7 @@ impl App {
                             region: region.to_string(),
                             url: Some(r.url.clone()),
                             ping_ms: None,
+                            latency: None,
                             status: ServerStatus::Unknown,
                         }
                     })
@@ -1410,75 +1863,55 @@ impl App {
         for server in &mut self.servers {
             server.status = ServerStatus::Testing;
             server.ping_ms = None;
+            server.latency = None;
         }
 
-        // Collect server info with URLs for pinging
-        let server_data: Vec<(String, Option<String>)> = self
+        // Hostnames from the zone URLs, or built from the zone ID
+        let targets: Vec<(String, String)> = self
             .servers
             .iter()
-            .map(|s| (s.id.clone(), s.url.clone()))
-            .collect();
-        let runtime = self.runtime.clone();
-
-        runtime.spawn(async move {
-            use futures_util::future::join_all;
-
-            // Create ping futures for all servers (run in parallel)
-            let ping_futures: Vec<_> = server_data
-                .into_iter()
-                .map(|(server_id, url_opt)| async move {
-                    // Extract hostname from URL or construct from server_id
-                    let hostname = if let Some(url) = url_opt {
+            .map(|s| {
+                let fallback = format!("{}.cloudmatchbeta.nvidiagrid.net", s.id);
+                let hostname = s
+                    .url
+                    .as_deref()
+                    .and_then(|url| {
                         url.trim_start_matches("https://")
                             .trim_start_matches("http://")
                             .split('/')
                             .next()
-                            .unwrap_or(&format!("{}.cloudmatchbeta.nvidiagrid.net", server_id))
-                            .to_string()
-                    } else {
-                        format!("{}.cloudmatchbeta.nvidiagrid.net", server_id)
-                    };
-
-                    // TCP ping with timeout (faster and more reliable than ICMP on Windows)
-                    let ping_result = Self::tcp_ping(&hostname).await;
-
-                    let (ping_ms, status) = match ping_result {
-                        Some(ms) => (Some(ms), ServerStatus::Online),
-                        None => (None, ServerStatus::Offline),
-                    };
-
-                    (server_id, ping_ms, status)
-                })
-                .collect();
+                            .filter(|host| !host.is_empty())
+                    })
+                    .map(str::to_string)
+                    .unwrap_or(fallback);
+                (s.id.clone(), hostname)
+            })
+            .collect();
+        let mode = self.settings.ping_mode;
+        let samples = self.settings.ping_samples;
+        let runtime = self.runtime.clone();
 
-            // Run all pings concurrently
-            let results: Vec<(String, Option<u32>, ServerStatus)> = join_all(ping_futures).await;
+        runtime.spawn(async move {
+            // Several samples per zone, a bounded number of zones at a time
+            let results: Vec<(String, Option<LatencyStats>, ServerStatus)> =
+                crate::utils::probe_hosts(targets, mode, samples)
+                    .await
+                    .into_iter()
+                    .map(|(server_id, latency)| {
+                        let status = if latency.is_some() {
+                            ServerStatus::Online
+                        } else {
+                            ServerStatus::Offline
+                        };
+                        (server_id, latency, status)
+                    })
+                    .collect();
 
             // Save results to cache
             cache::save_ping_results(&results);
         });
     }
 
-    /// Fast TCP ping - measures connection time to server (TLS handshake)
-    async fn tcp_ping(hostname: &str) -> Option<u32> {
-        use std::time::Instant;
-        use tokio::net::TcpStream;
-        use tokio::time::{timeout, Duration};
-
-        let addr = format!("{}:443", hostname);
-        let start = Instant::now();
-        // Short timeout for fast failure
-        let result = timeout(Duration::from_millis(1500), TcpStream::connect(&addr)).await;
-
-        match result {
-            Ok(Ok(_stream)) => {
-                let elapsed = start.elapsed().as_millis() as u32;
-                Some(elapsed)
-            }
-            _ => None,
-        }
-    }
-
     /// Load ping results from cache
     fn load_ping_results(&mut self) {
         if let Some(results) = cache::load_ping_results() {
@@ -1489,6 +1922,9 @@ impl App {
                             .get("ping_ms")
                             .and_then(|v| v.as_u64())
                             .map(|v| v as u32);
+                        server.latency = result
+                            .get("latency")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok());
                         server.status = match result.get("status").and_then(|v| v.as_str()) {
                             Some("Online") => ServerStatus::Online,
                             Some("Offline") => ServerStatus::Offline,
@@ -1522,22 +1958,23 @@ impl App {
         }
     }
 
-    /// Select the best server based on ping (lowest ping online server)
+    /// Select the best-scoring online server with a ping result
     fn select_best_server(&mut self) {
-        // Find the server with the lowest ping that is online
-        let best_server = self
-            .servers
-            .iter()
-            .enumerate()
-            .filter(|(_, s)| s.status == ServerStatus::Online && s.ping_ms.is_some())
-            .min_by_key(|(_, s)| s.ping_ms.unwrap_or(9999));
+        let scorer = ServerScorer::new(&self.settings, &self.zone_quality);
+        let best = scorer.best(
+            self.servers
+                .iter()
+                .filter(|s| s.status == ServerStatus::Online && s.ping_ms.is_some()),
+        );
 
-        if let Some((idx, server)) = best_server {
-            self.selected_server_index = idx;
+        if let Some((server, score)) = best {
+            if let Some(idx) = self.servers.iter().position(|s| s.id == server.id) {
+                self.selected_server_index = idx;
+            }
             info!(
-                "Auto-selected best server: {} ({}ms)",
+                "Auto-selected best server: {} ({})",
                 server.name,
-                server.ping_ms.unwrap_or(0)
+                score.explain()
             );
         }
     }
@@ -1560,26 +1997,22 @@ impl App {
             .iter()
             .map(|s| s.server_id.clone())
             .collect();
+        let mode = self.settings.ping_mode;
+        let samples = self.settings.ping_samples;
         let runtime = self.runtime.clone();
 
         runtime.spawn(async move {
-            use futures_util::future::join_all;
-
-            // Create ping futures for all queue servers (run in parallel)
-            let ping_futures: Vec<_> = server_ids
+            // Construct hostname from VPC ID (e.g., NP-AMS-07 -> np-ams-07.cloudmatchbeta.nvidiagrid.net)
+            let targets: Vec<(String, String)> = server_ids
                 .into_iter()
-                .map(|server_id| async move {
-                    // Construct hostname from VPC ID (e.g., NP-AMS-07 -> np-ams-07.cloudmatchbeta.nvidiagrid.net)
+                .map(|server_id| {
                     let hostname =
                         format!("{}.cloudmatchbeta.nvidiagrid.net", server_id.to_lowercase());
-
-                    let ping_result = Self::tcp_ping(&hostname).await;
-                    (server_id, ping_result)
+                    (server_id, hostname)
                 })
                 .collect();
 
-            // Run all pings concurrently
-            let results: Vec<(String, Option<u32>)> = join_all(ping_futures).await;
+            let results = crate::utils::probe_hosts(targets, mode, samples).await;
 
             // Save results to cache
             cache::save_queue_ping_results(&results);
@@ -1589,13 +2022,14 @@ impl App {
     /// Load queue ping results from cache
     fn load_queue_ping_results(&mut self) {
         if let Some(results) = cache::load_queue_ping_results() {
-            for (server_id, ping_ms) in results {
+            for (server_id, latency) in results {
                 if let Some(server) = self
                     .queue_servers
                     .iter_mut()
                     .find(|s| s.server_id == server_id)
                 {
-                    server.ping_ms = ping_ms;
+                    server.ping_ms = latency.map(|l| l.ping_ms());
+                    server.latency = latency;
                 }
             }
 
@@ -1607,7 +2041,13 @@ impl App {
     /// Launch a game session
     pub fn launch_game(&mut self, game: &GameInfo) {
         info!("Launching game: {} (ID: {})", game.title, game.id);
+        self.launch_recovery = None;
+        self.check_sessions_and_launch(game);
+    }
 
+    /// Offer the active session dialog if sessions are running, otherwise
+    /// start `game` (keeps any launch recovery state)
+    fn check_sessions_and_launch(&mut self, game: &GameInfo) {
         // Get token first
         let token = match &self.auth_tokens {
             Some(t) => t.jwt().to_string(),
@@ -1661,6 +2101,15 @@ impl App {
         self.state = AppState::Session;
         self.status_message = format!("Starting {}...", game.title);
         self.error_message = None;
+        self.session_error = None;
+        // Relaunches keep their retry counts
+        if self
+            .launch_recovery
+            .as_ref()
+            .is_none_or(|r| r.game.id != game.id)
+        {
+            self.launch_recovery = Some(recovery::LaunchRecovery::new(game.clone()));
+        }
         self.is_loading = true;
         self.last_poll_time = std::time::Instant::now() - POLL_INTERVAL;
 
@@ -1683,6 +2132,35 @@ impl App {
             .map(|s| s.id.clone())
             .unwrap_or_else(|| "eu-netherlands-south".to_string());
 
+        // Queue racing needs at least two pinged zones
+        let race_zones = if self.settings.queue_race_enabled {
+            queue_race::select_race_zones(
+                &self.servers,
+                Some(&zone),
+                self.settings.queue_race_zones as usize,
+            )
+        } else {
+            Vec::new()
+        };
+        if self.settings.queue_race_enabled && race_zones.len() < 2 {
+            info!(
+                "Queue racing needs ping results for at least two zones, using {}",
+                zone
+            );
+        }
+        let race = (race_zones.len() >= 2).then(|| QueueRace::new(&race_zones));
+        let race_timeout =
+            std::time::Duration::from_secs(self.settings.queue_race_timeout_secs as u64);
+
+        if let Err(e) = self.session_controller.begin(&zone) {
+            warn!("Not starting session: {}", e);
+            self.error_message = Some("A session is already in progress".to_string());
+            self.is_loading = false;
+            return;
+        }
+        self.queue_watch.start(&game.id, &game.title, &zone);
+        self.queue_race = race.clone();
+
         let is_install_to_play = game.is_install_to_play;
 
         let mut api_client = GfnApiClient::new();
@@ -1714,6 +2192,19 @@ impl App {
                 game_title, account_linked
             );
 
+            if let Some(race) = race {
+                race.run(
+                    &api_client,
+                    &app_id,
+                    &game_title,
+                    &settings,
+                    account_linked,
+                    race_timeout,
+                )
+                .await;
+                return;
+            }
+
             match api_client
                 .create_session(&app_id, &game_title, &settings, &zone, account_linked)
                 .await
@@ -1727,6 +2218,9 @@ impl App {
                 }
                 Err(e) => {
                     error!("Failed to create session: {}", e);
+                    if let Some(details) = recovery::session_error_details(&e) {
+                        cache::save_session_error_details(&details);
+                    }
                     cache::save_session_error(&format!("Failed to create session: {}", e));
                 }
             }
@@ -1745,9 +2239,11 @@ impl App {
 
         self.show_session_conflict = false;
         self.pending_game_launch = None;
+        self.launch_recovery = None;
         self.state = AppState::Session;
         self.status_message = "Resuming session...".to_string();
         self.error_message = None;
+        self.session_error = None;
         self.is_loading = true;
         self.last_poll_time = std::time::Instant::now() - POLL_INTERVAL;
 
@@ -1774,6 +2270,14 @@ impl App {
             }
         };
 
+        // Zone isn't known for resumed sessions; the claim goes to the server directly
+        if let Err(e) = self.session_controller.begin("") {
+            warn!("Not resuming session: {}", e);
+            self.error_message = Some("A session is already in progress".to_string());
+            self.is_loading = false;
+            return;
+        }
+
         let app_id = session_info.app_id.to_string();
         let settings = self.settings.clone();
 
@@ -1842,65 +2346,80 @@ impl App {
         });
     }
 
+    /// Follow a running queue race. Returns true while it's still undecided
+    /// or when it settled this frame.
+    fn poll_queue_race(&mut self) -> bool {
+        let Some(race) = self.queue_race.clone() else {
+            return false;
+        };
+
+        if let Some(error) = race.take_error() {
+            self.queue_race = None;
+            self.session_controller
+                .fail(&format!("Queue race failed: {}", error));
+            self.handle_session_events();
+            return true;
+        }
+
+        if let Some(session) = race.take_winner() {
+            self.queue_race = None;
+            info!("Queue race won by {}", session.zone);
+            self.queue_watch.set_zone(&session.zone);
+            // Normal polling takes over from here
+            if let Err(e) = self.session_controller.apply_update(session) {
+                warn!("Ignoring race winner: {}", e);
+            }
+            self.handle_session_events();
+            return true;
+        }
+
+        let racers = race.racers();
+        let field: Vec<String> = racers
+            .iter()
+            .map(|r| match &r.state {
+                SessionState::Requesting => format!("{} starting", r.name),
+                SessionState::InQueue { position, .. } => format!("{} #{}", r.name, position),
+                SessionState::Ready => format!("{} ready", r.name),
+                SessionState::Error(_) | SessionState::Terminated => format!("{} out", r.name),
+                _ => format!("{} setting up", r.name),
+            })
+            .collect();
+        self.status_message = format!("Racing {} zones: {}", racers.len(), field.join(" \u{b7} "));
+
+        // Notifications and title progress follow the best queue
+        let best = racers
+            .iter()
+            .filter(|r| r.is_alive())
+            .filter_map(|r| match r.state {
+                SessionState::InQueue { position, eta_secs } => Some((position, eta_secs)),
+                _ => None,
+            })
+            .min();
+        if let Some((position, eta_secs)) = best {
+            let thresholds = self.settings.queue_notify_thresholds.clone();
+            if let Some(note) = self.queue_watch.update(position, eta_secs, &thresholds) {
+                self.notify(note);
+            }
+        }
+        true
+    }
+
     /// Poll session state and update UI
     fn poll_session_status(&mut self) {
-        // First check cache for state updates (from in-flight or completed requests)
-        // First check cache for state updates (from in-flight or completed requests)
-        if let Some(session) = cache::load_session_cache() {
-            if session.state == SessionState::Ready {
-                // User requested: "make it pull few times before connecting to it so you can get the candidates"
-                // We delay streaming start until we've polled a few times in Ready state
-                if self.session_ready_poll_count < 3 {
-                    self.status_message = format!(
-                        "Session ready, finalizing connection ({}/3)...",
-                        self.session_ready_poll_count + 1
-                    );
-                    // Don't return, allow fall-through to polling logic
-                } else {
-                    info!(
-                        "Session ready! GPU: {:?}, Server: {}",
-                        session.gpu_type, session.server_ip
-                    );
-
-                    // Update status message
-                    if let Some(gpu) = &session.gpu_type {
-                        self.status_message = format!("Connecting to GPU: {}", gpu);
-                    } else {
-                        self.status_message =
-                            format!("Connecting to server: {}", session.server_ip);
-                    }
+        if self.poll_queue_race() {
+            return;
+        }
 
-                    cache::clear_session_cache();
-                    self.start_streaming(session);
-                    return;
-                }
-            } else if let SessionState::InQueue { position, eta_secs } = session.state {
-                self.status_message = format!("Queue position: {} (ETA: {}s)", position, eta_secs);
-            } else if let SessionState::WatchingAds {
-                remaining_secs,
-                total_secs,
-            } = session.state
-            {
-                self.ads_required = true;
-                self.ads_remaining_secs = remaining_secs;
-                self.ads_total_secs = total_secs;
-                self.status_message =
-                    format!("Waiting for ads... (~{}s remaining)", remaining_secs);
-            } else if let SessionState::Error(ref msg) = session.state {
-                self.error_message = Some(msg.clone());
-                self.is_loading = false;
+        // Feed the latest state (from in-flight or completed requests) to the lifecycle
+        if let Some(session) = cache::load_session_cache() {
+            if let Err(e) = self.session_controller.apply_update(session) {
+                warn!("Ignoring session update: {}", e);
                 cache::clear_session_cache();
-                return;
-            } else if session.state == SessionState::Connecting {
-                self.status_message = "Connecting to server...".to_string();
-            } else if session.state == SessionState::CleaningUp {
-                self.status_message = "Cleaning up previous session...".to_string();
-            } else if session.state == SessionState::WaitingForStorage {
-                self.status_message = "Waiting for storage to be ready...".to_string();
-            } else {
-                self.status_message = "Setting up session...".to_string();
             }
         }
+        if self.handle_session_events() {
+            return;
+        }
 
         // Rate limit polling - only poll every POLL_INTERVAL (2 seconds)
         let now = std::time::Instant::now();
@@ -1908,68 +2427,66 @@ impl App {
             return;
         }
 
-        if let Some(session) = cache::load_session_cache() {
-            let mut should_poll = matches!(
-                session.state,
-                SessionState::Requesting
-                    | SessionState::Launching
-                    | SessionState::Connecting
-                    | SessionState::CleaningUp
-                    | SessionState::WaitingForStorage
-                    | SessionState::InQueue { .. }
-                    | SessionState::WatchingAds { .. }
-            );
+        if self.session_controller.should_poll() {
+            // Update timestamp to rate limit next poll
+            self.last_poll_time = now;
 
-            // Also poll if Ready but count < 3
-            if session.state == SessionState::Ready && self.session_ready_poll_count < 3 {
-                should_poll = true;
-                // Increment poll count here, as we are about to poll
-                self.session_ready_poll_count += 1;
-            }
-
-            if should_poll {
-                // Update timestamp to rate limit next poll
-                self.last_poll_time = now;
+            let token = match &self.auth_tokens {
+                Some(t) => t.jwt().to_string(),
+                None => return,
+            };
 
-                let token = match &self.auth_tokens {
-                    Some(t) => t.jwt().to_string(),
-                    None => return,
-                };
+            let Some(session) = self.session_controller.session() else {
+                return;
+            };
+            let session_id = session.session_id.clone();
+            let zone = session.zone.clone();
+            let server_ip = if session.server_ip.is_empty() {
+                None
+            } else {
+                Some(session.server_ip.clone())
+            };
 
-                let session_id = session.session_id.clone();
-                let zone = session.zone.clone();
-                let server_ip = if session.server_ip.is_empty() {
-                    None
-                } else {
-                    Some(session.server_ip.clone())
-                };
+            // While ready, "pull a few times before connecting so you can get the candidates"
+            self.session_controller.note_poll();
 
-                let mut api_client = GfnApiClient::new();
-                api_client.set_access_token(token);
+            let mut api_client = GfnApiClient::new();
+            api_client.set_access_token(token);
 
-                let runtime = self.runtime.clone();
-                runtime.spawn(async move {
-                    match api_client
-                        .poll_session(&session_id, &zone, server_ip.as_deref())
-                        .await
-                    {
-                        Ok(updated_session) => {
-                            info!("Session poll: state={:?}", updated_session.state);
-                            cache::save_session_cache(&updated_session);
-                        }
-                        Err(e) => {
-                            error!("Session poll failed: {}", e);
-                        }
+            let runtime = self.runtime.clone();
+            runtime.spawn(async move {
+                match api_client
+                    .poll_session(&session_id, &zone, server_ip.as_deref())
+                    .await
+                {
+                    Ok(updated_session) => {
+                        info!("Session poll: state={:?}", updated_session.state);
+                        cache::save_session_cache(&updated_session);
                     }
-                });
+                    // CloudMatch rejected the session: fail it with the details.
+                    // Other errors (network, HTTP status) are retried next poll.
+                    Err(e) => match e.downcast_ref::<api::SessionError>() {
+                        Some(details) => {
+                            error!("Session failed while polling: {}", e);
+                            cache::save_session_error_details(details);
+                            cache::save_session_error(&format!("Session failed: {}", e));
+                        }
+                        None => error!("Session poll failed: {}", e),
+                    },
+                }
+            });
+
+            if self.handle_session_events() {
+                return;
             }
         }
 
         // Check for session errors
         if let Some(error) = cache::load_session_error() {
-            self.error_message = Some(error);
-            self.is_loading = false;
+            self.session_error = cache::load_session_error_details();
             cache::clear_session_error();
+            self.session_controller.fail(&error);
+            self.handle_session_events();
         }
 
         // Check for popup game details updates
@@ -1987,6 +2504,99 @@ impl App {
         }
     }
 
+    /// Apply session lifecycle events to the UI.
+    /// Returns true once the session has left setup (streaming or failed).
+    fn handle_session_events(&mut self) -> bool {
+        let mut done = false;
+        for event in self.session_controller.drain_events() {
+            match event {
+                LifecycleEvent::StateChanged(state) => match state {
+                    SessionState::InQueue { position, eta_secs } => {
+                        self.status_message =
+                            format!("Queue position: {} (ETA: {}s)", position, eta_secs);
+                        if let Some(estimate) = self.estimate_queue_wait(position) {
+                            self.status_message.push_str(&format!(
+                                " - usually ~{} from here",
+                                queue_watch::format_wait(estimate)
+                            ));
+                        }
+
+                        let thresholds = self.settings.queue_notify_thresholds.clone();
+                        if let Some(note) = self.queue_watch.update(position, eta_secs, &thresholds)
+                        {
+                            self.notify(note);
+                        }
+                    }
+                    SessionState::WatchingAds {
+                        remaining_secs,
+                        total_secs,
+                    } => {
+                        self.ads_required = true;
+                        self.ads_remaining_secs = remaining_secs;
+                        self.ads_total_secs = total_secs;
+                        self.status_message =
+                            format!("Waiting for ads... (~{}s remaining)", remaining_secs);
+                    }
+                    SessionState::Connecting => {
+                        self.status_message = "Connecting to server...".to_string();
+                    }
+                    SessionState::CleaningUp => {
+                        self.status_message = "Cleaning up previous session...".to_string();
+                    }
+                    SessionState::WaitingForStorage => {
+                        self.status_message = "Waiting for storage to be ready...".to_string();
+                    }
+                    // Ready is reported through Finalizing
+                    SessionState::Ready => {}
+                    _ => {
+                        self.status_message = "Setting up session...".to_string();
+                    }
+                },
+                LifecycleEvent::Finalizing(poll) => {
+                    self.status_message = format!(
+                        "Session ready, finalizing connection ({}/{})...",
+                        poll,
+                        session_controller::READY_POLLS
+                    );
+                }
+                LifecycleEvent::ReadyToStream(session) => {
+                    info!(
+                        "Session ready! GPU: {:?}, Server: {}",
+                        session.gpu_type, session.server_ip
+                    );
+
+                    // Update status message
+                    if let Some(gpu) = &session.gpu_type {
+                        self.status_message = format!("Connecting to GPU: {}", gpu);
+                    } else {
+                        self.status_message =
+                            format!("Connecting to server: {}", session.server_ip);
+                    }
+
+                    if let Some(note) = self.queue_watch.ready() {
+                        self.notify(note);
+                    }
+                    self.queue_history = queue_watch::load_history();
+
+                    cache::clear_session_cache();
+                    self.launch_recovery = None;
+                    self.start_streaming(*session);
+                    done = true;
+                }
+                LifecycleEvent::Failed(message) => {
+                    self.queue_watch.abandon();
+                    self.error_message = Some(message);
+                    self.is_loading = false;
+                    cache::clear_session_cache();
+                    self.recover_launch();
+                    done = true;
+                }
+                LifecycleEvent::Terminated => {}
+            }
+        }
+        done
+    }
+
     /// Start streaming once session is ready
     pub fn start_streaming(&mut self, session: SessionInfo) {
         info!("Starting streaming to {}", session.server_ip);
@@ -1997,8 +2607,9 @@ impl App {
         self.cursor_captured = true;
         self.is_loading = false;
 
-        // Reset session ready poll count for this new session
-        self.session_ready_poll_count = 0;
+        if let Err(e) = self.session_controller.mark_streaming() {
+            warn!("{}", e);
+        }
 
         // Initialize session timing for proper input timestamps
         // This must be called BEFORE any input events are sent
@@ -2023,6 +2634,11 @@ impl App {
         // No buffering - decoder writes latest frame, renderer reads it immediately
         let shared_frame = Arc::new(SharedFrame::new());
         self.shared_frame = Some(shared_frame.clone());
+        // Refresh rate is refined from the monitor and present timing
+        self.frame_pacer = self.settings.frame_pacing.then(|| {
+            info!("Frame pacing enabled");
+            FramePacer::new(60.0)
+        });
 
         // Stats channel (small buffer is fine for stats)
         let (stats_tx, stats_rx) = mpsc::channel(8);
@@ -2032,6 +2648,14 @@ impl App {
         );
 
         self.stats_rx = Some(stats_rx);
+        self.stats_history.clear();
+        self.session_quality = Default::default();
+        self.active_session = Some(session_history::ActiveSession::start(
+            &session,
+            self.selected_game.as_ref(),
+            &self.settings,
+        ));
+        self.start_playtime_tracker();
 
         // Create input handler with clean state
         let input_handler = Arc::new(InputHandler::new());
@@ -2041,26 +2665,41 @@ impl App {
 
         // Clone settings for the async task
         let settings = self.settings.clone();
+        let recorder = self.recorder.clone();
+        recorder.set_replay_buffer(
+            settings
+                .replay_buffer_enabled
+                .then(|| std::time::Duration::from_secs(settings.replay_buffer_secs as u64)),
+            settings.max_bitrate_mbps,
+        );
+        let session_log = self.create_session_log(&session);
+        let exit_tx = self.stream_exit_tx.clone();
 
         // Spawn the streaming task
         let runtime = self.runtime.clone();
         runtime.spawn(async move {
             use crate::webrtc::StreamingResult;
+            use session_history::SessionExit;
 
-            match crate::webrtc::run_streaming(
+            let session_id = session.session_id.clone();
+            let exit = match crate::webrtc::run_streaming(
                 session.clone(),
                 settings.clone(),
                 shared_frame.clone(),
                 stats_tx.clone(),
                 input_handler.clone(),
+                recorder.clone(),
+                session_log.clone(),
             )
             .await
             {
                 StreamingResult::Normal => {
                     info!("Streaming ended normally");
+                    SessionExit::Ended
                 }
                 StreamingResult::Error(e) => {
                     error!("Streaming error: {}", e);
+                    SessionExit::Error(e)
                 }
                 StreamingResult::SsrcChangeDetected { stall_duration_ms } => {
                     // SSRC change detected - attempt auto-reconnect
@@ -2069,6 +2708,9 @@ impl App {
                         stall_duration_ms
                     );
 
+                    // RTP timestamps restart with the new stream, finish the file here
+                    recorder.stop();
+
                     // Brief delay to let resources clean up
                     tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
@@ -2092,14 +2734,18 @@ impl App {
                         new_shared_frame,
                         new_stats_tx,
                         new_input_handler,
+                        recorder.clone(),
+                        session_log,
                     )
                     .await
                     {
                         StreamingResult::Normal => {
                             info!("Reconnected stream ended normally");
+                            SessionExit::Ended
                         }
                         StreamingResult::Error(e) => {
                             error!("Reconnected stream error: {}", e);
+                            SessionExit::Error(e)
                         }
                         StreamingResult::SsrcChangeDetected { stall_duration_ms } => {
                             // Second SSRC change - give up and let user know
@@ -2107,11 +2753,45 @@ impl App {
                                 "Second SSRC change detected after {}ms. Auto-reconnect failed. Please restart the session manually.",
                                 stall_duration_ms
                             );
+                            SessionExit::StreamChanged
                         }
                     }
                 }
+            };
+            let _ = exit_tx.send((session_id, exit));
+        });
+    }
+
+    /// Open the per-session performance log if enabled in settings
+    fn create_session_log(&self, session: &SessionInfo) -> Option<Arc<SessionLog>> {
+        if !self.settings.session_log_enabled {
+            return None;
+        }
+
+        let format = self.settings.session_log_format;
+        let title = self.selected_game.as_ref().map(|g| g.title.as_str());
+        let path = crate::media::capture_file_path(
+            &self.settings.session_logs_path(),
+            title,
+            format.extension(),
+        );
+        let log = match SessionLog::create(path, format) {
+            Ok(log) => log,
+            Err(e) => {
+                warn!("Failed to create session log: {:#}", e);
+                return None;
             }
+        };
+
+        log.event(SessionEvent::SessionStart {
+            game: title.unwrap_or("Unknown").to_string(),
+            zone: session.zone.clone(),
+            codec: self.settings.codec.as_str().to_string(),
+            resolution: self.settings.resolution.clone(),
+            fps: self.settings.fps,
+            decoder: format!("{:?}", self.settings.decoder_backend),
         });
+        Some(Arc::new(log))
     }
 
     /// Terminate current session via API and stop streaming
@@ -2160,6 +2840,11 @@ impl App {
     pub fn stop_streaming(&mut self) {
         info!("Stopping streaming");
 
+        // Finalize any in-progress recording and drop the replay buffer
+        self.recorder.stop();
+        self.recorder.clear_stream();
+        self.screenshot_pending = false;
+
         // Clear session cache first to prevent stale session data
         cache::clear_session_cache();
 
@@ -2174,6 +2859,30 @@ impl App {
         #[cfg(any(target_os = "windows", target_os = "macos"))]
         crate::input::clear_raw_input_sender();
 
+        // Remember how the zone performed for server scoring, under both the
+        // zone ID (zone list) and the server's VPC ID (queue data)
+        if let Some(session) = &self.session {
+            let quality = std::mem::take(&mut self.session_quality);
+            let mut recorded = false;
+            for key in server_scoring::quality_keys(session) {
+                recorded |=
+                    server_scoring::record_session_quality(&mut self.zone_quality, key, &quality);
+            }
+            if recorded {
+                server_scoring::save_zone_quality(&self.zone_quality);
+            }
+        }
+
+        if let Some(active) = self.active_session.take() {
+            let record = active.finish();
+            session_history::append_history(&record);
+            self.session_history.push(record);
+        }
+        // Pick up the hours this session used
+        if self.playtime.take().is_some() {
+            self.spawn_subscription_fetch();
+        }
+
         self.cursor_captured = false;
         self.state = AppState::Games;
         self.streaming_session = None;
@@ -2181,13 +2890,21 @@ impl App {
         self.input_handler = None;
         self.current_frame = None;
         self.shared_frame = None;
+        self.frame_pacer = None;
         self.stats_rx = None;
         self.selected_game = None;
+        self.show_color_overlay = false;
         self.is_loading = false;
         self.error_message = None;
+        self.session_error = None;
+        self.launch_recovery = None;
 
-        // Reset session ready poll count for next session
-        self.session_ready_poll_count = 0;
+        if let Some(race) = self.queue_race.take() {
+            race.cancel();
+        }
+        self.session_controller.terminate();
+        self.session_controller.drain_events();
+        self.queue_watch.abandon();
 
         // Reset ads state
         self.ads_required = false;
@@ -2197,11 +2914,261 @@ impl App {
         self.status_message = "Stream ended".to_string();
     }
 
+    /// Start the countdown to the session limit / end of account time
+    fn start_playtime_tracker(&mut self) {
+        let now = std::time::Instant::now();
+        let session_limit = match self.settings.session_limit_minutes {
+            0 => {
+                let tier = self
+                    .subscription
+                    .as_ref()
+                    .map(|s| s.membership_tier.as_str())
+                    .or_else(|| self.user_info.as_ref().map(|u| u.membership_tier.as_str()))
+                    .unwrap_or("FREE");
+                playtime::tier_session_limit(tier)
+            }
+            minutes => Some(std::time::Duration::from_secs(minutes as u64 * 60)),
+        };
+        let mut tracker = playtime::PlaytimeTracker::new(
+            now,
+            session_limit,
+            &self.settings.playtime_warning_minutes,
+        );
+        tracker.estimate_account_remaining(
+            self.subscription.as_ref().and_then(playtime::account_remaining),
+            now,
+        );
+        if let Some((left, kind)) = tracker.remaining(now) {
+            info!(
+                "Session cut off in {} ({})",
+                playtime::format_countdown(left),
+                kind.label()
+            );
+        }
+        self.playtime = Some(tracker);
+        // The cached hours may predate earlier sessions
+        self.spawn_subscription_fetch();
+    }
+
+    /// Raise playtime warnings and keep account time fresh while streaming
+    fn update_playtime(&mut self, now: std::time::Instant) {
+        let Some(tracker) = self.playtime.as_mut() else {
+            return;
+        };
+        if let Some(warning) = tracker.check(now) {
+            info!("Playtime: {} ({})", warning.title(), warning.kind.label());
+            self.show_capture_notice(&format!(
+                "{} ({})",
+                warning.title(),
+                warning.kind.label()
+            ));
+            if self.settings.playtime_warnings {
+                crate::utils::desktop_notification(
+                    &warning.title(),
+                    warning.body(),
+                    self.settings.queue_notify_sound,
+                );
+            }
+        }
+        if self.subscription_fetched_at.elapsed() >= playtime::SUBSCRIPTION_REFRESH {
+            self.spawn_subscription_fetch();
+        }
+    }
+
+    /// Time left before the session is cut off, once the in-stream countdown should show
+    pub fn playtime_countdown(&self) -> Option<(std::time::Duration, playtime::LimitKind)> {
+        let tracker = self.playtime.as_ref()?;
+        let now = std::time::Instant::now();
+        tracker
+            .show_countdown(now)
+            .then(|| tracker.remaining(now))
+            .flatten()
+    }
+
+    /// Pick and start the recovery step for a failed launch
+    fn recover_launch(&mut self) {
+        let can_refresh_login = self.auth_tokens.as_ref().is_some_and(|t| t.can_refresh());
+        let Some(recovery) = self.launch_recovery.as_mut() else {
+            return;
+        };
+        let step = recovery.on_failure(
+            self.session_error.as_ref(),
+            can_refresh_login,
+            std::time::Instant::now(),
+        );
+        info!("Launch recovery for {}: {:?}", recovery.game.title, step);
+
+        match step {
+            recovery::RecoveryStep::RefreshLogin => {
+                let Some(refresh_token) = self
+                    .auth_tokens
+                    .as_ref()
+                    .and_then(|t| t.refresh_token.clone())
+                else {
+                    return;
+                };
+                let login_refresh_tx = self.login_refresh_tx.clone();
+                self.runtime.spawn(async move {
+                    let result = auth::refresh_token(&refresh_token).await;
+                    if let Ok(tokens) = &result {
+                        cache::save_tokens(tokens);
+                    }
+                    let _ = login_refresh_tx.send(result.map_err(|e| e.to_string()));
+                });
+            }
+            recovery::RecoveryStep::LinkSteam => {
+                // Steam Guard waits on the user's phone
+                self.attention_requested = true;
+            }
+            recovery::RecoveryStep::ResolveConflict => {
+                // Back to the games screen, where the active session dialog
+                // shows. If the listing misses the session this relaunches,
+                // still counting towards the recovery's limits.
+                let game = recovery.game.clone();
+                self.state = AppState::Games;
+                self.error_message = None;
+                self.check_sessions_and_launch(&game);
+            }
+            _ => {}
+        }
+    }
+
+    /// Relaunch when a retry is due, or once the login has been refreshed
+    fn update_launch_recovery(&mut self, now: std::time::Instant) {
+        while let Ok(result) = self.login_refresh_rx.try_recv() {
+            if self.auth_tokens.is_none() {
+                continue; // Logged out meanwhile
+            }
+            match result {
+                Ok(tokens) => {
+                    info!("Login refreshed, relaunching");
+                    self.api_client.set_access_token(tokens.jwt().to_string());
+                    self.auth_tokens = Some(tokens);
+                    self.relaunch();
+                }
+                Err(e) => {
+                    warn!("Login refresh for relaunch failed: {}", e);
+                    if let Some(recovery) = self.launch_recovery.as_mut() {
+                        recovery.login_refresh_failed();
+                    }
+                }
+            }
+        }
+
+        if self
+            .launch_recovery
+            .as_ref()
+            .is_some_and(|r| r.relaunch_due(now))
+        {
+            self.relaunch();
+        }
+    }
+
+    /// Launch the recovering game again (scheduled or "Try Again")
+    fn relaunch(&mut self) {
+        if self.state != AppState::Session {
+            return;
+        }
+        let Some(recovery) = self
+            .launch_recovery
+            .as_mut()
+            .filter(|r| r.step().is_some())
+        else {
+            return;
+        };
+        recovery.relaunching();
+        let game = recovery.game.clone();
+        info!("Relaunching {}", game.title);
+        self.start_new_session(&game);
+    }
+
+    /// Failure details for the session screen, None unless a session failed
+    pub fn recovery_prompt(&self) -> Option<recovery::RecoveryPrompt> {
+        let message = self.error_message.as_deref()?;
+        let recovery = self.launch_recovery.as_ref();
+        let zone = self
+            .session_controller
+            .session()
+            .map(|s| s.zone.as_str());
+        Some(recovery::RecoveryPrompt {
+            status: recovery.and_then(|r| r.status(std::time::Instant::now())),
+            offer: recovery.and_then(|r| r.offer()),
+            diagnostics: recovery::diagnostics_report(
+                message,
+                self.session_error.as_ref(),
+                self.selected_game.as_ref(),
+                zone,
+                recovery.map_or(0, |r| r.failures()),
+            ),
+        })
+    }
+
+    /// Typical wait from `position` in the current zone, from past queues
+    fn estimate_queue_wait(&self, position: u32) -> Option<u32> {
+        let zone = self.queue_watch.zone()?;
+        queue_watch::estimate_wait_secs(&self.queue_history, zone, position)
+    }
+
+    /// Show a queue notification (if enabled) and flag the window
+    fn notify(&mut self, note: queue_watch::QueueNotification) {
+        info!("{}: {}", note.title, note.body);
+        if !self.settings.queue_notifications {
+            return;
+        }
+        crate::utils::desktop_notification(
+            &note.title,
+            &note.body,
+            self.settings.queue_notify_sound,
+        );
+        self.attention_requested = true;
+    }
+
+    /// Whether the window should request attention (clears the flag)
+    pub fn take_attention_request(&mut self) -> bool {
+        std::mem::take(&mut self.attention_requested)
+    }
+
+    /// Window title, with queue position and progress while queued
+    pub fn window_title(&self) -> String {
+        if self.state == AppState::Session {
+            if let Some((position, progress)) = self.queue_watch.progress() {
+                return format!("[#{} \u{b7} {:.0}%] OpenNow", position, progress * 100.0);
+            }
+        }
+        "OpenNow".to_string()
+    }
+
     /// Toggle stats overlay
     pub fn toggle_stats(&mut self) {
         self.show_stats = !self.show_stats;
     }
 
+    /// Toggle rolling graphs in the stats overlay (shows the overlay if hidden)
+    pub fn toggle_stats_graphs(&mut self) {
+        self.show_stats_graphs = !self.show_stats_graphs;
+        if self.show_stats_graphs {
+            self.show_stats = true;
+        }
+    }
+
+    /// Toggle the in-stream colour adjustment overlay
+    pub fn toggle_color_overlay(&mut self) {
+        self.show_color_overlay = !self.show_color_overlay;
+    }
+
+    /// Colour adjustments for the current game (or the global ones)
+    pub fn color_adjustments(&self) -> config::ColorAdjustments {
+        self.settings
+            .color_adjustments_for(self.selected_game.as_ref().map(|g| g.id.as_str()))
+    }
+
+    /// Cycle the stream scaling mode (hotkey)
+    pub fn cycle_scaling_mode(&mut self) {
+        self.settings.scaling_mode = self.settings.scaling_mode.next();
+        info!("Scaling mode: {}", self.settings.scaling_mode.as_str());
+        self.save_settings();
+    }
+
     /// Save settings
     pub fn save_settings(&self) {
         if let Err(e) = self.settings.save() {
@@ -0,0 +1,316 @@
+//! Playtime Tracker
+//!
+//! Counts down to the end of the current session, whichever comes first of
+//! the tier's session length limit and the account's remaining hours (the
+//! server ends the session with `SessionEntitledTimeExceeded` when those run
+//! out), and raises a warning as each configured number of minutes left is
+//! reached. Also projects this month's usage from the local session history.
+
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use std::time::{Duration, Instant};
+
+use super::session_history::playtime_by_month;
+use super::{SessionRecord, SubscriptionInfo};
+
+/// How often account time is re-fetched while streaming
+pub const SUBSCRIPTION_REFRESH: Duration = Duration::from_secs(10 * 60);
+
+/// Session length limit for a membership tier (None if unknown)
+pub fn tier_session_limit(tier: &str) -> Option<Duration> {
+    let hours = match tier {
+        "FREE" => 1,
+        "PRIORITY" | "PERFORMANCE" => 6,
+        "ULTIMATE" => 8,
+        _ => return None,
+    };
+    Some(Duration::from_secs(hours * 3600))
+}
+
+/// Account time left, if the plan has an hour cap
+pub fn account_remaining(subscription: &SubscriptionInfo) -> Option<Duration> {
+    if subscription.is_unlimited || subscription.total_hours <= 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f32(
+        subscription.remaining_hours.max(0.0) * 3600.0,
+    ))
+}
+
+/// What ends the session first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// The tier's maximum session length
+    SessionLength,
+    /// The account's remaining hours
+    AccountTime,
+}
+
+impl LimitKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LimitKind::SessionLength => "session limit",
+            LimitKind::AccountTime => "playtime left",
+        }
+    }
+}
+
+/// A warning threshold was reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaytimeWarning {
+    pub minutes: u32,
+    pub kind: LimitKind,
+}
+
+impl PlaytimeWarning {
+    pub fn title(&self) -> String {
+        let unit = if self.minutes == 1 {
+            "minute"
+        } else {
+            "minutes"
+        };
+        format!("{} {} left", self.minutes, unit)
+    }
+
+    pub fn body(&self) -> &'static str {
+        match self.kind {
+            LimitKind::SessionLength => {
+                "The session will end when it reaches your tier's length limit."
+            }
+            LimitKind::AccountTime => {
+                "The session will end when your account runs out of playtime."
+            }
+        }
+    }
+}
+
+/// Countdown for the session being streamed
+#[derive(Debug)]
+pub struct PlaytimeTracker {
+    started: Instant,
+    session_limit: Option<Duration>,
+    /// Account time left when last measured, and when
+    account_remaining: Option<(Duration, Instant)>,
+    /// Whether the server has reported account time since the start
+    account_refreshed: bool,
+    /// Warning thresholds, largest first
+    warning_minutes: Vec<u32>,
+    /// Index of the next threshold to fire
+    next_warning: usize,
+}
+
+impl PlaytimeTracker {
+    pub fn new(started: Instant, session_limit: Option<Duration>, warning_minutes: &[u32]) -> Self {
+        let mut warning_minutes = warning_minutes.to_vec();
+        warning_minutes.sort_unstable_by(|a, b| b.cmp(a));
+        warning_minutes.dedup();
+        Self {
+            started,
+            session_limit,
+            account_remaining: None,
+            account_refreshed: false,
+            warning_minutes,
+            next_warning: 0,
+        }
+    }
+
+    /// Account time from cached subscription info, until the server reports
+    pub fn estimate_account_remaining(&mut self, remaining: Option<Duration>, at: Instant) {
+        self.account_remaining = remaining.map(|remaining| (remaining, at));
+    }
+
+    /// Account time from the server as of `at`. The first report replaces
+    /// the cached estimate; later ones never extend the countdown, as the
+    /// server may only charge the session once it ends.
+    pub fn set_account_remaining(&mut self, remaining: Option<Duration>, at: Instant) {
+        let first = !std::mem::replace(&mut self.account_refreshed, true);
+        let Some(remaining) = remaining else {
+            self.account_remaining = None;
+            return;
+        };
+        let remaining = match self.account_left(at) {
+            Some(current) if !first => remaining.min(current),
+            _ => remaining,
+        };
+        self.account_remaining = Some((remaining, at));
+    }
+
+    fn account_left(&self, now: Instant) -> Option<Duration> {
+        self.account_remaining
+            .map(|(left, at)| left.saturating_sub(now.saturating_duration_since(at)))
+    }
+
+    /// Time until the session is cut off, and by what
+    pub fn remaining(&self, now: Instant) -> Option<(Duration, LimitKind)> {
+        let session = self.session_limit.map(|limit| {
+            (
+                limit.saturating_sub(now.saturating_duration_since(self.started)),
+                LimitKind::SessionLength,
+            )
+        });
+        let account = self
+            .account_left(now)
+            .map(|left| (left, LimitKind::AccountTime));
+        match (session, account) {
+            (Some(s), Some(a)) => Some(if a.0 < s.0 { a } else { s }),
+            (s, a) => s.or(a),
+        }
+    }
+
+    /// The smallest threshold reached since the last check. Thresholds
+    /// already passed when the session starts are folded into one warning.
+    pub fn check(&mut self, now: Instant) -> Option<PlaytimeWarning> {
+        let (remaining, kind) = self.remaining(now)?;
+        let mut reached = None;
+        while let Some(&minutes) = self.warning_minutes.get(self.next_warning) {
+            if remaining > Duration::from_secs(minutes as u64 * 60) {
+                break;
+            }
+            reached = Some(minutes);
+            self.next_warning += 1;
+        }
+        reached.map(|minutes| PlaytimeWarning { minutes, kind })
+    }
+
+    /// Whether the in-stream countdown should show (within the largest threshold)
+    pub fn show_countdown(&self, now: Instant) -> bool {
+        let Some(&largest) = self.warning_minutes.first() else {
+            return false;
+        };
+        self.remaining(now)
+            .is_some_and(|(left, _)| left <= Duration::from_secs(largest as u64 * 60))
+    }
+}
+
+/// "1:04:05" / "14:32"
+pub fn format_countdown(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// This month's usage so far and where it is heading
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageProjection {
+    pub used_hours: f32,
+    /// Hours by the end of the month at the current daily pace
+    pub projected_hours: f32,
+    /// Day the account runs out at this pace
+    pub runs_out_on: Option<NaiveDate>,
+}
+
+/// Project this month's playtime from the local session history
+pub fn project_month_usage(
+    history: &[SessionRecord],
+    today: NaiveDate,
+    remaining_hours: Option<f32>,
+) -> Option<UsageProjection> {
+    let used_hours = playtime_by_month(history)
+        .into_iter()
+        .find(|m| m.year == today.year() && m.month == today.month())?
+        .hours();
+
+    let first = today.with_day(1)?;
+    let next_month = if today.month() == 12 {
+        NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)?
+    };
+    let days_in_month = (next_month - first).num_days() as f32;
+    let daily = used_hours / today.day() as f32;
+
+    let runs_out_on = remaining_hours
+        .filter(|_| daily > 0.0)
+        .map(|left| today + ChronoDuration::days((left / daily).floor() as i64));
+
+    Some(UsageProjection {
+        used_hours,
+        projected_hours: daily * days_in_month,
+        runs_out_on,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::session_history::test_record;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_warnings_fire_once_per_threshold() {
+        let start = Instant::now();
+        let mut tracker = PlaytimeTracker::new(start, Some(60 * MIN), &[5, 15, 1]);
+        assert_eq!(tracker.check(start), None);
+        assert!(!tracker.show_countdown(start));
+
+        let at = |minutes: u32| start + MIN * minutes;
+        let warning = tracker.check(at(45)).unwrap();
+        assert_eq!(warning.minutes, 15);
+        assert_eq!(warning.kind, LimitKind::SessionLength);
+        assert_eq!(warning.title(), "15 minutes left");
+        assert!(tracker.show_countdown(at(45)));
+        assert_eq!(tracker.check(at(50)), None);
+        assert_eq!(tracker.check(at(55)).unwrap().minutes, 5);
+        // Missed checks fold into the smallest threshold reached
+        assert_eq!(tracker.check(at(60)).unwrap().minutes, 1);
+        assert_eq!(tracker.check(at(61)), None);
+    }
+
+    #[test]
+    fn test_account_time_ends_first() {
+        let start = Instant::now();
+        let mut tracker = PlaytimeTracker::new(start, Some(360 * MIN), &[15, 5]);
+        tracker.estimate_account_remaining(Some(10 * MIN), start);
+
+        // Already under 15 minutes: a single warning for it
+        let warning = tracker.check(start).unwrap();
+        assert_eq!(
+            (warning.minutes, warning.kind),
+            (15, LimitKind::AccountTime)
+        );
+        assert_eq!(
+            tracker.remaining(start + 4 * MIN),
+            Some((6 * MIN, LimitKind::AccountTime))
+        );
+
+        // The first server report replaces a stale cached value, even upwards
+        tracker.set_account_remaining(Some(12 * MIN), start + 4 * MIN);
+        assert_eq!(tracker.remaining(start + 4 * MIN).unwrap().0, 12 * MIN);
+
+        // A later refresh that reports the start value doesn't extend the countdown
+        tracker.set_account_remaining(Some(12 * MIN), start + 5 * MIN);
+        assert_eq!(tracker.remaining(start + 5 * MIN).unwrap().0, 11 * MIN);
+        tracker.set_account_remaining(Some(2 * MIN), start + 4 * MIN);
+        assert_eq!(tracker.remaining(start + 4 * MIN).unwrap().0, 2 * MIN);
+        assert_eq!(
+            format_countdown(tracker.remaining(start + 4 * MIN).unwrap().0),
+            "02:00"
+        );
+    }
+
+    #[test]
+    fn test_month_projection() {
+        let history = [
+            test_record("Game", "2026-09-28T20:00:00+02:00", 7200),
+            test_record("Game", "2026-10-02T20:00:00+02:00", 3 * 3600),
+            test_record("Game", "2026-10-09T20:00:00+02:00", 2 * 3600),
+        ];
+        let today = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+
+        // 5 h in 10 days: 0.5 h/day, 15.5 h over October
+        let projection = project_month_usage(&history, today, Some(4.0)).unwrap();
+        assert_eq!(projection.used_hours, 5.0);
+        assert_eq!(projection.projected_hours, 15.5);
+        assert_eq!(
+            projection.runs_out_on,
+            NaiveDate::from_ymd_opt(2026, 10, 18)
+        );
+
+        let november = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+        assert_eq!(project_month_usage(&history, november, None), None);
+        assert_eq!(tier_session_limit("ULTIMATE"), Some(480 * MIN));
+    }
+}
@@ -0,0 +1,681 @@
+//! Queue Racing
+//!
+//! Opt-in launch mode that requests the same game in the best few zones at
+//! once, keeps whichever seat comes up first (or the best-scoring queue when
+//! the race times out) and stops the others.
+//!
+//! Orphan guardrails:
+//! - every session the race creates goes into a ledger on disk before it is
+//!   polled, and only leaves it once stopped or handed over to the app
+//! - all create requests finish before the race is decided, so a late
+//!   create can't slip past the cleanup
+//! - a create that failed on our side may still have started a session, so
+//!   the server's active sessions for the game are swept for ones the race
+//!   doesn't know about, and again for everything but the winner at the end
+//! - stops are retried; anything that still can't be stopped stays in the
+//!   ledger and is stopped on the next start ([`stop_orphaned_sessions`])
+//! - cancelling stops every session, including an unclaimed winner
+
+use anyhow::Result;
+use futures_util::future::join_all;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::server_scoring::{self, ScoreCandidate, ServerScorer};
+use super::{
+    cache, ActiveSessionInfo, ServerInfo, ServerStatus, SessionInfo, SessionState, Settings,
+};
+use crate::api::GfnApiClient;
+
+/// Most zones raced at once
+pub const MAX_RACE_ZONES: usize = 3;
+
+/// Poll interval while racing (same as the normal session poll)
+const RACE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Stop attempts per session before leaving it for the next start
+const STOP_ATTEMPTS: u32 = 3;
+
+/// Serializes ledger read-modify-write between the race and startup cleanup
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+/// A session the race created and hasn't finished with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub session_id: String,
+    pub zone: String,
+    pub server_ip: Option<String>,
+}
+
+impl LedgerEntry {
+    fn for_session(session: &SessionInfo) -> Self {
+        Self {
+            session_id: session.session_id.clone(),
+            zone: session.zone.clone(),
+            server_ip: (!session.server_ip.is_empty()).then(|| session.server_ip.clone()),
+        }
+    }
+
+    /// Active sessions don't report their zone; the server IP is enough to stop them
+    fn for_active(session: &ActiveSessionInfo) -> Self {
+        Self {
+            session_id: session.session_id.clone(),
+            zone: String::new(),
+            server_ip: session.server_ip.clone().filter(|ip| !ip.is_empty()),
+        }
+    }
+}
+
+/// One zone in the race
+#[derive(Debug, Clone)]
+pub struct Racer {
+    pub zone: String,
+    pub name: String,
+    pub region: String,
+    pub ping_ms: Option<u32>,
+    /// Latest session info (None until the create request returns)
+    pub session: Option<SessionInfo>,
+    /// Requesting until created; Error/Terminated once out of the race
+    pub state: SessionState,
+}
+
+impl Racer {
+    fn new(server: &ServerInfo) -> Self {
+        Self {
+            zone: server.id.clone(),
+            name: server.name.clone(),
+            region: server.region.clone(),
+            ping_ms: server.ping_ms,
+            session: None,
+            state: SessionState::Requesting,
+        }
+    }
+
+    /// Still has a session that could win
+    pub fn is_alive(&self) -> bool {
+        self.session.is_some()
+            && !matches!(
+                self.state,
+                SessionState::Error(_) | SessionState::Terminated
+            )
+    }
+
+    /// Server score (lower is better), None if out of the race
+    pub fn score(&self, scorer: &ServerScorer) -> Option<f64> {
+        if !self.is_alive() {
+            return None;
+        }
+        let eta_secs = match self.state {
+            SessionState::InQueue { eta_secs, .. } => eta_secs as i64,
+            SessionState::WatchingAds { remaining_secs, .. } => remaining_secs as i64,
+            _ => 0,
+        };
+        let score = scorer.score(ScoreCandidate {
+            id: &self.zone,
+            region: &self.region,
+            ping_ms: self.ping_ms,
+            eta_seconds: Some(eta_secs),
+            is_4080_server: false,
+            is_5080_server: false,
+        });
+        Some(score.total)
+    }
+}
+
+/// Zones worth racing: the preferred zone first, then the lowest-ping
+/// online zones. Zones without a ping result are skipped.
+pub fn select_race_zones(
+    servers: &[ServerInfo],
+    preferred: Option<&str>,
+    count: usize,
+) -> Vec<ServerInfo> {
+    let count = count.clamp(1, MAX_RACE_ZONES);
+    let mut candidates: Vec<&ServerInfo> = servers
+        .iter()
+        .filter(|s| s.status == ServerStatus::Online && s.ping_ms.is_some())
+        .collect();
+    candidates.sort_by_key(|s| (Some(s.id.as_str()) != preferred, s.ping_ms));
+    candidates.into_iter().take(count).cloned().collect()
+}
+
+/// Pick the winner: a ready seat (best score if several came up at once),
+/// or after the timeout the best-scoring session still in the race
+pub fn pick_winner(racers: &[Racer], timed_out: bool, scorer: &ServerScorer) -> Option<usize> {
+    let best = |ready_only: bool| {
+        racers
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !ready_only || r.state == SessionState::Ready)
+            .filter_map(|(i, r)| r.score(scorer).map(|score| (i, score)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    };
+    best(true).or_else(|| if timed_out { best(false) } else { None })
+}
+
+#[derive(Debug, Default)]
+struct RaceShared {
+    racers: Vec<Racer>,
+    /// Winning session, waiting for the app to take it
+    winner: Option<SessionInfo>,
+    handed_over: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+/// Handle to a running race, shared between the app and the race task
+#[derive(Debug, Clone)]
+pub struct QueueRace {
+    shared: Arc<Mutex<RaceShared>>,
+}
+
+impl QueueRace {
+    pub fn new(zones: &[ServerInfo]) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(RaceShared {
+                racers: zones.iter().map(Racer::new).collect(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Snapshot of the zones for display
+    pub fn racers(&self) -> Vec<Racer> {
+        self.shared.lock().racers.clone()
+    }
+
+    /// Take the winning session. From here on the app owns it.
+    pub fn take_winner(&self) -> Option<SessionInfo> {
+        let mut shared = self.shared.lock();
+        if shared.cancelled {
+            return None;
+        }
+        let winner = shared.winner.take()?;
+        shared.handed_over = true;
+        Some(winner)
+    }
+
+    /// Why the race ended without a seat
+    pub fn take_error(&self) -> Option<String> {
+        self.shared.lock().error.take()
+    }
+
+    /// Abandon the race. Every session it still owns is stopped.
+    pub fn cancel(&self) {
+        let mut shared = self.shared.lock();
+        if !shared.handed_over {
+            info!("Queue race cancelled");
+            shared.cancelled = true;
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.shared.lock().cancelled
+    }
+
+    /// Run the race to completion (call from a spawned task)
+    pub async fn run(
+        &self,
+        api_client: &GfnApiClient,
+        app_id: &str,
+        game_title: &str,
+        settings: &Settings,
+        account_linked: bool,
+        timeout: Duration,
+    ) {
+        // Leftovers from an earlier race would count against the session limit
+        stop_orphaned_sessions(api_client).await;
+
+        // Sessions for this game that the race didn't start are left alone.
+        // Without that baseline the sweeps below could stop them, so give up.
+        let mut keep = match active_session_ids(api_client, app_id).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!("Race: failed to list active sessions: {}", e);
+                self.shared.lock().error = Some(format!("Could not check active sessions: {}", e));
+                return;
+            }
+        };
+
+        let zones: Vec<String> = self.racers().into_iter().map(|r| r.zone).collect();
+        info!("Racing {} zones: {}", zones.len(), zones.join(", "));
+
+        // Create everywhere at once and wait for every request to finish
+        let creates = zones.iter().map(|zone| async move {
+            let result = api_client
+                .create_session(app_id, game_title, settings, zone, account_linked)
+                .await;
+            if let Ok(session) = &result {
+                ledger_add(LedgerEntry::for_session(session));
+            }
+            result
+        });
+        let results = join_all(creates).await;
+        {
+            let mut shared = self.shared.lock();
+            for (racer, result) in shared.racers.iter_mut().zip(results) {
+                match result {
+                    Ok(session) => {
+                        info!(
+                            "Race: {} created {} ({:?})",
+                            racer.zone, session.session_id, session.state
+                        );
+                        racer.state = session.state.clone();
+                        racer.session = Some(session);
+                    }
+                    Err(e) => {
+                        warn!("Race: {} create failed: {}", racer.zone, e);
+                        racer.state = SessionState::Error(e.to_string());
+                    }
+                }
+            }
+        }
+
+        // A failed create may still have started a session server-side
+        let created: Vec<String> = self
+            .racers()
+            .into_iter()
+            .filter_map(|r| r.session.map(|s| s.session_id))
+            .collect();
+        stop_untracked_sessions(api_client, app_id, &[keep.clone(), created].concat()).await;
+
+        let zone_quality = server_scoring::load_zone_quality();
+        let scorer = ServerScorer::new(settings, &zone_quality);
+        let deadline = Instant::now() + timeout;
+        let winner = loop {
+            if self.is_cancelled() {
+                break None;
+            }
+
+            let racers = self.racers();
+            if !racers.iter().any(Racer::is_alive) {
+                let reason = racers
+                    .iter()
+                    .find_map(|r| match &r.state {
+                        SessionState::Error(e) => Some(e.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| "No zone accepted the session".to_string());
+                self.shared.lock().error = Some(reason);
+                break None;
+            }
+            if let Some(index) = pick_winner(&racers, Instant::now() >= deadline, &scorer) {
+                break Some(index);
+            }
+
+            tokio::time::sleep(RACE_POLL_INTERVAL).await;
+            self.poll_all(api_client).await;
+        };
+
+        // Stop everything except the winner
+        let losers: Vec<SessionInfo> = {
+            let mut shared = self.shared.lock();
+            let mut losers = Vec::new();
+            for (i, racer) in shared.racers.iter_mut().enumerate() {
+                if Some(i) == winner {
+                    continue;
+                }
+                // Failed sessions too, in case the server still holds them
+                if let Some(session) = &racer.session {
+                    losers.push(session.clone());
+                    racer.state = SessionState::Terminated;
+                }
+            }
+            losers
+        };
+        stop_sessions(api_client, &losers).await;
+
+        let winner = winner.and_then(|index| self.shared.lock().racers[index].session.clone());
+        // Then whatever the server still runs for this game besides the winner
+        keep.extend(winner.iter().map(|s| s.session_id.clone()));
+        stop_untracked_sessions(api_client, app_id, &keep).await;
+
+        let Some(session) = winner else {
+            return;
+        };
+        info!(
+            "Race won by {} ({:?}), stopped {} other session(s)",
+            session.zone,
+            session.state,
+            losers.len()
+        );
+
+        // Hand over, unless the user gave up in the meantime
+        {
+            let mut shared = self.shared.lock();
+            if !shared.cancelled {
+                shared.winner = Some(session.clone());
+            }
+        }
+        loop {
+            {
+                let shared = self.shared.lock();
+                if shared.handed_over {
+                    ledger_remove(&session.session_id);
+                    return;
+                }
+                if shared.cancelled {
+                    break;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        stop_sessions(api_client, std::slice::from_ref(&session)).await;
+    }
+
+    /// Poll every live session once
+    async fn poll_all(&self, api_client: &GfnApiClient) {
+        let sessions: Vec<(usize, SessionInfo)> = self
+            .racers()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_alive())
+            .filter_map(|(i, r)| r.session.map(|s| (i, s)))
+            .collect();
+
+        let polls = sessions.iter().map(|(_, session)| {
+            let server_ip = (!session.server_ip.is_empty()).then_some(session.server_ip.as_str());
+            api_client.poll_session(&session.session_id, &session.zone, server_ip)
+        });
+        let results = join_all(polls).await;
+
+        let mut shared = self.shared.lock();
+        for ((index, _), result) in sessions.into_iter().zip(results) {
+            let racer = &mut shared.racers[index];
+            match result {
+                Ok(update) => {
+                    racer.state = update.state.clone();
+                    racer.session = Some(update);
+                }
+                // Transient; keep the last known state
+                Err(e) => warn!("Race: poll in {} failed: {}", racer.zone, e),
+            }
+        }
+    }
+}
+
+/// Stop sessions (with retries), dropping each from the ledger once stopped
+async fn stop_sessions(api_client: &GfnApiClient, sessions: &[SessionInfo]) {
+    let stops = sessions
+        .iter()
+        .map(|session| stop_with_retry(api_client, LedgerEntry::for_session(session)));
+    join_all(stops).await;
+}
+
+async fn stop_with_retry(api_client: &GfnApiClient, entry: LedgerEntry) {
+    for attempt in 1..=STOP_ATTEMPTS {
+        match api_client
+            .stop_session(&entry.session_id, &entry.zone, entry.server_ip.as_deref())
+            .await
+        {
+            Ok(()) => {
+                ledger_remove(&entry.session_id);
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to stop race session {} (attempt {}/{}): {}",
+                    entry.session_id, attempt, STOP_ATTEMPTS, e
+                );
+                tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            }
+        }
+    }
+    error!(
+        "Race session {} in {} could not be stopped, will retry on next start",
+        entry.session_id, entry.zone
+    );
+}
+
+/// IDs of the server's active sessions for `app_id`
+async fn active_session_ids(api_client: &GfnApiClient, app_id: &str) -> Result<Vec<String>> {
+    let sessions = api_client.get_active_sessions().await?;
+    Ok(untracked_sessions(&sessions, app_id, &[])
+        .into_iter()
+        .map(|entry| entry.session_id)
+        .collect())
+}
+
+/// Active sessions for `app_id` not in `keep`
+fn untracked_sessions(
+    active: &[ActiveSessionInfo],
+    app_id: &str,
+    keep: &[String],
+) -> Vec<LedgerEntry> {
+    active
+        .iter()
+        .filter(|s| s.app_id.to_string() == app_id && !keep.contains(&s.session_id))
+        .map(LedgerEntry::for_active)
+        .collect()
+}
+
+/// Stop the server's active sessions for `app_id` that aren't in `keep`
+async fn stop_untracked_sessions(api_client: &GfnApiClient, app_id: &str, keep: &[String]) {
+    let active = match api_client.get_active_sessions().await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            warn!("Race: failed to list active sessions: {}", e);
+            return;
+        }
+    };
+    let untracked = untracked_sessions(&active, app_id, keep);
+    if untracked.is_empty() {
+        return;
+    }
+    warn!("Race: stopping {} untracked session(s)", untracked.len());
+    for entry in &untracked {
+        ledger_add(entry.clone());
+    }
+    join_all(
+        untracked
+            .into_iter()
+            .map(|entry| stop_with_retry(api_client, entry)),
+    )
+    .await;
+}
+
+/// Stop sessions left over from a race that didn't finish (crash, failed stop)
+pub async fn stop_orphaned_sessions(api_client: &GfnApiClient) {
+    let orphans = load_ledger();
+    if orphans.is_empty() {
+        return;
+    }
+    warn!("Stopping {} orphaned race session(s)", orphans.len());
+    join_all(
+        orphans
+            .into_iter()
+            .map(|entry| stop_with_retry(api_client, entry)),
+    )
+    .await;
+}
+
+fn ledger_path() -> Option<PathBuf> {
+    cache::get_app_data_dir().map(|p| p.join("race_sessions.json"))
+}
+
+fn load_ledger() -> Vec<LedgerEntry> {
+    ledger_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_ledger(entries: &[LedgerEntry]) {
+    let Some(path) = ledger_path() else {
+        return;
+    };
+    if entries.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return;
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string(entries) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                error!("Failed to write race ledger {}: {}", path.display(), e);
+            }
+        }
+        Err(e) => error!("Failed to serialize race ledger: {}", e),
+    }
+}
+
+fn ledger_add(entry: LedgerEntry) {
+    let _guard = LEDGER_LOCK.lock();
+    let mut entries = load_ledger();
+    entries.retain(|e| e.session_id != entry.session_id);
+    entries.push(entry);
+    save_ledger(&entries);
+}
+
+fn ledger_remove(session_id: &str) {
+    let _guard = LEDGER_LOCK.lock();
+    let mut entries = load_ledger();
+    entries.retain(|e| e.session_id != session_id);
+    save_ledger(&entries);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(id: &str, ping_ms: Option<u32>, status: ServerStatus) -> ServerInfo {
+        ServerInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            region: String::new(),
+            url: None,
+            ping_ms,
+            latency: None,
+            status,
+        }
+    }
+
+    fn racer(zone: &str, ping_ms: u32, state: SessionState) -> Racer {
+        let mut session = SessionInfo::new_requesting(zone);
+        session.session_id = format!("session-{}", zone);
+        session.state = state.clone();
+        Racer {
+            zone: zone.to_string(),
+            name: zone.to_string(),
+            region: String::new(),
+            ping_ms: Some(ping_ms),
+            session: Some(session),
+            state,
+        }
+    }
+
+    /// Pick with the default scoring weights
+    fn pick(racers: &[Racer], timed_out: bool) -> Option<usize> {
+        let settings = Settings::default();
+        let quality = server_scoring::ZoneQualityMap::new();
+        pick_winner(racers, timed_out, &ServerScorer::new(&settings, &quality))
+    }
+
+    fn queued(position: u32, eta_secs: u32) -> SessionState {
+        SessionState::InQueue { position, eta_secs }
+    }
+
+    #[test]
+    fn test_select_race_zones() {
+        let servers = vec![
+            server("far", Some(90), ServerStatus::Online),
+            server("near", Some(15), ServerStatus::Online),
+            server("down", Some(5), ServerStatus::Offline),
+            server("untested", None, ServerStatus::Unknown),
+            server("mid", Some(40), ServerStatus::Online),
+        ];
+        let ids = |zones: Vec<ServerInfo>| zones.into_iter().map(|s| s.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(select_race_zones(&servers, None, 2)),
+            vec!["near", "mid"]
+        );
+        // Preferred zone goes first
+        assert_eq!(
+            ids(select_race_zones(&servers, Some("far"), 2)),
+            vec!["far", "near"]
+        );
+        // Capped
+        assert_eq!(select_race_zones(&servers, None, 10).len(), MAX_RACE_ZONES);
+    }
+
+    #[test]
+    fn test_ready_seat_wins_immediately() {
+        let racers = vec![
+            racer("a", 10, queued(3, 60)),
+            racer("b", 80, SessionState::Ready),
+        ];
+        assert_eq!(pick(&racers, false), Some(1));
+    }
+
+    #[test]
+    fn test_no_winner_while_queued() {
+        let racers = vec![
+            racer("a", 10, queued(30, 600)),
+            racer("b", 20, queued(5, 60)),
+        ];
+        assert_eq!(pick(&racers, false), None);
+    }
+
+    #[test]
+    fn test_timeout_picks_best_score() {
+        let racers = vec![
+            // Low ping but a long queue
+            racer("a", 10, queued(400, 3 * 3600)),
+            racer("b", 40, queued(5, 120)),
+            racer("c", 5, SessionState::Error("failed".to_string())),
+        ];
+        assert_eq!(pick(&racers, true), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_listing_failure_is_an_error() {
+        // No access token: the listing fails without a request
+        let api_client = GfnApiClient::new();
+        assert!(active_session_ids(&api_client, "100").await.is_err());
+    }
+
+    #[test]
+    fn test_untracked_sessions() {
+        let active = |session_id: &str, app_id: i64| ActiveSessionInfo {
+            session_id: session_id.to_string(),
+            app_id,
+            gpu_type: None,
+            status: 2,
+            server_ip: Some("10.0.0.1".to_string()),
+            signaling_url: None,
+            resolution: None,
+            fps: None,
+        };
+        let sessions = vec![
+            active("winner", 100),
+            active("orphan", 100),
+            active("other-game", 200),
+        ];
+
+        let untracked = untracked_sessions(&sessions, "100", &["winner".to_string()]);
+        assert_eq!(
+            untracked,
+            vec![LedgerEntry {
+                session_id: "orphan".to_string(),
+                zone: String::new(),
+                server_ip: Some("10.0.0.1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_dead_racers_never_win() {
+        let mut stopped = racer("a", 10, SessionState::Ready);
+        stopped.state = SessionState::Terminated;
+        let mut uncreated = racer("b", 10, SessionState::Requesting);
+        uncreated.session = None;
+        assert_eq!(pick(&[stopped, uncreated], true), None);
+    }
+}
@@ -0,0 +1,422 @@
+//! Queue Watch
+//!
+//! Follows the queue position while a session waits for a seat: raises
+//! notifications when configured thresholds are crossed and when the seat
+//! is ready, and records the position over time. Finished queues are kept
+//! in `queue_history.jsonl` and used to estimate future waits.
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::cache;
+
+/// Most recent queues kept on disk and considered for estimates
+const HISTORY_LIMIT: usize = 200;
+
+/// One queue position reading
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QueueSample {
+    /// Seconds since the session was requested
+    pub secs: u32,
+    pub position: u32,
+    /// Server ETA at this point
+    pub eta_secs: u32,
+}
+
+/// Queue curve for one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueRun {
+    pub game_id: String,
+    pub game_title: String,
+    pub zone: String,
+    /// RFC 3339 start time
+    pub started_at: String,
+    pub samples: Vec<QueueSample>,
+    /// Seconds from the request until the seat was ready (or the queue was left)
+    pub wait_secs: u32,
+    /// Whether the queue ended with a seat
+    pub reached_seat: bool,
+}
+
+impl QueueRun {
+    /// Positions cleared per second from the first reading to the seat
+    fn drain_rate(&self) -> Option<f64> {
+        let first = self.samples.first()?;
+        if !self.reached_seat || first.position == 0 {
+            return None;
+        }
+        let secs = self.wait_secs.saturating_sub(first.secs).max(1);
+        Some(first.position as f64 / secs as f64)
+    }
+}
+
+/// Something worth telling the user about
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueNotification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Tracks the queue for the current session
+#[derive(Debug, Default)]
+pub struct QueueWatch {
+    run: Option<QueueRun>,
+    started: Option<Instant>,
+    /// Highest position seen, for progress
+    first_position: u32,
+    /// Thresholds already announced (or already below at the start)
+    passed: Vec<u32>,
+}
+
+impl QueueWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching a new session
+    pub fn start(&mut self, game_id: &str, game_title: &str, zone: &str) {
+        self.run = Some(QueueRun {
+            game_id: game_id.to_string(),
+            game_title: game_title.to_string(),
+            zone: zone.to_string(),
+            started_at: chrono::Local::now().to_rfc3339(),
+            samples: Vec::new(),
+            wait_secs: 0,
+            reached_seat: false,
+        });
+        self.started = Some(Instant::now());
+        self.first_position = 0;
+        self.passed.clear();
+    }
+
+    /// Zone of the session being watched
+    pub fn zone(&self) -> Option<&str> {
+        self.run.as_ref().map(|run| run.zone.as_str())
+    }
+
+    /// Session moved to another zone (queue race settled)
+    pub fn set_zone(&mut self, zone: &str) {
+        if let Some(run) = self.run.as_mut() {
+            run.zone = zone.to_string();
+        }
+    }
+
+    /// Current position and progress (0.0 at the first reading, 1.0 at the front)
+    pub fn progress(&self) -> Option<(u32, f32)> {
+        let last = self.run.as_ref()?.samples.last()?;
+        if self.first_position == 0 {
+            return Some((last.position, 1.0));
+        }
+        let done = self.first_position.saturating_sub(last.position) as f32;
+        Some((last.position, done / self.first_position as f32))
+    }
+
+    /// Record a queue update. Returns a notification when the position
+    /// drops to or below a threshold that hasn't been announced yet.
+    pub fn update(
+        &mut self,
+        position: u32,
+        eta_secs: u32,
+        thresholds: &[u32],
+    ) -> Option<QueueNotification> {
+        let secs = self.elapsed_secs();
+        let run = self.run.as_mut()?;
+
+        let first = run.samples.is_empty();
+        if run.samples.last().map(|s| s.position) != Some(position) {
+            run.samples.push(QueueSample {
+                secs,
+                position,
+                eta_secs,
+            });
+        }
+
+        if first {
+            // Thresholds we're already past aren't news
+            self.first_position = position;
+            self.passed = thresholds
+                .iter()
+                .copied()
+                .filter(|&t| position <= t)
+                .collect();
+            return None;
+        }
+        self.first_position = self.first_position.max(position);
+
+        let crossed: Vec<u32> = thresholds
+            .iter()
+            .copied()
+            .filter(|&t| position <= t && !self.passed.contains(&t))
+            .collect();
+        if crossed.is_empty() {
+            return None;
+        }
+        self.passed.extend(&crossed);
+
+        let title = run.game_title.clone();
+        Some(QueueNotification {
+            title: format!("Queue position {}", position),
+            body: match eta_secs {
+                0 => format!("{} is getting close", title),
+                eta => format!("{} - about {} left", title, format_wait(eta)),
+            },
+        })
+    }
+
+    /// Seat is ready. Finishes the run; returns a notification if the
+    /// session had to queue (instant starts don't need one).
+    pub fn ready(&mut self) -> Option<QueueNotification> {
+        let run = self.finish(true)?;
+        if run.samples.is_empty() {
+            return None;
+        }
+        Some(QueueNotification {
+            title: "Your seat is ready".to_string(),
+            body: format!(
+                "{} is starting after {} in queue",
+                run.game_title,
+                format_wait(run.wait_secs)
+            ),
+        })
+    }
+
+    /// Queue left without a seat (cancelled or failed)
+    pub fn abandon(&mut self) {
+        self.finish(false);
+    }
+
+    /// Close the current run and save it if it queued
+    fn finish(&mut self, reached_seat: bool) -> Option<QueueRun> {
+        let wait_secs = self.elapsed_secs();
+        let mut run = self.run.take()?;
+        self.started = None;
+        run.wait_secs = wait_secs;
+        run.reached_seat = reached_seat;
+        if !run.samples.is_empty() {
+            append_history(&run);
+        }
+        Some(run)
+    }
+
+    fn elapsed_secs(&self) -> u32 {
+        self.started
+            .map(|s| s.elapsed().as_secs() as u32)
+            .unwrap_or(0)
+    }
+}
+
+/// Estimated wait from `position`, based on how fast past queues in `zone`
+/// drained (all zones if there's no history for this one). Median of the
+/// per-run rates so one stalled queue doesn't skew it.
+pub fn estimate_wait_secs(history: &[QueueRun], zone: &str, position: u32) -> Option<u32> {
+    let rates_for = |zone: Option<&str>| -> Vec<f64> {
+        history
+            .iter()
+            .filter(|run| zone.is_none_or(|z| run.zone == z))
+            .filter_map(QueueRun::drain_rate)
+            .collect()
+    };
+    let mut rates = rates_for(Some(zone));
+    if rates.is_empty() {
+        rates = rates_for(None);
+    }
+    if rates.is_empty() {
+        return None;
+    }
+
+    rates.sort_by(|a, b| a.total_cmp(b));
+    let mid = rates.len() / 2;
+    let median = if rates.len() % 2 == 0 {
+        (rates[mid - 1] + rates[mid]) / 2.0
+    } else {
+        rates[mid]
+    };
+    Some((position as f64 / median).round() as u32)
+}
+
+/// "45s", "7 min", "1 h 20 min"
+pub fn format_wait(secs: u32) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{} min", (secs + 30) / 60),
+        _ => format!("{} h {} min", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    cache::get_app_data_dir().map(|p| p.join("queue_history.jsonl"))
+}
+
+/// Past queues, oldest first (most recent [`HISTORY_LIMIT`])
+pub fn load_history() -> Vec<QueueRun> {
+    let Some(content) = history_path().and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<QueueRun> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    if runs.len() > HISTORY_LIMIT {
+        runs.drain(..runs.len() - HISTORY_LIMIT);
+    }
+    runs
+}
+
+fn append_history(run: &QueueRun) {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let line = match serde_json::to_string(run) {
+        Ok(line) => line,
+        Err(e) => {
+            warn!("Failed to serialize queue history: {}", e);
+            return;
+        }
+    };
+    match append_line(&path, &line, HISTORY_LIMIT) {
+        Ok(()) => info!(
+            "Queue history: {} samples over {}s ({})",
+            run.samples.len(),
+            run.wait_secs,
+            if run.reached_seat { "seated" } else { "left" }
+        ),
+        Err(e) => warn!("Failed to write queue history {}: {}", path.display(), e),
+    }
+}
+
+/// Append a line to `path`, rewriting the file with only the newest `limit`
+/// lines once it grows past that
+fn append_line(path: &Path, line: &str, limit: usize) -> std::io::Result<()> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut lines: Vec<&str> = existing.lines().filter(|l| !l.is_empty()).collect();
+    if lines.len() < limit {
+        return std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+    }
+
+    lines.push(line);
+    let mut content = lines[lines.len() - limit..].join("\n");
+    content.push('\n');
+    std::fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: &[u32] = &[50, 10, 3];
+
+    fn watching(position: u32) -> QueueWatch {
+        let mut watch = QueueWatch::new();
+        watch.run = Some(QueueRun {
+            game_id: "1".to_string(),
+            game_title: "Test Game".to_string(),
+            zone: "eu".to_string(),
+            started_at: String::new(),
+            samples: Vec::new(),
+            wait_secs: 0,
+            reached_seat: false,
+        });
+        assert_eq!(watch.update(position, 600, THRESHOLDS), None);
+        watch
+    }
+
+    fn run(zone: &str, first_position: u32, wait_secs: u32) -> QueueRun {
+        QueueRun {
+            game_id: "1".to_string(),
+            game_title: "Test Game".to_string(),
+            zone: zone.to_string(),
+            started_at: String::new(),
+            samples: vec![QueueSample {
+                secs: 0,
+                position: first_position,
+                eta_secs: 0,
+            }],
+            wait_secs,
+            reached_seat: true,
+        }
+    }
+
+    #[test]
+    fn test_thresholds_fire_once() {
+        let mut watch = watching(80);
+        assert_eq!(watch.update(60, 500, THRESHOLDS), None);
+        let note = watch.update(45, 400, THRESHOLDS).unwrap();
+        assert_eq!(note.title, "Queue position 45");
+        assert_eq!(watch.update(40, 350, THRESHOLDS), None);
+        // Jumping past two thresholds is one notification
+        assert!(watch.update(2, 20, THRESHOLDS).is_some());
+        assert_eq!(watch.update(1, 10, THRESHOLDS), None);
+    }
+
+    #[test]
+    fn test_thresholds_below_start_are_skipped() {
+        let mut watch = watching(8);
+        assert_eq!(watch.update(6, 60, THRESHOLDS), None);
+        assert!(watch.update(3, 30, THRESHOLDS).is_some());
+    }
+
+    #[test]
+    fn test_samples_and_progress() {
+        let mut watch = watching(40);
+        watch.update(40, 590, THRESHOLDS);
+        watch.update(30, 400, THRESHOLDS);
+        watch.update(10, 100, THRESHOLDS);
+        let samples = &watch.run.as_ref().unwrap().samples;
+        assert_eq!(
+            samples.iter().map(|s| s.position).collect::<Vec<_>>(),
+            vec![40, 30, 10]
+        );
+        assert_eq!(watch.progress(), Some((10, 0.75)));
+    }
+
+    #[test]
+    fn test_estimate_uses_zone_median() {
+        let history = vec![
+            run("eu", 100, 1000), // 0.1/s
+            run("eu", 100, 500),  // 0.2/s
+            run("eu", 100, 250),  // 0.4/s
+            run("us", 100, 10),   // other zone
+        ];
+        assert_eq!(estimate_wait_secs(&history, "eu", 50), Some(250));
+        // Zone without history falls back to all zones
+        assert!(estimate_wait_secs(&history, "asia", 50).is_some());
+        assert_eq!(estimate_wait_secs(&[], "eu", 50), None);
+
+        // Abandoned queues don't count
+        let mut left = run("eu", 100, 100);
+        left.reached_seat = false;
+        assert_eq!(estimate_wait_secs(&[left], "eu", 50), None);
+    }
+
+    #[test]
+    fn test_history_file_keeps_newest_lines() {
+        let dir = std::env::temp_dir().join(format!("opennow-queue-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("queue_history.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        for i in 0..5 {
+            append_line(&path, &i.to_string(), 3).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "2\n3\n4\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_wait() {
+        assert_eq!(format_wait(45), "45s");
+        assert_eq!(format_wait(420), "7 min");
+        assert_eq!(format_wait(4800), "1 h 20 min");
+    }
+}
@@ -0,0 +1,360 @@
+//! Launch Recovery
+//!
+//! Picks what to do after a launch fails, from the error's [`ErrorCategory`]:
+//! retry with backoff for transient errors, refresh the login and relaunch
+//! for auth errors, wait for the user to sort out Steam, retry later while
+//! under maintenance, or hand over to the active session dialog.
+
+use std::time::{Duration, Instant};
+
+use crate::api::error_codes::ErrorCategory;
+use crate::api::SessionError;
+
+use super::GameInfo;
+
+/// Automatic retries for transient errors
+pub const MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry, doubled for each one after
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(5);
+
+/// Wait between launches while under maintenance
+pub const MAINTENANCE_RETRY: Duration = Duration::from_secs(15 * 60);
+
+/// Maintenance retries before giving up (an hour)
+const MAX_MAINTENANCE_RETRIES: u32 = 4;
+
+/// Session conflicts handed to the active session check before giving up
+/// (the listing may not show the conflicting session, e.g. on another device)
+const MAX_CONFLICT_CHECKS: u32 = 2;
+
+/// Backoff before retry `attempt` (from 1): 5s, 10s, 20s, ...
+pub fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1).min(6))
+}
+
+/// Next step after a failed launch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryStep {
+    /// Relaunch at the given time
+    Retry(Instant),
+    /// Refresh the login, then relaunch
+    RefreshLogin,
+    /// Wait for the user to approve Steam Guard or link Steam
+    LinkSteam,
+    /// Relaunch once maintenance is likely over
+    RetryLater(Instant),
+    /// Show the active session dialog
+    ResolveConflict,
+    /// Nothing more to try automatically
+    GiveUp,
+}
+
+/// What the session screen offers besides "Copy Diagnostics"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryOffer {
+    /// "Try Again" (skips any wait)
+    Retry,
+    /// Steam instructions and "Try Again"
+    LinkSteam,
+    /// The login can't be refreshed, "Log In Again"
+    LogIn,
+}
+
+/// Failure details for the session screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryPrompt {
+    /// What happens next, e.g. "Retrying in 10s (attempt 2 of 3)"
+    pub status: Option<String>,
+    pub offer: Option<RecoveryOffer>,
+    /// Text for "Copy Diagnostics"
+    pub diagnostics: String,
+}
+
+/// Recovery state for the game being launched
+#[derive(Debug, Clone)]
+pub struct LaunchRecovery {
+    pub game: GameInfo,
+    /// None while a launch is in flight
+    step: Option<RecoveryStep>,
+    category: Option<ErrorCategory>,
+    failures: u32,
+    retries: u32,
+    maintenance_retries: u32,
+    conflict_checks: u32,
+    refreshed_login: bool,
+}
+
+impl LaunchRecovery {
+    pub fn new(game: GameInfo) -> Self {
+        Self {
+            game,
+            step: None,
+            category: None,
+            failures: 0,
+            retries: 0,
+            maintenance_retries: 0,
+            conflict_checks: 0,
+            refreshed_login: false,
+        }
+    }
+
+    pub fn step(&self) -> Option<RecoveryStep> {
+        self.step
+    }
+
+    /// Failed launches so far
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Pick the next step after a failed launch. Errors without CloudMatch
+    /// details (race failures, bad session status) aren't retried.
+    pub fn on_failure(
+        &mut self,
+        error: Option<&SessionError>,
+        can_refresh_login: bool,
+        now: Instant,
+    ) -> RecoveryStep {
+        let category = error.map_or(ErrorCategory::Fatal, SessionError::category);
+        let step = match category {
+            ErrorCategory::Transient if self.retries < MAX_RETRIES => {
+                self.retries += 1;
+                RecoveryStep::Retry(now + retry_delay(self.retries))
+            }
+            ErrorCategory::Auth if can_refresh_login && !self.refreshed_login => {
+                self.refreshed_login = true;
+                RecoveryStep::RefreshLogin
+            }
+            ErrorCategory::SteamLink => RecoveryStep::LinkSteam,
+            ErrorCategory::Maintenance if self.maintenance_retries < MAX_MAINTENANCE_RETRIES => {
+                self.maintenance_retries += 1;
+                RecoveryStep::RetryLater(now + MAINTENANCE_RETRY)
+            }
+            ErrorCategory::SessionConflict if self.conflict_checks < MAX_CONFLICT_CHECKS => {
+                self.conflict_checks += 1;
+                RecoveryStep::ResolveConflict
+            }
+            _ => RecoveryStep::GiveUp,
+        };
+        self.failures += 1;
+        self.category = Some(category);
+        self.step = Some(step);
+        step
+    }
+
+    /// The login refresh for a relaunch failed
+    pub fn login_refresh_failed(&mut self) {
+        self.step = Some(RecoveryStep::GiveUp);
+    }
+
+    /// Whether a scheduled relaunch is due
+    pub fn relaunch_due(&self, now: Instant) -> bool {
+        matches!(
+            self.step,
+            Some(RecoveryStep::Retry(at) | RecoveryStep::RetryLater(at)) if now >= at
+        )
+    }
+
+    /// A relaunch is starting
+    pub fn relaunching(&mut self) {
+        self.step = None;
+    }
+
+    /// What happens next, for the session screen
+    pub fn status(&self, now: Instant) -> Option<String> {
+        let left = |at: Instant| at.saturating_duration_since(now).as_secs();
+        match self.step? {
+            RecoveryStep::Retry(at) => Some(format!(
+                "Retrying in {}s (attempt {} of {})",
+                left(at),
+                self.retries,
+                MAX_RETRIES
+            )),
+            RecoveryStep::RefreshLogin => {
+                Some("Refreshing your login, then trying again...".to_string())
+            }
+            RecoveryStep::LinkSteam => Some(
+                "Steam needs your attention: approve the Steam Guard request, or link your Steam \
+                 account under Connections in GeForce NOW's settings, then try again."
+                    .to_string(),
+            ),
+            RecoveryStep::RetryLater(at) => Some(format!(
+                "Under maintenance, trying again in {} min",
+                left(at).div_ceil(60)
+            )),
+            RecoveryStep::ResolveConflict | RecoveryStep::GiveUp => None,
+        }
+    }
+
+    pub fn offer(&self) -> Option<RecoveryOffer> {
+        match self.step? {
+            RecoveryStep::LinkSteam => Some(RecoveryOffer::LinkSteam),
+            RecoveryStep::GiveUp if self.category == Some(ErrorCategory::Auth) => {
+                Some(RecoveryOffer::LogIn)
+            }
+            RecoveryStep::RefreshLogin | RecoveryStep::ResolveConflict => None,
+            _ => Some(RecoveryOffer::Retry),
+        }
+    }
+}
+
+/// Error codes behind a failed launch request. Requests that never reached
+/// CloudMatch count as network errors.
+pub fn session_error_details(error: &anyhow::Error) -> Option<SessionError> {
+    if let Some(session_error) = error.downcast_ref::<SessionError>() {
+        return Some(session_error.clone());
+    }
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .find(|e| e.is_connect() || e.is_timeout())
+        .map(|_| SessionError::network(&format!("{:#}", error)))
+}
+
+/// Diagnostics for a failed session, with the unified error code when
+/// CloudMatch sent one
+pub fn diagnostics_report(
+    message: &str,
+    error: Option<&SessionError>,
+    game: Option<&GameInfo>,
+    zone: Option<&str>,
+    failures: u32,
+) -> String {
+    let mut report = format!(
+        "OpenNow Streamer {} ({} {})\nTime: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        chrono::Local::now().to_rfc3339()
+    );
+    if let Some(game) = game {
+        report.push_str(&format!("Game: {} ({})\n", game.title, game.id));
+    }
+    if let Some(zone) = zone.filter(|z| !z.is_empty()) {
+        report.push_str(&format!("Zone: {}\n", zone));
+    }
+    if failures > 0 {
+        report.push_str(&format!("Failed launches: {}\n", failures));
+    }
+    report.push_str(&format!("Message: {}\n", message));
+    match error {
+        Some(error) => report.push_str(&error.diagnostics()),
+        None => report.push_str("Unified error code: none"),
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cloudmatch_error(status_code: i32) -> SessionError {
+        SessionError::from_response(
+            403,
+            &format!(
+                r#"{{"requestStatus":{{"statusCode":{},"unifiedErrorCode":-2121088763}}}}"#,
+                status_code
+            ),
+        )
+    }
+
+    fn recovery() -> LaunchRecovery {
+        let game: GameInfo = serde_json::from_str(
+            r#"{"id":"100932911","title":"Game","store":"Steam","app_id":100932911}"#,
+        )
+        .unwrap();
+        LaunchRecovery::new(game)
+    }
+
+    #[test]
+    fn test_transient_errors_back_off() {
+        let now = Instant::now();
+        let no_capacity = cloudmatch_error(85);
+        let mut recovery = recovery();
+        assert_eq!(recovery.step(), None);
+
+        for attempt in 1..=MAX_RETRIES {
+            let step = recovery.on_failure(Some(&no_capacity), true, now);
+            assert_eq!(step, RecoveryStep::Retry(now + retry_delay(attempt)));
+            assert!(!recovery.relaunch_due(now));
+            assert!(recovery.relaunch_due(now + retry_delay(attempt)));
+            recovery.relaunching();
+        }
+        assert_eq!(retry_delay(3), Duration::from_secs(20));
+
+        assert_eq!(
+            recovery.on_failure(Some(&no_capacity), true, now),
+            RecoveryStep::GiveUp
+        );
+        assert_eq!(recovery.offer(), Some(RecoveryOffer::Retry));
+        assert_eq!(recovery.failures(), MAX_RETRIES + 1);
+    }
+
+    #[test]
+    fn test_auth_refreshes_login_once() {
+        let now = Instant::now();
+        let expired = cloudmatch_error(16);
+        let mut recovery = recovery();
+        assert_eq!(
+            recovery.on_failure(Some(&expired), true, now),
+            RecoveryStep::RefreshLogin
+        );
+        assert_eq!(recovery.offer(), None);
+        recovery.relaunching();
+
+        // Still rejected after a refresh: the user has to log in
+        assert_eq!(
+            recovery.on_failure(Some(&expired), true, now),
+            RecoveryStep::GiveUp
+        );
+        assert_eq!(recovery.offer(), Some(RecoveryOffer::LogIn));
+
+        let mut no_refresh_token = self::recovery();
+        no_refresh_token.on_failure(Some(&expired), false, now);
+        assert_eq!(no_refresh_token.offer(), Some(RecoveryOffer::LogIn));
+    }
+
+    #[test]
+    fn test_steam_maintenance_and_conflict() {
+        let now = Instant::now();
+        let mut recovery = recovery();
+        assert_eq!(
+            recovery.on_failure(Some(&cloudmatch_error(26)), true, now),
+            RecoveryStep::LinkSteam
+        );
+        assert_eq!(recovery.offer(), Some(RecoveryOffer::LinkSteam));
+        assert!(!recovery.relaunch_due(now + MAINTENANCE_RETRY));
+
+        let step = recovery.on_failure(Some(&cloudmatch_error(72)), true, now);
+        assert_eq!(step, RecoveryStep::RetryLater(now + MAINTENANCE_RETRY));
+        assert_eq!(
+            recovery.status(now).as_deref(),
+            Some("Under maintenance, trying again in 15 min")
+        );
+
+        for _ in 0..MAX_CONFLICT_CHECKS {
+            assert_eq!(
+                recovery.on_failure(Some(&cloudmatch_error(11)), true, now),
+                RecoveryStep::ResolveConflict
+            );
+        }
+        // A conflict the session listing can't show doesn't loop forever
+        assert_eq!(
+            recovery.on_failure(Some(&cloudmatch_error(11)), true, now),
+            RecoveryStep::GiveUp
+        );
+        assert_eq!(recovery.offer(), Some(RecoveryOffer::Retry));
+        assert_eq!(recovery.on_failure(None, true, now), RecoveryStep::GiveUp);
+
+        let report = diagnostics_report(
+            "Failed to create session: Under Maintenance",
+            Some(&cloudmatch_error(72)),
+            Some(&recovery.game),
+            Some("NP-AMS-08"),
+            recovery.failures(),
+        );
+        assert!(report.contains("Game: Game (100932911)\nZone: NP-AMS-08\nFailed launches: 6\n"));
+        assert!(report.contains("Unified error code: -2121088763 (0x8192C105)"));
+    }
+}
@@ -0,0 +1,490 @@
+//! Server Scoring
+//!
+//! One scoring model for automatic server selection, shared by the zone
+//! list (Settings / launch) and the queue servers (free tier picker and
+//! Queue Times tab). Lower is better. The weights come from
+//! [`ServerScoring`] in the settings; jitter and packet loss come from past
+//! sessions on the same zone, kept in `zone_quality.json` under both the
+//! zone ID and the server's VPC ID so either list finds them.
+//!
+//! Every part of a score is kept so the UI can explain the pick.
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::config::ServerScoring;
+use super::{cache, ServerInfo, SessionInfo, Settings};
+use crate::api::{format_queue_eta, QueueServerInfo};
+use crate::media::StreamStats;
+
+/// Ping assumed for servers without a ping result
+const UNKNOWN_PING_MS: u32 = 500;
+
+/// Weight of the newest session in the per-zone averages
+const QUALITY_SMOOTHING: f32 = 0.3;
+
+/// Stats updates a session needs before it counts (about half a minute)
+const MIN_QUALITY_SAMPLES: u32 = 30;
+
+/// Network quality seen in past sessions on a zone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ZoneQuality {
+    pub sessions: u32,
+    /// Average jitter in ms
+    pub jitter_ms: f32,
+    /// Average packet loss in percent
+    pub packet_loss: f32,
+}
+
+/// Past session quality by zone ID or VPC ID
+pub type ZoneQualityMap = HashMap<String, ZoneQuality>;
+
+/// Network quality of the running session, folded into the zone's
+/// averages when it ends
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionQuality {
+    samples: u32,
+    jitter_sum: f64,
+    loss_sum: f64,
+}
+
+impl SessionQuality {
+    pub fn add(&mut self, stats: &StreamStats) {
+        self.samples += 1;
+        self.jitter_sum += stats.jitter_ms as f64;
+        self.loss_sum += stats.packet_loss as f64;
+    }
+
+    /// Average (jitter ms, packet loss %), None if the session was too short
+    pub fn averages(&self) -> Option<(f32, f32)> {
+        if self.samples < MIN_QUALITY_SAMPLES {
+            return None;
+        }
+        let n = self.samples as f64;
+        Some(((self.jitter_sum / n) as f32, (self.loss_sum / n) as f32))
+    }
+}
+
+/// Fold a finished session into the zone's averages. Returns false if the
+/// session was too short to say anything.
+pub fn record_session_quality(
+    map: &mut ZoneQualityMap,
+    zone: &str,
+    session: &SessionQuality,
+) -> bool {
+    let Some((jitter_ms, packet_loss)) = session.averages() else {
+        return false;
+    };
+    let entry = map.entry(zone.to_string()).or_default();
+    if entry.sessions == 0 {
+        entry.jitter_ms = jitter_ms;
+        entry.packet_loss = packet_loss;
+    } else {
+        let keep = 1.0 - QUALITY_SMOOTHING;
+        entry.jitter_ms = entry.jitter_ms * keep + jitter_ms * QUALITY_SMOOTHING;
+        entry.packet_loss = entry.packet_loss * keep + packet_loss * QUALITY_SMOOTHING;
+    }
+    entry.sessions += 1;
+    debug!(
+        "Zone {} quality: {:.1}ms jitter, {:.2}% loss over {} sessions",
+        zone, entry.jitter_ms, entry.packet_loss, entry.sessions
+    );
+    true
+}
+
+/// Keys a finished session's quality is stored under: the zone ID the zone
+/// list scores by, and the VPC ID queue servers are named by
+pub fn quality_keys(session: &SessionInfo) -> impl Iterator<Item = &str> {
+    std::iter::once(session.zone.as_str())
+        .chain(session.server_id.as_deref())
+        .filter(|key| !key.is_empty())
+}
+
+fn quality_path() -> Option<PathBuf> {
+    cache::get_app_data_dir().map(|p| p.join("zone_quality.json"))
+}
+
+/// Load past session quality (empty if none yet)
+pub fn load_zone_quality() -> ZoneQualityMap {
+    quality_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_zone_quality(map: &ZoneQualityMap) {
+    let Some(path) = quality_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string(map) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                error!("Failed to write zone quality {}: {}", path.display(), e);
+            }
+        }
+        Err(e) => error!("Failed to serialize zone quality: {}", e),
+    }
+}
+
+/// Broad region a zone or queue server is in. The zone list says
+/// "Europe", the queue data says "EU".
+pub fn region_group(region: &str) -> &str {
+    match region {
+        "EU" | "Europe" => "Europe",
+        "US" | "North America" => "North America",
+        "CA" | "Canada" => "Canada",
+        "JP" | "KR" | "THAI" | "MY" | "SG" | "TW" | "AU" | "Asia-Pacific" => "Asia-Pacific",
+        "LATAM" | "South America" => "South America",
+        other => other,
+    }
+}
+
+/// What the scoring model needs to know about a server
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreCandidate<'a> {
+    /// Zone or server ID (for past session quality)
+    pub id: &'a str,
+    pub region: &'a str,
+    pub ping_ms: Option<u32>,
+    pub eta_seconds: Option<i64>,
+    pub is_4080_server: bool,
+    pub is_5080_server: bool,
+}
+
+impl<'a> From<&'a QueueServerInfo> for ScoreCandidate<'a> {
+    fn from(server: &'a QueueServerInfo) -> Self {
+        Self {
+            id: &server.server_id,
+            region: &server.region,
+            ping_ms: server.ping_ms,
+            eta_seconds: server.eta_seconds,
+            is_4080_server: server.is_4080_server,
+            is_5080_server: server.is_5080_server,
+        }
+    }
+}
+
+impl<'a> From<&'a ServerInfo> for ScoreCandidate<'a> {
+    fn from(server: &'a ServerInfo) -> Self {
+        Self {
+            id: &server.id,
+            region: &server.region,
+            ping_ms: server.ping_ms,
+            eta_seconds: None,
+            is_4080_server: false,
+            is_5080_server: false,
+        }
+    }
+}
+
+/// One term of a score
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScorePart {
+    pub label: String,
+    pub points: f64,
+}
+
+/// A server's score with the terms that make it up
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerScore {
+    pub total: f64,
+    pub parts: Vec<ScorePart>,
+    /// Why the server is never auto-selected (max ping limit)
+    pub excluded: Option<String>,
+}
+
+impl ServerScore {
+    /// One-line breakdown, e.g. "ping 24ms +24 · queue ~10m +5 = 29"
+    pub fn explain(&self) -> String {
+        let mut text = self
+            .parts
+            .iter()
+            .map(|part| {
+                let sign = if part.points < 0.0 { '\u{2212}' } else { '+' };
+                format!("{} {}{:.0}", part.label, sign, part.points.abs())
+            })
+            .collect::<Vec<_>>()
+            .join(" \u{b7} ");
+        text.push_str(&format!(" = {:.0}", self.total));
+        if let Some(reason) = &self.excluded {
+            text.push_str(&format!(" (skipped: {})", reason));
+        }
+        text
+    }
+}
+
+/// Scores servers with the user's weights and past session quality
+#[derive(Debug, Clone, Copy)]
+pub struct ServerScorer<'a> {
+    weights: ServerScoring,
+    preferred_region: Option<&'a str>,
+    quality: &'a ZoneQualityMap,
+}
+
+impl<'a> ServerScorer<'a> {
+    pub fn new(settings: &'a Settings, quality: &'a ZoneQualityMap) -> Self {
+        Self {
+            weights: settings.server_scoring.clamped(),
+            preferred_region: settings.preferred_region.as_deref(),
+            quality,
+        }
+    }
+
+    pub fn score(&self, server: ScoreCandidate) -> ServerScore {
+        let w = &self.weights;
+        let mut parts = Vec::new();
+        let mut add = |label: String, points: f64| {
+            if points != 0.0 {
+                parts.push(ScorePart { label, points });
+            }
+        };
+
+        let ping_label = match server.ping_ms {
+            Some(ping) => format!("ping {}ms", ping),
+            None => "ping unknown".to_string(),
+        };
+        let ping = server.ping_ms.unwrap_or(UNKNOWN_PING_MS);
+        add(ping_label, ping as f64 * w.ping_weight as f64);
+
+        if let Some(eta) = server.eta_seconds.filter(|&eta| eta > 0) {
+            let minutes = eta as f64 / 60.0;
+            let points = (minutes * w.eta_weight as f64).min(w.eta_cap as f64);
+            add(format!("queue ~{}", format_queue_eta(Some(eta))), points);
+        }
+
+        if let Some(quality) = self.quality.get(server.id) {
+            add(
+                format!("jitter {:.1}ms", quality.jitter_ms),
+                quality.jitter_ms as f64 * w.jitter_weight as f64,
+            );
+            add(
+                format!("loss {:.2}%", quality.packet_loss),
+                quality.packet_loss as f64 * w.packet_loss_weight as f64,
+            );
+        }
+
+        if server.is_5080_server {
+            add("RTX 5080".to_string(), -(w.gpu_tier_bonus as f64));
+        } else if server.is_4080_server {
+            add("RTX 4080".to_string(), -(w.gpu_tier_bonus as f64) / 2.0);
+        }
+
+        if let Some(preferred) = self.preferred_region {
+            if region_group(server.region) == region_group(preferred) {
+                add("preferred region".to_string(), -(w.region_bonus as f64));
+            }
+        }
+
+        let excluded = match server.ping_ms {
+            _ if w.max_ping_ms == 0 => None,
+            None => Some("no ping result".to_string()),
+            Some(ping) if ping > w.max_ping_ms => Some(format!("ping over {}ms", w.max_ping_ms)),
+            Some(_) => None,
+        };
+
+        ServerScore {
+            total: parts.iter().map(|p| p.points).sum(),
+            parts,
+            excluded,
+        }
+    }
+
+    /// Lowest-scoring server that isn't excluded, with its score
+    pub fn best<'s, T>(
+        &self,
+        servers: impl IntoIterator<Item = &'s T>,
+    ) -> Option<(&'s T, ServerScore)>
+    where
+        T: 's,
+        &'s T: Into<ScoreCandidate<'s>>,
+    {
+        servers
+            .into_iter()
+            .map(|server| (server, self.score(server.into())))
+            .filter(|(_, score)| score.excluded.is_none())
+            .min_by(|a, b| a.1.total.total_cmp(&b.1.total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_server(id: &str, region: &str, ping: Option<u32>, eta_secs: i64) -> QueueServerInfo {
+        QueueServerInfo {
+            server_id: id.to_string(),
+            display_name: id.to_string(),
+            region: region.to_string(),
+            ping_ms: ping,
+            latency: None,
+            queue_position: 0,
+            eta_seconds: Some(eta_secs),
+            is_4080_server: false,
+            is_5080_server: false,
+            last_updated: 0,
+        }
+    }
+
+    #[test]
+    fn test_default_weights_match_previous_formula() {
+        let settings = Settings::default();
+        let quality = ZoneQualityMap::new();
+        let scorer = ServerScorer::new(&settings, &quality);
+
+        // ping + 0.5/min, queue part capped at 100
+        let score = scorer.score((&queue_server("a", "EU", Some(30), 600)).into());
+        assert_eq!(score.total, 35.0);
+        let score = scorer.score((&queue_server("b", "EU", Some(30), 10 * 3600)).into());
+        assert_eq!(score.total, 130.0);
+        let score = scorer.score((&queue_server("c", "EU", None, 0)).into());
+        assert_eq!(score.total, 500.0);
+        assert_eq!(score.explain(), "ping unknown +500 = 500");
+    }
+
+    #[test]
+    fn test_past_quality_gpu_and_region() {
+        let settings = Settings {
+            preferred_region: Some("Europe".to_string()),
+            server_scoring: ServerScoring {
+                gpu_tier_bonus: 20.0,
+                region_bonus: 10.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut quality = ZoneQualityMap::new();
+        quality.insert(
+            "flaky".to_string(),
+            ZoneQuality {
+                sessions: 3,
+                jitter_ms: 5.0,
+                packet_loss: 1.0,
+            },
+        );
+        let scorer = ServerScorer::new(&settings, &quality);
+
+        let flaky = queue_server("flaky", "US", Some(20), 0);
+        let score = scorer.score((&flaky).into());
+        // 20 ping + 5 jitter + 1% * 20 loss
+        assert_eq!(score.total, 45.0);
+        assert_eq!(
+            score.explain(),
+            "ping 20ms +20 \u{b7} jitter 5.0ms +5 \u{b7} loss 1.00% +20 = 45"
+        );
+
+        let mut fast_gpu = queue_server("gpu", "EU", Some(40), 0);
+        fast_gpu.is_5080_server = true;
+        // 40 - 20 (5080) - 10 (EU matches "Europe")
+        assert_eq!(scorer.score((&fast_gpu).into()).total, 10.0);
+
+        let servers = [flaky, fast_gpu];
+        let (best, _) = scorer.best(&servers).unwrap();
+        assert_eq!(best.server_id, "gpu");
+    }
+
+    #[test]
+    fn test_max_ping_excludes() {
+        let mut settings = Settings::default();
+        settings.server_scoring.max_ping_ms = 50;
+        let quality = ZoneQualityMap::new();
+        let scorer = ServerScorer::new(&settings, &quality);
+
+        let servers = [
+            // Best score (80) but too far away
+            queue_server("far", "EU", Some(80), 0),
+            // 30 + 100 (four-hour queue, capped)
+            queue_server("near", "EU", Some(30), 4 * 3600),
+            queue_server("untested", "EU", None, 0),
+        ];
+        let far = scorer.score((&servers[0]).into());
+        assert_eq!(far.excluded.as_deref(), Some("ping over 50ms"));
+        let (best, score) = scorer.best(&servers).unwrap();
+        assert_eq!(best.server_id, "near");
+        assert!(score.excluded.is_none());
+        assert!(far.total < score.total);
+        assert_eq!(
+            scorer.score((&servers[2]).into()).excluded.as_deref(),
+            Some("no ping result")
+        );
+
+        settings.server_scoring.max_ping_ms = 10;
+        let scorer = ServerScorer::new(&settings, &quality);
+        assert!(scorer.best(&servers).is_none());
+    }
+
+    #[test]
+    fn test_record_session_quality() {
+        let mut map = ZoneQualityMap::new();
+        let session = |jitter: f32, loss: f32, samples: u32| {
+            let mut quality = SessionQuality::default();
+            let stats = StreamStats {
+                jitter_ms: jitter,
+                packet_loss: loss,
+                ..Default::default()
+            };
+            for _ in 0..samples {
+                quality.add(&stats);
+            }
+            quality
+        };
+
+        // Too short to count
+        assert!(!record_session_quality(
+            &mut map,
+            "eu",
+            &session(50.0, 5.0, 5)
+        ));
+        assert!(map.is_empty());
+
+        assert!(record_session_quality(
+            &mut map,
+            "eu",
+            &session(10.0, 1.0, 60)
+        ));
+        assert!(record_session_quality(
+            &mut map,
+            "eu",
+            &session(20.0, 0.0, 60)
+        ));
+        let eu = map["eu"];
+        assert_eq!(eu.sessions, 2);
+        assert!((eu.jitter_ms - 13.0).abs() < 1e-4);
+        assert!((eu.packet_loss - 0.7).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_recorded_session_scores_queue_server() {
+        let settings = Settings::default();
+        let mut map = ZoneQualityMap::new();
+        let server = queue_server("NP-AMS-08", "EU", Some(30), 600);
+        let before = ServerScorer::new(&settings, &map)
+            .score((&server).into())
+            .total;
+
+        let mut session = SessionInfo::new_requesting("eu-netherlands-south");
+        session.server_id = Some("NP-AMS-08".to_string());
+        let mut quality = SessionQuality::default();
+        let stats = StreamStats {
+            jitter_ms: 10.0,
+            packet_loss: 1.0,
+            ..Default::default()
+        };
+        for _ in 0..MIN_QUALITY_SAMPLES {
+            quality.add(&stats);
+        }
+        for key in quality_keys(&session) {
+            assert!(record_session_quality(&mut map, key, &quality));
+        }
+
+        assert!(map.contains_key("eu-netherlands-south"));
+        let after = ServerScorer::new(&settings, &map)
+            .score((&server).into())
+            .total;
+        assert!(after > before);
+    }
+}
@@ -16,6 +16,10 @@ pub struct SessionInfo {
     /// Server region/zone
     pub zone: String,
 
+    /// VPC ID of the server CloudMatch answered from (e.g. "NP-AMS-08"),
+    /// the ID queue data uses
+    pub server_id: Option<String>,
+
     /// Current session state
     pub state: SessionState,
 
@@ -77,7 +81,8 @@ pub struct SessionAdsInfo {
 }
 
 /// Session state
-#[derive(Debug, Clone, PartialEq)]
+/// Transitions between states are validated by `SessionController`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SessionState {
     /// Requesting session from CloudMatch
     Requesting,
@@ -125,6 +130,7 @@ impl SessionInfo {
             session_id: String::new(),
             server_ip: String::new(),
             zone: zone.to_string(),
+            server_id: None,
             state: SessionState::Requesting,
             gpu_type: None,
             signaling_url: None,
@@ -154,6 +160,21 @@ impl SessionInfo {
     }
 }
 
+impl SessionState {
+    /// Server-side setup steps between the request and a ready seat
+    pub fn is_setup(&self) -> bool {
+        matches!(
+            self,
+            SessionState::Connecting
+                | SessionState::Launching
+                | SessionState::InQueue { .. }
+                | SessionState::WatchingAds { .. }
+                | SessionState::CleaningUp
+                | SessionState::WaitingForStorage
+        )
+    }
+}
+
 // ============================================
 // CloudMatch API Request Types (Browser Format)
 // ============================================
@@ -370,6 +391,91 @@ pub struct RequestStatus {
 }
 
 impl CloudMatchSession {
+    /// Session state from status and seat setup step, without the ads override
+    pub fn session_state(&self) -> SessionState {
+        // Status 2 = ready for streaming
+        if self.status == 2 {
+            return SessionState::Ready;
+        }
+
+        // Status 3 = already streaming
+        if self.status == 3 {
+            return SessionState::Streaming;
+        }
+
+        // Check seat setup info for detailed states
+        if let Some(ref seat_info) = self.seat_setup_info {
+            match seat_info.seat_setup_step {
+                0 => return SessionState::Connecting,
+                1 => {
+                    // In queue - show position
+                    return SessionState::InQueue {
+                        position: seat_info.queue_position.max(0) as u32,
+                        eta_secs: (seat_info.seat_setup_eta / 1000).max(0) as u32,
+                    };
+                }
+                5 => return SessionState::CleaningUp,
+                6 => return SessionState::WaitingForStorage,
+                _ => {
+                    // Other steps = general launching/configuring
+                    if seat_info.seat_setup_step > 0 {
+                        return SessionState::Launching;
+                    }
+                }
+            }
+        }
+
+        // Status 1 = setting up
+        if self.status == 1 {
+            return SessionState::Launching;
+        }
+
+        // Error states
+        if self.status <= 0 || self.error_code != 0 {
+            return SessionState::Error(format!(
+                "Error code: {} (status: {})",
+                self.error_code, self.status
+            ));
+        }
+
+        SessionState::Launching
+    }
+
+    /// Session state as shown to the user: free tier sessions that are
+    /// queued or launching are watching ads
+    pub fn lifecycle_state(&self) -> SessionState {
+        let state = self.session_state();
+        if !self.session_ads_required {
+            return state;
+        }
+        match state {
+            SessionState::InQueue { .. } | SessionState::Launching => {
+                let duration = self.ads_info().map(|a| a.duration_secs).unwrap_or(120);
+                SessionState::WatchingAds {
+                    remaining_secs: duration,
+                    total_secs: duration,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Ad configuration if present (free tier)
+    pub fn ads_info(&self) -> Option<SessionAdsInfo> {
+        self.session_ads.as_ref().map(|ads| SessionAdsInfo {
+            video_url: ads
+                .get("videoUrl")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            duration_secs: ads.get("duration").and_then(|v| v.as_u64()).unwrap_or(120) as u32,
+            completion_url: ads
+                .get("completionUrl")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            raw_config: Some(ads.clone()),
+        })
+    }
+
     /// Extract streaming server IP from connection info
     pub fn streaming_server_ip(&self) -> Option<String> {
         // Look for connection with usage=14 (signaling) first
@@ -520,6 +626,77 @@ impl CloudMatchSession {
             })
             .unwrap_or_default()
     }
+
+    /// Session info for the app. `server_id` is the response's
+    /// `requestStatus.serverId`; `fallback_server_ip` stands in when the
+    /// response names no streaming server (claim responses).
+    pub fn into_session_info(
+        self,
+        zone: &str,
+        server_id: Option<String>,
+        fallback_server_ip: Option<&str>,
+    ) -> SessionInfo {
+        let state = self.lifecycle_state();
+        let server_ip = self
+            .streaming_server_ip()
+            .or_else(|| fallback_server_ip.map(str::to_string))
+            .unwrap_or_default();
+
+        // Full signaling URL, defaulting to the server's /nvst/ endpoint
+        let signaling_url = self
+            .signaling_url()
+            .map(|path| build_signaling_url(&path, &server_ip))
+            .or_else(|| {
+                if !server_ip.is_empty() {
+                    Some(format!("wss://{}:443/nvst/", server_ip))
+                } else {
+                    None
+                }
+            });
+
+        let ice_servers = self.ice_servers();
+        let media_connection_info = self.media_connection_info();
+        let ads_info = self.ads_info();
+
+        SessionInfo {
+            session_id: self.session_id,
+            server_ip,
+            zone: zone.to_string(),
+            server_id,
+            state,
+            gpu_type: self.gpu_type,
+            signaling_url,
+            ice_servers,
+            media_connection_info,
+            ads_required: self.session_ads_required,
+            ads_info,
+        }
+    }
+}
+
+/// Build signaling WebSocket URL from raw path/URL
+fn build_signaling_url(raw: &str, server_ip: &str) -> String {
+    if raw.starts_with("rtsps://") || raw.starts_with("rtsp://") {
+        // Extract hostname from RTSP URL
+        let host = raw
+            .strip_prefix("rtsps://")
+            .or_else(|| raw.strip_prefix("rtsp://"))
+            .and_then(|s| s.split(':').next())
+            .filter(|h| !h.is_empty() && !h.starts_with('.'));
+
+        if let Some(h) = host {
+            format!("wss://{}/nvst/", h)
+        } else {
+            // Malformed URL, use server IP
+            format!("wss://{}:443/nvst/", server_ip)
+        }
+    } else if raw.starts_with("wss://") {
+        raw.to_string()
+    } else if raw.starts_with('/') {
+        format!("wss://{}:443{}", server_ip, raw)
+    } else {
+        format!("wss://{}:443/nvst/", server_ip)
+    }
 }
 
 // ============================================
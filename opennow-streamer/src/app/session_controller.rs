@@ -0,0 +1,378 @@
+//! Session Lifecycle
+//!
+//! Owns the GFN session state machine:
+//! Requesting -> setup (connecting, queue, ads, cleanup, storage) -> Ready
+//! -> Streaming -> Terminated. Every CloudMatch update (create, poll, claim)
+//! goes through [`SessionController::apply_update`], which rejects
+//! transitions the lifecycle doesn't allow and queues [`LifecycleEvent`]s for
+//! the UI.
+
+use anyhow::{bail, Result};
+use log::{debug, info};
+
+use super::session::{SessionInfo, SessionState};
+
+/// Polls to run in Ready before connecting, so the server has its ICE candidates in place
+pub const READY_POLLS: u32 = 3;
+
+/// Lifecycle events for the UI
+#[derive(Debug, Clone)]
+pub enum LifecycleEvent {
+    /// Session moved to a new state (or queue position / ads time changed)
+    StateChanged(SessionState),
+    /// Ready poll `n` of [`READY_POLLS`] before connecting
+    Finalizing(u32),
+    /// Seat is ready, start streaming
+    ReadyToStream(Box<SessionInfo>),
+    /// Session failed with a user-facing message
+    Failed(String),
+    /// Session was ended by the user or the stream closed
+    Terminated,
+}
+
+/// Session lifecycle state machine
+#[derive(Debug, Default)]
+pub struct SessionController {
+    /// Latest session info, None when idle
+    session: Option<SessionInfo>,
+    /// Polls sent since the session became ready
+    ready_polls: u32,
+    events: Vec<LifecycleEvent>,
+}
+
+impl SessionController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current state, None when no session has been requested
+    pub fn state(&self) -> Option<&SessionState> {
+        self.session.as_ref().map(|s| &s.state)
+    }
+
+    /// Latest session info from CloudMatch
+    pub fn session(&self) -> Option<&SessionInfo> {
+        self.session.as_ref()
+    }
+
+    /// Whether `from -> to` is a valid lifecycle step (`from` None = idle)
+    pub fn transition_allowed(from: Option<&SessionState>, to: &SessionState) -> bool {
+        match (from, to) {
+            // Anything can fail or be torn down
+            (_, SessionState::Error(_) | SessionState::Terminated) => true,
+            // New request from idle or after the previous session ended
+            (
+                None | Some(SessionState::Error(_)) | Some(SessionState::Terminated),
+                SessionState::Requesting,
+            ) => true,
+            // Setup steps come in any order (queue -> cleanup -> queue is normal)
+            (Some(from), to) if to.is_setup() => {
+                *from == SessionState::Requesting || from.is_setup()
+            }
+            (Some(from), SessionState::Ready) => {
+                *from == SessionState::Requesting || from.is_setup() || *from == SessionState::Ready
+            }
+            (Some(SessionState::Ready | SessionState::Streaming), SessionState::Streaming) => true,
+            _ => false,
+        }
+    }
+
+    /// Start a new session request (launch or resume) in `zone`
+    pub fn begin(&mut self, zone: &str) -> Result<()> {
+        self.transition(SessionState::Requesting)?;
+        self.session = Some(SessionInfo::new_requesting(zone));
+        self.ready_polls = 0;
+        Ok(())
+    }
+
+    /// Apply a session update from CloudMatch. Repeats of the current state
+    /// are ignored; invalid transitions are rejected and leave the state as is.
+    pub fn apply_update(&mut self, mut update: SessionInfo) -> Result<()> {
+        let Some(current) = self.session.as_ref() else {
+            bail!(
+                "Session update {:?} with no session requested",
+                update.state
+            );
+        };
+
+        // Status 3 before we've connected is a session we're re-attaching to
+        if update.state == SessionState::Streaming && current.state != SessionState::Streaming {
+            update.state = SessionState::Ready;
+        }
+        // Claim responses don't carry the zone
+        if update.zone.is_empty() {
+            update.zone = current.zone.clone();
+        }
+
+        let changed = update.state != current.state;
+        if changed {
+            self.transition(update.state.clone())?;
+        }
+        let state = update.state.clone();
+        self.session = Some(update);
+
+        if changed {
+            match state {
+                SessionState::Error(message) => self.events.push(LifecycleEvent::Failed(message)),
+                SessionState::Ready => {
+                    self.events
+                        .push(LifecycleEvent::StateChanged(SessionState::Ready));
+                    self.events
+                        .push(LifecycleEvent::Finalizing(self.ready_polls + 1));
+                }
+                state => self.events.push(LifecycleEvent::StateChanged(state)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the session needs another status poll
+    pub fn should_poll(&self) -> bool {
+        let Some(session) = self.session.as_ref() else {
+            return false;
+        };
+        if session.session_id.is_empty() {
+            // Create request still in flight
+            return false;
+        }
+        match session.state {
+            SessionState::Requesting => true,
+            SessionState::Ready => self.ready_polls < READY_POLLS,
+            ref state => state.is_setup(),
+        }
+    }
+
+    /// Record a status poll being sent. The last ready poll hands the
+    /// session over for streaming.
+    pub fn note_poll(&mut self) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        if session.state != SessionState::Ready {
+            return;
+        }
+
+        self.ready_polls += 1;
+        if self.ready_polls < READY_POLLS {
+            self.events
+                .push(LifecycleEvent::Finalizing(self.ready_polls + 1));
+        } else if self.ready_polls == READY_POLLS {
+            info!("Session ready after {} polls", self.ready_polls);
+            self.events
+                .push(LifecycleEvent::ReadyToStream(Box::new(session.clone())));
+        }
+    }
+
+    /// Client connected to the seat
+    pub fn mark_streaming(&mut self) -> Result<()> {
+        self.transition(SessionState::Streaming)
+    }
+
+    /// Session failed outside of a CloudMatch update (request error, etc.)
+    pub fn fail(&mut self, message: &str) {
+        if self
+            .transition(SessionState::Error(message.to_string()))
+            .is_ok()
+        {
+            self.events
+                .push(LifecycleEvent::Failed(message.to_string()));
+        }
+    }
+
+    /// Session ended (user stopped it or the stream closed)
+    pub fn terminate(&mut self) {
+        match self.state() {
+            None | Some(SessionState::Terminated) => {}
+            Some(_) => {
+                // Always allowed
+                let _ = self.transition(SessionState::Terminated);
+                self.events.push(LifecycleEvent::Terminated);
+            }
+        }
+    }
+
+    /// Take queued events
+    pub fn drain_events(&mut self) -> Vec<LifecycleEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn transition(&mut self, to: SessionState) -> Result<()> {
+        let from = self.state();
+        if !Self::transition_allowed(from, &to) {
+            bail!("Invalid session transition {:?} -> {:?}", from, to);
+        }
+        debug!("Session state: {:?} -> {:?}", from, to);
+        match self.session.as_mut() {
+            Some(session) => session.state = to,
+            None => {
+                let mut session = SessionInfo::new_requesting("");
+                session.state = to;
+                self.session = Some(session);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::session::CloudMatchResponse;
+
+    /// Recorded CloudMatch responses (IDs and addresses anonymised)
+    fn fixture(name: &str) -> SessionInfo {
+        let json = match name {
+            "connecting" => include_str!("../../tests/fixtures/cloudmatch/connecting.json"),
+            "queue" => include_str!("../../tests/fixtures/cloudmatch/queue.json"),
+            "queue_ads" => include_str!("../../tests/fixtures/cloudmatch/queue_ads.json"),
+            "cleaning_up" => include_str!("../../tests/fixtures/cloudmatch/cleaning_up.json"),
+            "storage" => include_str!("../../tests/fixtures/cloudmatch/storage.json"),
+            "ready" => include_str!("../../tests/fixtures/cloudmatch/ready.json"),
+            "streaming" => include_str!("../../tests/fixtures/cloudmatch/streaming.json"),
+            "error" => include_str!("../../tests/fixtures/cloudmatch/error.json"),
+            _ => panic!("unknown fixture {}", name),
+        };
+        let response: CloudMatchResponse = serde_json::from_str(json).unwrap();
+        response.session.into_session_info(
+            "eu-netherlands-south",
+            response.request_status.server_id,
+            None,
+        )
+    }
+
+    fn requested() -> SessionController {
+        let mut controller = SessionController::new();
+        controller.begin("eu-netherlands-south").unwrap();
+        controller
+    }
+
+    #[test]
+    fn test_fixture_states() {
+        assert_eq!(fixture("connecting").state, SessionState::Connecting);
+        assert_eq!(
+            fixture("queue").state,
+            SessionState::InQueue {
+                position: 14,
+                eta_secs: 420
+            }
+        );
+        assert_eq!(
+            fixture("queue_ads").state,
+            SessionState::WatchingAds {
+                remaining_secs: 90,
+                total_secs: 90
+            }
+        );
+        assert_eq!(fixture("cleaning_up").state, SessionState::CleaningUp);
+        assert_eq!(fixture("storage").state, SessionState::WaitingForStorage);
+        assert_eq!(fixture("ready").state, SessionState::Ready);
+        let ready = fixture("ready");
+        assert_eq!(ready.server_ip, "10.0.0.42");
+        assert_eq!(ready.gpu_type.as_deref(), Some("RTX 4080"));
+        assert_eq!(
+            ready.signaling_url.as_deref(),
+            Some("wss://10.0.0.42/nvst/")
+        );
+        let media = ready.media_connection_info.unwrap();
+        assert_eq!((media.ip.as_str(), media.port), ("10.0.0.42", 49006));
+        assert!(fixture("queue_ads").ads_info.is_some());
+        assert_eq!(fixture("streaming").state, SessionState::Streaming);
+        assert!(matches!(fixture("error").state, SessionState::Error(_)));
+    }
+
+    #[test]
+    fn test_full_lifecycle() {
+        let mut controller = requested();
+        assert!(
+            !controller.should_poll(),
+            "no session ID before create returns"
+        );
+
+        for name in ["connecting", "queue_ads", "queue", "cleaning_up", "storage"] {
+            controller.apply_update(fixture(name)).unwrap();
+            assert!(controller.should_poll());
+        }
+        let events = controller.drain_events();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            events[1],
+            LifecycleEvent::StateChanged(SessionState::WatchingAds { .. })
+        ));
+
+        // Same state again is not an event
+        controller.apply_update(fixture("storage")).unwrap();
+        assert!(controller.drain_events().is_empty());
+
+        controller.apply_update(fixture("ready")).unwrap();
+        for _ in 0..READY_POLLS {
+            assert!(controller.should_poll());
+            controller.note_poll();
+        }
+        assert!(!controller.should_poll());
+        let events = controller.drain_events();
+        assert!(matches!(events[1], LifecycleEvent::Finalizing(1)));
+        let Some(LifecycleEvent::ReadyToStream(session)) = events.last() else {
+            panic!("expected ReadyToStream, got {:?}", events);
+        };
+        assert_eq!(session.server_ip, "10.0.0.42");
+
+        controller.mark_streaming().unwrap();
+        assert!(!controller.should_poll());
+        controller.terminate();
+        assert_eq!(controller.state(), Some(&SessionState::Terminated));
+        assert!(matches!(
+            controller.drain_events().as_slice(),
+            [LifecycleEvent::Terminated]
+        ));
+
+        // A new session can start afterwards
+        controller.begin("us-east").unwrap();
+    }
+
+    #[test]
+    fn test_invalid_transitions_rejected() {
+        let mut controller = requested();
+        // Can't stream before the seat is ready
+        assert!(controller.mark_streaming().is_err());
+        // Can't start a second request while one is active
+        assert!(controller.begin("us-east").is_err());
+
+        controller.apply_update(fixture("ready")).unwrap();
+        // Server going back to the queue after Ready is ignored
+        assert!(controller.apply_update(fixture("queue")).is_err());
+        assert_eq!(controller.state(), Some(&SessionState::Ready));
+
+        // Updates with nothing requested
+        let mut idle = SessionController::new();
+        assert!(idle.apply_update(fixture("queue")).is_err());
+        assert!(idle.mark_streaming().is_err());
+    }
+
+    #[test]
+    fn test_error_fixture_fails_session() {
+        let mut controller = requested();
+        controller.apply_update(fixture("queue")).unwrap();
+        controller.apply_update(fixture("error")).unwrap();
+        let events = controller.drain_events();
+        assert!(matches!(events.last(), Some(LifecycleEvent::Failed(_))));
+        assert!(!controller.should_poll());
+
+        // Retry is a new request
+        controller.begin("eu-netherlands-south").unwrap();
+        assert_eq!(controller.state(), Some(&SessionState::Requesting));
+    }
+
+    #[test]
+    fn test_resumed_streaming_session_waits_for_ready_polls() {
+        let mut controller = requested();
+        let mut claimed = fixture("streaming");
+        claimed.zone = String::new();
+        controller.apply_update(claimed).unwrap();
+        assert_eq!(controller.state(), Some(&SessionState::Ready));
+        assert_eq!(
+            controller.session().map(|s| s.zone.as_str()),
+            Some("eu-netherlands-south")
+        );
+        assert!(controller.should_poll());
+    }
+}
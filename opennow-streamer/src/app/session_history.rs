@@ -0,0 +1,392 @@
+//! Session History
+//!
+//! Keeps a local record of every streamed session (game, server, settings,
+//! average stats and why it ended) in `session_history.jsonl`, and sums it
+//! up into per-game playtime and monthly totals for the History tab.
+
+use chrono::{DateTime, Datelike, Local};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use super::cache;
+use super::{GameInfo, SessionInfo, Settings};
+use crate::media::StreamStats;
+
+/// Why a session ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum SessionExit {
+    /// Stopped from the client (Stop / Ctrl+Shift+Q)
+    Stopped,
+    /// Stream ended on its own (server closed it, idle timeout)
+    Ended,
+    /// Stream failed
+    Error(String),
+    /// Stream changed on the server and could not be re-established
+    StreamChanged,
+}
+
+impl SessionExit {
+    pub fn label(&self) -> &str {
+        match self {
+            SessionExit::Stopped => "Stopped",
+            SessionExit::Ended => "Ended",
+            SessionExit::Error(_) => "Error",
+            SessionExit::StreamChanged => "Stream lost",
+        }
+    }
+}
+
+/// Average stream stats over a session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionAverages {
+    pub fps: f32,
+    pub bitrate_mbps: f32,
+    pub latency_ms: f32,
+    pub jitter_ms: f32,
+    pub packet_loss: f32,
+    pub decode_time_ms: f32,
+}
+
+/// One finished session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub game_id: String,
+    pub game_title: String,
+    pub zone: String,
+    #[serde(default)]
+    pub gpu_type: Option<String>,
+    /// RFC 3339 local times
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_secs: u64,
+    /// Settings the session was started with
+    pub resolution: String,
+    pub fps: u32,
+    pub codec: String,
+    pub max_bitrate_mbps: u32,
+    /// Resolution actually decoded last, if different from the requested one
+    #[serde(default)]
+    pub stream_resolution: Option<String>,
+    #[serde(default)]
+    pub averages: Option<SessionAverages>,
+    pub exit: SessionExit,
+}
+
+/// Session being streamed, turned into a [`SessionRecord`] when it stops
+pub struct ActiveSession {
+    session_id: String,
+    record: SessionRecord,
+    started: Instant,
+    stats: StatsTotals,
+    exit: Option<SessionExit>,
+}
+
+impl ActiveSession {
+    pub fn start(session: &SessionInfo, game: Option<&GameInfo>, settings: &Settings) -> Self {
+        let now = Local::now().to_rfc3339();
+        Self {
+            session_id: session.session_id.clone(),
+            record: SessionRecord {
+                game_id: game.map(|g| g.id.clone()).unwrap_or_default(),
+                game_title: game
+                    .map(|g| g.title.clone())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                zone: session.zone.clone(),
+                gpu_type: session.gpu_type.clone(),
+                started_at: now.clone(),
+                ended_at: now,
+                duration_secs: 0,
+                resolution: settings.resolution.clone(),
+                fps: settings.fps,
+                codec: settings.codec.as_str().to_string(),
+                max_bitrate_mbps: settings.max_bitrate_mbps,
+                stream_resolution: None,
+                averages: None,
+                exit: SessionExit::Stopped,
+            },
+            started: Instant::now(),
+            stats: StatsTotals::default(),
+            exit: None,
+        }
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn add_stats(&mut self, stats: &StreamStats) {
+        self.stats.add(stats);
+        if !stats.resolution.is_empty() {
+            self.record.stream_resolution = Some(stats.resolution.clone());
+        }
+    }
+
+    /// Remember how the stream ended; the first reason reported wins
+    pub fn set_exit(&mut self, exit: SessionExit) {
+        self.exit.get_or_insert(exit);
+    }
+
+    /// Close the record (stopped from the client unless the stream already ended)
+    pub fn finish(mut self) -> SessionRecord {
+        self.record.ended_at = Local::now().to_rfc3339();
+        self.record.duration_secs = self.started.elapsed().as_secs();
+        self.record.averages = self.stats.averages();
+        if self.record.stream_resolution.as_deref() == Some(self.record.resolution.as_str()) {
+            self.record.stream_resolution = None;
+        }
+        self.record.exit = self.exit.unwrap_or(SessionExit::Stopped);
+        self.record
+    }
+}
+
+/// Running sums for [`SessionAverages`]
+#[derive(Debug, Default)]
+struct StatsTotals {
+    samples: u32,
+    sums: SessionAverages,
+}
+
+impl StatsTotals {
+    fn add(&mut self, stats: &StreamStats) {
+        // Stats before the first frame are all zeros
+        if stats.fps <= 0.0 {
+            return;
+        }
+        self.samples += 1;
+        self.sums.fps += stats.fps;
+        self.sums.bitrate_mbps += stats.bitrate_mbps;
+        self.sums.latency_ms += stats.latency_ms;
+        self.sums.jitter_ms += stats.jitter_ms;
+        self.sums.packet_loss += stats.packet_loss;
+        self.sums.decode_time_ms += stats.decode_time_ms;
+    }
+
+    fn averages(&self) -> Option<SessionAverages> {
+        if self.samples == 0 {
+            return None;
+        }
+        let n = self.samples as f32;
+        Some(SessionAverages {
+            fps: self.sums.fps / n,
+            bitrate_mbps: self.sums.bitrate_mbps / n,
+            latency_ms: self.sums.latency_ms / n,
+            jitter_ms: self.sums.jitter_ms / n,
+            packet_loss: self.sums.packet_loss / n,
+            decode_time_ms: self.sums.decode_time_ms / n,
+        })
+    }
+}
+
+/// Playtime for one game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamePlaytime {
+    pub game_title: String,
+    pub sessions: u32,
+    pub total_secs: u64,
+    /// RFC 3339 start of the latest session
+    pub last_played: String,
+}
+
+/// Playtime for one calendar month
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthPlaytime {
+    pub year: i32,
+    pub month: u32,
+    pub sessions: u32,
+    pub total_secs: u64,
+}
+
+impl MonthPlaytime {
+    pub fn hours(&self) -> f32 {
+        self.total_secs as f32 / 3600.0
+    }
+}
+
+/// Per-game totals, most played first
+pub fn playtime_by_game(records: &[SessionRecord]) -> Vec<GamePlaytime> {
+    let mut games: HashMap<&str, GamePlaytime> = HashMap::new();
+    for record in records {
+        let key = if record.game_id.is_empty() {
+            record.game_title.as_str()
+        } else {
+            record.game_id.as_str()
+        };
+        let entry = games.entry(key).or_insert_with(|| GamePlaytime {
+            game_title: record.game_title.clone(),
+            sessions: 0,
+            total_secs: 0,
+            last_played: String::new(),
+        });
+        entry.sessions += 1;
+        entry.total_secs += record.duration_secs;
+        if record.started_at > entry.last_played {
+            entry.last_played = record.started_at.clone();
+            entry.game_title = record.game_title.clone();
+        }
+    }
+    let mut games: Vec<GamePlaytime> = games.into_values().collect();
+    games.sort_by(|a, b| {
+        b.total_secs
+            .cmp(&a.total_secs)
+            .then_with(|| a.game_title.cmp(&b.game_title))
+    });
+    games
+}
+
+/// Monthly totals, latest month first
+pub fn playtime_by_month(records: &[SessionRecord]) -> Vec<MonthPlaytime> {
+    let mut months: HashMap<(i32, u32), MonthPlaytime> = HashMap::new();
+    for record in records {
+        let Ok(started) = DateTime::parse_from_rfc3339(&record.started_at) else {
+            continue;
+        };
+        let key = (started.year(), started.month());
+        let entry = months.entry(key).or_insert_with(|| MonthPlaytime {
+            year: key.0,
+            month: key.1,
+            sessions: 0,
+            total_secs: 0,
+        });
+        entry.sessions += 1;
+        entry.total_secs += record.duration_secs;
+    }
+    let mut months: Vec<MonthPlaytime> = months.into_values().collect();
+    months.sort_by_key(|m| std::cmp::Reverse((m.year, m.month)));
+    months
+}
+
+/// "45 min", "3 h 20 min"
+pub fn format_playtime(secs: u64) -> String {
+    let minutes = (secs + 30) / 60;
+    if minutes < 60 {
+        format!("{} min", minutes)
+    } else {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    cache::get_app_data_dir().map(|p| p.join("session_history.jsonl"))
+}
+
+/// All recorded sessions, oldest first
+pub fn load_history() -> Vec<SessionRecord> {
+    let Some(content) = history_path().and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub fn append_history(record: &SessionRecord) {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let line = match serde_json::to_string(record) {
+        Ok(line) => line,
+        Err(e) => {
+            warn!("Failed to serialize session history: {}", e);
+            return;
+        }
+    };
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+    match result {
+        Ok(()) => info!(
+            "Session history: {} on {} for {} ({})",
+            record.game_title,
+            record.zone,
+            format_playtime(record.duration_secs),
+            record.exit.label()
+        ),
+        Err(e) => warn!("Failed to write session history {}: {}", path.display(), e),
+    }
+}
+
+/// Finished session for tests, 1080p60 H.264 on one zone
+#[cfg(test)]
+pub(crate) fn test_record(game: &str, started_at: &str, duration_secs: u64) -> SessionRecord {
+    SessionRecord {
+        game_id: game.to_lowercase(),
+        game_title: game.to_string(),
+        zone: "NP-AMS-01".to_string(),
+        gpu_type: None,
+        started_at: started_at.to_string(),
+        ended_at: started_at.to_string(),
+        duration_secs,
+        resolution: "1920x1080".to_string(),
+        fps: 60,
+        codec: "H264".to_string(),
+        max_bitrate_mbps: 50,
+        stream_resolution: None,
+        averages: None,
+        exit: SessionExit::Stopped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_averages_skip_empty() {
+        let mut totals = StatsTotals::default();
+        assert_eq!(totals.averages(), None);
+
+        totals.add(&StreamStats::default());
+        for (fps, latency) in [(60.0, 20.0), (50.0, 30.0)] {
+            totals.add(&StreamStats {
+                fps,
+                latency_ms: latency,
+                ..Default::default()
+            });
+        }
+        let averages = totals.averages().unwrap();
+        assert_eq!(averages.fps, 55.0);
+        assert_eq!(averages.latency_ms, 25.0);
+    }
+
+    #[test]
+    fn test_playtime_by_game_and_month() {
+        let records = [
+            test_record("Cyberpunk", "2026-09-30T22:00:00+02:00", 3600),
+            test_record("Fortnite", "2026-10-01T18:00:00+02:00", 1800),
+            test_record("Cyberpunk", "2026-10-02T20:00:00+02:00", 5400),
+        ];
+
+        let games = playtime_by_game(&records);
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].game_title, "Cyberpunk");
+        assert_eq!((games[0].sessions, games[0].total_secs), (2, 9000));
+        assert_eq!(games[0].last_played, "2026-10-02T20:00:00+02:00");
+
+        let months = playtime_by_month(&records);
+        assert_eq!(months.len(), 2);
+        assert_eq!((months[0].year, months[0].month), (2026, 10));
+        assert_eq!((months[0].sessions, months[0].total_secs), (2, 7200));
+        assert_eq!(months[0].hours(), 2.0);
+        assert_eq!((months[1].month, months[1].total_secs), (9, 3600));
+    }
+
+    #[test]
+    fn test_exit_round_trip() {
+        let mut r = test_record("Fortnite", "2026-10-01T18:00:00+02:00", 60);
+        r.exit = SessionExit::Error("ICE failed".to_string());
+        let line = serde_json::to_string(&r).unwrap();
+        assert!(line.contains(r#""exit":{"reason":"error","detail":"ICE failed"}"#));
+        let back: SessionRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(back.exit, r.exit);
+        assert_eq!(format_playtime(back.duration_secs), "1 min");
+        assert_eq!(format_playtime(3 * 3600 + 20 * 60), "3 h 20 min");
+    }
+}
@@ -5,8 +5,12 @@
 use parking_lot::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use super::config::{ColorQuality, VideoCodec, VideoDecoderBackend};
+use super::config::{
+    AudioDecoderBackend, ColorAdjustments, ColorQuality, PingMode, QueueProvider, ScalingMode,
+    ServerScoring, SessionLogFormat, ToneMapper, UpscaleFilter, VideoCodec, VideoDecoderBackend,
+};
 use crate::media::VideoFrame;
+use crate::utils::LatencyStats;
 
 /// Shared frame holder for zero-latency frame delivery
 /// Decoder writes latest frame, renderer reads it - no buffering
@@ -152,6 +156,7 @@ pub enum GamesTab {
     AllGames,   // Flat grid view
     MyLibrary,  // User's library
     QueueTimes, // Queue times for games (hidden, for free tier users)
+    History,    // Past sessions and playtime
 }
 
 impl Default for GamesTab {
@@ -197,6 +202,8 @@ pub struct ServerInfo {
     pub region: String,
     pub url: Option<String>,
     pub ping_ms: Option<u32>,
+    /// Full probe result behind `ping_ms`
+    pub latency: Option<LatencyStats>,
     pub status: ServerStatus,
 }
 
@@ -223,8 +230,12 @@ pub enum UiAction {
     LaunchGameDirect(GameInfo),
     /// Stop streaming
     StopStreaming,
+    /// Relaunch a failed game now (skips any scheduled retry)
+    RetryLaunch,
     /// Toggle stats overlay
     ToggleStats,
+    /// Toggle the in-stream colour adjustment overlay
+    ToggleColorOverlay,
     /// Update search query
     UpdateSearch(String),
     /// Toggle settings panel
@@ -247,6 +258,8 @@ pub enum UiAction {
     SetAutoServerSelection(bool),
     /// Start ping test for all servers
     StartPingTest,
+    /// Re-run the decoder capability probe, ignoring the cache
+    ProbeDecoders,
     /// Toggle settings modal
     ToggleSettingsModal,
     /// Resume an active session
@@ -291,10 +304,63 @@ pub enum SettingChange {
     Fullscreen(bool),
     VSync(bool),
     LowLatency(bool),
+    FramePacing(bool),
+    UpscaleFilter(UpscaleFilter),
+    UpscaleStrength(f32),
+    ScalingMode(ScalingMode),
+    CustomZoom(f32),
+    CustomPan(f32, f32),
     DecoderBackend(VideoDecoderBackend),
+    AudioDecoder(AudioDecoderBackend),
     ColorQuality(ColorQuality),
     Hdr(bool),
+    ToneMapper(ToneMapper),
+    HdrTargetNits(f32),
+    HdrPeakNits(f32),
+    HdrSaturation(f32),
+    HdrOutput(bool),
+    /// Colour adjustments for games without their own
+    ColorAdjustments(ColorAdjustments),
+    /// Colour adjustments for one game (game ID)
+    GameColorAdjustments(String, ColorAdjustments),
+    /// Drop a game's own colour adjustments (game ID)
+    ResetGameColorAdjustments(String),
     ClipboardPasteEnabled(bool),
+    QueueNotifications(bool),
+    QueueNotifySound(bool),
+    /// Queue positions to notify at
+    QueueNotifyThresholds(Vec<u32>),
+    PlaytimeWarnings(bool),
+    /// Minutes left to warn at
+    PlaytimeWarningMinutes(Vec<u32>),
+    /// Session length limit in minutes (0 = tier default)
+    SessionLimit(u32),
+    QueueProvider(QueueProvider),
+    /// Custom provider queue data URL
+    QueueCustomUrl(String),
+    /// Custom provider server mapping URL (empty = none)
+    QueueCustomMappingUrl(String),
+    QueueRace(bool),
+    QueueRaceZones(u32),
+    QueueRaceTimeout(u32),
+    /// Weights for automatic server selection
+    ServerScoring(ServerScoring),
+    /// Region favoured by automatic server selection (None = any)
+    PreferredRegion(Option<String>),
+    PingMode(PingMode),
+    /// Latency samples per server
+    PingSamples(u32),
+    /// Proxy URL (empty = direct)
+    Proxy(String),
+    /// Extra trusted CA certificate files
+    CaCertificates(Vec<String>),
+    /// HTTP request timeout in seconds (0 = none)
+    HttpTimeout(u32),
+    ReplayBuffer(bool),
+    ReplayDuration(u32),
+    ScreenshotHdr16Bit(bool),
+    SessionLog(bool),
+    SessionLogFormat(SessionLogFormat),
 }
 
 /// Application state enum
@@ -0,0 +1,206 @@
+//! Stream Viewport
+//!
+//! Where the stream lands in the window for each scaling mode, and the
+//! reverse mapping used to turn window cursor positions into stream pixels.
+
+use super::config::ScalingMode;
+
+/// Rectangle in window pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewRect {
+    /// Overlap with another rectangle, None if they don't intersect
+    pub fn intersect(&self, other: &ViewRect) -> Option<ViewRect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        if x1 <= x0 || y1 <= y0 {
+            return None;
+        }
+        Some(ViewRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        })
+    }
+}
+
+/// Placement of the stream inside the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Stream rectangle in window pixels; extends past the window when cropping
+    pub rect: ViewRect,
+    pub window: (u32, u32),
+    pub video: (u32, u32),
+}
+
+impl Viewport {
+    /// Lay out a `video` sized stream in a `window` sized surface.
+    /// `zoom` and `pan` only apply to [`ScalingMode::Custom`]; pan runs from
+    /// -1.0 (left/top edge) to 1.0 (right/bottom edge) of the zoomed image.
+    pub fn new(
+        mode: ScalingMode,
+        zoom: f32,
+        pan: (f32, f32),
+        video: (u32, u32),
+        window: (u32, u32),
+    ) -> Self {
+        let (ww, wh) = (window.0 as f32, window.1 as f32);
+        let (vw, vh) = (video.0.max(1) as f32, video.1.max(1) as f32);
+        let fit = (ww / vw).min(wh / vh);
+
+        let (width, height) = match mode {
+            ScalingMode::Stretch => (ww, wh),
+            ScalingMode::Fit => (vw * fit, vh * fit),
+            ScalingMode::Integer => {
+                // Largest whole multiple that fits; shrink normally if even 1x doesn't
+                let scale = if fit >= 1.0 { fit.floor() } else { fit };
+                (vw * scale, vh * scale)
+            }
+            ScalingMode::Fill => {
+                let fill = (ww / vw).max(wh / vh);
+                (vw * fill, vh * fill)
+            }
+            ScalingMode::Custom => {
+                let scale = fit * zoom.max(0.1);
+                (vw * scale, vh * scale)
+            }
+        };
+        let (width, height) = (width.round().max(1.0), height.round().max(1.0));
+
+        // Centre, then pan across whatever overflows the window
+        let mut x = (ww - width) / 2.0;
+        let mut y = (wh - height) / 2.0;
+        if mode == ScalingMode::Custom {
+            x -= pan.0.clamp(-1.0, 1.0) * ((width - ww).max(0.0) / 2.0);
+            y -= pan.1.clamp(-1.0, 1.0) * ((height - wh).max(0.0) / 2.0);
+        }
+
+        Self {
+            rect: ViewRect {
+                x: x.round(),
+                y: y.round(),
+                width,
+                height,
+            },
+            window,
+            video,
+        }
+    }
+
+    /// Full window, no transform
+    pub fn full(video: (u32, u32), window: (u32, u32)) -> Self {
+        Self::new(ScalingMode::Stretch, 1.0, (0.0, 0.0), video, window)
+    }
+
+    pub fn window_rect(&self) -> ViewRect {
+        ViewRect {
+            x: 0.0,
+            y: 0.0,
+            width: self.window.0 as f32,
+            height: self.window.1 as f32,
+        }
+    }
+
+    /// Part of the stream that is on screen
+    pub fn visible(&self) -> Option<ViewRect> {
+        self.rect.intersect(&self.window_rect())
+    }
+
+    /// Whether part of the stream is cut off by the window edges
+    pub fn is_cropped(&self) -> bool {
+        self.visible() != Some(self.rect)
+    }
+
+    /// Window position to stream pixel coordinates, clamped to the stream
+    pub fn window_to_stream(&self, x: f64, y: f64) -> (f64, f64) {
+        let sx = (x - self.rect.x as f64) * self.video.0 as f64 / self.rect.width as f64;
+        let sy = (y - self.rect.y as f64) * self.video.1 as f64 / self.rect.height as f64;
+        (
+            sx.clamp(0.0, self.video.0 as f64),
+            sy.clamp(0.0, self.video.1 as f64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIDEO: (u32, u32) = (1920, 1080);
+    const ULTRAWIDE: (u32, u32) = (3440, 1440);
+
+    fn layout(mode: ScalingMode, window: (u32, u32)) -> ViewRect {
+        Viewport::new(mode, 1.0, (0.0, 0.0), VIDEO, window).rect
+    }
+
+    #[test]
+    fn test_fit_pillarboxes_on_ultrawide() {
+        let rect = layout(ScalingMode::Fit, ULTRAWIDE);
+        assert_eq!((rect.width, rect.height), (2560.0, 1440.0));
+        assert_eq!((rect.x, rect.y), (440.0, 0.0));
+        assert!(!Viewport::new(ScalingMode::Fit, 1.0, (0.0, 0.0), VIDEO, ULTRAWIDE).is_cropped());
+    }
+
+    #[test]
+    fn test_integer_uses_whole_multiples() {
+        // 1.33x fits, so integer scaling stays at 1x
+        let rect = layout(ScalingMode::Integer, ULTRAWIDE);
+        assert_eq!((rect.width, rect.height), (1920.0, 1080.0));
+        assert_eq!((rect.x, rect.y), (760.0, 180.0));
+
+        // 4K window takes exactly 2x
+        let rect = layout(ScalingMode::Integer, (3840, 2160));
+        assert_eq!((rect.width, rect.height), (3840.0, 2160.0));
+
+        // Smaller window falls back to fit
+        let rect = layout(ScalingMode::Integer, (1280, 720));
+        assert_eq!((rect.width, rect.height), (1280.0, 720.0));
+    }
+
+    #[test]
+    fn test_fill_crops_and_stretch_covers() {
+        let viewport = Viewport::new(ScalingMode::Fill, 1.0, (0.0, 0.0), VIDEO, ULTRAWIDE);
+        assert_eq!(viewport.rect.width, 3440.0);
+        assert_eq!(viewport.rect.height, 1935.0);
+        assert!(viewport.is_cropped());
+        assert_eq!(viewport.visible(), Some(viewport.window_rect()));
+
+        let rect = layout(ScalingMode::Stretch, ULTRAWIDE);
+        assert_eq!(rect, Viewport::full(VIDEO, ULTRAWIDE).window_rect());
+    }
+
+    #[test]
+    fn test_custom_pan_reaches_edges() {
+        let window = (1920, 1080);
+        let left = Viewport::new(ScalingMode::Custom, 2.0, (-1.0, 0.0), VIDEO, window);
+        assert_eq!(left.rect.x, 0.0);
+        let right = Viewport::new(ScalingMode::Custom, 2.0, (1.0, 0.0), VIDEO, window);
+        assert_eq!(right.rect.x + right.rect.width, 1920.0);
+        // Zooming out leaves bars and ignores pan
+        let small = Viewport::new(ScalingMode::Custom, 0.5, (1.0, 1.0), VIDEO, window);
+        assert_eq!((small.rect.x, small.rect.y), (480.0, 270.0));
+    }
+
+    #[test]
+    fn test_cursor_maps_through_transform() {
+        let viewport = Viewport::new(ScalingMode::Fit, 1.0, (0.0, 0.0), VIDEO, ULTRAWIDE);
+        // Centre of the window is the centre of the stream
+        assert_eq!(viewport.window_to_stream(1720.0, 720.0), (960.0, 540.0));
+        // Pillarbox bars clamp to the stream edge
+        assert_eq!(viewport.window_to_stream(100.0, 0.0), (0.0, 0.0));
+        assert_eq!(viewport.window_to_stream(3440.0, 1440.0), (1920.0, 1080.0));
+
+        let zoomed = Viewport::new(ScalingMode::Custom, 2.0, (-1.0, -1.0), VIDEO, (1920, 1080));
+        // Top-left quarter of the stream fills the window
+        assert_eq!(zoomed.window_to_stream(1920.0, 1080.0), (960.0, 540.0));
+    }
+}
@@ -113,7 +113,7 @@ lazy_static::lazy_static! {
 pub async fn fetch_login_providers() -> Result<Vec<LoginProvider>> {
     info!("Fetching login providers from {}", SERVICE_URLS_ENDPOINT);
 
-    let client = reqwest::Client::builder()
+    let client = crate::api::http::client_builder()
         .user_agent(GFN_USER_AGENT)
         .build()?;
 
@@ -441,7 +441,7 @@ pub async fn exchange_code(code: &str, verifier: &str, port: u16) -> Result<Auth
 
     info!("Exchanging authorization code for tokens...");
 
-    let client = reqwest::Client::builder()
+    let client = crate::api::http::client_builder()
         .user_agent(GFN_USER_AGENT)
         .build()?;
 
@@ -496,7 +496,7 @@ pub async fn exchange_code(code: &str, verifier: &str, port: u16) -> Result<Auth
 
 /// Refresh an expired token
 pub async fn refresh_token(refresh_token: &str) -> Result<AuthTokens> {
-    let client = reqwest::Client::builder()
+    let client = crate::api::http::client_builder()
         .user_agent(GFN_USER_AGENT)
         .build()?;
 
@@ -593,7 +593,7 @@ pub fn decode_jwt_user_info(token: &str) -> Result<UserInfo> {
 
 /// Fetch user info from /userinfo endpoint
 pub async fn fetch_userinfo(access_token: &str) -> Result<UserInfo> {
-    let client = reqwest::Client::builder()
+    let client = crate::api::http::client_builder()
         .user_agent(GFN_USER_AGENT)
         .build()?;
 
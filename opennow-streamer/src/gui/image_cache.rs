@@ -24,21 +24,37 @@ pub enum ImageState {
 pub struct ImageCache {
     /// Map from URL to image state
     images: RwLock<HashMap<String, ImageState>>,
-    /// HTTP client for fetching images
-    client: reqwest::Client,
+    /// HTTP client for fetching images, with the network config generation it was built for
+    client: RwLock<(u64, reqwest::Client)>,
 }
 
 impl ImageCache {
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
+        Self {
+            images: RwLock::new(HashMap::new()),
+            client: RwLock::new((crate::api::http::generation(), Self::build_client())),
+        }
+    }
+
+    fn build_client() -> reqwest::Client {
+        crate::api::http::client_builder()
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/128.0.0.0")
             .build()
-            .expect("Failed to create HTTP client");
+            .expect("Failed to create HTTP client")
+    }
 
-        Self {
-            images: RwLock::new(HashMap::new()),
-            client,
+    /// HTTP client, rebuilt when the proxy or certificate settings change
+    fn client(&self) -> reqwest::Client {
+        let generation = crate::api::http::generation();
+        {
+            let client = self.client.read();
+            if client.0 == generation {
+                return client.1.clone();
+            }
         }
+        let client = Self::build_client();
+        *self.client.write() = (generation, client.clone());
+        client
     }
 
     /// Get image state for a URL
@@ -64,7 +80,7 @@ impl ImageCache {
         }
 
         // Spawn async task to load image
-        let client = self.client.clone();
+        let client = self.client();
         let url_clone = url.clone();
         let _images = Arc::new(self.images.read().clone());
 
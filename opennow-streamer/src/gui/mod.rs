@@ -5,9 +5,11 @@
 mod renderer;
 mod stats_panel;
 mod shaders;
+mod upscaler;
+mod ui_compositor;
 pub mod screens;
 pub mod image_cache;
 
 pub use renderer::Renderer;
-pub use stats_panel::StatsPanel;
+pub use stats_panel::{render_stats_graphs, StatsPanel};
 pub use image_cache::{get_image, request_image, update_cache};
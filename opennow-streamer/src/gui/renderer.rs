@@ -28,20 +28,29 @@ use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
 use super::image_cache;
 use super::screens::{
     render_ads_required_screen, render_alliance_warning_dialog, render_av1_warning_dialog,
-    render_login_screen, render_session_conflict_dialog, render_session_screen,
-    render_settings_modal, render_welcome_popup,
+    render_color_overlay, render_history_tab, render_login_screen,
+    render_session_conflict_dialog, render_session_screen, render_settings_modal,
+    render_welcome_popup,
 };
-use super::shaders::{EXTERNAL_TEXTURE_SHADER, NV12_HDR_TONEMAP_SHADER, NV12_SHADER, VIDEO_SHADER};
-use super::StatsPanel;
+use super::shaders::{
+    EXTERNAL_TEXTURE_SHADER, NV12_HDR_TONEMAP_SHADER, NV12_SHADER, VIDEO_BIND_GROUPS, VIDEO_SHADER,
+};
+use super::ui_compositor::{UiCompositor, UI_TARGET_FORMAT};
+use super::upscaler::Upscaler;
+use super::{render_stats_graphs, StatsPanel};
+use crate::app::config::{ColorAdjustments, QueueProvider, UpscaleFilter};
 use crate::app::session::ActiveSessionInfo;
-use crate::app::{App, AppState, GameInfo, GamesTab, UiAction};
+use crate::app::viewport::{ViewRect, Viewport};
+use crate::app::{App, AppState, GameInfo, GamesTab, Settings, UiAction};
 #[cfg(target_os = "windows")]
 use crate::media::D3D11TextureWrapper;
 #[cfg(target_os = "linux")]
 use crate::media::VAAPISurfaceWrapper;
 #[cfg(target_os = "macos")]
 use crate::media::{CVMetalTexture, MetalVideoRenderer, ZeroCopyTextureManager};
-use crate::media::{ColorSpace, PixelFormat, StreamStats, TransferFunction, VideoFrame};
+use crate::media::{
+    ColorSpace, MetricGraph, PixelFormat, StreamStats, TransferFunction, VideoFrame,
+};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 #[cfg(target_os = "windows")]
@@ -57,6 +66,64 @@ use windows::Win32::Graphics::Direct3D12::{ID3D12Device, ID3D12Resource};
 // Color conversion is now hardcoded in the shader using official GFN client BT.709 values
 // This eliminates potential initialization bugs with uniform buffers
 
+/// Tone mapping uniforms (matches `ToneMapParams` in NV12_HDR_TONEMAP_SHADER)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct ToneMapParams {
+    tone_mapper: u32,
+    transfer: u32,
+    target_nits: f32,
+    peak_nits: f32,
+    saturation: f32,
+    _pad: [f32; 3],
+}
+
+impl ToneMapParams {
+    fn from_settings(settings: &Settings, transfer: TransferFunction) -> Self {
+        Self {
+            tone_mapper: settings.tone_mapper.shader_index(),
+            transfer: match transfer {
+                TransferFunction::HLG => 2,
+                _ => 1,
+            },
+            target_nits: settings.hdr_target_nits.max(1.0),
+            peak_nits: settings.hdr_peak_nits.max(1.0),
+            saturation: settings.hdr_saturation.max(0.0),
+            _pad: [0.0; 3],
+        }
+    }
+}
+
+/// Colour adjustment uniforms (matches `ColorAdjust` in the video shaders)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct ColorAdjustParams {
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    saturation: f32,
+    vibrance: f32,
+    temperature: f32,
+    enabled: u32,
+    _pad: f32,
+}
+
+impl ColorAdjustParams {
+    fn from_adjustments(adjustments: &ColorAdjustments) -> Self {
+        let a = adjustments.clamped();
+        Self {
+            brightness: a.brightness,
+            contrast: a.contrast,
+            gamma: a.gamma,
+            saturation: a.saturation,
+            vibrance: a.vibrance,
+            temperature: a.temperature,
+            enabled: u32::from(!a.is_default()),
+            _pad: 0.0,
+        }
+    }
+}
+
 /// Resolution change notification for animated popup
 struct ResolutionNotification {
     old_resolution: String,
@@ -171,6 +238,34 @@ impl WheelNotification {
     }
 }
 
+/// Capture notification (recording stopped, replay saved, ...)
+struct CaptureNotification {
+    message: String,
+    start_time: Instant,
+}
+
+impl CaptureNotification {
+    const DURATION_SECS: f32 = 3.0;
+    const FADE_IN_SECS: f32 = 0.2;
+    const FADE_OUT_SECS: f32 = 0.6;
+
+    fn is_expired(&self) -> bool {
+        self.start_time.elapsed().as_secs_f32() > Self::DURATION_SECS
+    }
+
+    fn alpha(&self) -> f32 {
+        let elapsed = self.start_time.elapsed().as_secs_f32();
+
+        if elapsed < Self::FADE_IN_SECS {
+            elapsed / Self::FADE_IN_SECS
+        } else if elapsed > Self::DURATION_SECS - Self::FADE_OUT_SECS {
+            ((Self::DURATION_SECS - elapsed) / Self::FADE_OUT_SECS).max(0.0)
+        } else {
+            1.0
+        }
+    }
+}
+
 /// Main renderer
 pub struct Renderer {
     window: Arc<Window>,
@@ -184,6 +279,8 @@ pub struct Renderer {
     egui_ctx: egui::Context,
     egui_state: egui_winit::State,
     egui_renderer: egui_wgpu::Renderer,
+    // Draws egui through an sRGB intermediate on scRGB swapchains
+    ui_compositor: Option<UiCompositor>,
 
     // Video rendering pipeline (GPU YUV->RGB conversion)
     video_pipeline: wgpu::RenderPipeline,
@@ -201,6 +298,13 @@ pub struct Renderer {
     nv12_bind_group_layout: wgpu::BindGroupLayout,
     // NV12 HDR tone mapping pipeline (for HDR content on SDR displays)
     nv12_hdr_pipeline: wgpu::RenderPipeline,
+    // Group 1 of every video pipeline: tone mapping and colour adjustment
+    // uniforms (last uploaded values cached)
+    video_params_bind_group: wgpu::BindGroup,
+    tone_map_buffer: wgpu::Buffer,
+    tone_map_params: ToneMapParams,
+    color_adjust_buffer: wgpu::Buffer,
+    color_adjust_params: ColorAdjustParams,
     // NV12 textures: Y (R8) and UV interleaved (Rg8)
     uv_texture: Option<wgpu::Texture>,
     nv12_bind_group: Option<wgpu::BindGroup>,
@@ -208,6 +312,8 @@ pub struct Renderer {
     current_format: PixelFormat,
     // Current transfer function (for HDR detection)
     current_transfer_function: TransferFunction,
+    // Optional bicubic/Lanczos/FSR scaling passes
+    upscaler: Upscaler,
 
     // Direct access to decoder's frame buffer - pull frames here, not from App
     shared_frame: Option<Arc<crate::app::SharedFrame>>,
@@ -238,6 +344,8 @@ pub struct Renderer {
     // === UI Optimization: Stats throttling ===
     // Cached stats for throttled rendering (updates every 200ms instead of every frame)
     cached_stats: Option<StreamStats>,
+    // Graph snapshot taken at the same rate (empty when graphs are off)
+    cached_stats_graphs: Arc<Vec<MetricGraph>>,
     stats_last_update: Instant,
 
     // === UI Optimization: Game grid caching ===
@@ -255,6 +363,9 @@ pub struct Renderer {
     wheel_notification: Option<WheelNotification>,
     last_wheel_count: usize,
 
+    // Capture notification (recording/replay)
+    capture_notification: Option<CaptureNotification>,
+
     // macOS zero-copy video rendering (Metal-based, no CPU copy)
     #[cfg(target_os = "macos")]
     zero_copy_manager: Option<ZeroCopyTextureManager>,
@@ -637,12 +748,32 @@ impl Renderer {
         // Use non-sRGB (linear) format for video - H.264/HEVC output is already gamma-corrected
         // Using sRGB format would apply double gamma correction, causing washed-out colors
         let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| !f.is_srgb()) // Prefer linear format for video
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
+        // HDR output: wgpu only tags Rgba16Float swapchains as scRGB on DX12,
+        // other backends would present the linear values as sRGB
+        let scrgb_output = settings.hdr_output
+            && adapter_info.backend == wgpu::Backend::Dx12
+            && surface_caps
+                .formats
+                .contains(&wgpu::TextureFormat::Rgba16Float);
+        let surface_format = if scrgb_output {
+            info!("HDR output enabled - using scRGB (Rgba16Float) swapchain");
+            wgpu::TextureFormat::Rgba16Float
+        } else {
+            if settings.hdr_output {
+                warn!(
+                    "HDR output requested but not supported on {:?} - using SDR swapchain",
+                    adapter_info.backend
+                );
+            }
+            surface_caps
+                .formats
+                .iter()
+                .find(|f| !f.is_srgb()) // Prefer linear format for video
+                .copied()
+                .unwrap_or(surface_caps.formats[0])
+        };
+        // Swapchain encoding for the video shaders' OUTPUT_MODE override
+        let output_constants = [("OUTPUT_MODE", if scrgb_output { 1.0 } else { 0.0 })];
 
         // Start with Fifo (VSync) for low CPU usage in menus
         // Switches to Immediate when streaming for lowest latency
@@ -691,9 +822,15 @@ impl Renderer {
         );
 
         // Create egui-wgpu renderer (egui 0.33 API)
+        // On scRGB egui targets an sRGB intermediate so its colours get linearized
+        let ui_compositor = scrgb_output.then(|| UiCompositor::new(&device, surface_format));
         let egui_renderer = egui_wgpu::Renderer::new(
             &device,
-            surface_format,
+            if scrgb_output {
+                UI_TARGET_FORMAT
+            } else {
+                surface_format
+            },
             egui_wgpu::RendererOptions::default(),
         );
 
@@ -751,10 +888,65 @@ impl Renderer {
                 ],
             });
 
+        // Params shared by every video pipeline at group 1: tone mapping (HDR only)
+        // and colour adjustments, updated from Settings every frame they change
+        let uniform_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let video_params_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Video Params Bind Group Layout"),
+                entries: &[uniform_entry(0), uniform_entry(1)],
+            });
+
+        let tone_map_params = ToneMapParams::from_settings(&settings, TransferFunction::PQ);
+        let tone_map_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tone Map Params"),
+            size: std::mem::size_of::<ToneMapParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&tone_map_buffer, 0, bytemuck::bytes_of(&tone_map_params));
+
+        let color_adjust_params = ColorAdjustParams::from_adjustments(&settings.color_adjustments);
+        let color_adjust_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Color Adjust Params"),
+            size: std::mem::size_of::<ColorAdjustParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(
+            &color_adjust_buffer,
+            0,
+            bytemuck::bytes_of(&color_adjust_params),
+        );
+
+        let video_params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Video Params Bind Group"),
+            layout: &video_params_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: tone_map_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: color_adjust_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
         let video_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Video Pipeline Layout"),
-                bind_group_layouts: &[&video_bind_group_layout],
+                bind_group_layouts: &[&video_bind_group_layout, &video_params_bind_group_layout],
                 immediate_size: 0,
             });
 
@@ -775,7 +967,10 @@ impl Renderer {
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
-                compilation_options: Default::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &output_constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -847,7 +1042,7 @@ impl Renderer {
 
         let nv12_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("NV12 Pipeline Layout"),
-            bind_group_layouts: &[&nv12_bind_group_layout],
+            bind_group_layouts: &[&nv12_bind_group_layout, &video_params_bind_group_layout],
             immediate_size: 0,
         });
 
@@ -868,7 +1063,10 @@ impl Renderer {
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
-                compilation_options: Default::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &output_constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -891,10 +1089,17 @@ impl Renderer {
             source: wgpu::ShaderSource::Wgsl(NV12_HDR_TONEMAP_SHADER.into()),
         });
 
-        // HDR pipeline uses the same bind group layout as NV12
+        // HDR pipeline reads the tone mapping params from the shared video params group
+        let nv12_hdr_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("NV12 HDR Pipeline Layout"),
+                bind_group_layouts: &[&nv12_bind_group_layout, &video_params_bind_group_layout],
+                immediate_size: 0,
+            });
+
         let nv12_hdr_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("NV12 HDR Tonemap Pipeline"),
-            layout: Some(&nv12_pipeline_layout),
+            layout: Some(&nv12_hdr_pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &nv12_hdr_shader,
                 entry_point: Some("vs_main"),
@@ -909,7 +1114,10 @@ impl Renderer {
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
-                compilation_options: Default::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &output_constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -961,7 +1169,10 @@ impl Renderer {
                 let external_texture_pipeline_layout =
                     device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                         label: Some("External Texture Pipeline Layout"),
-                        bind_group_layouts: &[&external_texture_bind_group_layout],
+                        bind_group_layouts: &[
+                            &external_texture_bind_group_layout,
+                            &video_params_bind_group_layout,
+                        ],
                         immediate_size: 0,
                     });
 
@@ -983,7 +1194,10 @@ impl Renderer {
                                 blend: Some(wgpu::BlendState::REPLACE),
                                 write_mask: wgpu::ColorWrites::ALL,
                             })],
-                            compilation_options: Default::default(),
+                            compilation_options: wgpu::PipelineCompilationOptions {
+                                constants: &output_constants,
+                                ..Default::default()
+                            },
                         }),
                         primitive: wgpu::PrimitiveState {
                             topology: wgpu::PrimitiveTopology::TriangleList,
@@ -1009,6 +1223,9 @@ impl Renderer {
                 (None, None)
             };
 
+        // Upscaling passes (only used when a filter is selected)
+        let upscaler = Upscaler::new(&device, surface_format);
+
         // Create stats panel
         let stats_panel = StatsPanel::new();
 
@@ -1022,6 +1239,7 @@ impl Renderer {
             egui_ctx,
             egui_state,
             egui_renderer,
+            ui_compositor,
             video_pipeline,
             video_bind_group_layout,
             video_sampler,
@@ -1033,10 +1251,16 @@ impl Renderer {
             nv12_pipeline,
             nv12_bind_group_layout,
             nv12_hdr_pipeline,
+            video_params_bind_group,
+            tone_map_buffer,
+            tone_map_params,
+            color_adjust_buffer,
+            color_adjust_params,
             uv_texture: None,
             nv12_bind_group: None,
             current_format: PixelFormat::YUV420P,
             current_transfer_function: TransferFunction::SDR,
+            upscaler,
             shared_frame: None,
             external_texture_pipeline,
             external_texture_bind_group_layout,
@@ -1050,6 +1274,7 @@ impl Renderer {
             game_textures: HashMap::new(),
             // UI optimization: stats throttling (200ms intervals)
             cached_stats: None,
+            cached_stats_graphs: Arc::default(),
             stats_last_update: Instant::now(),
             // UI optimization: game grid caching
             games_cache_hash: 0,
@@ -1061,6 +1286,8 @@ impl Renderer {
             // Racing wheel connection notification
             wheel_notification: None,
             last_wheel_count: 0,
+            // Capture notification
+            capture_notification: None,
             #[cfg(target_os = "macos")]
             zero_copy_manager: ZeroCopyTextureManager::new(),
             #[cfg(target_os = "macos")]
@@ -3305,7 +3532,13 @@ impl Renderer {
     /// Render video frame to screen
     /// Automatically selects the correct pipeline based on current pixel format
     /// Priority: External Texture (true zero-copy) > NV12 > YUV420P
-    fn render_video(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    /// Draws into `viewport` when given, otherwise fills the target
+    fn render_video(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        viewport: Option<&ViewRect>,
+    ) {
         // Priority 1: Use External Texture pipeline if available (hardware YUV->RGB conversion)
         // This is the true zero-copy path with automatic color space conversion
         if let (Some(ref pipeline), Some(ref bind_group)) = (
@@ -3327,8 +3560,11 @@ impl Renderer {
                 ..Default::default()
             });
 
+            if let Some(rect) = viewport {
+                render_pass.set_viewport(rect.x, rect.y, rect.width, rect.height, 0.0, 1.0);
+            }
             render_pass.set_pipeline(pipeline);
-            render_pass.set_bind_group(0, bind_group, &[]);
+            self.set_video_bind_groups(&mut render_pass, bind_group);
             render_pass.draw(0..6, 0..1);
             return;
         }
@@ -3337,8 +3573,8 @@ impl Renderer {
         let (pipeline, bind_group) = match self.current_format {
             PixelFormat::NV12 => {
                 if let Some(ref bg) = self.nv12_bind_group {
-                    // Use HDR tone mapping pipeline for PQ content on SDR displays
-                    let pipeline = if self.current_transfer_function == TransferFunction::PQ {
+                    // Use HDR tone mapping pipeline for PQ/HLG content
+                    let pipeline = if self.is_hdr_content() {
                         &self.nv12_hdr_pipeline
                     } else {
                         &self.nv12_pipeline
@@ -3356,9 +3592,9 @@ impl Renderer {
                 }
             }
             PixelFormat::P010 => {
-                // P010 is 10-bit HDR format - use HDR pipeline for PQ content
+                // P010 is 10-bit HDR format - use HDR pipeline for PQ/HLG content
                 if let Some(ref bg) = self.nv12_bind_group {
-                    let pipeline = if self.current_transfer_function == TransferFunction::PQ {
+                    let pipeline = if self.is_hdr_content() {
                         &self.nv12_hdr_pipeline
                     } else {
                         &self.nv12_pipeline
@@ -3385,11 +3621,54 @@ impl Renderer {
             ..Default::default()
         });
 
+        if let Some(rect) = viewport {
+            render_pass.set_viewport(rect.x, rect.y, rect.width, rect.height, 0.0, 1.0);
+        }
         render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, bind_group, &[]);
+        self.set_video_bind_groups(&mut render_pass, bind_group);
         render_pass.draw(0..6, 0..1); // Draw 6 vertices (2 triangles = 1 quad)
     }
 
+    /// Bind the frame's planes and the shared video params
+    /// (every video pipeline layout has exactly these groups)
+    fn set_video_bind_groups(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        frame: &wgpu::BindGroup,
+    ) {
+        let groups: [&wgpu::BindGroup; VIDEO_BIND_GROUPS] =
+            [frame, &self.video_params_bind_group];
+        for (index, group) in groups.into_iter().enumerate() {
+            render_pass.set_bind_group(index as u32, group, &[]);
+        }
+    }
+
+    /// Whether the current stream needs the HDR (tone mapping) pipeline
+    fn is_hdr_content(&self) -> bool {
+        matches!(
+            self.current_transfer_function,
+            TransferFunction::PQ | TransferFunction::HLG
+        )
+    }
+
+    /// Upload tone mapping and colour adjustment params when settings,
+    /// the current game or the stream's transfer function change
+    fn update_video_params(&mut self, app: &App) {
+        let params = ToneMapParams::from_settings(&app.settings, self.current_transfer_function);
+        if params != self.tone_map_params {
+            self.queue
+                .write_buffer(&self.tone_map_buffer, 0, bytemuck::bytes_of(&params));
+            self.tone_map_params = params;
+        }
+
+        let params = ColorAdjustParams::from_adjustments(&app.color_adjustments());
+        if params != self.color_adjust_params {
+            self.queue
+                .write_buffer(&self.color_adjust_buffer, 0, bytemuck::bytes_of(&params));
+            self.color_adjust_params = params;
+        }
+    }
+
     /// Render frame and return UI actions plus optional repaint delay
     /// The Duration indicates when the next repaint should happen (for idle throttling)
     pub fn render(&mut self, app: &App) -> Result<(Vec<UiAction>, Option<Duration>)> {
@@ -3497,15 +3776,58 @@ impl Renderer {
             profile_scope!("update_video");
             self.update_video(frame);
         }
+        self.update_video_params(app);
 
         // Render video or clear based on state
         // Check for either YUV420P (video_bind_group) or NV12 (nv12_bind_group)
         let has_video = self.video_bind_group.is_some() || self.nv12_bind_group.is_some();
         if app.state == AppState::Streaming && has_video {
             profile_scope!("render_video");
-            // Render video full-screen
-            self.render_video(&mut encoder, &view);
+            let settings = &app.settings;
+            let window_size = (self.config.width, self.config.height);
+            let viewport = if self.video_size.0 > 0 && self.video_size.1 > 0 {
+                Viewport::new(
+                    settings.scaling_mode,
+                    settings.custom_zoom,
+                    settings.custom_pan,
+                    self.video_size,
+                    window_size,
+                )
+            } else {
+                Viewport::full(self.video_size, window_size)
+            };
+            // Cursor positions are mapped through the same transform
+            if let Some(ref input_handler) = app.input_handler {
+                input_handler.set_viewport(viewport);
+            }
+
+            let upscaling =
+                Upscaler::is_active(settings.upscale_filter, self.video_size, &viewport.rect);
+            match viewport.visible() {
+                Some(visible) if upscaling || viewport.is_cropped() => {
+                    // Convert at native resolution, then scale/crop into the viewport
+                    let filter = if upscaling {
+                        settings.upscale_filter
+                    } else {
+                        UpscaleFilter::Bilinear
+                    };
+                    let source_view = self.upscaler.prepare(
+                        &self.device,
+                        &self.queue,
+                        filter,
+                        settings.upscale_strength,
+                        &viewport,
+                    );
+                    self.render_video(&mut encoder, &source_view, None);
+                    self.upscaler.render(&mut encoder, filter, &view, &visible);
+                }
+                visible => {
+                    self.upscaler.release();
+                    self.render_video(&mut encoder, &view, visible.as_ref());
+                }
+            }
         } else {
+            self.upscaler.release();
             // Clear pass for non-streaming states
             let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Clear Pass"),
@@ -3537,6 +3859,11 @@ impl Renderer {
         const STATS_UPDATE_INTERVAL: Duration = Duration::from_millis(200);
         if self.stats_last_update.elapsed() >= STATS_UPDATE_INTERVAL {
             self.cached_stats = Some(app.stats.clone());
+            self.cached_stats_graphs = if app.show_stats_graphs {
+                Arc::new(app.stats_history.graphs(Instant::now()))
+            } else {
+                Arc::default()
+            };
             self.stats_last_update = Instant::now();
 
             // Detect resolution changes and show notification
@@ -3572,6 +3899,26 @@ impl Renderer {
             }
         }
 
+        // Pick up new capture notices from the app
+        if let Some((message, time)) = &app.capture_notice {
+            if self
+                .capture_notification
+                .as_ref()
+                .is_none_or(|n| n.start_time != *time)
+                && time.elapsed().as_secs_f32() < CaptureNotification::DURATION_SECS
+            {
+                self.capture_notification = Some(CaptureNotification {
+                    message: message.clone(),
+                    start_time: *time,
+                });
+            }
+        }
+        if let Some(ref notif) = self.capture_notification {
+            if notif.is_expired() {
+                self.capture_notification = None;
+            }
+        }
+
         // Extract state needed for UI rendering
         let app_state = app.state;
         // Use cached stats for display (throttled to 200ms updates)
@@ -3579,13 +3926,16 @@ impl Renderer {
             .cached_stats
             .clone()
             .unwrap_or_else(|| app.stats.clone());
+        let stats_graphs = self.cached_stats_graphs.clone();
         let show_stats = app.show_stats;
         let status_message = app.status_message.clone();
         let error_message = app.error_message.clone();
+        let recovery_prompt = app.recovery_prompt();
         let selected_game = app.selected_game.clone();
         let stats_position = self.stats_panel.position;
         let stats_visible = self.stats_panel.visible;
         let show_settings = app.show_settings;
+        let show_color_overlay = app.show_color_overlay;
         let settings = app.settings.clone();
         let login_providers = app.login_providers.clone();
         let selected_provider_index = app.selected_provider_index;
@@ -3623,6 +3973,14 @@ impl Renderer {
             .as_ref()
             .map(|n| (n.wheel_count, n.alpha()));
 
+        // Recording indicator and capture notification state
+        let recording_elapsed = app.recorder.elapsed();
+        let playtime_countdown = app.playtime_countdown();
+        let capture_notif = self
+            .capture_notification
+            .as_ref()
+            .map(|n| (n.message.clone(), n.alpha()));
+
         // Queue times state
         let mut queue_servers = app.queue_servers.clone();
         let queue_loading = app.queue_loading;
@@ -3659,7 +4017,7 @@ impl Renderer {
                     .map(|(i, g)| (i, g.clone()))
                     .collect()
             }
-            GamesTab::QueueTimes => Vec::new(),
+            GamesTab::QueueTimes | GamesTab::History => Vec::new(),
         };
 
         // Get game sections for Home tab - only clone if on Home tab
@@ -3754,6 +4112,8 @@ impl Renderer {
                             show_server_selection,
                             &selected_queue_server,
                             pending_server_selection_game.as_ref(),
+                            &app.zone_quality,
+                            &app.session_history,
                             &mut actions,
                         );
                     }
@@ -3776,6 +4136,7 @@ impl Renderer {
                                 &selected_game,
                                 &status_message,
                                 &error_message,
+                                recovery_prompt.as_ref(),
                                 &mut actions,
                             );
                         }
@@ -3783,7 +4144,7 @@ impl Renderer {
                     AppState::Streaming => {
                         // Render stats overlay
                         if show_stats && stats_visible {
-                            render_stats_panel(ctx, &stats, stats_position);
+                            render_stats_panel(ctx, &stats, &stats_graphs, stats_position);
                         }
 
                         // Render resolution change notification
@@ -3798,6 +4159,31 @@ impl Renderer {
                             render_wheel_notification(ctx, wheel_count, alpha);
                         }
 
+                        // Render recording indicator
+                        if let Some(elapsed) = recording_elapsed {
+                            render_recording_indicator(ctx, elapsed);
+                        }
+
+                        // Countdown to the session limit / end of account time
+                        if let Some((remaining, kind)) = playtime_countdown {
+                            render_playtime_countdown(ctx, remaining, kind);
+                        }
+
+                        // Render capture notification
+                        if let Some((message, alpha)) = &capture_notif {
+                            render_capture_notification(ctx, message, *alpha);
+                        }
+
+                        // Colour adjustment overlay (Ctrl+Shift+C)
+                        if show_color_overlay {
+                            render_color_overlay(
+                                ctx,
+                                selected_game.as_ref(),
+                                &settings,
+                                &mut actions,
+                            );
+                        }
+
                         // Small overlay hint
                         egui::Area::new(egui::Id::new("stream_hint"))
                             .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
@@ -3805,7 +4191,7 @@ impl Renderer {
                             .show(ctx, |ui| {
                                 ui.label(
                                     egui::RichText::new(
-                                        "Ctrl+Shift+Q to stop • F3 stats • F11 fullscreen",
+                                        "Ctrl+Shift+Q to stop • F3 stats • F11 fullscreen • Ctrl+Shift+R record • Ctrl+Shift+S save replay • Ctrl+Shift+P screenshot • Ctrl+Shift+C colour",
                                     )
                                     .color(egui::Color32::from_rgba_unmultiplied(
                                         255, 255, 255, 100,
@@ -3860,14 +4246,23 @@ impl Renderer {
             &screen_descriptor,
         );
 
+        // scRGB: draw into the compositor's target, blended onto the swapchain below
+        let ui_view = self.ui_compositor.as_mut().map(|compositor| {
+            compositor.prepare(&self.device, (self.config.width, self.config.height))
+        });
+
         {
             let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Egui Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: ui_view.as_ref().unwrap_or(&view),
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
+                        load: if ui_view.is_some() {
+                            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                        } else {
+                            wgpu::LoadOp::Load
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
@@ -3882,6 +4277,10 @@ impl Renderer {
                 .render(&mut render_pass, &clipped_primitives, &screen_descriptor);
         }
 
+        if let Some(compositor) = &self.ui_compositor {
+            compositor.render(&mut encoder, &view);
+        }
+
         // Free egui textures
         for id in &full_output.textures_delta.free {
             self.egui_renderer.free_texture(id);
@@ -3942,8 +4341,12 @@ impl Renderer {
         show_server_selection: bool,
         selected_queue_server: &Option<String>,
         pending_server_selection_game: Option<&GameInfo>,
+        zone_quality: &crate::app::ZoneQualityMap,
+        session_history: &[crate::app::SessionRecord],
         actions: &mut Vec<UiAction>,
     ) {
+        let scorer = crate::app::ServerScorer::new(settings, zone_quality);
+
         // Top bar with tabs, search, and logout - subscription info moved to bottom
         egui::Panel::top("top_bar")
             .frame(
@@ -3975,6 +4378,7 @@ impl Renderer {
                     let all_games_selected = current_tab == GamesTab::AllGames;
                     let library_selected = current_tab == GamesTab::MyLibrary;
                     let queue_times_selected = current_tab == GamesTab::QueueTimes;
+                    let history_selected = current_tab == GamesTab::History;
 
                     // Home tab button
                     let home_btn = egui::Button::new(
@@ -4057,6 +4461,30 @@ impl Renderer {
                         actions.push(UiAction::SwitchTab(GamesTab::QueueTimes));
                     }
 
+                    ui.add_space(8.0);
+
+                    let history_btn = egui::Button::new(
+                        egui::RichText::new("History")
+                            .size(13.0)
+                            .color(egui::Color32::WHITE)
+                            .strong(),
+                    )
+                    .fill(if history_selected {
+                        egui::Color32::from_rgb(118, 185, 0)
+                    } else {
+                        egui::Color32::from_rgb(50, 50, 65)
+                    })
+                    .corner_radius(6.0);
+
+                    if ui
+                        .add_sized([80.0, 32.0], history_btn)
+                        .on_hover_text("Past sessions and playtime")
+                        .clicked()
+                        && !history_selected
+                    {
+                        actions.push(UiAction::SwitchTab(GamesTab::History));
+                    }
+
                     ui.add_space(20.0);
 
                     // Search box in the middle
@@ -4241,6 +4669,41 @@ impl Renderer {
                                     .size(12.0)
                                     .color(egui::Color32::GRAY),
                             );
+
+                            // Projected usage from locally tracked session time
+                            let today = chrono::Local::now().date_naive();
+                            if let Some(projection) = crate::app::playtime::project_month_usage(
+                                session_history,
+                                today,
+                                Some(sub.remaining_hours),
+                            ) {
+                                let over_plan = sub.total_hours > 0.0
+                                    && projection.projected_hours > sub.total_hours;
+                                ui.add_space(12.0);
+                                let mut hover = format!(
+                                    "{:.1}h played this month (tracked locally).\nAt this pace: {:.0}h by the end of the month.",
+                                    projection.used_hours, projection.projected_hours
+                                );
+                                if let Some(date) = projection.runs_out_on {
+                                    hover.push_str(&format!(
+                                        "\nHours run out around {}.",
+                                        date.format("%b %-d")
+                                    ));
+                                }
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "≈ {:.0}h this month",
+                                        projection.projected_hours
+                                    ))
+                                    .size(12.0)
+                                    .color(if over_plan {
+                                        egui::Color32::from_rgb(255, 200, 50)
+                                    } else {
+                                        egui::Color32::GRAY
+                                    }),
+                                )
+                                .on_hover_text(hover);
+                            }
                         }
 
                         ui.add_space(20.0);
@@ -4377,13 +4840,16 @@ impl Renderer {
                             });
                     }
                 }
+                GamesTab::History => {
+                    render_history_tab(ui, session_history, subscription);
+                }
                 GamesTab::QueueTimes => {
                     // Check if we have any ping data (to know if ping test is still running)
                     let has_ping_data = queue_servers.iter().any(|s| s.ping_ms.is_some());
 
                     // Get recommended server (only if we have ping data)
                     let recommended_server = if has_ping_data {
-                        crate::api::get_auto_selected_server(queue_servers)
+                        crate::api::get_auto_selected_server(queue_servers, &scorer)
                     } else {
                         None
                     };
@@ -4398,6 +4864,7 @@ impl Renderer {
                         server_count: usize,
                         has_5080: bool,
                         has_4080: bool,
+                        score: f64,
                     }
 
                     // Apply region filter to servers
@@ -4440,6 +4907,16 @@ impl Renderer {
                             let best_ping = servers.iter().filter_map(|s| s.ping_ms).min();
                             let has_5080 = servers.iter().any(|s| s.is_5080_server);
                             let has_4080 = servers.iter().any(|s| s.is_4080_server);
+                            let score = scorer
+                                .score(crate::app::server_scoring::ScoreCandidate {
+                                    id: display_name,
+                                    region,
+                                    ping_ms: best_ping,
+                                    eta_seconds: avg_eta,
+                                    is_4080_server: has_4080,
+                                    is_5080_server: has_5080,
+                                })
+                                .total;
 
                             location_list.push(LocationInfo {
                                 display_name: display_name.clone(),
@@ -4449,6 +4926,7 @@ impl Renderer {
                                 server_count: count,
                                 has_5080,
                                 has_4080,
+                                score,
                             });
                         }
 
@@ -4456,13 +4934,9 @@ impl Renderer {
                         // All sorts use display_name as a tiebreaker for stable ordering
                         match queue_sort_mode {
                             crate::app::QueueSortMode::BestValue => {
-                                // Sort by a combined score (lower is better)
+                                // Sort by the server score (lower is better)
                                 location_list.sort_by(|a, b| {
-                                    let score_a = a.best_ping_ms.unwrap_or(500) as f64
-                                        + (a.avg_eta_seconds.unwrap_or(0) as f64 / 60.0 * 0.5).min(100.0);
-                                    let score_b = b.best_ping_ms.unwrap_or(500) as f64
-                                        + (b.avg_eta_seconds.unwrap_or(0) as f64 / 60.0 * 0.5).min(100.0);
-                                    score_a.partial_cmp(&score_b)
+                                    a.score.partial_cmp(&b.score)
                                         .unwrap_or(std::cmp::Ordering::Equal)
                                         .then_with(|| a.display_name.cmp(&b.display_name))
                                 });
@@ -4612,7 +5086,7 @@ impl Renderer {
                                                     });
                                                 });
                                             });
-                                    } else if let Some(best) = recommended_server {
+                                    } else if let Some((best, best_score)) = &recommended_server {
                                         egui::Frame::new()
                                             .fill(egui::Color32::from_rgb(25, 45, 25))
                                             .stroke(egui::Stroke::new(1.5, egui::Color32::from_rgb(118, 185, 0)))
@@ -4716,7 +5190,9 @@ impl Renderer {
                                                         }
                                                     });
                                                 });
-                                            });
+                                            })
+                                            .response
+                                            .on_hover_text(format!("Why this server: {}", best_score.explain()));
                                     }
                                 });
 
@@ -4918,6 +5394,14 @@ impl Renderer {
                                 // Attribution footer
                                 ui.add_space(16.0);
                                 ui.vertical_centered(|ui| {
+                                    if settings.queue_provider != QueueProvider::PrintedWaste {
+                                        ui.label(
+                                            egui::RichText::new(format!("Queue data: {}", settings.queue_provider.as_str()))
+                                                .size(11.0)
+                                                .color(egui::Color32::from_rgb(80, 80, 80))
+                                        );
+                                        return;
+                                    }
                                     ui.horizontal(|ui| {
                                         ui.label(
                                             egui::RichText::new("Powered by")
@@ -5052,6 +5536,8 @@ impl Renderer {
                     game,
                     queue_servers,
                     queue_loading,
+                    settings.queue_provider,
+                    &scorer,
                     selected_queue_server,
                     actions,
                 );
@@ -5371,6 +5857,8 @@ impl Renderer {
         game: &crate::app::GameInfo,
         queue_servers: &[crate::api::QueueServerInfo],
         queue_loading: bool,
+        queue_provider: QueueProvider,
+        scorer: &crate::app::ServerScorer,
         selected_server: &Option<String>,
         actions: &mut Vec<UiAction>,
     ) {
@@ -5396,7 +5884,7 @@ impl Renderer {
 
         // Get recommended server (best score across all servers)
         let recommended = if has_ping_data {
-            crate::api::get_auto_selected_server(queue_servers)
+            crate::api::get_auto_selected_server(queue_servers, scorer)
         } else {
             None
         };
@@ -5408,8 +5896,8 @@ impl Renderer {
         for server in queue_servers {
             let entry = region_best.entry(server.region.clone()).or_insert(server);
             // Replace if this server has better score
-            let current_score = crate::api::calculate_server_score(entry);
-            let new_score = crate::api::calculate_server_score(server);
+            let current_score = crate::api::calculate_server_score(entry, scorer);
+            let new_score = crate::api::calculate_server_score(server, scorer);
             if new_score < current_score {
                 *entry = server;
             }
@@ -5503,7 +5991,7 @@ impl Renderer {
                                                     .color(egui::Color32::from_rgb(140, 140, 160)),
                                             );
                                         });
-                                    } else if let Some(best) = recommended {
+                                    } else if let Some((best, score)) = &recommended {
                                         ui.label(
                                             egui::RichText::new(format!(
                                                 "{} • {}ms • ~{}",
@@ -5514,6 +6002,19 @@ impl Renderer {
                                             .size(12.0)
                                             .color(egui::Color32::from_rgb(118, 185, 0)),
                                         );
+                                        // Why this server: the score terms
+                                        ui.label(
+                                            egui::RichText::new(format!("Why: {}", score.explain()))
+                                                .size(11.0)
+                                                .color(egui::Color32::from_rgb(140, 140, 160)),
+                                        )
+                                        .on_hover_text("Score breakdown, lower is better.\nWeights are in Settings > Server Scoring.");
+                                    } else {
+                                        ui.label(
+                                            egui::RichText::new("No server within the max ping limit")
+                                                .size(12.0)
+                                                .color(egui::Color32::from_rgb(255, 165, 0)),
+                                        );
                                     }
                                 });
                             });
@@ -5745,6 +6246,13 @@ impl Renderer {
                                             })
                                             .response;
 
+                                        let score = scorer.score((*best_server).into());
+                                        let mut hover = format!("Score: {}", score.explain());
+                                        if let Some(latency) = &best_server.latency {
+                                            hover.push_str(&format!("\nLatency: {}", latency.summary()));
+                                        }
+                                        let server_response = server_response.on_hover_text(hover);
+
                                         if server_response.interact(egui::Sense::click()).clicked()
                                         {
                                             actions.push(UiAction::SelectQueueServer(Some(
@@ -5792,6 +6300,14 @@ impl Renderer {
                     // Attribution footer
                     ui.add_space(10.0);
                     ui.horizontal(|ui| {
+                        if queue_provider != QueueProvider::PrintedWaste {
+                            ui.label(
+                                egui::RichText::new(format!("Queue data: {}", queue_provider.as_str()))
+                                    .size(10.0)
+                                    .color(egui::Color32::from_rgb(80, 80, 80)),
+                            );
+                            return;
+                        }
                         ui.label(
                             egui::RichText::new("Powered by")
                                 .size(10.0)
@@ -6006,6 +6522,7 @@ impl Renderer {
 fn render_stats_panel(
     ctx: &egui::Context,
     stats: &crate::media::StreamStats,
+    graphs: &[MetricGraph],
     position: crate::app::StatsPosition,
 ) {
     use egui::{Align2, Color32, FontId, RichText};
@@ -6249,6 +6766,30 @@ fn render_stats_panel(
                         );
                     }
 
+                    // Audio loss concealment
+                    if stats.audio_packets_lost > 0 {
+                        ui.label(
+                            RichText::new(format!(
+                                "Audio: {} lost, {} concealed",
+                                stats.audio_packets_lost, stats.audio_frames_concealed
+                            ))
+                            .font(FontId::monospace(10.0))
+                            .color(Color32::DARK_GRAY),
+                        );
+                    }
+
+                    // Frame pacing
+                    if stats.frame_pacing {
+                        ui.label(
+                            RichText::new(format!(
+                                "Pacing: {} shown, {} repeat, {} skip",
+                                stats.frames_presented, stats.frames_repeated, stats.frames_skipped
+                            ))
+                            .font(FontId::monospace(10.0))
+                            .color(Color32::DARK_GRAY),
+                        );
+                    }
+
                     // GPU and server info
                     if !stats.gpu_type.is_empty() || !stats.server_region.is_empty() {
                         let info = format!(
@@ -6268,6 +6809,9 @@ fn render_stats_panel(
                                 .color(Color32::DARK_GRAY),
                         );
                     }
+
+                    // Rolling graphs (Shift+F3)
+                    render_stats_graphs(ui, graphs);
                 });
         });
 }
@@ -6459,3 +7003,116 @@ fn render_wheel_notification(ctx: &egui::Context, wheel_count: usize, alpha: f32
     // Request repaint for smooth animation
     ctx.request_repaint();
 }
+
+/// Render recording indicator (top-right corner, blinking dot + elapsed time)
+fn render_recording_indicator(ctx: &egui::Context, elapsed: Duration) {
+    use egui::{Align2, Color32, FontId, RichText};
+
+    let secs = elapsed.as_secs();
+    let time_text = if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    };
+
+    // Blink the dot once per second
+    let dot_alpha = if elapsed.as_millis() % 1000 < 600 { 255 } else { 60 };
+
+    egui::Area::new(egui::Id::new("recording_indicator"))
+        .anchor(Align2::RIGHT_TOP, [-12.0, 12.0])
+        .interactable(false)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(Color32::from_rgba_unmultiplied(20, 20, 20, 180))
+                .corner_radius(6.0)
+                .inner_margin(egui::Margin::symmetric(10, 6))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new("●")
+                                .font(FontId::proportional(14.0))
+                                .color(Color32::from_rgba_unmultiplied(230, 40, 40, dot_alpha)),
+                        );
+                        ui.label(
+                            RichText::new(format!("REC {}", time_text))
+                                .font(FontId::monospace(13.0))
+                                .color(Color32::WHITE),
+                        );
+                    });
+                });
+        });
+}
+
+/// Render the countdown to the end of the session (top-center, below the hint line)
+fn render_playtime_countdown(
+    ctx: &egui::Context,
+    remaining: Duration,
+    kind: crate::app::playtime::LimitKind,
+) {
+    use egui::{Align2, Color32, FontId, RichText};
+
+    let color = if remaining.as_secs() <= 5 * 60 {
+        Color32::from_rgb(255, 80, 80)
+    } else {
+        Color32::from_rgb(255, 200, 50)
+    };
+
+    egui::Area::new(egui::Id::new("playtime_countdown"))
+        .anchor(Align2::CENTER_TOP, [0.0, 34.0])
+        .interactable(false)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(Color32::from_rgba_unmultiplied(20, 20, 20, 180))
+                .corner_radius(6.0)
+                .inner_margin(egui::Margin::symmetric(10, 6))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(format!(
+                                "⏱ {}",
+                                crate::app::playtime::format_countdown(remaining)
+                            ))
+                            .font(FontId::monospace(13.0))
+                            .color(color),
+                        );
+                        ui.label(
+                            RichText::new(kind.label())
+                                .font(FontId::proportional(12.0))
+                                .color(Color32::from_rgb(200, 200, 200)),
+                        );
+                    });
+                });
+        });
+}
+
+/// Render capture notification (top-right, below the recording indicator)
+fn render_capture_notification(ctx: &egui::Context, message: &str, alpha: f32) {
+    use egui::{Align2, Color32, FontId, RichText};
+
+    let alpha_u8 = (alpha * 255.0) as u8;
+
+    egui::Area::new(egui::Id::new("capture_notification"))
+        .anchor(Align2::RIGHT_TOP, [-12.0, 52.0])
+        .interactable(false)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(Color32::from_rgba_unmultiplied(20, 20, 20, (alpha * 200.0) as u8))
+                .corner_radius(6.0)
+                .inner_margin(egui::Margin::symmetric(12, 8))
+                .stroke(egui::Stroke::new(
+                    1.0,
+                    Color32::from_rgba_unmultiplied(118, 185, 0, alpha_u8),
+                ))
+                .show(ui, |ui| {
+                    ui.label(
+                        RichText::new(message)
+                            .font(FontId::proportional(13.0))
+                            .color(Color32::from_rgba_unmultiplied(255, 255, 255, alpha_u8)),
+                    );
+                });
+        });
+    ctx.request_repaint();
+}
@@ -0,0 +1,243 @@
+//! History Tab
+//!
+//! Renders past sessions, playtime per game and monthly totals.
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::app::session_history::{
+    format_playtime, playtime_by_game, playtime_by_month, SessionExit,
+};
+use crate::app::{SessionRecord, SubscriptionInfo};
+
+/// Sessions listed under "Recent Sessions"
+const RECENT_SESSIONS: usize = 25;
+
+/// Months listed in the monthly summary
+const SUMMARY_MONTHS: usize = 12;
+
+const ACCENT: egui::Color32 = egui::Color32::from_rgb(118, 185, 0);
+const MUTED: egui::Color32 = egui::Color32::from_rgb(140, 140, 160);
+
+/// Render the History tab (inside the games screen's central panel)
+pub fn render_history_tab(
+    ui: &mut egui::Ui,
+    history: &[SessionRecord],
+    subscription: Option<&SubscriptionInfo>,
+) {
+    if history.is_empty() {
+        ui.vertical_centered(|ui| {
+            ui.add_space(100.0);
+            ui.label(
+                egui::RichText::new(
+                    "No sessions yet.\nPlaytime shows up here after your first stream.",
+                )
+                .size(14.0)
+                .color(egui::Color32::from_rgb(120, 120, 120)),
+            );
+        });
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.add_space(5.0);
+            render_this_month(ui, history, subscription);
+            ui.add_space(20.0);
+
+            section_header(ui, "Monthly Summary");
+            egui::Grid::new("history_months_grid")
+                .num_columns(4)
+                .spacing([32.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Month", "Sessions", "Playtime", "Of plan"] {
+                        ui.label(egui::RichText::new(header).size(12.0).color(MUTED));
+                    }
+                    ui.end_row();
+                    for month in playtime_by_month(history).iter().take(SUMMARY_MONTHS) {
+                        let name = NaiveDate::from_ymd_opt(month.year, month.month, 1)
+                            .map(|d| d.format("%B %Y").to_string())
+                            .unwrap_or_default();
+                        ui.label(name);
+                        ui.label(month.sessions.to_string());
+                        ui.label(format_playtime(month.total_secs));
+                        ui.label(match plan_hours(subscription) {
+                            Some(total) => format!("{:.0}%", month.hours() / total * 100.0),
+                            None => "-".to_string(),
+                        });
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(20.0);
+
+            section_header(ui, "Playtime by Game");
+            egui::Grid::new("history_games_grid")
+                .num_columns(4)
+                .spacing([32.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Game", "Sessions", "Playtime", "Last played"] {
+                        ui.label(egui::RichText::new(header).size(12.0).color(MUTED));
+                    }
+                    ui.end_row();
+                    for game in playtime_by_game(history) {
+                        ui.label(egui::RichText::new(&game.game_title).color(egui::Color32::WHITE));
+                        ui.label(game.sessions.to_string());
+                        ui.label(format_playtime(game.total_secs));
+                        ui.label(format_time(&game.last_played));
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(20.0);
+
+            section_header(ui, "Recent Sessions");
+            egui::Grid::new("history_sessions_grid")
+                .num_columns(6)
+                .spacing([24.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Started", "Game", "Server", "Duration", "Stream", "Ended"] {
+                        ui.label(egui::RichText::new(header).size(12.0).color(MUTED));
+                    }
+                    ui.end_row();
+                    for record in history.iter().rev().take(RECENT_SESSIONS) {
+                        render_session_row(ui, record);
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(20.0);
+        });
+}
+
+fn section_header(ui: &mut egui::Ui, title: &str) {
+    ui.label(
+        egui::RichText::new(title)
+            .size(18.0)
+            .strong()
+            .color(egui::Color32::WHITE),
+    );
+    ui.add_space(8.0);
+}
+
+/// Hours included in the plan per month (None for unlimited / unknown)
+fn plan_hours(subscription: Option<&SubscriptionInfo>) -> Option<f32> {
+    subscription
+        .filter(|s| !s.is_unlimited && s.total_hours > 0.0)
+        .map(|s| s.total_hours)
+}
+
+/// Current month's playtime against the plan's hours
+fn render_this_month(
+    ui: &mut egui::Ui,
+    history: &[SessionRecord],
+    subscription: Option<&SubscriptionInfo>,
+) {
+    let now = Local::now();
+    let month = playtime_by_month(history)
+        .into_iter()
+        .find(|m| m.year == now.year() && m.month == now.month());
+    let (hours, sessions, secs) = month
+        .map(|m| (m.hours(), m.sessions, m.total_secs))
+        .unwrap_or_default();
+
+    egui::Frame::new()
+        .fill(egui::Color32::from_rgb(35, 35, 45))
+        .corner_radius(8.0)
+        .inner_margin(egui::Margin::same(16))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width().min(520.0));
+            ui.label(
+                egui::RichText::new("This Month")
+                    .size(18.0)
+                    .strong()
+                    .color(ACCENT),
+            );
+            ui.add_space(6.0);
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} played over {} session{}",
+                    format_playtime(secs),
+                    sessions,
+                    if sessions == 1 { "" } else { "s" }
+                ))
+                .size(14.0)
+                .color(egui::Color32::WHITE),
+            );
+
+            match (subscription, plan_hours(subscription)) {
+                (Some(sub), Some(total)) => {
+                    ui.add_space(8.0);
+                    ui.add(
+                        egui::ProgressBar::new((hours / total).clamp(0.0, 1.0))
+                            .fill(ACCENT)
+                            .text(format!("{:.1} h of {:.0} h", hours, total)),
+                    );
+                    ui.add_space(4.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{:.1} h left on your account ({})",
+                            sub.remaining_hours, sub.membership_tier
+                        ))
+                        .size(12.0)
+                        .color(MUTED),
+                    );
+                }
+                (Some(sub), None) if sub.is_unlimited => {
+                    ui.label(
+                        egui::RichText::new("Unlimited playtime")
+                            .size(12.0)
+                            .color(MUTED),
+                    );
+                }
+                _ => {}
+            }
+        });
+}
+
+fn render_session_row(ui: &mut egui::Ui, record: &SessionRecord) {
+    ui.label(format_time(&record.started_at));
+    ui.label(egui::RichText::new(&record.game_title).color(egui::Color32::WHITE));
+    let server = ui.label(&record.zone);
+    if let Some(gpu) = &record.gpu_type {
+        server.on_hover_text(gpu);
+    }
+    ui.label(format_playtime(record.duration_secs));
+
+    let resolution = record
+        .stream_resolution
+        .as_deref()
+        .unwrap_or(&record.resolution);
+    let stream = ui.label(format!(
+        "{} {} @ {}fps",
+        record.codec, resolution, record.fps
+    ));
+    if let Some(avg) = &record.averages {
+        stream.on_hover_text(format!(
+            "Average: {:.0} fps, {:.1} Mbps (max {})\nLatency {:.0} ms, jitter {:.1} ms, loss {:.2}%\nDecode {:.1} ms",
+            avg.fps,
+            avg.bitrate_mbps,
+            record.max_bitrate_mbps,
+            avg.latency_ms,
+            avg.jitter_ms,
+            avg.packet_loss,
+            avg.decode_time_ms
+        ));
+    }
+
+    let exit_color = match record.exit {
+        SessionExit::Error(_) | SessionExit::StreamChanged => egui::Color32::from_rgb(220, 80, 80),
+        _ => MUTED,
+    };
+    let exit = ui.label(egui::RichText::new(record.exit.label()).color(exit_color));
+    if let SessionExit::Error(detail) = &record.exit {
+        exit.on_hover_text(detail);
+    }
+}
+
+/// RFC 3339 timestamp as local "2026-10-18 21:04"
+fn format_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
@@ -2,14 +2,19 @@
 //!
 //! Renders the session loading/connecting screen.
 
+use crate::app::recovery::{RecoveryOffer, RecoveryPrompt};
 use crate::app::{GameInfo, UiAction};
 
+/// Where Steam accounts are linked (Settings > Connections)
+const GFN_WEB_URL: &str = "https://play.geforcenow.com/";
+
 /// Render the session screen (loading/connecting state)
 pub fn render_session_screen(
     ctx: &egui::Context,
     selected_game: &Option<GameInfo>,
     status_message: &str,
     error_message: &Option<String>,
+    recovery: Option<&RecoveryPrompt>,
     actions: &mut Vec<UiAction>
 ) {
     egui::CentralPanel::default().show(ctx, |ui| {
@@ -28,8 +33,11 @@ pub fn render_session_screen(
 
             ui.add_space(40.0);
 
-            // Spinner
-            ui.spinner();
+            // Spinner (unless a failure is waiting on the user)
+            let waiting = error_message.is_none() || recovery.is_some_and(|r| r.status.is_some());
+            if waiting {
+                ui.spinner();
+            }
 
             ui.add_space(20.0);
 
@@ -50,6 +58,10 @@ pub fn render_session_screen(
                 );
             }
 
+            if let Some(recovery) = recovery {
+                render_recovery(ui, recovery, actions);
+            }
+
             ui.add_space(40.0);
 
             // Cancel button
@@ -59,3 +71,60 @@ pub fn render_session_screen(
         });
     });
 }
+
+/// What happens next after a failure, and the buttons to act on it
+fn render_recovery(ui: &mut egui::Ui, recovery: &RecoveryPrompt, actions: &mut Vec<UiAction>) {
+    if let Some(ref status) = recovery.status {
+        ui.add_space(10.0);
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new(status)
+                    .size(13.0)
+                    .color(egui::Color32::from_rgb(230, 190, 90))
+            )
+            .wrap()
+        );
+    }
+
+    ui.add_space(20.0);
+    ui.horizontal(|ui| {
+        // Center the row under the message
+        let width = match recovery.offer {
+            Some(RecoveryOffer::LinkSteam) => 430.0,
+            Some(_) => 280.0,
+            None => 140.0,
+        };
+        ui.add_space(((ui.available_width() - width) / 2.0).max(0.0));
+
+        match recovery.offer {
+            Some(RecoveryOffer::LinkSteam) => {
+                if ui.button("Open GeForce NOW").clicked() {
+                    if let Err(e) = open::that(GFN_WEB_URL) {
+                        log::warn!("Failed to open browser: {}", e);
+                    }
+                }
+                if ui.button("Try Again").clicked() {
+                    actions.push(UiAction::RetryLaunch);
+                }
+            }
+            Some(RecoveryOffer::Retry) => {
+                if ui.button("Try Again").clicked() {
+                    actions.push(UiAction::RetryLaunch);
+                }
+            }
+            Some(RecoveryOffer::LogIn) => {
+                if ui.button("Log In Again").clicked() {
+                    actions.push(UiAction::Logout);
+                }
+            }
+            None => {}
+        }
+
+        let copy = ui
+            .button("Copy Diagnostics")
+            .on_hover_text("Error codes and session details for bug reports");
+        if copy.clicked() {
+            ui.ctx().copy_text(recovery.diagnostics.clone());
+        }
+    });
+}
@@ -1,15 +1,27 @@
 //! GPU Shaders for video rendering
 //!
 //! WGSL shaders for YUV to RGB conversion on the GPU.
-//! Supports SDR (BT.709) and HDR (BT.2020 + PQ) with automatic tone mapping.
+//! Supports SDR (BT.709) and HDR (BT.2020 + PQ or HLG) with automatic tone mapping.
 //!
 //! HDR Detection:
 //! The VideoFrame struct carries color_space and transfer_function metadata.
-//! When HDR content is detected (BT.2020 + PQ), the shader will apply:
-//! 1. PQ EOTF to convert to linear light
+//! When HDR content is detected (BT.2020 + PQ or HLG), the shader will apply:
+//! 1. PQ or HLG EOTF to convert to linear light
 //! 2. BT.2020 to BT.709 color space conversion
-//! 3. ACES tone mapping to compress HDR to SDR range
+//! 3. Tone mapping to SDR range (ACES, Reinhard, BT.2390 EETF or Hable)
 //! 4. SDR gamma for display
+//!
+//! With an scRGB swapchain (OUTPUT_MODE = 1) HDR skips steps 3-4 and SDR
+//! content is linearized so both land at the right brightness. The UI is
+//! composited separately by `UI_COMPOSITE_SHADER`.
+//!
+//! All video shaders share bind group 1: tone mapping params (binding 0, HDR
+//! only) and the user's colour adjustments (binding 1). Adjustments work on
+//! SDR output; the scRGB HDR passthrough is left untouched.
+
+/// Bind groups every video shader uses: the frame's planes (0) and the
+/// shared params (1). The renderer binds exactly this many before a draw.
+pub const VIDEO_BIND_GROUPS: usize = 2;
 
 /// WGSL shader for YUV420P format (3 separate planes)
 /// Uses BT.709 Limited range conversion (standard for video)
@@ -56,6 +68,54 @@ var v_texture: texture_2d<f32>;
 @group(0) @binding(3)
 var video_sampler: sampler;
 
+// Swapchain encoding, fixed at startup: 0 = SDR (sRGB), 1 = scRGB (linear, 1.0 = 80 nits)
+override OUTPUT_MODE: u32 = 0u;
+
+// Map an sRGB-encoded colour to the swapchain encoding.
+// On scRGB, SDR white sits at 203 nits (BT.2408 graphics white).
+fn encode_output(srgb: vec3<f32>) -> vec3<f32> {
+    if (OUTPUT_MODE == 1u) {
+        let low = srgb / 12.92;
+        let high = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+        return select(high, low, srgb <= vec3<f32>(0.04045)) * (203.0 / 80.0);
+    }
+    return srgb;
+}
+
+// Colour adjustments from the settings / in-stream overlay (per game)
+struct ColorAdjust {
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    saturation: f32,
+    vibrance: f32,
+    // -1.0 = cool (blue), 1.0 = warm (orange)
+    temperature: f32,
+    // 0 when everything is at its default, skips the maths
+    enabled: u32,
+    _pad0: f32,
+};
+
+@group(1) @binding(1)
+var<uniform> color_adjust: ColorAdjust;
+
+// Apply the colour adjustments to an sRGB-encoded colour (0-1)
+fn adjust_color(srgb: vec3<f32>) -> vec3<f32> {
+    if (color_adjust.enabled == 0u) {
+        return srgb;
+    }
+    let t = color_adjust.temperature;
+    var c = srgb * vec3<f32>(1.0 + 0.1 * t, 1.0, 1.0 - 0.1 * t);
+    c = (c - 0.5) * color_adjust.contrast + 0.5 + color_adjust.brightness;
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, color_adjust.saturation);
+    // Vibrance: saturation boost weighted towards muted colours
+    let chroma = clamp(max(c.r, max(c.g, c.b)) - min(c.r, min(c.g, c.b)), 0.0, 1.0);
+    c = mix(vec3<f32>(luma), c, 1.0 + color_adjust.vibrance * (1.0 - chroma));
+    c = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    return pow(c, vec3<f32>(1.0 / color_adjust.gamma));
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Sample Y, U, V planes
@@ -74,7 +134,8 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let g = y - 0.1873 * u - 0.4681 * v;
     let b = y + 1.8556 * u;
 
-    return vec4<f32>(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0), 1.0);
+    let rgb = vec3<f32>(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0));
+    return vec4<f32>(encode_output(adjust_color(rgb)), 1.0);
 }
 "#;
 
@@ -121,6 +182,54 @@ var uv_texture: texture_2d<f32>;
 @group(0) @binding(2)
 var video_sampler: sampler;
 
+// Swapchain encoding, fixed at startup: 0 = SDR (sRGB), 1 = scRGB (linear, 1.0 = 80 nits)
+override OUTPUT_MODE: u32 = 0u;
+
+// Map an sRGB-encoded colour to the swapchain encoding.
+// On scRGB, SDR white sits at 203 nits (BT.2408 graphics white).
+fn encode_output(srgb: vec3<f32>) -> vec3<f32> {
+    if (OUTPUT_MODE == 1u) {
+        let low = srgb / 12.92;
+        let high = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+        return select(high, low, srgb <= vec3<f32>(0.04045)) * (203.0 / 80.0);
+    }
+    return srgb;
+}
+
+// Colour adjustments from the settings / in-stream overlay (per game)
+struct ColorAdjust {
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    saturation: f32,
+    vibrance: f32,
+    // -1.0 = cool (blue), 1.0 = warm (orange)
+    temperature: f32,
+    // 0 when everything is at its default, skips the maths
+    enabled: u32,
+    _pad0: f32,
+};
+
+@group(1) @binding(1)
+var<uniform> color_adjust: ColorAdjust;
+
+// Apply the colour adjustments to an sRGB-encoded colour (0-1)
+fn adjust_color(srgb: vec3<f32>) -> vec3<f32> {
+    if (color_adjust.enabled == 0u) {
+        return srgb;
+    }
+    let t = color_adjust.temperature;
+    var c = srgb * vec3<f32>(1.0 + 0.1 * t, 1.0, 1.0 - 0.1 * t);
+    c = (c - 0.5) * color_adjust.contrast + 0.5 + color_adjust.brightness;
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, color_adjust.saturation);
+    // Vibrance: saturation boost weighted towards muted colours
+    let chroma = clamp(max(c.r, max(c.g, c.b)) - min(c.r, min(c.g, c.b)), 0.0, 1.0);
+    c = mix(vec3<f32>(luma), c, 1.0 + color_adjust.vibrance * (1.0 - chroma));
+    c = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    return pow(c, vec3<f32>(1.0 / color_adjust.gamma));
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Sample Y (full res) and UV (half res, interleaved)
@@ -142,12 +251,14 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let g = y - 0.1873 * u - 0.4681 * v;
     let b = y + 1.8556 * u;
 
-    return vec4<f32>(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0), 1.0);
+    let rgb = vec3<f32>(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0));
+    return vec4<f32>(encode_output(adjust_color(rgb)), 1.0);
 }
 "#;
 
-/// WGSL shader for NV12 HDR content (BT.2020 + PQ to SDR tone mapping)
-/// Used when HDR content is detected and display is in SDR mode
+/// WGSL shader for NV12 HDR content (BT.2020 + PQ/HLG)
+/// Tone maps to SDR with the configured operator, or passes linear light
+/// straight through to an scRGB swapchain (OUTPUT_MODE = 1)
 pub const NV12_HDR_TONEMAP_SHADER: &str = r#"
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -188,20 +299,101 @@ var uv_texture: texture_2d<f32>;
 @group(0) @binding(2)
 var video_sampler: sampler;
 
+// Tone mapping settings (updated live from Settings)
+struct ToneMapParams {
+    // 0 = ACES, 1 = Reinhard, 2 = BT.2390 EETF, 3 = Hable
+    tone_mapper: u32,
+    // 1 = PQ, 2 = HLG
+    transfer: u32,
+    // Brightness that maps to SDR white
+    target_nits: f32,
+    // Assumed mastering peak of the content
+    peak_nits: f32,
+    saturation: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> tone_map: ToneMapParams;
+
+// Swapchain encoding, fixed at startup: 0 = SDR (sRGB), 1 = scRGB (linear, 1.0 = 80 nits)
+override OUTPUT_MODE: u32 = 0u;
+
+// Colour adjustments from the settings / in-stream overlay (per game)
+struct ColorAdjust {
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    saturation: f32,
+    vibrance: f32,
+    // -1.0 = cool (blue), 1.0 = warm (orange)
+    temperature: f32,
+    // 0 when everything is at its default, skips the maths
+    enabled: u32,
+    _pad0: f32,
+};
+
+@group(1) @binding(1)
+var<uniform> color_adjust: ColorAdjust;
+
+// Apply the colour adjustments to an sRGB-encoded colour (0-1)
+fn adjust_color(srgb: vec3<f32>) -> vec3<f32> {
+    if (color_adjust.enabled == 0u) {
+        return srgb;
+    }
+    let t = color_adjust.temperature;
+    var c = srgb * vec3<f32>(1.0 + 0.1 * t, 1.0, 1.0 - 0.1 * t);
+    c = (c - 0.5) * color_adjust.contrast + 0.5 + color_adjust.brightness;
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, color_adjust.saturation);
+    // Vibrance: saturation boost weighted towards muted colours
+    let chroma = clamp(max(c.r, max(c.g, c.b)) - min(c.r, min(c.g, c.b)), 0.0, 1.0);
+    c = mix(vec3<f32>(luma), c, 1.0 + color_adjust.vibrance * (1.0 - chroma));
+    c = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    return pow(c, vec3<f32>(1.0 / color_adjust.gamma));
+}
+
+const PQ_M1: f32 = 0.1593017578125;   // 2610/16384
+const PQ_M2: f32 = 78.84375;          // 2523/32 * 128
+const PQ_C1: f32 = 0.8359375;         // 3424/4096
+const PQ_C2: f32 = 18.8515625;        // 2413/128
+const PQ_C3: f32 = 18.6875;           // 2392/128
+
+const LUMA_709: vec3<f32> = vec3<f32>(0.2126, 0.7152, 0.0722);
+
 // PQ EOTF (SMPTE ST 2084) - converts PQ signal to linear light (nits)
+fn pq_to_nits(pq: f32) -> f32 {
+    let pq_pow = pow(max(pq, 0.0), 1.0 / PQ_M2);
+    let num = max(pq_pow - PQ_C1, 0.0);
+    let den = PQ_C2 - PQ_C3 * pq_pow;
+
+    // Output is in units of 10000 nits (PQ reference white)
+    return pow(num / max(den, 0.0001), 1.0 / PQ_M1) * 10000.0;
+}
+
+// Inverse PQ EOTF - linear light (nits) to PQ signal
+fn nits_to_pq(nits: f32) -> f32 {
+    let y = pow(max(nits / 10000.0, 0.0), PQ_M1);
+    return pow((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y), PQ_M2);
+}
+
 fn pq_eotf(pq: vec3<f32>) -> vec3<f32> {
-    let m1 = 0.1593017578125;   // 2610/16384
-    let m2 = 78.84375;          // 2523/32 * 128
-    let c1 = 0.8359375;         // 3424/4096
-    let c2 = 18.8515625;        // 2413/128
-    let c3 = 18.6875;           // 2392/128
+    return vec3<f32>(pq_to_nits(pq.r), pq_to_nits(pq.g), pq_to_nits(pq.b));
+}
 
-    let pq_pow = pow(max(pq, vec3<f32>(0.0)), vec3<f32>(1.0 / m2));
-    let num = max(pq_pow - c1, vec3<f32>(0.0));
-    let den = c2 - c3 * pq_pow;
+// HLG inverse OETF + OOTF (BT.2100) for a 1000 nit display, system gamma 1.2
+fn hlg_eotf(signal: vec3<f32>) -> vec3<f32> {
+    let a = 0.17883277;
+    let b = 0.28466892;
+    let c = 0.55991073;
+    let low = signal * signal / 3.0;
+    let high = (exp((signal - c) / a) + b) / 12.0;
+    let scene = select(high, low, signal <= vec3<f32>(0.5));
 
-    // Output is in units of 10000 nits (PQ reference white)
-    return pow(num / max(den, vec3<f32>(0.0001)), vec3<f32>(1.0 / m1)) * 10000.0;
+    let ys = dot(scene, vec3<f32>(0.2627, 0.6780, 0.0593));
+    return scene * 1000.0 * pow(max(ys, 1e-6), 0.2);
 }
 
 // BT.2020 to BT.709 color gamut conversion
@@ -213,19 +405,71 @@ fn bt2020_to_bt709(color: vec3<f32>) -> vec3<f32> {
     return vec3<f32>(r, g, b);
 }
 
-// ACES-inspired filmic tone mapping
-fn tonemap_aces(hdr: vec3<f32>) -> vec3<f32> {
-    // Normalize from nits to [0,1] range
-    // Assume 203 nits as SDR reference white (BT.2408)
-    let x = hdr / 203.0;
+// The operators below take linear light relative to target_nits (1.0 = SDR white)
 
-    // ACES filmic curve approximation (Narkowicz 2015)
+// ACES filmic curve approximation (Narkowicz 2015)
+fn tonemap_aces(x: vec3<f32>) -> vec3<f32> {
     let a = 2.51;
     let b = 0.03;
     let c = 2.43;
     let d = 0.59;
     let e = 0.14;
-    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+    return (x * (a * x + b)) / (x * (c * x + d) + e);
+}
+
+// Extended Reinhard on luminance (keeps hue), peak maps to 1.0
+fn tonemap_reinhard(x: vec3<f32>, white: f32) -> vec3<f32> {
+    let l = dot(x, LUMA_709);
+    if (l <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+    let mapped = l * (1.0 + l / (white * white)) / (1.0 + l);
+    return x * (mapped / l);
+}
+
+// BT.2390 EETF: hermite knee in PQ space from [0, peak] to [0, target],
+// applied to the max channel so hue is kept
+fn tonemap_bt2390(x: vec3<f32>) -> vec3<f32> {
+    let peak = max(x.r, max(x.g, x.b));
+    if (peak <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+    let src_max = nits_to_pq(tone_map.peak_nits);
+    let max_lum = nits_to_pq(tone_map.target_nits) / src_max;
+    let ks = 1.5 * max_lum - 0.5;
+
+    let e1 = min(nits_to_pq(peak * tone_map.target_nits) / src_max, 1.0);
+    var e2 = e1;
+    if (e1 > ks) {
+        let t = (e1 - ks) / (1.0 - ks);
+        let t2 = t * t;
+        let t3 = t2 * t;
+        e2 = (2.0 * t3 - 3.0 * t2 + 1.0) * ks
+            + (t3 - 2.0 * t2 + t) * (1.0 - ks)
+            + (-2.0 * t3 + 3.0 * t2) * max_lum;
+    }
+    let mapped = pq_to_nits(e2 * src_max) / tone_map.target_nits;
+    return x * (mapped / peak);
+}
+
+// Hable / Uncharted 2 filmic curve, white point at the content peak
+fn hable_curve(x: vec3<f32>) -> vec3<f32> {
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn tonemap_hable(x: vec3<f32>, white: f32) -> vec3<f32> {
+    return hable_curve(x * 2.0) / hable_curve(vec3<f32>(white));
+}
+
+fn apply_saturation(color: vec3<f32>, saturation: f32) -> vec3<f32> {
+    let l = dot(color, LUMA_709);
+    return vec3<f32>(l) + (color - vec3<f32>(l)) * saturation;
 }
 
 // Apply SDR gamma curve (sRGB approximation)
@@ -260,17 +504,45 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
 
     var rgb = vec3<f32>(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0));
 
-    // Apply PQ EOTF to get linear light in nits
-    let linear_hdr = pq_eotf(rgb);
+    // Decode to linear light in nits
+    var linear_hdr: vec3<f32>;
+    if (tone_map.transfer == 2u) {
+        linear_hdr = hlg_eotf(rgb);
+    } else {
+        linear_hdr = pq_eotf(rgb);
+    }
 
     // Convert from BT.2020 to BT.709 color gamut
     let bt709_linear = bt2020_to_bt709(linear_hdr);
 
+    // scRGB swapchain: no tone mapping, the display handles its own range.
+    // Out-of-709 colours stay as negative values, which scRGB allows.
+    if (OUTPUT_MODE == 1u) {
+        return vec4<f32>(apply_saturation(bt709_linear, tone_map.saturation) / 80.0, 1.0);
+    }
+
     // Tone map HDR to SDR range
-    let tonemapped = tonemap_aces(max(bt709_linear, vec3<f32>(0.0)));
+    let x = max(bt709_linear, vec3<f32>(0.0)) / tone_map.target_nits;
+    let white = max(tone_map.peak_nits / tone_map.target_nits, 1.0);
+    var tonemapped: vec3<f32>;
+    switch tone_map.tone_mapper {
+        case 1u: {
+            tonemapped = tonemap_reinhard(x, white);
+        }
+        case 2u: {
+            tonemapped = tonemap_bt2390(x);
+        }
+        case 3u: {
+            tonemapped = tonemap_hable(x, white);
+        }
+        default: {
+            tonemapped = tonemap_aces(x);
+        }
+    }
+    tonemapped = clamp(apply_saturation(tonemapped, tone_map.saturation), vec3<f32>(0.0), vec3<f32>(1.0));
 
-    // Apply sRGB gamma for SDR display
-    let sdr = linear_to_srgb(tonemapped);
+    // Apply sRGB gamma for SDR display, then the user's colour adjustments
+    let sdr = adjust_color(linear_to_srgb(tonemapped));
 
     return vec4<f32>(sdr, 1.0);
 }
@@ -319,10 +591,477 @@ var video_texture: texture_external;
 @group(0) @binding(1)
 var video_sampler: sampler;
 
+// Swapchain encoding, fixed at startup: 0 = SDR (sRGB), 1 = scRGB (linear, 1.0 = 80 nits)
+override OUTPUT_MODE: u32 = 0u;
+
+// Map an sRGB-encoded colour to the swapchain encoding.
+// On scRGB, SDR white sits at 203 nits (BT.2408 graphics white).
+fn encode_output(srgb: vec3<f32>) -> vec3<f32> {
+    if (OUTPUT_MODE == 1u) {
+        let low = srgb / 12.92;
+        let high = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+        return select(high, low, srgb <= vec3<f32>(0.04045)) * (203.0 / 80.0);
+    }
+    return srgb;
+}
+
+// Colour adjustments from the settings / in-stream overlay (per game)
+struct ColorAdjust {
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    saturation: f32,
+    vibrance: f32,
+    // -1.0 = cool (blue), 1.0 = warm (orange)
+    temperature: f32,
+    // 0 when everything is at its default, skips the maths
+    enabled: u32,
+    _pad0: f32,
+};
+
+@group(1) @binding(1)
+var<uniform> color_adjust: ColorAdjust;
+
+// Apply the colour adjustments to an sRGB-encoded colour (0-1)
+fn adjust_color(srgb: vec3<f32>) -> vec3<f32> {
+    if (color_adjust.enabled == 0u) {
+        return srgb;
+    }
+    let t = color_adjust.temperature;
+    var c = srgb * vec3<f32>(1.0 + 0.1 * t, 1.0, 1.0 - 0.1 * t);
+    c = (c - 0.5) * color_adjust.contrast + 0.5 + color_adjust.brightness;
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, color_adjust.saturation);
+    // Vibrance: saturation boost weighted towards muted colours
+    let chroma = clamp(max(c.r, max(c.g, c.b)) - min(c.r, min(c.g, c.b)), 0.0, 1.0);
+    c = mix(vec3<f32>(luma), c, 1.0 + color_adjust.vibrance * (1.0 - chroma));
+    c = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    return pow(c, vec3<f32>(1.0 / color_adjust.gamma));
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // textureSampleBaseClampToEdge automatically converts YUV to RGB
     // using the color space information from the ExternalTexture descriptor
-    return textureSampleBaseClampToEdge(video_texture, video_sampler, input.tex_coord);
+    let color = textureSampleBaseClampToEdge(video_texture, video_sampler, input.tex_coord);
+    return vec4<f32>(encode_output(adjust_color(color.rgb)), color.a);
+}
+"#;
+
+/// WGSL shader for the upscaling passes (see `upscaler.rs`)
+/// Input is the video already converted to RGB at its native resolution,
+/// drawn into the destination rectangle chosen by the scaling mode.
+/// Entry points:
+/// - fs_bilinear: plain bilinear, used when only cropping
+/// - fs_bicubic: 4x4 Mitchell-Netravali, strength moves B/C towards Catmull-Rom
+/// - fs_lanczos: 6x6 Lanczos-3, strength lifts the anti-ringing clamp
+/// - fs_easu: FSR 1 Edge Adaptive Spatial Upsampling (12-tap)
+/// - fs_rcas: FSR 1 Robust Contrast Adaptive Sharpening, runs at output size
+pub const UPSCALE_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var positions = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 1.0, -1.0),
+        vec2<f32>(-1.0,  1.0),
+        vec2<f32>(-1.0,  1.0),
+        vec2<f32>( 1.0, -1.0),
+        vec2<f32>( 1.0,  1.0),
+    );
+
+    var tex_coords = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+    );
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(positions[vertex_index], 0.0, 1.0);
+    output.tex_coord = tex_coords[vertex_index];
+    return output;
+}
+
+struct UpscaleParams {
+    source_size: vec2<f32>,
+    // Stream rectangle in output pixels (may extend past the output)
+    dest_origin: vec2<f32>,
+    dest_size: vec2<f32>,
+    strength: f32,
+    _pad0: f32,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: UpscaleParams;
+
+const PI: f32 = 3.14159265;
+
+// Fetch a texel, clamped to the edge
+fn load(p: vec2<i32>) -> vec3<f32> {
+    let max_p = vec2<i32>(textureDimensions(source_texture)) - vec2<i32>(1);
+    return textureLoad(source_texture, clamp(p, vec2<i32>(0), max_p), 0).rgb;
+}
+
+// Source pixel position (texel centres on integers) for an output fragment
+fn source_position(frag: vec2<f32>) -> vec2<f32> {
+    return (frag - params.dest_origin) * (params.source_size / params.dest_size) - 0.5;
+}
+
+// Cheap luma used by FSR (green-weighted, skips the matrix)
+fn luma(c: vec3<f32>) -> f32 {
+    return c.b * 0.5 + (c.r * 0.5 + c.g);
+}
+
+// ---------------------------------------------------------------------------
+// Bilinear
+// ---------------------------------------------------------------------------
+
+@fragment
+fn fs_bilinear(input: VertexOutput) -> @location(0) vec4<f32> {
+    let p = source_position(input.position.xy);
+    let base = floor(p);
+    let f = p - base;
+    let o = vec2<i32>(base);
+
+    let top = mix(load(o), load(o + vec2<i32>(1, 0)), f.x);
+    let bottom = mix(load(o + vec2<i32>(0, 1)), load(o + vec2<i32>(1, 1)), f.x);
+    return vec4<f32>(mix(top, bottom, f.y), 1.0);
+}
+
+// ---------------------------------------------------------------------------
+// Bicubic
+// ---------------------------------------------------------------------------
+
+fn cubic_weight(x: f32, b: f32, c: f32) -> f32 {
+    let ax = abs(x);
+    let ax2 = ax * ax;
+    let ax3 = ax2 * ax;
+    if (ax < 1.0) {
+        return ((12.0 - 9.0 * b - 6.0 * c) * ax3
+            + (-18.0 + 12.0 * b + 6.0 * c) * ax2
+            + (6.0 - 2.0 * b)) / 6.0;
+    }
+    if (ax < 2.0) {
+        return ((-b - 6.0 * c) * ax3
+            + (6.0 * b + 30.0 * c) * ax2
+            + (-12.0 * b - 48.0 * c) * ax
+            + (8.0 * b + 24.0 * c)) / 6.0;
+    }
+    return 0.0;
+}
+
+@fragment
+fn fs_bicubic(input: VertexOutput) -> @location(0) vec4<f32> {
+    let p = source_position(input.position.xy);
+    let base = floor(p);
+    let f = p - base;
+
+    // strength 0 = Mitchell (B = C = 1/3), 1 = Catmull-Rom (B = 0, C = 0.5)
+    let b = (1.0 - params.strength) / 3.0;
+    let c = (1.0 - b) * 0.5;
+
+    var color = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var j = -1; j <= 2; j++) {
+        let wy = cubic_weight(f32(j) - f.y, b, c);
+        for (var i = -1; i <= 2; i++) {
+            let w = wy * cubic_weight(f32(i) - f.x, b, c);
+            color += w * load(vec2<i32>(base) + vec2<i32>(i, j));
+            total += w;
+        }
+    }
+    return vec4<f32>(color / total, 1.0);
+}
+
+// ---------------------------------------------------------------------------
+// Lanczos-3
+// ---------------------------------------------------------------------------
+
+fn lanczos_weight(x: f32) -> f32 {
+    let ax = abs(x);
+    if (ax < 1e-5) {
+        return 1.0;
+    }
+    if (ax >= 3.0) {
+        return 0.0;
+    }
+    let px = PI * ax;
+    return 3.0 * sin(px) * sin(px / 3.0) / (px * px);
+}
+
+@fragment
+fn fs_lanczos(input: VertexOutput) -> @location(0) vec4<f32> {
+    let p = source_position(input.position.xy);
+    let base = floor(p);
+    let f = p - base;
+
+    var color = vec3<f32>(0.0);
+    var total = 0.0;
+    var lo = vec3<f32>(1e9);
+    var hi = vec3<f32>(-1e9);
+    for (var j = -2; j <= 3; j++) {
+        let wy = lanczos_weight(f32(j) - f.y);
+        for (var i = -2; i <= 3; i++) {
+            let texel = load(vec2<i32>(base) + vec2<i32>(i, j));
+            let w = wy * lanczos_weight(f32(i) - f.x);
+            color += w * texel;
+            total += w;
+            // The 2x2 nearest texels bound the anti-ringing clamp
+            if (i >= 0 && i <= 1 && j >= 0 && j <= 1) {
+                lo = min(lo, texel);
+                hi = max(hi, texel);
+            }
+        }
+    }
+    color = color / total;
+
+    // strength 0 = fully clamped (no halos), 1 = raw kernel
+    let clamped = clamp(color, lo, hi);
+    return vec4<f32>(mix(clamped, color, params.strength), 1.0);
+}
+
+// ---------------------------------------------------------------------------
+// FSR 1 EASU
+// ---------------------------------------------------------------------------
+
+// Accumulate direction and edge length for one bilinear quadrant
+//    a
+//  b c d
+//    e
+fn easu_set(w: f32, la: f32, lb: f32, lc: f32, ld: f32, le: f32) -> vec3<f32> {
+    let dir_x = ld - lb;
+    var len_x = max(abs(ld - lc), abs(lc - lb));
+    len_x = clamp(abs(dir_x) / max(len_x, 1e-5), 0.0, 1.0);
+    len_x *= len_x;
+
+    let dir_y = le - la;
+    var len_y = max(abs(le - lc), abs(lc - la));
+    len_y = clamp(abs(dir_y) / max(len_y, 1e-5), 0.0, 1.0);
+    len_y *= len_y;
+
+    return vec3<f32>(dir_x * w, dir_y * w, (len_x + len_y) * w);
+}
+
+// Accumulate one filter tap
+fn easu_tap(
+    off: vec2<f32>,
+    dir: vec2<f32>,
+    len: vec2<f32>,
+    lob: f32,
+    clp: f32,
+    c: vec3<f32>,
+) -> vec4<f32> {
+    // Rotate into the edge direction, then anisotropic scale
+    var v = vec2<f32>(off.x * dir.x + off.y * dir.y, off.x * -dir.y + off.y * dir.x);
+    v *= len;
+    let d2 = min(v.x * v.x + v.y * v.y, clp);
+
+    // Approximation of lanczos2 without sin() or rcp()
+    var wb = 2.0 / 5.0 * d2 - 1.0;
+    var wa = lob * d2 - 1.0;
+    wb *= wb;
+    wa *= wa;
+    wb = 25.0 / 16.0 * wb - (25.0 / 16.0 - 1.0);
+    let w = wb * wa;
+    return vec4<f32>(c * w, w);
+}
+
+@fragment
+fn fs_easu(input: VertexOutput) -> @location(0) vec4<f32> {
+    var pp = source_position(input.position.xy);
+    let fp = floor(pp);
+    pp -= fp;
+    let o = vec2<i32>(fp);
+
+    // 12-tap kernel
+    //    b c
+    //  e f g h
+    //  i j k l
+    //    n o
+    let b = load(o + vec2<i32>(0, -1));
+    let c = load(o + vec2<i32>(1, -1));
+    let e = load(o + vec2<i32>(-1, 0));
+    let f = load(o + vec2<i32>(0, 0));
+    let g = load(o + vec2<i32>(1, 0));
+    let h = load(o + vec2<i32>(2, 0));
+    let i = load(o + vec2<i32>(-1, 1));
+    let j = load(o + vec2<i32>(0, 1));
+    let k = load(o + vec2<i32>(1, 1));
+    let l = load(o + vec2<i32>(2, 1));
+    let n = load(o + vec2<i32>(0, 2));
+    let oo = load(o + vec2<i32>(1, 2));
+
+    let bl = luma(b);
+    let cl = luma(c);
+    let el = luma(e);
+    let fl = luma(f);
+    let gl = luma(g);
+    let hl = luma(h);
+    let il = luma(i);
+    let jl = luma(j);
+    let kl = luma(k);
+    let ll = luma(l);
+    let nl = luma(n);
+    let ol = luma(oo);
+
+    // Edge direction and length, bilinearly weighted over the 4 centre texels
+    var acc = easu_set((1.0 - pp.x) * (1.0 - pp.y), bl, el, fl, gl, jl);
+    acc += easu_set(pp.x * (1.0 - pp.y), cl, fl, gl, hl, kl);
+    acc += easu_set((1.0 - pp.x) * pp.y, fl, il, jl, kl, nl);
+    acc += easu_set(pp.x * pp.y, gl, jl, kl, ll, ol);
+
+    var dir = acc.xy;
+    var len = acc.z;
+
+    // Normalize direction, defaulting to horizontal on flat areas
+    let dir_r = dir.x * dir.x + dir.y * dir.y;
+    if (dir_r < 1.0 / 32768.0) {
+        dir = vec2<f32>(1.0, 0.0);
+    } else {
+        dir *= inverseSqrt(dir_r);
+    }
+
+    // Shape the kernel: stretch along edges, shrink across them
+    len = len * 0.5;
+    len *= len;
+    let stretch = (dir.x * dir.x + dir.y * dir.y) / max(abs(dir.x), abs(dir.y));
+    let len2 = vec2<f32>(1.0 + (stretch - 1.0) * len, 1.0 - 0.5 * len);
+    let lob = 0.5 + ((1.0 / 4.0 - 0.04) - 0.5) * len;
+    let clp = 1.0 / lob;
+
+    var sum = easu_tap(vec2<f32>(0.0, -1.0) - pp, dir, len2, lob, clp, b);
+    sum += easu_tap(vec2<f32>(1.0, -1.0) - pp, dir, len2, lob, clp, c);
+    sum += easu_tap(vec2<f32>(-1.0, 1.0) - pp, dir, len2, lob, clp, i);
+    sum += easu_tap(vec2<f32>(0.0, 1.0) - pp, dir, len2, lob, clp, j);
+    sum += easu_tap(vec2<f32>(0.0, 0.0) - pp, dir, len2, lob, clp, f);
+    sum += easu_tap(vec2<f32>(-1.0, 0.0) - pp, dir, len2, lob, clp, e);
+    sum += easu_tap(vec2<f32>(1.0, 1.0) - pp, dir, len2, lob, clp, k);
+    sum += easu_tap(vec2<f32>(2.0, 1.0) - pp, dir, len2, lob, clp, l);
+    sum += easu_tap(vec2<f32>(2.0, 0.0) - pp, dir, len2, lob, clp, h);
+    sum += easu_tap(vec2<f32>(1.0, 0.0) - pp, dir, len2, lob, clp, g);
+    sum += easu_tap(vec2<f32>(1.0, 2.0) - pp, dir, len2, lob, clp, oo);
+    sum += easu_tap(vec2<f32>(0.0, 2.0) - pp, dir, len2, lob, clp, n);
+
+    // Dering: clamp to the range of the 2x2 nearest texels
+    let min4 = min(min(f, g), min(j, k));
+    let max4 = max(max(f, g), max(j, k));
+    let color = clamp(sum.rgb / sum.w, min4, max4);
+    return vec4<f32>(color, 1.0);
+}
+
+// ---------------------------------------------------------------------------
+// FSR 1 RCAS
+// ---------------------------------------------------------------------------
+
+// Maximum negative lobe; stronger sharpening than this artifacts
+const RCAS_LIMIT: f32 = 0.25 - 1.0 / 16.0;
+
+@fragment
+fn fs_rcas(input: VertexOutput) -> @location(0) vec4<f32> {
+    let p = vec2<i32>(input.position.xy);
+
+    //    b
+    //  d e f
+    //    h
+    let b = load(p + vec2<i32>(0, -1));
+    let d = load(p + vec2<i32>(-1, 0));
+    let e = load(p);
+    let f = load(p + vec2<i32>(1, 0));
+    let h = load(p + vec2<i32>(0, 1));
+
+    // Largest lobe that doesn't push any channel outside [0, 1]
+    let mn4 = min(min(b, d), min(f, h));
+    let mx4 = max(max(b, d), max(f, h));
+    let hit_min = mn4 / max(4.0 * mx4, vec3<f32>(1e-5));
+    let hit_max = (1.0 - mx4) / min(4.0 * mn4 - 4.0, vec3<f32>(-1e-5));
+    let lobe_rgb = max(-hit_min, hit_max);
+    var lobe = max(-RCAS_LIMIT, min(max(lobe_rgb.r, max(lobe_rgb.g, lobe_rgb.b)), 0.0))
+        * params.strength;
+
+    // Back off on noise (a lone pixel differing from its neighbours)
+    let bl = luma(b);
+    let dl = luma(d);
+    let el = luma(e);
+    let fl = luma(f);
+    let hl = luma(h);
+    let range = max(max(max(bl, dl), max(el, fl)), hl) - min(min(min(bl, dl), min(el, fl)), hl);
+    var nz = 0.25 * (bl + dl + fl + hl) - el;
+    nz = clamp(abs(nz) / max(range, 1e-5), 0.0, 1.0);
+    lobe *= -0.5 * nz + 1.0;
+
+    let color = (lobe * (b + d + f + h) + e) / (4.0 * lobe + 1.0);
+    return vec4<f32>(color, 1.0);
 }
 "#;
+
+/// WGSL shader compositing the UI onto an scRGB swapchain (see `ui_compositor.rs`)
+/// Input is egui drawn into an sRGB target, so loads return linear premultiplied
+/// colour; it is scaled so UI white sits at 203 nits like SDR video.
+pub const UI_COMPOSITE_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var positions = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 1.0, -1.0),
+        vec2<f32>(-1.0,  1.0),
+        vec2<f32>(-1.0,  1.0),
+        vec2<f32>( 1.0, -1.0),
+        vec2<f32>( 1.0,  1.0),
+    );
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(positions[vertex_index], 0.0, 1.0);
+    return output;
+}
+
+@group(0) @binding(0)
+var ui_texture: texture_2d<f32>;
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureLoad(ui_texture, vec2<i32>(input.position.xy), 0);
+    // Premultiplied, so scaling rgb keeps the blend correct
+    return vec4<f32>(color.rgb * (203.0 / 80.0), color.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Highest `@group(N)` index a shader declares, plus one
+    fn bind_group_count(shader: &str) -> usize {
+        shader
+            .split("@group(")
+            .skip(1)
+            .filter_map(|rest| rest.split(')').next()?.parse::<usize>().ok())
+            .max()
+            .map_or(0, |group| group + 1)
+    }
+
+    #[test]
+    fn test_video_shaders_match_bound_groups() {
+        for shader in [
+            VIDEO_SHADER,
+            NV12_SHADER,
+            NV12_HDR_TONEMAP_SHADER,
+            EXTERNAL_TEXTURE_SHADER,
+        ] {
+            assert_eq!(bind_group_count(shader), VIDEO_BIND_GROUPS);
+        }
+        assert_eq!(bind_group_count(UI_COMPOSITE_SHADER), 1);
+    }
+}
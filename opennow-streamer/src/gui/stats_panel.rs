@@ -3,8 +3,9 @@
 //! Bottom-left stats display matching the web client style.
 //! Includes throttling to reduce CPU usage - stats update every 200ms instead of every frame.
 
-use egui::{Align2, Color32, FontId, RichText};
-use crate::media::StreamStats;
+use egui::{Align2, Color32, FontId, RichText, Sense, Stroke};
+use crate::media::stats_history::HISTORY_WINDOW;
+use crate::media::{MetricGraph, StatsMetric, StreamStats};
 use crate::app::StatsPosition;
 use std::time::{Duration, Instant};
 
@@ -211,6 +212,33 @@ impl StatsPanel {
                             );
                         }
 
+                        // Audio loss concealment
+                        if display_stats.audio_packets_lost > 0 {
+                            ui.label(
+                                RichText::new(format!(
+                                    "Audio: {} lost, {} concealed",
+                                    display_stats.audio_packets_lost,
+                                    display_stats.audio_frames_concealed
+                                ))
+                                .font(FontId::monospace(10.0))
+                                .color(Color32::DARK_GRAY)
+                            );
+                        }
+
+                        // Frame pacing
+                        if display_stats.frame_pacing {
+                            ui.label(
+                                RichText::new(format!(
+                                    "Pacing: {} shown, {} repeat, {} skip",
+                                    display_stats.frames_presented,
+                                    display_stats.frames_repeated,
+                                    display_stats.frames_skipped
+                                ))
+                                .font(FontId::monospace(10.0))
+                                .color(Color32::DARK_GRAY)
+                            );
+                        }
+
                         // GPU and server info
                         if !display_stats.gpu_type.is_empty() || !display_stats.server_region.is_empty() {
                             let info = format!(
@@ -246,3 +274,77 @@ impl Default for StatsPanel {
         Self::new()
     }
 }
+
+/// Size of one rolling graph in the stats overlay
+const GRAPH_SIZE: egui::Vec2 = egui::vec2(220.0, 26.0);
+
+fn graph_color(metric: StatsMetric) -> Color32 {
+    match metric {
+        StatsMetric::DecodeTime => Color32::from_rgb(118, 185, 0),
+        StatsMetric::RenderTime => Color32::from_rgb(80, 170, 255),
+        StatsMetric::FrameDelivery => Color32::from_rgb(255, 200, 60),
+        StatsMetric::Rtt => Color32::from_rgb(200, 130, 255),
+        StatsMetric::Jitter => Color32::from_rgb(255, 140, 80),
+        StatsMetric::Bitrate => Color32::from_rgb(90, 220, 200),
+        StatsMetric::AudioBuffer => Color32::from_rgb(180, 180, 180),
+    }
+}
+
+/// Render rolling graphs (last ~10s) with percentile readouts and spike markers
+pub fn render_stats_graphs(ui: &mut egui::Ui, graphs: &[MetricGraph]) {
+    if graphs.is_empty() {
+        return;
+    }
+
+    ui.add_space(4.0);
+    ui.separator();
+
+    let window = HISTORY_WINDOW.as_secs_f32();
+    for graph in graphs {
+        let p = graph.percentiles;
+        let color = graph_color(graph.metric);
+
+        ui.label(
+            RichText::new(format!(
+                "{:<9} {:>6.1} {:<4} p50 {:.1} p95 {:.1} p99 {:.1}",
+                graph.metric.label(),
+                graph.latest,
+                graph.metric.unit(),
+                p.p50,
+                p.p95,
+                p.p99
+            ))
+            .font(FontId::monospace(10.0))
+            .color(color)
+        );
+
+        let (rect, _) = ui.allocate_exact_size(GRAPH_SIZE, Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, Color32::from_rgba_unmultiplied(255, 255, 255, 12));
+
+        // Scale to the worst sample so spikes stay on screen
+        let y_max = (p.max * 1.1).max(1.0);
+        let to_screen = |[x, y]: [f32; 2]| {
+            egui::pos2(
+                rect.left() + (x + window) / window * rect.width(),
+                rect.bottom() - (y / y_max).clamp(0.0, 1.0) * rect.height(),
+            )
+        };
+
+        // p95 reference line
+        let p95_y = to_screen([0.0, p.p95]).y;
+        painter.line_segment(
+            [egui::pos2(rect.left(), p95_y), egui::pos2(rect.right(), p95_y)],
+            Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 40)),
+        );
+
+        let points: Vec<egui::Pos2> = graph.points.iter().map(|&pt| to_screen(pt)).collect();
+        if points.len() >= 2 {
+            painter.line(points, Stroke::new(1.0, color));
+        }
+
+        for &spike in &graph.spikes {
+            painter.circle_filled(to_screen(spike), 2.0, Color32::from_rgb(255, 70, 70));
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Video Upscaler
+//!
+//! Optional scaling passes between YUV->RGB conversion and the swapchain.
+//! When a filter other than bilinear is selected and the stream is drawn
+//! larger than its resolution, or the scaling mode crops it, the video
+//! pipelines render into an intermediate target at the stream's resolution
+//! and one of the passes in `UPSCALE_SHADER` scales it into the viewport.
+//! Works the same for every video path (CPU upload, NV12/P010 and external
+//! textures) since it only sees RGB.
+
+use crate::app::config::UpscaleFilter;
+use crate::app::viewport::{ViewRect, Viewport};
+
+use super::shaders::UPSCALE_SHADER;
+
+/// Uniforms shared by all passes (matches `UpscaleParams` in the shader)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct UpscaleParams {
+    source_size: [f32; 2],
+    dest_origin: [f32; 2],
+    dest_size: [f32; 2],
+    strength: f32,
+    _pad: f32,
+}
+
+/// Render target sampled by a pass
+struct UpscaleTarget {
+    size: (u32, u32),
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+pub struct Upscaler {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    bilinear_pipeline: wgpu::RenderPipeline,
+    bicubic_pipeline: wgpu::RenderPipeline,
+    lanczos_pipeline: wgpu::RenderPipeline,
+    easu_pipeline: wgpu::RenderPipeline,
+    rcas_pipeline: wgpu::RenderPipeline,
+    /// Video at native resolution
+    source: Option<UpscaleTarget>,
+    /// EASU output at surface resolution, sharpened by RCAS
+    scaled: Option<UpscaleTarget>,
+}
+
+impl Upscaler {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Upscale Shader"),
+            source: wgpu::ShaderSource::Wgsl(UPSCALE_SHADER.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Upscale Bind Group Layout"),
+            entries: &[
+                // Source texture (read with textureLoad, no sampler)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // Params
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Upscale Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let create_pipeline = |label: &str, entry_point: &str| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview_mask: None,
+                cache: None,
+            })
+        };
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Upscale Params"),
+            size: std::mem::size_of::<UpscaleParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            format,
+            bilinear_pipeline: create_pipeline("Upscale Bilinear Pipeline", "fs_bilinear"),
+            bicubic_pipeline: create_pipeline("Upscale Bicubic Pipeline", "fs_bicubic"),
+            lanczos_pipeline: create_pipeline("Upscale Lanczos Pipeline", "fs_lanczos"),
+            easu_pipeline: create_pipeline("Upscale EASU Pipeline", "fs_easu"),
+            rcas_pipeline: create_pipeline("Upscale RCAS Pipeline", "fs_rcas"),
+            bind_group_layout,
+            params_buffer,
+            source: None,
+            scaled: None,
+        }
+    }
+
+    /// Whether `filter` needs the extra passes to draw a `source` sized
+    /// stream into `dest`. Downscaling and 1:1 stay on the direct path.
+    pub fn is_active(filter: UpscaleFilter, source: (u32, u32), dest: &ViewRect) -> bool {
+        filter != UpscaleFilter::Bilinear
+            && source.0 > 0
+            && source.1 > 0
+            && (dest.width > source.0 as f32 || dest.height > source.1 as f32)
+    }
+
+    /// Size the targets and upload params for this frame.
+    /// Returns the view the video should be rendered into.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        filter: UpscaleFilter,
+        strength: f32,
+        viewport: &Viewport,
+    ) -> wgpu::TextureView {
+        let source = match self.source.take() {
+            Some(target) if target.size == viewport.video => target,
+            _ => self.create_target(device, "Upscale Source", viewport.video),
+        };
+        let source_view = source.view.clone();
+        self.source = Some(source);
+
+        self.scaled = match self.scaled.take() {
+            _ if filter != UpscaleFilter::Fsr => None,
+            Some(target) if target.size == viewport.window => Some(target),
+            _ => Some(self.create_target(device, "Upscale EASU Output", viewport.window)),
+        };
+
+        let params = UpscaleParams {
+            source_size: [viewport.video.0 as f32, viewport.video.1 as f32],
+            dest_origin: [viewport.rect.x, viewport.rect.y],
+            dest_size: [viewport.rect.width, viewport.rect.height],
+            strength: strength.clamp(0.0, 1.0),
+            _pad: 0.0,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        source_view
+    }
+
+    /// Scale the prepared source into the `visible` part of the viewport on `output`
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        filter: UpscaleFilter,
+        output: &wgpu::TextureView,
+        visible: &ViewRect,
+    ) {
+        let Some(source) = self.source.as_ref() else {
+            return;
+        };
+
+        let pipeline = match filter {
+            UpscaleFilter::Bilinear => &self.bilinear_pipeline,
+            UpscaleFilter::Bicubic => &self.bicubic_pipeline,
+            UpscaleFilter::Lanczos => &self.lanczos_pipeline,
+            UpscaleFilter::Fsr => {
+                // EASU to the intermediate, then RCAS sharpens into the output
+                let Some(scaled) = self.scaled.as_ref() else {
+                    return;
+                };
+                Self::draw(encoder, &self.easu_pipeline, source, &scaled.view, visible);
+                Self::draw(encoder, &self.rcas_pipeline, scaled, output, visible);
+                return;
+            }
+        };
+        Self::draw(encoder, pipeline, source, output, visible);
+    }
+
+    /// Drop intermediate targets (e.g. when streaming ends)
+    pub fn release(&mut self) {
+        self.source = None;
+        self.scaled = None;
+    }
+
+    fn draw(
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::RenderPipeline,
+        input: &UpscaleTarget,
+        output: &wgpu::TextureView,
+        visible: &ViewRect,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Upscale Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            ..Default::default()
+        });
+
+        render_pass.set_viewport(
+            visible.x,
+            visible.y,
+            visible.width,
+            visible.height,
+            0.0,
+            1.0,
+        );
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &input.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+
+    fn create_target(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        (width, height): (u32, u32),
+    ) -> UpscaleTarget {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        UpscaleTarget {
+            size: (width, height),
+            _texture: texture,
+            view,
+            bind_group,
+        }
+    }
+}
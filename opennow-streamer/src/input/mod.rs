@@ -129,6 +129,7 @@ use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use tokio::sync::mpsc;
 use winit::event::{ElementState, MouseButton};
 
+use crate::app::viewport::Viewport;
 use crate::webrtc::{InputEncoder, InputEvent, MAX_CLIPBOARD_PASTE_SIZE};
 
 /// Mouse event coalescing interval in microseconds
@@ -356,9 +357,12 @@ pub struct InputHandler {
     accumulated_dx: AtomicI32,
     accumulated_dy: AtomicI32,
 
-    /// Last known cursor position
+    /// Last known cursor position (stream pixels)
     last_x: AtomicI32,
     last_y: AtomicI32,
+
+    /// Stream placement in the window, for mapping cursor positions
+    viewport: Mutex<Option<Viewport>>,
 }
 
 impl InputHandler {
@@ -376,6 +380,7 @@ impl InputHandler {
             accumulated_dy: AtomicI32::new(0),
             last_x: AtomicI32::new(0),
             last_y: AtomicI32::new(0),
+            viewport: Mutex::new(None),
         }
     }
 
@@ -401,6 +406,11 @@ impl InputHandler {
         self.local_cursor.set_active(true);
     }
 
+    /// Update the stream's placement in the window (call from the renderer)
+    pub fn set_viewport(&self, viewport: Viewport) {
+        *self.viewport.lock() = Some(viewport);
+    }
+
     /// Update queue depth estimate (call from WebRTC layer)
     pub fn update_queue_depth(&self, depth: u64) {
         self.queue_depth.store(depth, Ordering::Release);
@@ -450,6 +460,11 @@ impl InputHandler {
             return;
         }
 
+        // Map the window position into stream pixels through the scaling mode
+        let (x, y) = match *self.viewport.lock() {
+            Some(viewport) => viewport.window_to_stream(x, y),
+            None => (x, y),
+        };
         let x = x as i32;
         let y = y as i32;
 
@@ -462,7 +477,7 @@ impl InputHandler {
 
             if dx != 0 || dy != 0 {
                 // Update local cursor for instant feedback
-                self.local_cursor.apply_delta(dx, dy);
+                self.local_cursor.set_position(x, y);
 
                 // Use coalescer for network events
                 if let Some((cdx, cdy, timestamp_us)) = self.mouse_coalescer.accumulate(dx, dy) {
@@ -27,7 +27,7 @@ use winit::event::{DeviceEvent, DeviceId, ElementState, KeyEvent, Modifiers, Win
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
 use winit::platform::scancode::PhysicalKeyExtScancode;
-use winit::window::WindowId;
+use winit::window::{UserAttentionType, WindowId};
 
 use app::{App, AppState, UiAction};
 use gui::Renderer;
@@ -44,6 +44,8 @@ struct OpenNowApp {
     modifiers: Modifiers,
     /// Track if we were streaming (for cursor lock state changes)
     was_streaming: bool,
+    /// Last window title set (queue progress)
+    window_title: String,
 }
 
 /// Convert winit KeyCode to Windows Virtual Key code
@@ -175,6 +177,7 @@ impl OpenNowApp {
             renderer: None,
             modifiers: Modifiers::default(),
             was_streaming: false,
+            window_title: "OpenNow".to_string(),
         }
     }
 
@@ -304,6 +307,19 @@ impl ApplicationHandler for OpenNowApp {
                     }
                 }
             }
+            // Shift+F3 to toggle stats graphs
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: Key::Named(NamedKey::F3),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                app.toggle_stats_graphs();
+            }
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
@@ -331,6 +347,85 @@ impl ApplicationHandler for OpenNowApp {
                     app.toggle_anti_afk();
                 }
             }
+            // Ctrl+Shift+R to start/stop recording the stream
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyR),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().control_key() && self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                app.toggle_recording();
+            }
+            // Ctrl+Shift+S to save the instant replay buffer
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyS),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().control_key() && self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                app.save_replay();
+            }
+            // Ctrl+Shift+P to take a screenshot
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyP),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().control_key() && self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                app.request_screenshot();
+            }
+            // Ctrl+Shift+A to cycle stream scaling modes
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyA),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().control_key() && self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                app.cycle_scaling_mode();
+            }
+            // Ctrl+Shift+C to toggle the colour adjustment overlay
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyC),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.state().control_key() && self.modifiers.state().shift_key() => {
+                let mut app = self.app.lock();
+                if app.state == AppState::Streaming {
+                    app.toggle_color_overlay();
+                    // The sliders need a free cursor
+                    if app.show_color_overlay && app.cursor_captured {
+                        app.cursor_captured = false;
+                        renderer.unlock_cursor();
+                        #[cfg(any(target_os = "windows", target_os = "macos"))]
+                        input::pause_raw_input();
+                    }
+                }
+            }
             // F8 to toggle mouse lock during streaming (for windowed mode)
             WindowEvent::KeyboardInput {
                 event:
@@ -479,7 +574,19 @@ impl ApplicationHandler for OpenNowApp {
                 if is_streaming && !self.was_streaming {
                     // Just started streaming - lock cursor, start raw input, disable vsync
                     renderer.lock_cursor();
-                    renderer.set_vsync(false); // Immediate mode for lowest latency
+                    // Immediate mode for lowest latency; frame pacing needs vsync to
+                    // present on refresh boundaries
+                    let pacing = app_guard.frame_pacer.is_some();
+                    renderer.set_vsync(pacing);
+                    if let Some(ref mut pacer) = app_guard.frame_pacer {
+                        if let Some(mhz) = renderer
+                            .window()
+                            .current_monitor()
+                            .and_then(|m| m.refresh_rate_millihertz())
+                        {
+                            pacer.set_refresh_rate(mhz as f64 / 1000.0);
+                        }
+                    }
                     self.was_streaming = true;
 
                     // Start Raw Input for unaccelerated mouse movement (Windows/macOS)
@@ -508,8 +615,25 @@ impl ApplicationHandler for OpenNowApp {
 
                 app_guard.update();
 
+                // Queue position in the title so it shows in the taskbar
+                let title = app_guard.window_title();
+                if title != self.window_title {
+                    renderer.window().set_title(&title);
+                    self.window_title = title;
+                }
+                if app_guard.take_attention_request() {
+                    renderer
+                        .window()
+                        .request_user_attention(Some(UserAttentionType::Informational));
+                }
+
+                let render_start = std::time::Instant::now();
                 match renderer.render(&app_guard) {
                     Ok((actions, repaint_after)) => {
+                        if is_streaming {
+                            app_guard.on_frame_presented(render_start.elapsed());
+                        }
+
                         // Apply UI actions to app state
                         for action in actions {
                             app_guard.handle_action(action);
@@ -597,6 +721,7 @@ impl ApplicationHandler for OpenNowApp {
             .as_ref()
             .map(|sf| sf.has_new_frame())
             .unwrap_or(false);
+        let pacing = app_guard.frame_pacer.is_some();
         drop(app_guard);
 
         // Dynamically switch control flow based on app state
@@ -606,7 +731,8 @@ impl ApplicationHandler for OpenNowApp {
                 _event_loop.set_control_flow(ControlFlow::Poll);
                 // Only request redraw when decoder has produced a new frame
                 // This synchronizes render rate to decode rate, avoiding wasted GPU cycles
-                if has_new_frame {
+                // Frame pacing redraws every refresh (vsync blocks) to repeat or advance
+                if has_new_frame || pacing {
                     renderer.window().request_redraw();
                 }
             }
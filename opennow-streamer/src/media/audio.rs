@@ -2,17 +2,27 @@
 //!
 //! Decode Opus audio and play through cpal.
 //! - macOS: Uses FFmpeg for Opus decoding
-//! - Linux/Windows: Uses GStreamer for Opus decoding
+//! - Linux/Windows x64: Uses GStreamer for Opus decoding
+//! - Built-in: libopus compiled into the binary, used when the above is unavailable
+//! - Neither: the stream plays on with silent audio
 //! Optimized for low-latency streaming with jitter buffer.
 //! Supports dynamic device switching and sample rate conversion.
+//! Lost RTP packets are concealed (Opus PLC/in-band FEC on GStreamer,
+//! faded repetition of the last frame on FFmpeg).
 
 use anyhow::{anyhow, Context, Result};
 use log::{debug, error, info, warn};
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
+
+#[cfg(feature = "builtin-opus")]
+use super::opus::OpusDecoder;
+use crate::app::config::AudioDecoderBackend;
 
 // ============================================================================
 // macOS: FFmpeg-based Opus decoder
@@ -34,14 +44,360 @@ pub struct AudioDecoder {
     sample_rx: Option<tokio::sync::mpsc::Receiver<Vec<i16>>>,
     sample_rate: u32,
     channels: u32,
+    /// Frames synthesized to cover lost packets
+    concealed_frames: Arc<AtomicU64>,
 }
 
 enum AudioCommand {
     /// Decode audio and send result to channel
-    DecodeAsync(Vec<u8>),
+    DecodeAsync {
+        data: Vec<u8>,
+        rtp_timestamp: u32,
+    },
+    /// Packets were lost before the next DecodeAsync
+    Conceal(u16),
     Stop,
 }
 
+/// Longest gap (in packets) that is concealed; beyond ~100ms PLC output is
+/// just decaying noise and the jitter buffer absorbs the rest
+const MAX_CONCEALED_PACKETS: u16 = 5;
+
+/// Opus frame duration assumed until the first packet has been parsed
+#[cfg(any(target_os = "linux", all(windows, target_arch = "x86_64")))]
+const DEFAULT_OPUS_FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// RTP clock rate for Opus payloads (RFC 7587), whatever the output rate
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+const OPUS_RTP_CLOCK: u64 = 48_000;
+
+/// Sequence jumps larger than this are a stream restart, not loss
+const MAX_SEQUENCE_GAP: u16 = 1000;
+
+/// Detects lost packets from RTP sequence numbers
+#[derive(Debug, Default)]
+pub struct RtpSequenceTracker {
+    last_sequence: Option<u16>,
+}
+
+impl RtpSequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of packets lost before `sequence`
+    ///
+    /// Returns None for duplicate or late packets: their slot has already
+    /// been concealed, so they should be dropped rather than decoded.
+    pub fn update(&mut self, sequence: u16) -> Option<u16> {
+        let Some(last) = self.last_sequence else {
+            self.last_sequence = Some(sequence);
+            return Some(0);
+        };
+
+        let delta = sequence.wrapping_sub(last);
+        if delta == 0 || delta >= 0x8000 {
+            return None;
+        }
+
+        self.last_sequence = Some(sequence);
+        if delta > MAX_SEQUENCE_GAP {
+            debug!("RTP sequence jumped by {}, resyncing", delta);
+            return Some(0);
+        }
+        Some(delta - 1)
+    }
+}
+
+/// Opus presentation time from 48 kHz RTP timestamps
+///
+/// Extends timestamps across wraparound; packets reordered ahead of the
+/// first one are placed at zero.
+#[derive(Debug, Default)]
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+struct OpusRtpClock {
+    last: Option<u32>,
+    /// Ticks since the first packet
+    ticks: i64,
+}
+
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+impl OpusRtpClock {
+    /// Nanoseconds since the first packet
+    fn pts_ns(&mut self, rtp_timestamp: u32) -> u64 {
+        if let Some(last) = self.last {
+            self.ticks += rtp_timestamp.wrapping_sub(last) as i32 as i64;
+        }
+        self.last = Some(rtp_timestamp);
+        self.ticks.max(0) as u64 * 1_000_000_000 / OPUS_RTP_CLOCK
+    }
+}
+
+/// Duration of an Opus packet from its TOC byte (RFC 6716 section 3.1)
+pub fn opus_packet_duration(packet: &[u8]) -> Option<Duration> {
+    let toc = *packet.first()?;
+    let config = toc >> 3;
+    let frame_us: u64 = match config {
+        // SILK-only: 10, 20, 40, 60 ms
+        0..=11 => [10_000, 20_000, 40_000, 60_000][(config % 4) as usize],
+        // Hybrid: 10, 20 ms
+        12..=15 => [10_000, 20_000][(config % 2) as usize],
+        // CELT-only: 2.5, 5, 10, 20 ms
+        _ => [2_500, 5_000, 10_000, 20_000][(config % 4) as usize],
+    };
+    let frames: u64 = match toc & 0x03 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => (*packet.get(1)? & 0x3F) as u64,
+    };
+    if frames == 0 {
+        return None;
+    }
+    Some(Duration::from_micros(frame_us * frames))
+}
+
+/// Span (start, end) in nanoseconds to conceal before a packet at `pts`
+///
+/// The gap runs from where the previous packet ended (`expected`) to `pts`,
+/// trimmed to the last `lost` frames so a timestamp jump doesn't become a
+/// long stretch of PLC.
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+fn gap_span(expected: u64, pts: u64, frame_ns: u64, lost: u16) -> Option<(u64, u64)> {
+    let start = expected.max(pts.saturating_sub(frame_ns * lost as u64));
+    (start < pts).then_some((start, pts))
+}
+
+/// Output ranges the decoder was asked to conceal
+///
+/// Decoded buffers overlapping a range are counted as concealed frames, so
+/// the stat reflects what the decoder actually produced.
+#[derive(Debug, Default)]
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+struct ConcealedSpans {
+    /// (start, end, frame duration) in nanoseconds
+    spans: VecDeque<(u64, u64, u64)>,
+}
+
+#[cfg_attr(
+    not(any(target_os = "linux", all(windows, target_arch = "x86_64"))),
+    allow(dead_code)
+)]
+impl ConcealedSpans {
+    fn push(&mut self, start: u64, end: u64, frame_ns: u64) {
+        self.spans.push_back((start, end, frame_ns));
+    }
+
+    /// Concealed frames within an output buffer at `pts` lasting `duration`
+    fn frames_in(&mut self, pts: u64, duration: u64) -> u64 {
+        // Output is in order, so spans ending before this buffer are done
+        self.spans.retain(|&(_, end, _)| end > pts);
+        let buffer_end = pts + duration;
+        self.spans
+            .iter()
+            .map(|&(start, end, frame_ns)| {
+                let overlap = end.min(buffer_end).saturating_sub(start.max(pts));
+                (overlap + frame_ns / 2) / frame_ns
+            })
+            .sum()
+    }
+}
+
+/// Repeat the last decoded frame with a fade so a lost packet doesn't click
+///
+/// `index` is the position of this frame within the gap; each successive
+/// frame continues the fade from where the previous one ended.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn fade_concealment(last_frame: &[i16], channels: usize, index: u16, total: u16) -> Vec<i16> {
+    let channels = channels.max(1);
+    let frames = last_frame.len() / channels;
+    if frames == 0 || total == 0 {
+        return Vec::new();
+    }
+
+    let step = 1.0 / (total as f32 * frames as f32);
+    let start = 1.0 - index as f32 / total as f32;
+    last_frame
+        .chunks_exact(channels)
+        .enumerate()
+        .flat_map(|(i, frame)| {
+            let gain = (start - i as f32 * step).max(0.0);
+            frame.iter().map(move |&s| (s as f32 * gain) as i16)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Backend selection and built-in decoder (all platforms)
+// ============================================================================
+
+impl AudioDecoder {
+    /// Create an Opus decoder using the requested backend
+    /// Decoded samples are read from take_sample_receiver (async operation)
+    ///
+    /// Without a working decoder the stream continues with silent audio.
+    pub fn new(sample_rate: u32, channels: u32, backend: AudioDecoderBackend) -> Self {
+        let decoder = match backend {
+            AudioDecoderBackend::Platform => Self::new_platform(sample_rate, channels),
+            AudioDecoderBackend::Builtin => Self::new_builtin(sample_rate, channels),
+            AudioDecoderBackend::Auto => Self::new_platform(sample_rate, channels).or_else(|e| {
+                warn!("Platform audio decoder unavailable ({}), using built-in decoder", e);
+                Self::new_builtin(sample_rate, channels)
+            }),
+        };
+        decoder.unwrap_or_else(|e| {
+            warn!("No Opus decoder available ({}), audio will be silent", e);
+            Self::silent(sample_rate, channels)
+        })
+    }
+
+    /// Decoder that discards every packet
+    fn silent(sample_rate: u32, channels: u32) -> Self {
+        // Dropping both ends makes commands no-ops and ends the player's stream
+        let (cmd_tx, _cmd_rx) = mpsc::channel::<AudioCommand>();
+        let (_sample_tx, sample_rx) = tokio::sync::mpsc::channel::<Vec<i16>>(1);
+
+        Self {
+            cmd_tx,
+            sample_rx: Some(sample_rx),
+            sample_rate,
+            channels,
+            concealed_frames: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    #[cfg(not(feature = "builtin-opus"))]
+    fn new_builtin(_sample_rate: u32, _channels: u32) -> Result<Self> {
+        Err(anyhow!(
+            "Built-in Opus decoder not compiled in (enable the builtin-opus feature)"
+        ))
+    }
+
+    /// Create an in-process libopus decoder (no GStreamer/FFmpeg needed)
+    #[cfg(feature = "builtin-opus")]
+    fn new_builtin(sample_rate: u32, channels: u32) -> Result<Self> {
+        info!(
+            "Creating Opus audio decoder (built-in): {}Hz, {} channels",
+            sample_rate, channels
+        );
+
+        let mut decoder = OpusDecoder::new(sample_rate, channels)?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel::<AudioCommand>();
+        let (sample_tx, sample_rx) = tokio::sync::mpsc::channel::<Vec<i16>>(512);
+        let concealed_frames = Arc::new(AtomicU64::new(0));
+        let concealed_frames_clone = concealed_frames.clone();
+
+        thread::spawn(move || {
+            info!("Opus audio decoder initialized (built-in, async mode)");
+
+            // Loss is concealed when the next packet arrives so its in-band
+            // FEC data can recover the last lost frame
+            let mut pending_lost = 0u16;
+
+            while let Ok(cmd) = cmd_rx.recv() {
+                match cmd {
+                    AudioCommand::DecodeAsync { data, .. } => {
+                        if data.is_empty() {
+                            continue;
+                        }
+                        if pending_lost > 0 {
+                            match decoder.conceal(pending_lost, Some(&data)) {
+                                Ok(samples) => {
+                                    concealed_frames_clone
+                                        .fetch_add(pending_lost as u64, Ordering::Relaxed);
+                                    let _ = sample_tx.try_send(samples);
+                                }
+                                Err(e) => debug!("Opus concealment failed: {}", e),
+                            }
+                            pending_lost = 0;
+                        }
+                        match decoder.decode(&data) {
+                            Ok(samples) if !samples.is_empty() => {
+                                // Non-blocking send - drop samples if channel is full
+                                let _ = sample_tx.try_send(samples);
+                            }
+                            Ok(_) => {}
+                            Err(e) => debug!("Opus decode error: {}", e),
+                        }
+                    }
+                    AudioCommand::Conceal(lost) => {
+                        pending_lost = pending_lost.saturating_add(lost).min(MAX_CONCEALED_PACKETS);
+                    }
+                    AudioCommand::Stop => break,
+                }
+            }
+
+            debug!("Audio decoder thread stopped");
+        });
+
+        Ok(Self {
+            cmd_tx,
+            sample_rx: Some(sample_rx),
+            sample_rate,
+            channels,
+            concealed_frames,
+        })
+    }
+
+    /// Take the sample receiver (for passing to audio player thread)
+    pub fn take_sample_receiver(&mut self) -> Option<tokio::sync::mpsc::Receiver<Vec<i16>>> {
+        self.sample_rx.take()
+    }
+
+    /// Decode an Opus packet asynchronously (non-blocking, fire-and-forget)
+    /// Decoded samples are sent to the sample_rx channel; `rtp_timestamp`
+    /// places the packet (and any concealed gap before it) on the timeline
+    pub fn decode_async(&self, data: &[u8], rtp_timestamp: u32) {
+        let _ = self.cmd_tx.send(AudioCommand::DecodeAsync {
+            data: data.to_vec(),
+            rtp_timestamp,
+        });
+    }
+
+    /// Conceal packets lost before the next decode_async call
+    pub fn conceal(&self, lost_packets: u16) {
+        if lost_packets > 0 {
+            let _ = self.cmd_tx.send(AudioCommand::Conceal(lost_packets));
+        }
+    }
+
+    /// Get sample rate
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get channel count
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Total frames synthesized to cover lost packets
+    pub fn concealed_frames(&self) -> u64 {
+        self.concealed_frames.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for AudioDecoder {
+    fn drop(&mut self) {
+        let _ = self.cmd_tx.send(AudioCommand::Stop);
+    }
+}
+
 // ============================================================================
 // macOS implementation using FFmpeg
 // ============================================================================
@@ -49,8 +405,7 @@ enum AudioCommand {
 #[cfg(target_os = "macos")]
 impl AudioDecoder {
     /// Create a new Opus audio decoder using FFmpeg (macOS)
-    /// Returns decoder and a receiver for decoded samples (for async operation)
-    pub fn new(sample_rate: u32, channels: u32) -> Result<Self> {
+    fn new_platform(sample_rate: u32, channels: u32) -> Result<Self> {
         info!(
             "Creating Opus audio decoder (FFmpeg): {}Hz, {} channels",
             sample_rate, channels
@@ -63,6 +418,8 @@ impl AudioDecoder {
         let (cmd_tx, cmd_rx) = mpsc::channel::<AudioCommand>();
         // Async channel for decoded samples - large buffer to prevent blocking
         let (sample_tx, sample_rx) = tokio::sync::mpsc::channel::<Vec<i16>>(512);
+        let concealed_frames = Arc::new(AtomicU64::new(0));
+        let concealed_frames_clone = concealed_frames.clone();
 
         // Spawn decoder thread (FFmpeg types are not Send)
         let sample_rate_clone = sample_rate;
@@ -90,9 +447,12 @@ impl AudioDecoder {
 
             info!("Opus audio decoder initialized (FFmpeg, async mode)");
 
+            // Last decoded frame, repeated with a fade to cover lost packets
+            let mut last_frame: Vec<i16> = Vec::new();
+
             while let Ok(cmd) = cmd_rx.recv() {
                 match cmd {
-                    AudioCommand::DecodeAsync(data) => {
+                    AudioCommand::DecodeAsync { data, .. } => {
                         let samples = Self::decode_opus_packet(
                             &mut decoder,
                             &data,
@@ -100,10 +460,25 @@ impl AudioDecoder {
                             channels_clone,
                         );
                         if !samples.is_empty() {
+                            last_frame.clone_from(&samples);
                             // Non-blocking send - drop samples if channel is full
                             let _ = sample_tx.try_send(samples);
                         }
                     }
+                    AudioCommand::Conceal(lost) => {
+                        // FFmpeg's Opus decoder has no PLC entry point
+                        let count = lost.min(MAX_CONCEALED_PACKETS);
+                        for i in 0..count {
+                            let samples =
+                                fade_concealment(&last_frame, channels_clone as usize, i, count);
+                            if samples.is_empty() {
+                                break;
+                            }
+                            concealed_frames_clone.fetch_add(1, Ordering::Relaxed);
+                            let _ = sample_tx.try_send(samples);
+                        }
+                        last_frame.clear();
+                    }
                     AudioCommand::Stop => break,
                 }
             }
@@ -116,14 +491,10 @@ impl AudioDecoder {
             sample_rx: Some(sample_rx),
             sample_rate,
             channels,
+            concealed_frames,
         })
     }
 
-    /// Take the sample receiver (for passing to audio player thread)
-    pub fn take_sample_receiver(&mut self) -> Option<tokio::sync::mpsc::Receiver<Vec<i16>>> {
-        self.sample_rx.take()
-    }
-
     /// Decode an Opus packet from RTP payload
     fn decode_opus_packet(
         decoder: &mut decoder::Audio,
@@ -240,30 +611,8 @@ impl AudioDecoder {
 
         output
     }
-
-    /// Decode an Opus packet asynchronously (non-blocking, fire-and-forget)
-    /// Decoded samples are sent to the sample_rx channel
-    pub fn decode_async(&self, data: &[u8]) {
-        let _ = self.cmd_tx.send(AudioCommand::DecodeAsync(data.to_vec()));
-    }
-
-    /// Get sample rate
-    pub fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
-
-    /// Get channel count
-    pub fn channels(&self) -> u32 {
-        self.channels
-    }
 }
 
-#[cfg(target_os = "macos")]
-impl Drop for AudioDecoder {
-    fn drop(&mut self) {
-        let _ = self.cmd_tx.send(AudioCommand::Stop);
-    }
-}
 
 // ============================================================================
 // Linux/Windows x64 implementation using GStreamer
@@ -272,8 +621,7 @@ impl Drop for AudioDecoder {
 #[cfg(any(target_os = "linux", all(windows, target_arch = "x86_64")))]
 impl AudioDecoder {
     /// Create a new Opus audio decoder using GStreamer (Linux/Windows x64)
-    /// Returns decoder and a receiver for decoded samples (for async operation)
-    pub fn new(sample_rate: u32, channels: u32) -> Result<Self> {
+    fn new_platform(sample_rate: u32, channels: u32) -> Result<Self> {
         use gstreamer as gst;
         use gstreamer::prelude::*;
         use gstreamer_app as gst_app;
@@ -286,10 +634,22 @@ impl AudioDecoder {
         // Initialize GStreamer (uses bundled runtime on Windows)
         super::init_gstreamer()?;
 
+        // Minimal installs often lack the Opus plugins; fail here so the
+        // caller can fall back to the built-in decoder
+        for element in ["opusparse", "opusdec"] {
+            if gst::ElementFactory::find(element).is_none() {
+                return Err(anyhow!("GStreamer element '{}' not available", element));
+            }
+        }
+
         // Create channels for thread communication
         let (cmd_tx, cmd_rx) = mpsc::channel::<AudioCommand>();
         // Async channel for decoded samples - large buffer to prevent blocking
         let (sample_tx, sample_rx) = tokio::sync::mpsc::channel::<Vec<i16>>(512);
+        let concealed_frames = Arc::new(AtomicU64::new(0));
+        let concealed_frames_clone = concealed_frames.clone();
+        let concealed_spans = Arc::new(Mutex::new(ConcealedSpans::default()));
+        let concealed_spans_clone = concealed_spans.clone();
 
         let sample_rate_clone = sample_rate;
         let channels_clone = channels;
@@ -298,10 +658,11 @@ impl AudioDecoder {
             // Build GStreamer pipeline for Opus decoding
             // Use opusparse to properly frame raw Opus packets from WebRTC
             // The pipeline: appsrc -> opusparse -> opusdec -> audioconvert -> audioresample -> appsink
+            // Buffers are timestamped from RTP so GAP events line up with the loss
             let pipeline_str = format!(
-                "appsrc name=src format=time do-timestamp=true ! \
+                "appsrc name=src format=time ! \
                  opusparse ! \
-                 opusdec plc=true ! \
+                 opusdec plc=true use-inband-fec=true ! \
                  audioconvert ! \
                  audioresample ! \
                  audio/x-raw,format=S16LE,rate={},channels={} ! \
@@ -346,13 +707,22 @@ impl AudioDecoder {
 
             // Set up appsink callback
             let sample_tx_clone = sample_tx.clone();
-            use std::sync::atomic::{AtomicU64, Ordering};
             static DECODED_SAMPLE_COUNT: AtomicU64 = AtomicU64::new(0);
             appsink.set_callbacks(
                 gst_app::AppSinkCallbacks::builder()
                     .new_sample(move |sink| {
                         if let Ok(sample) = sink.pull_sample() {
                             if let Some(buffer) = sample.buffer() {
+                                if let (Some(pts), Some(duration)) =
+                                    (buffer.pts(), buffer.duration())
+                                {
+                                    let frames = concealed_spans_clone
+                                        .lock()
+                                        .frames_in(pts.nseconds(), duration.nseconds());
+                                    if frames > 0 {
+                                        concealed_frames_clone.fetch_add(frames, Ordering::Relaxed);
+                                    }
+                                }
                                 if let Ok(map) = buffer.map_readable() {
                                     // Convert bytes to i16 samples
                                     let bytes = map.as_slice();
@@ -414,13 +784,59 @@ impl AudioDecoder {
             info!("Opus audio decoder initialized (GStreamer, async mode)");
 
             let mut packets_pushed = 0u64;
+            let mut frame_duration = DEFAULT_OPUS_FRAME_DURATION;
+            let mut rtp_clock = OpusRtpClock::default();
+            // Where the stream continues if nothing is lost (ns)
+            let mut expected_pts = None;
+            // Loss is concealed when the next packet arrives, from the end of
+            // the last packet up to it, so in-band FEC can recover a frame
+            let mut pending_lost = 0u16;
             while let Ok(cmd) = cmd_rx.recv() {
                 match cmd {
-                    AudioCommand::DecodeAsync(data) => {
+                    AudioCommand::DecodeAsync {
+                        data,
+                        rtp_timestamp,
+                    } => {
                         if !data.is_empty() {
                             let data_len = data.len();
+                            if let Some(duration) = opus_packet_duration(&data) {
+                                frame_duration = duration;
+                            }
+                            let frame_ns = frame_duration.as_nanos() as u64;
+                            let pts = rtp_clock.pts_ns(rtp_timestamp);
+
+                            if pending_lost > 0 {
+                                // A GAP event ahead of the buffer makes opusdec run
+                                // PLC over the missing span, or recover the first
+                                // lost frame from this packet's in-band FEC data
+                                let span = expected_pts.and_then(|expected| {
+                                    gap_span(expected, pts, frame_ns, pending_lost)
+                                });
+                                if let Some((start, end)) = span {
+                                    let position = gst::ClockTime::from_nseconds(start);
+                                    let duration = gst::ClockTime::from_nseconds(end - start);
+                                    let gap = gst::event::Gap::builder(position)
+                                        .duration(duration)
+                                        .build();
+                                    if appsrc.send_event(gap) {
+                                        concealed_spans.lock().push(start, end, frame_ns);
+                                    } else {
+                                        debug!(
+                                            "Audio GAP event not handled ({} packets lost)",
+                                            pending_lost
+                                        );
+                                    }
+                                }
+                                pending_lost = 0;
+                            }
+                            expected_pts = Some(pts + frame_ns);
+
                             // Push Opus packet to GStreamer pipeline
-                            let buffer = gst::Buffer::from_slice(data);
+                            let mut buffer = gst::Buffer::from_slice(data);
+                            if let Some(buffer) = buffer.get_mut() {
+                                buffer.set_pts(gst::ClockTime::from_nseconds(pts));
+                                buffer.set_duration(gst::ClockTime::from_nseconds(frame_ns));
+                            }
                             match appsrc.push_buffer(buffer) {
                                 Ok(_) => {
                                     packets_pushed += 1;
@@ -436,6 +852,9 @@ impl AudioDecoder {
                             }
                         }
                     }
+                    AudioCommand::Conceal(lost) => {
+                        pending_lost = pending_lost.saturating_add(lost).min(MAX_CONCEALED_PACKETS);
+                    }
                     AudioCommand::Stop => break,
                 }
             }
@@ -451,89 +870,24 @@ impl AudioDecoder {
             sample_rx: Some(sample_rx),
             sample_rate,
             channels,
+            concealed_frames,
         })
     }
-
-    /// Take the sample receiver (for passing to audio player thread)
-    pub fn take_sample_receiver(&mut self) -> Option<tokio::sync::mpsc::Receiver<Vec<i16>>> {
-        self.sample_rx.take()
-    }
-
-    /// Decode an Opus packet asynchronously (non-blocking, fire-and-forget)
-    /// Decoded samples are sent to the sample_rx channel
-    pub fn decode_async(&self, data: &[u8]) {
-        let _ = self.cmd_tx.send(AudioCommand::DecodeAsync(data.to_vec()));
-    }
-
-    /// Get sample rate
-    pub fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
-
-    /// Get channel count
-    pub fn channels(&self) -> u32 {
-        self.channels
-    }
 }
 
-#[cfg(any(target_os = "linux", all(windows, target_arch = "x86_64")))]
-impl Drop for AudioDecoder {
-    fn drop(&mut self) {
-        let _ = self.cmd_tx.send(AudioCommand::Stop);
-    }
-}
 
 // ============================================================================
-// Windows ARM64 - No audio decoding (GStreamer not available)
+// Windows ARM64 - no platform decoder (GStreamer not available)
 // ============================================================================
 
 #[cfg(all(windows, target_arch = "aarch64"))]
 impl AudioDecoder {
-    /// Create a stub audio decoder for Windows ARM64
-    /// Note: GStreamer ARM64 binaries are not available, so audio is disabled
-    pub fn new(sample_rate: u32, channels: u32) -> Result<Self> {
-        warn!(
-            "Audio decoding not available on Windows ARM64 (GStreamer not available). \
-             Audio will be silent. Sample rate: {}Hz, channels: {}",
-            sample_rate, channels
-        );
-
-        let (cmd_tx, _cmd_rx) = mpsc::channel::<AudioCommand>();
-        let (_sample_tx, sample_rx) = tokio::sync::mpsc::channel::<Vec<i16>>(1);
-
-        Ok(Self {
-            cmd_tx,
-            sample_rx: Some(sample_rx),
-            sample_rate,
-            channels,
-        })
-    }
-
-    /// Take the sample receiver (for passing to audio player thread)
-    pub fn take_sample_receiver(&mut self) -> Option<tokio::sync::mpsc::Receiver<Vec<i16>>> {
-        self.sample_rx.take()
-    }
-
-    /// Decode an Opus packet asynchronously - stub that does nothing on ARM64
-    pub fn decode_async(&self, _data: &[u8]) {
-        // No-op: audio not supported on Windows ARM64
-    }
-
-    /// Get sample rate
-    pub fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
-
-    /// Get channel count
-    pub fn channels(&self) -> u32 {
-        self.channels
-    }
-}
-
-#[cfg(all(windows, target_arch = "aarch64"))]
-impl Drop for AudioDecoder {
-    fn drop(&mut self) {
-        let _ = self.cmd_tx.send(AudioCommand::Stop);
+    /// GStreamer ARM64 binaries are not available; audio needs the built-in
+    /// decoder (builtin-opus feature)
+    fn new_platform(_sample_rate: u32, _channels: u32) -> Result<Self> {
+        Err(anyhow!(
+            "No platform audio decoder on Windows ARM64 (GStreamer not available)"
+        ))
     }
 }
 
@@ -1073,6 +1427,12 @@ impl AudioPlayer {
         self.buffer.available()
     }
 
+    /// Buffered audio in milliseconds
+    pub fn buffer_ms(&self) -> f32 {
+        let samples_per_sec = self.output_sample_rate * self.output_channels.max(1);
+        self.buffer_available() as f32 * 1000.0 / samples_per_sec.max(1) as f32
+    }
+
     /// Get output sample rate (device rate)
     pub fn sample_rate(&self) -> u32 {
         self.output_sample_rate
@@ -1244,3 +1604,90 @@ impl AudioPlayer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_tracker_detects_gaps() {
+        let mut tracker = RtpSequenceTracker::new();
+        assert_eq!(tracker.update(100), Some(0));
+        assert_eq!(tracker.update(101), Some(0));
+        assert_eq!(tracker.update(104), Some(2));
+        // Late packet from the gap is dropped
+        assert_eq!(tracker.update(102), None);
+        assert_eq!(tracker.update(104), None);
+        assert_eq!(tracker.update(105), Some(0));
+    }
+
+    #[test]
+    fn test_sequence_tracker_wraparound_and_resync() {
+        let mut tracker = RtpSequenceTracker::new();
+        tracker.update(65534);
+        assert_eq!(tracker.update(1), Some(2));
+        assert_eq!(tracker.update(20_000), Some(0));
+        assert_eq!(tracker.update(20_001), Some(0));
+    }
+
+    #[test]
+    fn test_opus_packet_duration() {
+        // CELT-only fullband 20ms, one frame (WebRTC default)
+        assert_eq!(opus_packet_duration(&[0xF8, 0x00]), Some(Duration::from_millis(20)));
+        // SILK 10ms, two frames
+        assert_eq!(opus_packet_duration(&[0x01]), Some(Duration::from_millis(20)));
+        // CELT 2.5ms, code 3 with 4 frames
+        assert_eq!(opus_packet_duration(&[0x83, 0x04]), Some(Duration::from_micros(10_000)));
+        assert_eq!(opus_packet_duration(&[]), None);
+        assert_eq!(opus_packet_duration(&[0x03]), None);
+    }
+
+    #[test]
+    fn test_fade_concealment_decays_to_silence() {
+        let last = vec![1000i16; 960 * 2];
+        let first = fade_concealment(&last, 2, 0, 2);
+        let second = fade_concealment(&last, 2, 1, 2);
+        assert_eq!(first.len(), last.len());
+        assert_eq!(first[0], 1000);
+        assert!(first[first.len() - 1] >= second[0]);
+        assert!(second[second.len() - 1].abs() <= 2);
+    }
+
+    #[test]
+    fn test_opus_clock_extends_across_wraparound() {
+        let mut clock = OpusRtpClock::default();
+        assert_eq!(clock.pts_ns(u32::MAX - 959), 0);
+        // 20ms later, past the wrap
+        assert_eq!(clock.pts_ns(0), 20_000_000);
+        assert_eq!(clock.pts_ns(960), 40_000_000);
+        // Reordered packet from before the first one
+        assert_eq!(clock.pts_ns(u32::MAX - 1919), 0);
+        assert_eq!(clock.pts_ns(1920), 60_000_000);
+    }
+
+    #[test]
+    fn test_gap_span_from_expected_timestamp() {
+        const FRAME: u64 = 20_000_000;
+        // Two packets lost after one ending at 40ms; next arrives at 80ms
+        assert_eq!(gap_span(2 * FRAME, 4 * FRAME, FRAME, 2), Some((2 * FRAME, 4 * FRAME)));
+        // Timestamp jumped further than the loss covers
+        assert_eq!(gap_span(2 * FRAME, 10 * FRAME, FRAME, 2), Some((8 * FRAME, 10 * FRAME)));
+        // Nothing missing on the timeline
+        assert_eq!(gap_span(4 * FRAME, 4 * FRAME, FRAME, 1), None);
+    }
+
+    #[test]
+    fn test_concealed_spans_count_decoder_output() {
+        const FRAME: u64 = 20_000_000;
+        let mut spans = ConcealedSpans::default();
+        spans.push(2 * FRAME, 4 * FRAME, FRAME);
+
+        let per_frame: Vec<u64> = (1..6).map(|i| spans.frames_in(i * FRAME, FRAME)).collect();
+        assert_eq!(per_frame, [0, 1, 1, 0, 0]);
+        assert!(spans.spans.is_empty());
+
+        // One PLC buffer covering the whole gap
+        spans.push(6 * FRAME, 9 * FRAME, FRAME);
+        assert_eq!(spans.frames_in(6 * FRAME, 3 * FRAME), 3);
+    }
+}
@@ -0,0 +1,896 @@
+//! AV1 OBU Parser
+//!
+//! Parses the sequence header (profile, colour config, max frame size),
+//! HDR metadata OBUs and the start of frame headers so 10-bit/HDR streams
+//! can be detected before frames reach the decoder.
+//!
+//! Based on the AV1 Bitstream & Decoding Process Specification.
+
+use anyhow::{anyhow, Result};
+use log::debug;
+
+use super::hevc_parser::BitReader;
+use super::{ColorRange, ColorSpace, TransferFunction};
+
+/// AV1 OBU types (AV1 spec section 6.2.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Av1ObuType {
+    SequenceHeader = 1,
+    TemporalDelimiter = 2,
+    FrameHeader = 3,
+    TileGroup = 4,
+    Metadata = 5,
+    Frame = 6,
+    RedundantFrameHeader = 7,
+    TileList = 8,
+    Padding = 15,
+    Reserved = 255,
+}
+
+impl From<u8> for Av1ObuType {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => Self::SequenceHeader,
+            2 => Self::TemporalDelimiter,
+            3 => Self::FrameHeader,
+            4 => Self::TileGroup,
+            5 => Self::Metadata,
+            6 => Self::Frame,
+            7 => Self::RedundantFrameHeader,
+            8 => Self::TileList,
+            15 => Self::Padding,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+impl Av1ObuType {
+    /// Check if this OBU carries tile data (a decodable picture)
+    pub fn has_picture_data(&self) -> bool {
+        matches!(self, Self::TileGroup | Self::Frame)
+    }
+
+    /// Check if this OBU starts with a frame header
+    pub fn has_frame_header(&self) -> bool {
+        matches!(
+            self,
+            Self::FrameHeader | Self::Frame | Self::RedundantFrameHeader
+        )
+    }
+}
+
+/// A single OBU in a low-overhead bitstream
+#[derive(Debug, Clone, Copy)]
+pub struct Av1Obu<'a> {
+    pub obu_type: Av1ObuType,
+    pub temporal_id: u8,
+    pub spatial_id: u8,
+    /// Full OBU bytes (header, extension, size field and payload)
+    pub data: &'a [u8],
+    /// OBU payload only
+    pub payload: &'a [u8],
+}
+
+/// Split a low-overhead AV1 bitstream into OBUs
+///
+/// An OBU without a size field extends to the end of the data.
+pub fn parse_obus(data: &[u8]) -> Vec<Av1Obu<'_>> {
+    let mut obus = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let header = data[pos];
+        let obu_type = Av1ObuType::from((header >> 3) & 0x0F);
+        let has_extension = header & 0x04 != 0;
+        let has_size = header & 0x02 != 0;
+        let header_len = 1 + has_extension as usize;
+        if pos + header_len > data.len() {
+            break;
+        }
+        let (temporal_id, spatial_id) = if has_extension {
+            let ext = data[pos + 1];
+            (ext >> 5, (ext >> 3) & 0x03)
+        } else {
+            (0, 0)
+        };
+
+        let (payload_start, end) = if has_size {
+            let Some((size, leb_len)) = read_leb128(&data[pos + header_len..]) else {
+                break;
+            };
+            let start = pos + header_len + leb_len;
+            (start, start + size as usize)
+        } else {
+            (pos + header_len, data.len())
+        };
+        if end > data.len() {
+            break;
+        }
+
+        obus.push(Av1Obu {
+            obu_type,
+            temporal_id,
+            spatial_id,
+            data: &data[pos..end],
+            payload: &data[payload_start..end],
+        });
+        pos = end;
+    }
+
+    obus
+}
+
+/// Read a LEB128 value, returning (value, bytes read)
+pub fn read_leb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().take(8).enumerate() {
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Write a LEB128 value
+pub fn write_leb128(output: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let mut byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80; // More bytes follow
+        }
+        output.push(byte);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// color_config() (AV1 spec section 5.5.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Av1ColorConfig {
+    pub bit_depth: u8,
+    pub mono_chrome: bool,
+    /// Colour description (2 = unspecified)
+    pub color_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub full_range: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+    pub chroma_sample_position: u8,
+    pub separate_uv_delta_q: bool,
+}
+
+impl Default for Av1ColorConfig {
+    fn default() -> Self {
+        Self {
+            bit_depth: 8,
+            mono_chrome: false,
+            color_primaries: 2,
+            transfer_characteristics: 2,
+            matrix_coefficients: 2,
+            full_range: false,
+            subsampling_x: true,
+            subsampling_y: true,
+            chroma_sample_position: 0,
+            separate_uv_delta_q: false,
+        }
+    }
+}
+
+impl Av1ColorConfig {
+    pub fn color_range(&self) -> ColorRange {
+        if self.full_range {
+            ColorRange::Full
+        } else {
+            ColorRange::Limited
+        }
+    }
+
+    /// YUV matrix, if signalled
+    pub fn color_space(&self) -> Option<ColorSpace> {
+        match self.matrix_coefficients {
+            1 => Some(ColorSpace::BT709),
+            5 | 6 => Some(ColorSpace::BT601),
+            9 | 10 => Some(ColorSpace::BT2020),
+            _ => None,
+        }
+    }
+
+    pub fn transfer_function(&self) -> TransferFunction {
+        match self.transfer_characteristics {
+            16 => TransferFunction::PQ,
+            18 => TransferFunction::HLG,
+            _ => TransferFunction::SDR,
+        }
+    }
+}
+
+/// AV1 sequence header OBU
+#[derive(Debug, Clone, Default)]
+pub struct Av1SequenceHeader {
+    /// 0 = Main, 1 = High (4:4:4), 2 = Professional
+    pub seq_profile: u8,
+    pub still_picture: bool,
+    pub reduced_still_picture_header: bool,
+    pub timing_info_present: bool,
+    pub decoder_model_info_present: bool,
+    pub equal_picture_interval: bool,
+    pub frame_presentation_time_length: u8,
+    /// Level and tier of operating point 0
+    pub seq_level_idx: u8,
+    pub seq_tier: u8,
+    pub max_frame_width: u32,
+    pub max_frame_height: u32,
+    pub frame_id_numbers_present: bool,
+    pub use_128x128_superblock: bool,
+    pub enable_order_hint: bool,
+    pub order_hint_bits: u8,
+    pub enable_superres: bool,
+    pub enable_cdef: bool,
+    pub enable_restoration: bool,
+    pub color_config: Av1ColorConfig,
+    pub film_grain_params_present: bool,
+    /// Raw OBU (with header and size field) for decoder configuration
+    pub raw_data: Vec<u8>,
+}
+
+impl Av1SequenceHeader {
+    /// Parse a sequence header OBU payload
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let mut reader = BitReader::new(payload);
+        let mut seq = Self {
+            seq_profile: reader.read_bits(3)? as u8,
+            still_picture: reader.read_bit()? != 0,
+            reduced_still_picture_header: reader.read_bit()? != 0,
+            ..Default::default()
+        };
+        if seq.seq_profile > 2 {
+            return Err(anyhow!("Invalid AV1 seq_profile {}", seq.seq_profile));
+        }
+
+        if seq.reduced_still_picture_header {
+            seq.seq_level_idx = reader.read_bits(5)? as u8;
+        } else {
+            let mut buffer_delay_length = 0;
+            seq.timing_info_present = reader.read_bit()? != 0;
+            if seq.timing_info_present {
+                reader.skip_bits(64)?; // num_units_in_display_tick, time_scale
+                seq.equal_picture_interval = reader.read_bit()? != 0;
+                if seq.equal_picture_interval {
+                    read_uvlc(&mut reader)?; // num_ticks_per_picture_minus_1
+                }
+                seq.decoder_model_info_present = reader.read_bit()? != 0;
+                if seq.decoder_model_info_present {
+                    buffer_delay_length = reader.read_bits(5)? as u8 + 1;
+                    reader.skip_bits(32)?; // num_units_in_decoding_tick
+                    reader.skip_bits(5)?; // buffer_removal_time_length_minus_1
+                    seq.frame_presentation_time_length = reader.read_bits(5)? as u8 + 1;
+                }
+            }
+            let initial_display_delay_present = reader.read_bit()? != 0;
+            let operating_points_cnt = reader.read_bits(5)? + 1;
+            for i in 0..operating_points_cnt {
+                reader.skip_bits(12)?; // operating_point_idc
+                let level = reader.read_bits(5)? as u8;
+                let tier = if level > 7 { reader.read_bit()? } else { 0 };
+                if i == 0 {
+                    seq.seq_level_idx = level;
+                    seq.seq_tier = tier;
+                }
+                if seq.decoder_model_info_present {
+                    let decoder_model_present = reader.read_bit()? != 0;
+                    if decoder_model_present {
+                        // decoder_buffer_delay, encoder_buffer_delay, low_delay_mode_flag
+                        reader.skip_bits(buffer_delay_length as u32 * 2 + 1)?;
+                    }
+                }
+                if initial_display_delay_present {
+                    let present = reader.read_bit()? != 0;
+                    if present {
+                        reader.skip_bits(4)?; // initial_display_delay_minus_1
+                    }
+                }
+            }
+        }
+
+        let frame_width_bits = reader.read_bits(4)? as u8 + 1;
+        let frame_height_bits = reader.read_bits(4)? as u8 + 1;
+        seq.max_frame_width = reader.read_bits(frame_width_bits)? + 1;
+        seq.max_frame_height = reader.read_bits(frame_height_bits)? + 1;
+
+        if !seq.reduced_still_picture_header {
+            seq.frame_id_numbers_present = reader.read_bit()? != 0;
+        }
+        if seq.frame_id_numbers_present {
+            // delta_frame_id_length_minus_2, additional_frame_id_length_minus_1
+            reader.skip_bits(7)?;
+        }
+
+        seq.use_128x128_superblock = reader.read_bit()? != 0;
+        reader.skip_bits(2)?; // enable_filter_intra, enable_intra_edge_filter
+
+        if !seq.reduced_still_picture_header {
+            // enable_interintra_compound, enable_masked_compound,
+            // enable_warped_motion, enable_dual_filter
+            reader.skip_bits(4)?;
+            seq.enable_order_hint = reader.read_bit()? != 0;
+            if seq.enable_order_hint {
+                reader.skip_bits(2)?; // enable_jnt_comp, enable_ref_frame_mvs
+            }
+            let seq_choose_screen_content_tools = reader.read_bit()? != 0;
+            let seq_force_screen_content_tools = if seq_choose_screen_content_tools {
+                2 // SELECT_SCREEN_CONTENT_TOOLS
+            } else {
+                reader.read_bit()?
+            };
+            if seq_force_screen_content_tools > 0 {
+                let seq_choose_integer_mv = reader.read_bit()? != 0;
+                if !seq_choose_integer_mv {
+                    reader.skip_bits(1)?; // seq_force_integer_mv
+                }
+            }
+            if seq.enable_order_hint {
+                seq.order_hint_bits = reader.read_bits(3)? as u8 + 1;
+            }
+        }
+
+        seq.enable_superres = reader.read_bit()? != 0;
+        seq.enable_cdef = reader.read_bit()? != 0;
+        seq.enable_restoration = reader.read_bit()? != 0;
+        seq.color_config = Self::parse_color_config(&mut reader, seq.seq_profile)?;
+        seq.film_grain_params_present = reader.read_bit()? != 0;
+
+        Ok(seq)
+    }
+
+    fn parse_color_config(reader: &mut BitReader, seq_profile: u8) -> Result<Av1ColorConfig> {
+        let mut cc = Av1ColorConfig::default();
+
+        let high_bitdepth = reader.read_bit()? != 0;
+        cc.bit_depth = if seq_profile == 2 && high_bitdepth {
+            if reader.read_bit()? != 0 {
+                12
+            } else {
+                10
+            }
+        } else if high_bitdepth {
+            10
+        } else {
+            8
+        };
+
+        cc.mono_chrome = seq_profile != 1 && reader.read_bit()? != 0;
+
+        let color_description_present = reader.read_bit()? != 0;
+        if color_description_present {
+            cc.color_primaries = reader.read_bits(8)? as u8;
+            cc.transfer_characteristics = reader.read_bits(8)? as u8;
+            cc.matrix_coefficients = reader.read_bits(8)? as u8;
+        }
+
+        if cc.mono_chrome {
+            cc.full_range = reader.read_bit()? != 0;
+            return Ok(cc);
+        }
+
+        // sRGB (BT.709 primaries, sRGB transfer, identity matrix) is always 4:4:4 full range
+        if cc.color_primaries == 1
+            && cc.transfer_characteristics == 13
+            && cc.matrix_coefficients == 0
+        {
+            cc.full_range = true;
+            cc.subsampling_x = false;
+            cc.subsampling_y = false;
+        } else {
+            cc.full_range = reader.read_bit()? != 0;
+            match seq_profile {
+                0 => {}
+                1 => {
+                    cc.subsampling_x = false;
+                    cc.subsampling_y = false;
+                }
+                _ if cc.bit_depth == 12 => {
+                    cc.subsampling_x = reader.read_bit()? != 0;
+                    cc.subsampling_y = cc.subsampling_x && reader.read_bit()? != 0;
+                }
+                _ => cc.subsampling_y = false,
+            }
+            if cc.subsampling_x && cc.subsampling_y {
+                cc.chroma_sample_position = reader.read_bits(2)? as u8;
+            }
+        }
+        cc.separate_uv_delta_q = reader.read_bit()? != 0;
+
+        Ok(cc)
+    }
+
+    /// AV1CodecConfigurationRecord (AV1 ISOBMFF binding, section 2.3)
+    pub fn codec_config_record(&self) -> Vec<u8> {
+        let cc = &self.color_config;
+        let mut out = vec![
+            0x81, // marker + version 1
+            (self.seq_profile << 5) | (self.seq_level_idx & 0x1F),
+            (self.seq_tier << 7)
+                | (((cc.bit_depth > 8) as u8) << 6)
+                | (((cc.bit_depth == 12) as u8) << 5)
+                | ((cc.mono_chrome as u8) << 4)
+                | ((cc.subsampling_x as u8) << 3)
+                | ((cc.subsampling_y as u8) << 2)
+                | (cc.chroma_sample_position & 0x03),
+            0x00, // no initial_presentation_delay
+        ];
+        out.extend_from_slice(&self.raw_data);
+        out
+    }
+}
+
+/// Metadata OBU contents (AV1 spec section 5.8)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Av1Metadata {
+    /// Content light level in cd/m²
+    HdrCll {
+        max_cll: u16,
+        max_fall: u16,
+    },
+    /// Mastering display colour volume
+    /// Chromaticities in 0.16 fixed point, max luminance 24.8, min luminance 18.14
+    HdrMdcv {
+        primaries: [(u16, u16); 3],
+        white_point: (u16, u16),
+        luminance_max: u32,
+        luminance_min: u32,
+    },
+    Other {
+        metadata_type: u64,
+    },
+}
+
+impl Av1Metadata {
+    /// Parse a metadata OBU payload
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let (metadata_type, leb_len) =
+            read_leb128(payload).ok_or_else(|| anyhow!("Invalid metadata_type"))?;
+        let mut reader = BitReader::new(&payload[leb_len..]);
+
+        let metadata = match metadata_type {
+            1 => Self::HdrCll {
+                max_cll: reader.read_bits(16)? as u16,
+                max_fall: reader.read_bits(16)? as u16,
+            },
+            2 => {
+                let mut primaries = [(0u16, 0u16); 3];
+                for primary in &mut primaries {
+                    *primary = (reader.read_bits(16)? as u16, reader.read_bits(16)? as u16);
+                }
+                Self::HdrMdcv {
+                    primaries,
+                    white_point: (reader.read_bits(16)? as u16, reader.read_bits(16)? as u16),
+                    luminance_max: reader.read_bits(32)?,
+                    luminance_min: reader.read_bits(32)?,
+                }
+            }
+            _ => Self::Other { metadata_type },
+        };
+        Ok(metadata)
+    }
+}
+
+/// AV1 frame types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Av1FrameType {
+    #[default]
+    Key,
+    Inter,
+    IntraOnly,
+    Switch,
+}
+
+/// Start of an uncompressed frame header
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Av1FrameHeader {
+    pub show_existing_frame: bool,
+    /// Reference slot shown when show_existing_frame is set
+    pub frame_to_show_map_idx: u8,
+    pub frame_type: Av1FrameType,
+    pub show_frame: bool,
+    pub showable_frame: bool,
+    pub error_resilient_mode: bool,
+}
+
+impl Av1FrameHeader {
+    /// Parse the leading fields of a frame header OBU payload
+    ///
+    /// Without a sequence header the stream is assumed not to use
+    /// reduced still picture headers.
+    pub fn parse(payload: &[u8], seq: Option<&Av1SequenceHeader>) -> Result<Self> {
+        let mut reader = BitReader::new(payload);
+
+        if seq.is_some_and(|s| s.reduced_still_picture_header) {
+            return Ok(Self {
+                frame_type: Av1FrameType::Key,
+                show_frame: true,
+                error_resilient_mode: true,
+                ..Default::default()
+            });
+        }
+
+        let mut header = Self {
+            show_existing_frame: reader.read_bit()? != 0,
+            ..Default::default()
+        };
+        if header.show_existing_frame {
+            header.frame_to_show_map_idx = reader.read_bits(3)? as u8;
+            header.show_frame = true;
+            return Ok(header);
+        }
+
+        header.frame_type = match reader.read_bits(2)? {
+            0 => Av1FrameType::Key,
+            1 => Av1FrameType::Inter,
+            2 => Av1FrameType::IntraOnly,
+            _ => Av1FrameType::Switch,
+        };
+        header.show_frame = reader.read_bit()? != 0;
+        if header.show_frame {
+            if let Some(seq) = seq {
+                if seq.decoder_model_info_present && !seq.equal_picture_interval {
+                    // frame_presentation_time
+                    reader.skip_bits(seq.frame_presentation_time_length as u32)?;
+                }
+            }
+            header.showable_frame = header.frame_type != Av1FrameType::Key;
+        } else {
+            header.showable_frame = reader.read_bit()? != 0;
+        }
+
+        header.error_resilient_mode = if header.frame_type == Av1FrameType::Switch
+            || (header.frame_type == Av1FrameType::Key && header.show_frame)
+        {
+            true
+        } else {
+            reader.read_bit()? != 0
+        };
+
+        Ok(header)
+    }
+
+    /// A shown key frame starts a new decodable sequence
+    pub fn is_keyframe(&self) -> bool {
+        !self.show_existing_frame && self.frame_type == Av1FrameType::Key && self.show_frame
+    }
+}
+
+/// Stream properties derived from the sequence header and HDR metadata
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Av1StreamFormat {
+    pub max_width: u32,
+    pub max_height: u32,
+    pub seq_profile: u8,
+    pub seq_level_idx: u8,
+    pub bit_depth: u8,
+    pub mono_chrome: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+    pub color_range: ColorRange,
+    pub color_space: ColorSpace,
+    pub transfer_function: TransferFunction,
+    /// Content light level (max_cll, max_fall) in cd/m², if signalled
+    pub content_light_level: Option<(u16, u16)>,
+    /// Mastering display luminance (max, min) in cd/m², if signalled
+    pub mastering_luminance: Option<(f32, f32)>,
+}
+
+impl Av1StreamFormat {
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self.transfer_function,
+            TransferFunction::PQ | TransferFunction::HLG
+        )
+    }
+
+    /// Whether common hardware decoders (NVDEC, VA-API, D3D11, VideoToolbox)
+    /// can decode this stream: Main profile, 8/10-bit 4:2:0 or monochrome
+    pub fn hardware_decodable(&self) -> bool {
+        self.seq_profile == 0 && self.bit_depth <= 10
+    }
+
+    /// Human readable profile name
+    pub fn profile_name(&self) -> &'static str {
+        match self.seq_profile {
+            0 => "Main",
+            1 => "High",
+            _ => "Professional",
+        }
+    }
+
+    fn from_sequence_header(seq: &Av1SequenceHeader, metadata: &[Av1Metadata]) -> Self {
+        let cc = &seq.color_config;
+        let mut format = Self {
+            max_width: seq.max_frame_width,
+            max_height: seq.max_frame_height,
+            seq_profile: seq.seq_profile,
+            seq_level_idx: seq.seq_level_idx,
+            bit_depth: cc.bit_depth,
+            mono_chrome: cc.mono_chrome,
+            subsampling_x: cc.subsampling_x,
+            subsampling_y: cc.subsampling_y,
+            color_range: cc.color_range(),
+            // Unsignalled matrix: BT.709, like the decoders default to
+            color_space: cc.color_space().unwrap_or(ColorSpace::BT709),
+            transfer_function: cc.transfer_function(),
+            content_light_level: None,
+            mastering_luminance: None,
+        };
+        for meta in metadata {
+            match *meta {
+                Av1Metadata::HdrCll { max_cll, max_fall } => {
+                    format.content_light_level = Some((max_cll, max_fall));
+                }
+                Av1Metadata::HdrMdcv {
+                    luminance_max,
+                    luminance_min,
+                    ..
+                } => {
+                    format.mastering_luminance =
+                        Some((luminance_max as f32 / 256.0, luminance_min as f32 / 16384.0));
+                }
+                Av1Metadata::Other { .. } => {}
+            }
+        }
+        format
+    }
+}
+
+/// AV1 Bitstream Parser
+#[derive(Default)]
+pub struct Av1Parser {
+    /// Most recent sequence header
+    pub sequence_header: Option<Av1SequenceHeader>,
+    /// HDR metadata from the most recent temporal unit that carried any
+    pub hdr_metadata: Vec<Av1Metadata>,
+    /// Format reported by the last change
+    format: Option<Av1StreamFormat>,
+}
+
+impl Av1Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse and store a sequence header OBU
+    pub fn parse_sequence_header(&mut self, obu: &Av1Obu) -> Result<&Av1SequenceHeader> {
+        let mut seq = Av1SequenceHeader::parse(obu.payload)?;
+        seq.raw_data = obu.data.to_vec();
+        debug!(
+            "Parsed AV1 sequence header: profile={}, level={}, {}x{} max, {}-bit, mono={}, cp/tc/mc={}/{}/{}",
+            seq.seq_profile,
+            seq.seq_level_idx,
+            seq.max_frame_width,
+            seq.max_frame_height,
+            seq.color_config.bit_depth,
+            seq.color_config.mono_chrome,
+            seq.color_config.color_primaries,
+            seq.color_config.transfer_characteristics,
+            seq.color_config.matrix_coefficients
+        );
+        Ok(self.sequence_header.insert(seq))
+    }
+
+    /// Parse the first frame header in a temporal unit
+    pub fn parse_frame_header(&self, data: &[u8]) -> Option<Av1FrameHeader> {
+        let obu = parse_obus(data)
+            .into_iter()
+            .find(|obu| obu.obu_type.has_frame_header())?;
+        Av1FrameHeader::parse(obu.payload, self.sequence_header.as_ref()).ok()
+    }
+
+    /// Parse the sequence header and metadata OBUs of a temporal unit
+    ///
+    /// Returns the new stream format when the sequence header or HDR
+    /// metadata changes, so the decoder backend and renderer can react
+    /// before the frame is decoded.
+    pub fn process_temporal_unit(&mut self, data: &[u8]) -> Option<Av1StreamFormat> {
+        let mut metadata = Vec::new();
+        for obu in parse_obus(data) {
+            match obu.obu_type {
+                Av1ObuType::SequenceHeader => {
+                    if let Err(e) = self.parse_sequence_header(&obu) {
+                        debug!("Failed to parse AV1 sequence header: {}", e);
+                    }
+                }
+                Av1ObuType::Metadata => match Av1Metadata::parse(obu.payload) {
+                    Ok(meta @ (Av1Metadata::HdrCll { .. } | Av1Metadata::HdrMdcv { .. })) => {
+                        metadata.push(meta);
+                    }
+                    Ok(_) => {}
+                    Err(e) => debug!("Failed to parse AV1 metadata OBU: {}", e),
+                },
+                _ => {}
+            }
+        }
+        if !metadata.is_empty() {
+            self.hdr_metadata = metadata;
+        }
+
+        let seq = self.sequence_header.as_ref()?;
+        let format = Av1StreamFormat::from_sequence_header(seq, &self.hdr_metadata);
+        if self.format == Some(format) {
+            return None;
+        }
+        self.format = Some(format);
+        Some(format)
+    }
+
+    /// Format reported by the last change
+    pub fn format(&self) -> Option<Av1StreamFormat> {
+        self.format
+    }
+}
+
+/// Variable length unsigned value (AV1 spec section 4.10.3)
+fn read_uvlc(reader: &mut BitReader) -> Result<u32> {
+    let mut leading_zeros = 0u32;
+    while reader.read_bit()? == 0 {
+        leading_zeros += 1;
+        if leading_zeros >= 32 {
+            return Ok(u32::MAX);
+        }
+    }
+    let value = reader.read_bits(leading_zeros as u8)?;
+    Ok(value + ((1u64 << leading_zeros) - 1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Temporal units from rav1e 0.7 (64x48, low latency)
+
+    /// 8-bit 4:2:0 key frame without colour description
+    const KEY_SDR: &[u8] = &[
+        0x12, 0x00, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0xF9, 0x57, 0xFB, 0xC4, 0x21, 0x50, 0x14, 0x32,
+        0x1C, 0x10, 0x02, 0x89, 0x1D, 0x8B, 0xFE, 0xF1, 0xE9, 0x60, 0x00, 0x10, 0x00, 0x08, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0xC3, 0x0C, 0x10, 0x41, 0x10, 0xB3, 0xF6,
+    ];
+    /// 10-bit BT.2020 PQ sequence header with CLL and MDCV metadata OBUs
+    const SEQ_HDR10: &[u8] = &[
+        0x12, 0x00, 0x0A, 0x0D, 0x00, 0x00, 0x00, 0xF9, 0x57, 0xFB, 0xC4, 0x21, 0x55, 0x09, 0x10,
+        0x09, 0x14, 0x2A, 0x06, 0x01, 0x03, 0xE8, 0x01, 0x90, 0x80, 0x2A, 0x1A, 0x02, 0x21, 0x34,
+        0x9B, 0xAA, 0x19, 0x96, 0x08, 0xFC, 0x8A, 0x48, 0x39, 0x08, 0x3D, 0x13, 0x40, 0x42, 0x00,
+        0x03, 0xE8, 0x00, 0x00, 0x00, 0x00, 0x32, 0x80,
+    ];
+    /// High profile 8-bit 4:4:4 sequence header OBU
+    const SEQ_HIGH_444: &[u8] = &[
+        0x0A, 0x0A, 0x20, 0x00, 0x00, 0xF9, 0x57, 0xFB, 0xC4, 0x21, 0x50, 0xA0,
+    ];
+    /// Inter frame OBU following a temporal delimiter
+    const INTER: &[u8] = &[
+        0x12, 0x00, 0x32, 0x17, 0x30, 0x0A, 0x02, 0x00, 0x00, 0x00, 0x05, 0x82, 0x2F, 0x07, 0xEB,
+        0xCB, 0xB4, 0xC0, 0xA2, 0x8A, 0x28, 0x20, 0x80, 0x50, 0x00, 0xB5, 0x5E,
+    ];
+
+    #[test]
+    fn test_parse_obus() {
+        let obus = parse_obus(SEQ_HDR10);
+        let types: Vec<_> = obus.iter().map(|o| o.obu_type).collect();
+        assert_eq!(
+            &types[..4],
+            &[
+                Av1ObuType::TemporalDelimiter,
+                Av1ObuType::SequenceHeader,
+                Av1ObuType::Metadata,
+                Av1ObuType::Metadata,
+            ]
+        );
+        assert_eq!(obus[1].payload.len(), 13);
+        assert_eq!(obus[1].data, &SEQ_HDR10[2..17]);
+
+        let mut leb = Vec::new();
+        write_leb128(&mut leb, 300);
+        assert_eq!(leb, [0xAC, 0x02]);
+        assert_eq!(read_leb128(&leb), Some((300, 2)));
+        assert_eq!(read_leb128(&[0x80]), None);
+    }
+
+    #[test]
+    fn test_parse_sequence_header_sdr() {
+        let mut parser = Av1Parser::new();
+        let format = parser.process_temporal_unit(KEY_SDR).unwrap();
+        let seq = parser.sequence_header.as_ref().unwrap();
+
+        assert_eq!(seq.seq_profile, 0);
+        assert_eq!(seq.seq_level_idx, 31);
+        assert_eq!((seq.max_frame_width, seq.max_frame_height), (64, 48));
+        assert_eq!(seq.color_config.bit_depth, 8);
+        assert!(seq.color_config.subsampling_x && seq.color_config.subsampling_y);
+        assert_eq!(seq.color_config.color_space(), None);
+
+        assert_eq!(format.color_space, ColorSpace::BT709);
+        assert_eq!(format.color_range, ColorRange::Limited);
+        assert!(!format.is_hdr());
+        assert!(format.hardware_decodable());
+
+        // Matches the av1C rav1e writes for this stream
+        assert_eq!(&seq.codec_config_record()[..4], &[0x81, 0x1F, 0x0C, 0x00]);
+
+        // Same sequence header on the next key frame: no change
+        assert!(parser.process_temporal_unit(KEY_SDR).is_none());
+    }
+
+    #[test]
+    fn test_parse_sequence_header_hdr10() {
+        let mut parser = Av1Parser::new();
+        let format = parser.process_temporal_unit(SEQ_HDR10).unwrap();
+        let cc = parser.sequence_header.as_ref().unwrap().color_config;
+
+        assert_eq!(cc.bit_depth, 10);
+        assert_eq!(
+            (
+                cc.color_primaries,
+                cc.transfer_characteristics,
+                cc.matrix_coefficients
+            ),
+            (9, 16, 9)
+        );
+        assert_eq!(format.color_space, ColorSpace::BT2020);
+        assert_eq!(format.transfer_function, TransferFunction::PQ);
+        assert!(format.is_hdr());
+        assert!(format.hardware_decodable());
+        assert_eq!(format.content_light_level, Some((1000, 400)));
+        assert_eq!(format.mastering_luminance, Some((1000.0, 50.0 / 16384.0)));
+
+        assert_eq!(
+            parser.hdr_metadata[1],
+            Av1Metadata::HdrMdcv {
+                primaries: [(8500, 39850), (6550, 2300), (35400, 14600)],
+                white_point: (15635, 16450),
+                luminance_max: 1000 << 8,
+                luminance_min: 50,
+            }
+        );
+        assert_eq!(
+            &parser
+                .sequence_header
+                .as_ref()
+                .unwrap()
+                .codec_config_record()[..4],
+            &[0x81, 0x1F, 0x4C, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_parse_sequence_header_high_profile() {
+        let obus = parse_obus(SEQ_HIGH_444);
+        let seq = Av1SequenceHeader::parse(obus[0].payload).unwrap();
+        assert_eq!(seq.seq_profile, 1);
+        assert!(!seq.color_config.subsampling_x && !seq.color_config.subsampling_y);
+
+        let format = Av1StreamFormat::from_sequence_header(&seq, &[]);
+        assert_eq!(format.profile_name(), "High");
+        assert!(!format.hardware_decodable());
+    }
+
+    #[test]
+    fn test_parse_frame_headers() {
+        let mut parser = Av1Parser::new();
+        parser.process_temporal_unit(KEY_SDR);
+
+        let key = parser.parse_frame_header(KEY_SDR).unwrap();
+        assert_eq!(key.frame_type, Av1FrameType::Key);
+        assert!(key.show_frame && key.is_keyframe());
+
+        let inter = parser.parse_frame_header(INTER).unwrap();
+        assert_eq!(inter.frame_type, Av1FrameType::Inter);
+        assert!(inter.show_frame && !inter.is_keyframe());
+
+        // show_existing_frame of slot 3
+        let existing = Av1FrameHeader::parse(&[0xB0], None).unwrap();
+        assert!(existing.show_existing_frame);
+        assert_eq!(existing.frame_to_show_map_idx, 3);
+        assert!(!existing.is_keyframe());
+    }
+}
@@ -0,0 +1,537 @@
+//! Decoder Capability Probing
+//!
+//! Test-decodes tiny embedded H.264/HEVC/AV1 key frames on every decoder
+//! backend, caches the results per GPU driver, and builds the fallback chain
+//! used when a decoder keeps failing mid-stream.
+
+use log::{debug, info, warn};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+
+use super::video::{decoder_backend_candidates, gpu_driver_description, UnifiedVideoDecoder};
+use super::{PixelFormat, VideoFrame};
+use crate::app::cache;
+use crate::app::config::VideoDecoderBackend;
+use crate::app::{SharedFrame, VideoCodec};
+
+/// 64x64 grey IDR: Baseline SPS/PPS and 16 I_16x16 DC macroblocks (CAVLC)
+const PROBE_CLIP_H264: &[u8] = &[
+    0x00, 0x00, 0x00, 0x01, 0x67, 0x42, 0xC0, 0x0A, 0xDA, 0x10, 0x99, 0x00, 0x00, 0x00, 0x01, 0x68,
+    0xCE, 0x38, 0x80, 0x00, 0x00, 0x00, 0x01, 0x65, 0x88, 0x84, 0x93, 0x93, 0x93, 0x93, 0x93, 0x93,
+    0x93, 0x93, 0x93, 0x93, 0x93, 0x93, 0x93, 0x93, 0x93, 0x93, 0xC0,
+];
+
+/// 64x64 grey Main profile IDR with VPS/SPS/PPS (x265)
+const PROBE_CLIP_H265: &[u8] = &[
+    0x00, 0x00, 0x00, 0x01, 0x40, 0x01, 0x0C, 0x01, 0xFF, 0xFF, 0x04, 0x08, 0x00, 0x00, 0x03, 0x00,
+    0x9F, 0xA8, 0x00, 0x00, 0x03, 0x00, 0x00, 0x1E, 0xBA, 0x02, 0x40, 0x00, 0x00, 0x00, 0x01, 0x42,
+    0x01, 0x01, 0x04, 0x08, 0x00, 0x00, 0x03, 0x00, 0x9F, 0xA8, 0x00, 0x00, 0x03, 0x00, 0x00, 0x1E,
+    0xA0, 0x20, 0x81, 0x05, 0x96, 0xE9, 0x29, 0x30, 0xB8, 0x04, 0x00, 0x00, 0x0F, 0xA0, 0x00, 0x01,
+    0xD4, 0xC0, 0x20, 0x00, 0x00, 0x00, 0x01, 0x44, 0x01, 0xC0, 0x71, 0x81, 0x12, 0x00, 0x00, 0x01,
+    0x28, 0x01, 0xAD, 0xE0, 0xD1, 0x1C, 0x44, 0x7B, 0x18,
+];
+
+/// 64x64 8-bit Main profile key frame temporal unit (rav1e)
+const PROBE_CLIP_AV1: &[u8] = &[
+    0x12, 0x00, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0xF9, 0x57, 0xFF, 0xC4, 0x21, 0x50, 0x14, 0x32, 0x1F,
+    0x10, 0x02, 0x89, 0x1D, 0x8B, 0xFE, 0xF1, 0xE9, 0x60, 0x00, 0x10, 0x00, 0x08, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x30, 0xC3, 0x0C, 0x10, 0x41, 0x10, 0xBB, 0xF9, 0x96, 0x0A, 0x48,
+];
+
+/// Dimensions of every probe clip
+const PROBE_SIZE: (u32, u32) = (64, 64);
+/// Luma of the grey probe pictures, and how far a decode may stray from it
+const PROBE_LUMA: u32 = 128;
+const PROBE_LUMA_TOLERANCE: u32 = 24;
+
+/// How long a backend gets to produce the probe frame
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Interval between re-submitting the probe clip (async decoders buffer)
+const PROBE_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Codecs covered by the capability matrix
+pub const PROBE_CODECS: [VideoCodec; 3] = [VideoCodec::H264, VideoCodec::H265, VideoCodec::AV1];
+
+fn probe_clip(codec: VideoCodec) -> &'static [u8] {
+    match codec {
+        VideoCodec::H264 => PROBE_CLIP_H264,
+        VideoCodec::H265 => PROBE_CLIP_H265,
+        VideoCodec::AV1 => PROBE_CLIP_AV1,
+    }
+}
+
+/// Outcome of test-decoding one codec on one backend
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub backend: VideoDecoderBackend,
+    pub codec: VideoCodec,
+    /// Backend decoded the probe clip to the expected picture
+    pub supported: bool,
+    pub hw_accelerated: bool,
+    /// Time to first frame, including decoder warm-up
+    pub decode_ms: f32,
+}
+
+/// Probe results for the current GPU driver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecoderCapabilities {
+    /// GPU, driver, OS and app version the results were measured with
+    pub driver: String,
+    /// Unix timestamp of the probe
+    pub probed_at: i64,
+    pub results: Vec<ProbeResult>,
+}
+
+impl DecoderCapabilities {
+    /// Whether `backend` decoded `codec`, or None if it wasn't probed
+    pub fn supports(&self, backend: VideoDecoderBackend, codec: VideoCodec) -> Option<bool> {
+        self.result(backend, codec).map(|r| r.supported)
+    }
+
+    pub fn result(&self, backend: VideoDecoderBackend, codec: VideoCodec) -> Option<&ProbeResult> {
+        self.results
+            .iter()
+            .find(|r| r.backend == backend && r.codec == codec)
+    }
+
+    /// Probed backends in probe order
+    pub fn backends(&self) -> Vec<VideoDecoderBackend> {
+        let mut backends = Vec::new();
+        for result in &self.results {
+            if !backends.contains(&result.backend) {
+                backends.push(result.backend);
+            }
+        }
+        backends
+    }
+
+    /// Backends that decoded at least one codec
+    pub fn working_backends(&self) -> Vec<VideoDecoderBackend> {
+        self.backends()
+            .into_iter()
+            .filter(|&b| self.results.iter().any(|r| r.backend == b && r.supported))
+            .collect()
+    }
+}
+
+/// Latest probe results (loaded from cache or measured this run)
+static CAPABILITIES: RwLock<Option<DecoderCapabilities>> = RwLock::new(None);
+/// Set while a probe runs in the background
+static PROBE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Get the current decoder capabilities, if probed
+pub fn decoder_capabilities() -> Option<DecoderCapabilities> {
+    CAPABILITIES.read().clone()
+}
+
+/// Check if a probe is running
+pub fn is_probe_running() -> bool {
+    PROBE_RUNNING.load(Ordering::Relaxed)
+}
+
+/// Cache key: results are only reused on the same GPU driver and app build
+fn driver_key() -> String {
+    format!(
+        "{} | {} | {}",
+        gpu_driver_description(),
+        std::env::consts::OS,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Load cached capabilities for the current driver, or probe in the background
+///
+/// `force` ignores the cache (settings "Re-test" button).
+pub fn start_decoder_probe(runtime: &Handle, force: bool) {
+    if PROBE_RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    // Decoders may spawn tokio tasks, so probe on the runtime's blocking pool
+    runtime.spawn_blocking(move || {
+        let key = driver_key();
+        let cached = cache::load_decoder_capabilities().filter(|c| !force && c.driver == key);
+
+        let capabilities = match cached {
+            Some(caps) => {
+                info!("Using cached decoder capabilities for {}", caps.driver);
+                caps
+            }
+            None => {
+                info!("Probing video decoders for {}", key);
+                let caps = run_probe(key);
+                cache::save_decoder_capabilities(&caps);
+                caps
+            }
+        };
+
+        *CAPABILITIES.write() = Some(capabilities);
+        PROBE_RUNNING.store(false, Ordering::Release);
+    });
+}
+
+fn run_probe(driver: String) -> DecoderCapabilities {
+    let mut results = Vec::new();
+    for backend in decoder_backend_candidates() {
+        if backend == VideoDecoderBackend::Auto {
+            continue;
+        }
+        for codec in PROBE_CODECS {
+            let result = probe_backend(codec, backend);
+            info!(
+                "  {:?} {:?}: {}",
+                backend,
+                codec,
+                if result.supported {
+                    format!("ok ({:.1}ms)", result.decode_ms)
+                } else {
+                    "unsupported".to_string()
+                }
+            );
+            results.push(result);
+        }
+    }
+
+    DecoderCapabilities {
+        driver,
+        probed_at: chrono::Utc::now().timestamp(),
+        results,
+    }
+}
+
+/// Test-decode the embedded clip for `codec` on `backend`
+fn probe_backend(codec: VideoCodec, backend: VideoDecoderBackend) -> ProbeResult {
+    let mut result = ProbeResult {
+        backend,
+        codec,
+        supported: false,
+        hw_accelerated: false,
+        decode_ms: 0.0,
+    };
+
+    let shared_frame = Arc::new(SharedFrame::new());
+    let (mut decoder, mut stats_rx) =
+        match UnifiedVideoDecoder::new_async(codec, backend, shared_frame.clone()) {
+            Ok(decoder) => decoder,
+            Err(e) => {
+                debug!("Probe: {:?} {:?} unavailable: {}", backend, codec, e);
+                return result;
+            }
+        };
+    result.hw_accelerated = decoder.is_hw_accelerated();
+
+    let start = Instant::now();
+    while start.elapsed() < PROBE_TIMEOUT {
+        if let Err(e) = decoder.decode_async(probe_clip(codec), Instant::now(), 0) {
+            debug!("Probe: {:?} {:?} decode failed: {}", backend, codec, e);
+            break;
+        }
+        std::thread::sleep(PROBE_RETRY_INTERVAL);
+        while stats_rx.try_recv().is_ok() {}
+
+        // Reporting a frame isn't enough: some decoders do so after only
+        // parsing the headers, so check the picture that came out
+        if let Some(frame) = shared_frame.read() {
+            if is_probe_picture(&frame) {
+                result.supported = true;
+                result.decode_ms = start.elapsed().as_secs_f32() * 1000.0;
+            } else {
+                debug!(
+                    "Probe: {:?} {:?} produced a wrong {}x{} picture",
+                    backend, codec, frame.width, frame.height
+                );
+            }
+            return result;
+        }
+    }
+
+    result
+}
+
+/// Whether `frame` is the decoded probe clip
+///
+/// Checks the size, and for frames in system memory that the luma is the
+/// clip's mid-grey. Zero-copy GPU frames only carry the size.
+fn is_probe_picture(frame: &VideoFrame) -> bool {
+    if (frame.width, frame.height) != PROBE_SIZE {
+        return false;
+    }
+    if frame.y_plane.is_empty() {
+        return true;
+    }
+
+    // P010 keeps its 10 bits in the top of little-endian 16-bit words
+    let bytes_per_sample = if frame.format == PixelFormat::P010 {
+        2
+    } else {
+        1
+    };
+    let row_len = PROBE_SIZE.0 as usize * bytes_per_sample;
+    let mut sum = 0u32;
+    for row in 0..PROBE_SIZE.1 as usize {
+        let offset = row * frame.y_stride as usize;
+        let Some(line) = frame.y_plane.get(offset..offset + row_len) else {
+            return false;
+        };
+        sum += line
+            .chunks_exact(bytes_per_sample)
+            .map(|sample| sample[bytes_per_sample - 1] as u32)
+            .sum::<u32>();
+    }
+    let mean = sum / (PROBE_SIZE.0 * PROBE_SIZE.1);
+    mean.abs_diff(PROBE_LUMA) <= PROBE_LUMA_TOLERANCE
+}
+
+/// Backends to try for `codec`, starting with the preferred one
+/// (Auto is resolved to the backend it would pick)
+///
+/// Backends the probe found unable to decode `codec` are skipped, and
+/// software decoding is always part of the chain. A user who picked
+/// software decoding gets only that: nothing falls back to hardware.
+pub fn fallback_chain(
+    codec: VideoCodec,
+    preferred: VideoDecoderBackend,
+) -> Vec<VideoDecoderBackend> {
+    build_fallback_chain(
+        &decoder_backend_candidates(),
+        decoder_capabilities().as_ref(),
+        codec,
+        preferred,
+    )
+}
+
+fn build_fallback_chain(
+    candidates: &[VideoDecoderBackend],
+    capabilities: Option<&DecoderCapabilities>,
+    codec: VideoCodec,
+    preferred: VideoDecoderBackend,
+) -> Vec<VideoDecoderBackend> {
+    // Unprobed backends are worth a try
+    let usable = |backend| {
+        capabilities
+            .and_then(|c| c.supports(backend, codec))
+            .unwrap_or(true)
+    };
+
+    // Auto stands for the first usable candidate; naming that backend keeps
+    // it from being tried a second time further down the chain
+    let preferred = if preferred == VideoDecoderBackend::Auto {
+        candidates
+            .iter()
+            .copied()
+            .find(|&b| b != VideoDecoderBackend::Auto && usable(b))
+            .unwrap_or(VideoDecoderBackend::Software)
+    } else {
+        preferred
+    };
+    if preferred == VideoDecoderBackend::Software {
+        return vec![VideoDecoderBackend::Software];
+    }
+
+    let mut chain = Vec::new();
+    if usable(preferred) {
+        chain.push(preferred);
+    } else {
+        warn!(
+            "{:?} failed the {:?} decode probe, trying other decoders first",
+            preferred, codec
+        );
+    }
+
+    for &backend in candidates {
+        if matches!(
+            backend,
+            VideoDecoderBackend::Auto | VideoDecoderBackend::Software
+        ) || chain.contains(&backend)
+        {
+            continue;
+        }
+        if usable(backend) {
+            chain.push(backend);
+        }
+    }
+
+    if !chain.contains(&VideoDecoderBackend::Software) {
+        chain.push(VideoDecoderBackend::Software);
+    }
+    chain
+}
+
+/// Detects a decoder that stopped producing frames while data keeps arriving
+pub struct DecoderWatchdog {
+    last_frame: Instant,
+    submitted_since_frame: u32,
+}
+
+impl DecoderWatchdog {
+    /// Access units without output before the decoder counts as stuck
+    /// (keyframe requests get a few rounds to recover first)
+    const STALL_FRAMES: u32 = 90;
+    const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+    pub fn new() -> Self {
+        Self {
+            last_frame: Instant::now(),
+            submitted_since_frame: 0,
+        }
+    }
+
+    /// An access unit was handed to the decoder (or failed to be)
+    pub fn frame_submitted(&mut self) {
+        self.submitted_since_frame += 1;
+    }
+
+    /// The decoder produced a frame
+    pub fn frame_decoded(&mut self) {
+        self.reset();
+    }
+
+    /// Start a new observation window (after switching decoders)
+    pub fn reset(&mut self) {
+        self.last_frame = Instant::now();
+        self.submitted_since_frame = 0;
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.is_stalled_at(Instant::now())
+    }
+
+    fn is_stalled_at(&self, now: Instant) -> bool {
+        self.submitted_since_frame >= Self::STALL_FRAMES
+            && now.duration_since(self.last_frame) >= Self::STALL_TIMEOUT
+    }
+}
+
+impl Default for DecoderWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::av1_parser::Av1Parser;
+    use crate::media::h264_parser::H264Parser;
+    use crate::media::hevc_parser::HevcParser;
+    use VideoDecoderBackend as B;
+
+    fn capabilities(results: &[(B, VideoCodec, bool)]) -> DecoderCapabilities {
+        DecoderCapabilities {
+            driver: "test".to_string(),
+            probed_at: chrono::Utc::now().timestamp(),
+            results: results
+                .iter()
+                .map(|&(backend, codec, supported)| ProbeResult {
+                    backend,
+                    codec,
+                    supported,
+                    hw_accelerated: backend != B::Software,
+                    decode_ms: 1.0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_probe_clips_are_64x64_keyframes() {
+        let mut h264 = H264Parser::new();
+        let format = h264.process_access_unit(PROBE_CLIP_H264).unwrap();
+        assert_eq!((format.width, format.height), (64, 64));
+        let idr = h264
+            .find_nal_units(PROBE_CLIP_H264)
+            .into_iter()
+            .find(|n| n.nal_type.is_idr())
+            .unwrap();
+        assert!(h264.parse_slice_header(&idr).unwrap().slice_type.is_intra());
+
+        let mut hevc = HevcParser::new();
+        for nal in hevc.find_nal_units(PROBE_CLIP_H265) {
+            hevc.process_nal(&nal).unwrap();
+        }
+        assert_eq!(
+            hevc.get_dimensions().map(|(w, h, _)| (w, h)),
+            Some((64, 64))
+        );
+
+        let mut av1 = Av1Parser::new();
+        let format = av1.process_temporal_unit(PROBE_CLIP_AV1).unwrap();
+        assert_eq!((format.max_width, format.max_height), (64, 64));
+        assert!(av1
+            .parse_frame_header(PROBE_CLIP_AV1)
+            .unwrap()
+            .is_keyframe());
+    }
+
+    #[test]
+    fn test_probe_picture_must_be_grey_64x64() {
+        // Black frame of the right size
+        let mut frame = VideoFrame::empty(64, 64);
+        assert!(!is_probe_picture(&frame));
+
+        frame.y_plane = vec![126; 64 * 64];
+        assert!(is_probe_picture(&frame));
+        assert!(!is_probe_picture(&VideoFrame::empty(1920, 1080)));
+
+        // Truncated plane
+        frame.y_plane.truncate(64 * 32);
+        assert!(!is_probe_picture(&frame));
+    }
+
+    #[test]
+    fn test_fallback_chain_skips_failed_backends() {
+        let candidates = [B::Auto, B::VulkanVideo, B::Cuvid, B::Vaapi, B::Software];
+        let caps = capabilities(&[
+            (B::VulkanVideo, VideoCodec::AV1, false),
+            (B::Cuvid, VideoCodec::AV1, true),
+            (B::Vaapi, VideoCodec::AV1, false),
+            (B::Vaapi, VideoCodec::H264, true),
+        ]);
+
+        assert_eq!(
+            build_fallback_chain(&candidates, Some(&caps), VideoCodec::AV1, B::Auto),
+            vec![B::Cuvid, B::Software]
+        );
+        // Auto resolves to the backend it would pick, which appears only once
+        assert_eq!(
+            build_fallback_chain(&candidates, None, VideoCodec::AV1, B::Auto),
+            vec![B::VulkanVideo, B::Cuvid, B::Vaapi, B::Software]
+        );
+        // Preferred backend that failed the probe moves out of the way
+        assert_eq!(
+            build_fallback_chain(&candidates, Some(&caps), VideoCodec::AV1, B::Vaapi),
+            vec![B::Cuvid, B::Software]
+        );
+        // Unprobed codecs keep every backend
+        assert_eq!(
+            build_fallback_chain(&candidates, Some(&caps), VideoCodec::H265, B::Vaapi),
+            vec![B::Vaapi, B::VulkanVideo, B::Cuvid, B::Software]
+        );
+        // Software by choice never falls back to hardware
+        assert_eq!(
+            build_fallback_chain(&candidates, None, VideoCodec::H264, B::Software),
+            vec![B::Software]
+        );
+        assert_eq!(caps.working_backends(), vec![B::Cuvid, B::Vaapi]);
+    }
+
+    #[test]
+    fn test_watchdog_needs_frames_and_time() {
+        let mut watchdog = DecoderWatchdog::new();
+        let later = Instant::now() + Duration::from_secs(5);
+        assert!(!watchdog.is_stalled_at(later));
+
+        for _ in 0..DecoderWatchdog::STALL_FRAMES {
+            watchdog.frame_submitted();
+        }
+        assert!(!watchdog.is_stalled());
+        assert!(watchdog.is_stalled_at(later));
+
+        watchdog.frame_decoded();
+        assert!(!watchdog.is_stalled_at(later));
+    }
+}
@@ -0,0 +1,380 @@
+//! Frame Pacing
+//!
+//! Optional presentation scheduler for smooth playback. Frames carry their
+//! RTP time; the pacer maps it onto the local clock, tracks the display's
+//! vsync phase from present timestamps, and hands each frame to the renderer
+//! on the refresh closest to its due time. At most one frame waits for
+//! presentation, so pacing adds at most one frame of latency over the
+//! default latest-frame mode.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use super::VideoFrame;
+
+/// RTP clock rate for video payloads (Hz)
+const RTP_VIDEO_CLOCK: u64 = 90_000;
+
+/// Frames waiting for their refresh slot
+const QUEUE_CAP: usize = 1;
+/// How fast the clock offset follows later-arriving frames (clock drift)
+const OFFSET_DRIFT_GAIN: f64 = 0.002;
+/// How fast the jitter estimate adapts
+const JITTER_GAIN: f64 = 0.05;
+/// Offset jump treated as a timestamp discontinuity (seconds)
+const CLOCK_RESET_THRESHOLD: f64 = 1.0;
+/// Refresh period and vsync phase correction per present
+const PERIOD_GAIN: f64 = 0.05;
+const PHASE_GAIN: f64 = 0.1;
+/// Plausible display refresh rates (Hz)
+const MIN_REFRESH_HZ: f64 = 20.0;
+const MAX_REFRESH_HZ: f64 = 500.0;
+
+/// Extends 32-bit RTP timestamps across wraparound and converts them to microseconds
+#[derive(Debug, Default)]
+pub struct RtpClock {
+    last: Option<u32>,
+    extended: u64,
+}
+
+impl RtpClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timestamp_us(&mut self, rtp_timestamp: u32) -> u64 {
+        self.extended = match self.last {
+            // Start one wrap in so reordered packets before the first stay positive
+            None => (1 << 32) + rtp_timestamp as u64,
+            Some(last) => {
+                let delta = rtp_timestamp.wrapping_sub(last) as i32 as i64;
+                (self.extended as i64 + delta) as u64
+            }
+        };
+        self.last = Some(rtp_timestamp);
+        self.extended * 1_000_000 / RTP_VIDEO_CLOCK
+    }
+}
+
+/// Presentation counters for the stats overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacingStats {
+    /// Refreshes that showed a new frame
+    pub presented: u64,
+    /// Refreshes that showed the previous frame again
+    pub repeated: u64,
+    /// Frames replaced before they were shown
+    pub skipped: u64,
+}
+
+/// Schedules decoded frames onto display refreshes
+pub struct FramePacer {
+    epoch: Instant,
+    /// Frames with their due time (seconds since epoch)
+    queue: VecDeque<(VideoFrame, f64)>,
+    /// Local arrival time minus media time of the earliest frames
+    clock_offset: Option<f64>,
+    /// Average lateness relative to the clock offset
+    jitter: f64,
+    /// Media time between frames
+    frame_interval: f64,
+    last_media_time: Option<f64>,
+    refresh_period: f64,
+    last_vsync: Option<f64>,
+    has_presented: bool,
+    stats: PacingStats,
+}
+
+impl FramePacer {
+    /// Create a pacer for a display refreshing at `refresh_hz` (refined from presents)
+    pub fn new(refresh_hz: f64) -> Self {
+        let refresh_hz = refresh_hz.clamp(MIN_REFRESH_HZ, MAX_REFRESH_HZ);
+        Self {
+            epoch: Instant::now(),
+            queue: VecDeque::with_capacity(QUEUE_CAP + 1),
+            clock_offset: None,
+            jitter: 0.0,
+            frame_interval: 1.0 / refresh_hz,
+            last_media_time: None,
+            refresh_period: 1.0 / refresh_hz,
+            last_vsync: None,
+            has_presented: false,
+            stats: PacingStats::default(),
+        }
+    }
+
+    /// Set the display refresh rate (monitor change)
+    pub fn set_refresh_rate(&mut self, refresh_hz: f64) {
+        self.refresh_period = 1.0 / refresh_hz.clamp(MIN_REFRESH_HZ, MAX_REFRESH_HZ);
+        self.last_vsync = None;
+    }
+
+    pub fn stats(&self) -> PacingStats {
+        self.stats
+    }
+
+    /// Current refresh rate estimate (Hz)
+    pub fn refresh_rate(&self) -> f64 {
+        1.0 / self.refresh_period
+    }
+
+    /// Queue a decoded frame that arrived at `now`
+    pub fn push(&mut self, frame: VideoFrame, now: Instant) {
+        let arrival = self.seconds(now);
+        self.push_at(frame, arrival);
+    }
+
+    /// Frame to show on the upcoming refresh, or None to repeat the current one
+    pub fn next_frame(&mut self, now: Instant) -> Option<VideoFrame> {
+        let vsync = self.next_vsync(self.seconds(now));
+        self.frame_for_vsync(vsync)
+    }
+
+    /// Record that a refresh was presented at `now` (tracks vsync phase)
+    pub fn on_present(&mut self, now: Instant) {
+        let t = self.seconds(now);
+        self.present_at(t);
+    }
+
+    fn seconds(&self, t: Instant) -> f64 {
+        t.saturating_duration_since(self.epoch).as_secs_f64()
+    }
+
+    fn push_at(&mut self, frame: VideoFrame, arrival: f64) {
+        let media = frame.timestamp_us as f64 / 1_000_000.0;
+
+        if let Some(last) = self.last_media_time {
+            let interval = media - last;
+            if interval > 0.0 && interval < CLOCK_RESET_THRESHOLD {
+                self.frame_interval += (interval - self.frame_interval) * JITTER_GAIN;
+            }
+        }
+        self.last_media_time = Some(media);
+
+        let sample = arrival - media;
+        let offset = match self.clock_offset {
+            Some(offset) if (sample - offset).abs() < CLOCK_RESET_THRESHOLD => {
+                if sample < offset {
+                    sample
+                } else {
+                    self.jitter += ((sample - offset) - self.jitter) * JITTER_GAIN;
+                    offset + (sample - offset) * OFFSET_DRIFT_GAIN
+                }
+            }
+            // First frame or timestamp discontinuity (stream restart)
+            _ => {
+                self.jitter = 0.0;
+                sample
+            }
+        };
+        self.clock_offset = Some(offset);
+
+        // Hold frames back by the typical lateness, but never more than a frame
+        let due = media + offset + self.jitter.min(self.frame_interval);
+        self.queue.push_back((frame, due));
+        while self.queue.len() > QUEUE_CAP {
+            self.queue.pop_front();
+            self.stats.skipped += 1;
+        }
+    }
+
+    fn frame_for_vsync(&mut self, vsync: f64) -> Option<VideoFrame> {
+        // Show frames on the refresh nearest their due time
+        let deadline = vsync + self.refresh_period / 2.0;
+        let mut chosen = None;
+        while self.queue.front().is_some_and(|(_, due)| *due <= deadline) {
+            if chosen.replace(self.queue.pop_front()?.0).is_some() {
+                self.stats.skipped += 1;
+            }
+        }
+
+        if chosen.is_some() {
+            self.stats.presented += 1;
+            self.has_presented = true;
+        } else if self.has_presented {
+            self.stats.repeated += 1;
+        }
+        chosen
+    }
+
+    fn present_at(&mut self, t: f64) {
+        let Some(last) = self.last_vsync else {
+            self.last_vsync = Some(t);
+            return;
+        };
+
+        let elapsed = t - last;
+        let cycles = (elapsed / self.refresh_period).round().max(1.0);
+        if cycles == 1.0 {
+            let period = self.refresh_period + (elapsed - self.refresh_period) * PERIOD_GAIN;
+            self.refresh_period = period.clamp(1.0 / MAX_REFRESH_HZ, 1.0 / MIN_REFRESH_HZ);
+        }
+        let predicted = last + cycles * self.refresh_period;
+        self.last_vsync = Some(predicted + (t - predicted) * PHASE_GAIN);
+    }
+
+    fn next_vsync(&self, now: f64) -> f64 {
+        match self.last_vsync {
+            Some(last) => {
+                let cycles = ((now - last) / self.refresh_period).ceil().max(1.0);
+                last + cycles * self.refresh_period
+            }
+            None => now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(media_ms: f64) -> VideoFrame {
+        let mut frame = VideoFrame::empty(16, 16);
+        frame.timestamp_us = (media_ms * 1000.0) as u64;
+        frame
+    }
+
+    /// Drive the pacer with a vsync-locked render loop
+    ///
+    /// `arrivals` are (media time, arrival time) in ms; returns the media
+    /// time shown on each refresh (None = repeat).
+    fn simulate(
+        refresh_hz: f64,
+        arrivals: &[(f64, f64)],
+        refreshes: usize,
+    ) -> (FramePacer, Vec<Option<u64>>) {
+        let mut pacer = FramePacer::new(refresh_hz);
+        let period_ms = 1000.0 / refresh_hz;
+        let mut pending = arrivals.iter().peekable();
+        let mut shown = Vec::new();
+
+        for i in 0..refreshes {
+            let now = i as f64 * period_ms;
+            while let Some(&&(media, arrival)) = pending.peek() {
+                if arrival > now {
+                    break;
+                }
+                pacer.push_at(frame(media), arrival / 1000.0);
+                pending.next();
+            }
+            let vsync = pacer.next_vsync(now / 1000.0 + 0.0001);
+            shown.push(pacer.frame_for_vsync(vsync).map(|f| f.timestamp_us / 1000));
+            pacer.present_at(vsync);
+        }
+        (pacer, shown)
+    }
+
+    #[test]
+    fn test_rtp_clock_unwraps() {
+        let mut clock = RtpClock::new();
+        let start = clock.timestamp_us(u32::MAX - 1499);
+        assert_eq!(clock.timestamp_us(u32::MAX) - start, 16_655);
+        // 3000 ticks (1/30 s) later, across the wrap
+        assert_eq!(clock.timestamp_us(1500) - start, 33_333);
+        // Reordered frame from before the wrap
+        assert_eq!(clock.timestamp_us(u32::MAX - 1499), start);
+    }
+
+    #[test]
+    fn test_matching_rates_present_every_frame() {
+        let arrivals: Vec<_> = (0..120)
+            .map(|i| (i as f64 * 1000.0 / 60.0, i as f64 * 1000.0 / 60.0 + 5.0))
+            .collect();
+        let (pacer, shown) = simulate(60.0, &arrivals, 120);
+
+        let stats = pacer.stats();
+        assert_eq!(stats.skipped, 0);
+        assert!(stats.presented >= 118, "{:?}", stats);
+        assert!(stats.repeated <= 1, "{:?}", stats);
+        assert!(shown
+            .iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_jitter_is_smoothed() {
+        // 60fps with +/-4ms network jitter on a 60Hz display
+        let arrivals: Vec<_> = (0..240)
+            .map(|i| {
+                let media = i as f64 * 1000.0 / 60.0;
+                let jitter = if i % 2 == 0 { 0.0 } else { 8.0 };
+                (media, media + 3.0 + jitter)
+            })
+            .collect();
+        let (pacer, shown) = simulate(60.0, &arrivals, 240);
+
+        // After settling, every refresh shows the next frame
+        let settled: Vec<u64> = shown[60..].iter().map(|s| s.unwrap()).collect();
+        assert!(
+            settled
+                .windows(2)
+                .all(|w| w[1] - w[0] == 16 || w[1] - w[0] == 17),
+            "{:?}",
+            settled
+        );
+        assert_eq!(pacer.stats().skipped, 0);
+    }
+
+    #[test]
+    fn test_slow_stream_repeats_evenly() {
+        let arrivals: Vec<_> = (0..60)
+            .map(|i| (i as f64 * 1000.0 / 30.0, i as f64 * 1000.0 / 30.0 + 2.0))
+            .collect();
+        let (pacer, shown) = simulate(60.0, &arrivals, 120);
+
+        let stats = pacer.stats();
+        assert_eq!(stats.skipped, 0);
+        assert!(
+            (stats.presented as i64 - stats.repeated as i64).abs() <= 2,
+            "{:?}",
+            stats
+        );
+        // New frame and repeat alternate
+        assert!(shown[10..]
+            .windows(2)
+            .all(|w| w[0].is_some() != w[1].is_some()));
+    }
+
+    #[test]
+    fn test_fast_stream_skips_extra_frames() {
+        let arrivals: Vec<_> = (0..240)
+            .map(|i| (i as f64 * 1000.0 / 120.0, i as f64 * 1000.0 / 120.0 + 1.0))
+            .collect();
+        let (pacer, _) = simulate(60.0, &arrivals, 120);
+
+        let stats = pacer.stats();
+        assert_eq!(stats.repeated, 0);
+        assert!(
+            (stats.skipped as i64 - stats.presented as i64).abs() <= 2,
+            "{:?}",
+            stats
+        );
+    }
+
+    #[test]
+    fn test_vsync_period_is_refined() {
+        let mut pacer = FramePacer::new(60.0);
+        for i in 0..200 {
+            pacer.present_at(i as f64 / 144.0);
+        }
+        assert!(
+            (pacer.refresh_rate() - 144.0).abs() < 1.0,
+            "{}",
+            pacer.refresh_rate()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_discontinuity_resets_clock() {
+        let mut pacer = FramePacer::new(60.0);
+        pacer.push_at(frame(1000.0), 0.0);
+        // Stream restarted with unrelated timestamps
+        pacer.push_at(frame(900_000.0), 0.02);
+        assert_eq!(pacer.stats().skipped, 1);
+        let shown = pacer.frame_for_vsync(0.02).unwrap();
+        assert_eq!(shown.timestamp_us, 900_000_000);
+    }
+}
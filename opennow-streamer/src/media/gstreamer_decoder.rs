@@ -30,6 +30,7 @@ use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 use super::{ColorRange, ColorSpace, PixelFormat, TransferFunction, VideoFrame};
+use crate::app::config::VideoDecoderBackend;
 
 /// Initialize GStreamer with support for bundled runtime on Windows
 /// This function MUST be called before any other GStreamer operations.
@@ -157,6 +158,30 @@ impl GstCodec {
             GstCodec::AV1 => "av1dec", // dav1d-based decoder (preferred) or avdec_av1
         }
     }
+
+    /// Decoder elements for an explicitly selected backend, in preference order
+    ///
+    /// Empty for backends that let the pipeline pick the best decoder itself.
+    fn backend_decoders(&self, backend: VideoDecoderBackend) -> &'static [&'static str] {
+        match (backend, self) {
+            (VideoDecoderBackend::Cuvid, GstCodec::H264) => &["nvh264dec", "nvd3d11h264dec"],
+            (VideoDecoderBackend::Cuvid, GstCodec::H265) => &["nvh265dec", "nvd3d11h265dec"],
+            (VideoDecoderBackend::Cuvid, GstCodec::AV1) => &["nvav1dec", "nvd3d11av1dec"],
+            (VideoDecoderBackend::Qsv, GstCodec::H264) => &["qsvh264dec"],
+            (VideoDecoderBackend::Qsv, GstCodec::H265) => &["qsvh265dec"],
+            (VideoDecoderBackend::Qsv, GstCodec::AV1) => &["qsvav1dec"],
+            (VideoDecoderBackend::Vaapi, GstCodec::H264) => &["vah264dec", "vaapih264dec"],
+            (VideoDecoderBackend::Vaapi, GstCodec::H265) => &["vah265dec", "vaapih265dec"],
+            (VideoDecoderBackend::Vaapi, GstCodec::AV1) => &["vaav1dec", "vaapiav1dec"],
+            (VideoDecoderBackend::Dxva, GstCodec::H264) => &["d3d11h264dec"],
+            (VideoDecoderBackend::Dxva, GstCodec::H265) => &["d3d11h265dec"],
+            (VideoDecoderBackend::Dxva, GstCodec::AV1) => &["d3d11av1dec"],
+            (VideoDecoderBackend::Software, GstCodec::H264) => &["avdec_h264"],
+            (VideoDecoderBackend::Software, GstCodec::H265) => &["avdec_h265"],
+            (VideoDecoderBackend::Software, GstCodec::AV1) => &["dav1ddec", "av1dec", "avdec_av1"],
+            _ => &[],
+        }
+    }
 }
 
 /// GStreamer decoder configuration
@@ -167,6 +192,8 @@ pub struct GstDecoderConfig {
     pub height: u32,
     /// Enable low latency mode (minimize buffering)
     pub low_latency: bool,
+    /// Decoder backend to pin (Auto selects the best available decoder)
+    pub backend: VideoDecoderBackend,
 }
 
 impl Default for GstDecoderConfig {
@@ -176,6 +203,7 @@ impl Default for GstDecoderConfig {
             width: 1920,
             height: 1080,
             low_latency: true, // Default to low latency for streaming
+            backend: VideoDecoderBackend::Auto,
         }
     }
 }
@@ -460,6 +488,45 @@ impl GStreamerDecoder {
 
         // Check if the hardware decoder is available
         let registry = gst::Registry::get();
+
+        // Explicitly selected backend: use its decoder or fail, never substitute
+        let pinned = config.codec.backend_decoders(config.backend);
+        if !pinned.is_empty() {
+            let decoder = pinned
+                .iter()
+                .copied()
+                .find(|name| {
+                    registry
+                        .find_feature(name, gst::ElementFactory::static_type())
+                        .is_some()
+                })
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No GStreamer decoder for {:?} on {:?} (tried {})",
+                        config.codec,
+                        config.backend,
+                        pinned.join(", ")
+                    )
+                })?;
+            info!("Using {:?} decoder: {}", config.backend, decoder);
+
+            // D3D11-based decoders output GPU textures
+            let download =
+                if cfg!(target_os = "windows") && config.backend != VideoDecoderBackend::Software {
+                    "! d3d11download "
+                } else {
+                    ""
+                };
+            return Ok(format!(
+                "appsrc name=src is-live=true format=time do-timestamp=true max-buffers=1 \
+                 ! {} \
+                 ! {} \
+                 {}! videoconvert n-threads=2 \
+                 ! video/x-raw,format=NV12 \
+                 ! appsink name=sink emit-signals=true {}",
+                parser, decoder, download, sink_opts
+            ));
+        }
         let hw_decoder_available = registry
             .find_feature(decoder, gst::ElementFactory::static_type())
             .is_some();
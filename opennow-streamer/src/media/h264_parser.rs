@@ -0,0 +1,1395 @@
+//! H.264/AVC NAL Unit Parser
+//!
+//! Parses SPS (including VUI colour description), PPS, slice headers and SEI
+//! messages so stream properties are known before frames reach the decoder:
+//! resolution changes, colour range/matrix and reference structure.
+//!
+//! Based on ITU-T H.264 specification.
+
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+use super::hevc_parser::BitReader;
+use super::mkv::{remove_emulation_prevention, split_annexb};
+use super::{ColorRange, ColorSpace, TransferFunction};
+
+/// Largest frame any level allows, in macroblocks (level 6.2 MaxFS)
+const MAX_FRAME_SIZE_IN_MBS: u32 = 139_264;
+
+/// H.264 NAL unit types (ITU-T H.264 Table 7-1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum H264NalType {
+    Unspecified = 0,
+    Slice = 1,
+    SliceDataA = 2,
+    SliceDataB = 3,
+    SliceDataC = 4,
+    IdrSlice = 5,
+    Sei = 6,
+    Sps = 7,
+    Pps = 8,
+    Aud = 9,
+    EndOfSequence = 10,
+    EndOfStream = 11,
+    Filler = 12,
+    SpsExtension = 13,
+    PrefixNal = 14,
+    SubsetSps = 15,
+    // Reserved 16-18
+    AuxiliarySlice = 19,
+    SliceExtension = 20,
+    // Reserved/unspecified 21-31
+    Unknown = 255,
+}
+
+impl From<u8> for H264NalType {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => Self::Unspecified,
+            1 => Self::Slice,
+            2 => Self::SliceDataA,
+            3 => Self::SliceDataB,
+            4 => Self::SliceDataC,
+            5 => Self::IdrSlice,
+            6 => Self::Sei,
+            7 => Self::Sps,
+            8 => Self::Pps,
+            9 => Self::Aud,
+            10 => Self::EndOfSequence,
+            11 => Self::EndOfStream,
+            12 => Self::Filler,
+            13 => Self::SpsExtension,
+            14 => Self::PrefixNal,
+            15 => Self::SubsetSps,
+            19 => Self::AuxiliarySlice,
+            20 => Self::SliceExtension,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl H264NalType {
+    /// Check if this NAL type is a VCL (Video Coding Layer) NAL unit
+    pub fn is_vcl(&self) -> bool {
+        matches!(*self as u8, 1..=5)
+    }
+
+    /// Check if this NAL type is an IDR frame
+    pub fn is_idr(&self) -> bool {
+        matches!(self, Self::IdrSlice)
+    }
+
+    /// Check if this NAL contains a slice header
+    pub fn is_slice(&self) -> bool {
+        matches!(self, Self::Slice | Self::IdrSlice)
+    }
+}
+
+/// Parsed H.264 NAL unit
+#[derive(Debug, Clone)]
+pub struct H264NalUnit {
+    /// NAL unit type
+    pub nal_type: H264NalType,
+    /// nal_ref_idc (0 = not used for reference)
+    pub nal_ref_idc: u8,
+    /// Raw NAL unit data (without start code, with header)
+    pub data: Vec<u8>,
+}
+
+impl H264NalUnit {
+    /// Parse the one-byte NAL header
+    pub fn new(data: &[u8]) -> Option<Self> {
+        let header = *data.first()?;
+        Some(Self {
+            nal_type: H264NalType::from(header & 0x1F),
+            nal_ref_idc: (header >> 5) & 0x03,
+            data: data.to_vec(),
+        })
+    }
+
+    /// RBSP payload (header stripped, emulation prevention removed)
+    fn rbsp(&self) -> Vec<u8> {
+        remove_emulation_prevention(&self.data[1..])
+    }
+}
+
+/// Video usability information (ITU-T H.264 Annex E)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct H264Vui {
+    /// Sample aspect ratio (width, height); (0, 0) when unspecified
+    pub sar: (u16, u16),
+    pub video_format: u8,
+    pub video_full_range: bool,
+    /// Colour description (2 = unspecified)
+    pub colour_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+    pub fixed_frame_rate: bool,
+    /// Frames the decoder may have to hold back for reordering (B-frames)
+    pub max_num_reorder_frames: Option<u32>,
+    pub max_dec_frame_buffering: Option<u32>,
+}
+
+/// H.264 Sequence Parameter Set (SPS)
+#[derive(Debug, Clone, Default)]
+pub struct H264Sps {
+    pub sps_id: u8,
+    pub profile_idc: u8,
+    pub constraint_flags: u8,
+    pub level_idc: u8,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub log2_max_frame_num: u8,
+    pub pic_order_cnt_type: u8,
+    pub log2_max_poc_lsb: u8,
+    pub delta_pic_order_always_zero: bool,
+    pub max_num_ref_frames: u32,
+    pub gaps_in_frame_num_allowed: bool,
+    pub pic_width_in_mbs: u32,
+    pub pic_height_in_map_units: u32,
+    pub frame_mbs_only: bool,
+    pub mb_adaptive_frame_field: bool,
+    pub direct_8x8_inference: bool,
+    /// Frame cropping offsets (left, right, top, bottom) in crop units
+    pub frame_crop: (u32, u32, u32, u32),
+    pub vui: Option<H264Vui>,
+    /// Raw NAL data for decoder configuration
+    pub raw_data: Vec<u8>,
+}
+
+impl H264Sps {
+    /// Display width after cropping
+    pub fn width(&self) -> u32 {
+        let (crop_unit_x, _) = self.crop_units();
+        let (left, right, _, _) = self.frame_crop;
+        let crop = left.saturating_add(right).saturating_mul(crop_unit_x);
+        self.pic_width_in_mbs
+            .saturating_mul(16)
+            .saturating_sub(crop)
+    }
+
+    /// Display height after cropping
+    pub fn height(&self) -> u32 {
+        let (_, crop_unit_y) = self.crop_units();
+        let (_, _, top, bottom) = self.frame_crop;
+        let frame_height_in_mbs =
+            (2 - self.frame_mbs_only as u32).saturating_mul(self.pic_height_in_map_units);
+        let crop = top.saturating_add(bottom).saturating_mul(crop_unit_y);
+        frame_height_in_mbs.saturating_mul(16).saturating_sub(crop)
+    }
+
+    /// CropUnitX/CropUnitY (equations 7-19 to 7-22)
+    fn crop_units(&self) -> (u32, u32) {
+        let field_factor = 2 - self.frame_mbs_only as u32;
+        if self.separate_colour_plane || self.chroma_format_idc == 0 {
+            return (1, field_factor);
+        }
+        let (sub_width_c, sub_height_c) = match self.chroma_format_idc {
+            1 => (2, 2),
+            2 => (2, 1),
+            _ => (1, 1),
+        };
+        (sub_width_c, sub_height_c * field_factor)
+    }
+
+    /// Frame within the largest level and cropping inside the frame, so
+    /// width/height are meaningful
+    fn check_frame_size(&self) -> Result<()> {
+        let field_factor = 2 - self.frame_mbs_only as u32;
+        let frame_height_in_mbs = self.pic_height_in_map_units * field_factor;
+        let frame_size_in_mbs = self.pic_width_in_mbs.checked_mul(frame_height_in_mbs);
+        if !matches!(frame_size_in_mbs, Some(size) if size <= MAX_FRAME_SIZE_IN_MBS) {
+            return Err(anyhow!(
+                "SPS frame too large: {}x{} macroblocks",
+                self.pic_width_in_mbs,
+                frame_height_in_mbs
+            ));
+        }
+
+        let (crop_unit_x, crop_unit_y) = self.crop_units();
+        let (left, right, top, bottom) = self.frame_crop;
+        let crop_x = left
+            .checked_add(right)
+            .and_then(|crop| crop.checked_mul(crop_unit_x));
+        let crop_y = top
+            .checked_add(bottom)
+            .and_then(|crop| crop.checked_mul(crop_unit_y));
+        let inside = crop_x.is_some_and(|crop| crop < self.pic_width_in_mbs * 16)
+            && crop_y.is_some_and(|crop| crop < frame_height_in_mbs * 16);
+        if !inside {
+            return Err(anyhow!(
+                "SPS cropping {:?} exceeds the frame",
+                self.frame_crop
+            ));
+        }
+        Ok(())
+    }
+
+    /// Colour range from the VUI (limited unless signalled otherwise)
+    pub fn color_range(&self) -> ColorRange {
+        match &self.vui {
+            Some(vui) if vui.video_full_range => ColorRange::Full,
+            _ => ColorRange::Limited,
+        }
+    }
+
+    /// YUV matrix from the VUI, if signalled
+    pub fn color_space(&self) -> Option<ColorSpace> {
+        match self.vui.as_ref()?.matrix_coefficients {
+            1 => Some(ColorSpace::BT709),
+            5 | 6 => Some(ColorSpace::BT601),
+            9 | 10 => Some(ColorSpace::BT2020),
+            _ => None,
+        }
+    }
+
+    /// Transfer function from the VUI (SDR unless PQ/HLG is signalled)
+    pub fn transfer_function(&self) -> TransferFunction {
+        match self.vui.as_ref().map(|v| v.transfer_characteristics) {
+            Some(16) => TransferFunction::PQ,
+            Some(18) => TransferFunction::HLG,
+            _ => TransferFunction::SDR,
+        }
+    }
+}
+
+/// H.264 Picture Parameter Set (PPS)
+#[derive(Debug, Clone, Default)]
+pub struct H264Pps {
+    pub pps_id: u8,
+    pub sps_id: u8,
+    /// CABAC (true) or CAVLC (false)
+    pub entropy_coding_mode: bool,
+    pub bottom_field_pic_order_in_frame_present: bool,
+    pub num_slice_groups: u32,
+    pub num_ref_idx_l0_default_active: u8,
+    pub num_ref_idx_l1_default_active: u8,
+    pub weighted_pred: bool,
+    pub weighted_bipred_idc: u8,
+    pub pic_init_qp: i8,
+    pub pic_init_qs: i8,
+    pub chroma_qp_index_offset: i8,
+    pub deblocking_filter_control_present: bool,
+    pub constrained_intra_pred: bool,
+    pub redundant_pic_cnt_present: bool,
+    pub transform_8x8_mode: bool,
+    pub pic_scaling_matrix_present: bool,
+    pub second_chroma_qp_index_offset: i8,
+    /// Raw NAL data for decoder configuration
+    pub raw_data: Vec<u8>,
+}
+
+/// H.264 slice types (slice_type % 5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum H264SliceType {
+    #[default]
+    P,
+    B,
+    I,
+    SP,
+    SI,
+}
+
+impl H264SliceType {
+    fn from_ue(val: u32) -> Self {
+        match val % 5 {
+            0 => Self::P,
+            1 => Self::B,
+            2 => Self::I,
+            3 => Self::SP,
+            _ => Self::SI,
+        }
+    }
+
+    pub fn is_intra(&self) -> bool {
+        matches!(self, Self::I | Self::SI)
+    }
+}
+
+/// H.264 slice header (up to the reference list sizes)
+#[derive(Debug, Clone, Default)]
+pub struct H264SliceHeader {
+    pub first_mb_in_slice: u32,
+    pub slice_type: H264SliceType,
+    pub pps_id: u8,
+    pub colour_plane_id: u8,
+    pub frame_num: u32,
+    pub field_pic: bool,
+    pub bottom_field: bool,
+    pub idr_pic_id: Option<u32>,
+    pub pic_order_cnt_lsb: u32,
+    pub delta_pic_order_cnt_bottom: i32,
+    pub redundant_pic_cnt: u32,
+    pub direct_spatial_mv_pred: bool,
+    pub num_ref_idx_l0_active: u8,
+    pub num_ref_idx_l1_active: u8,
+    /// Picture is used as a reference by later pictures (nal_ref_idc != 0)
+    pub is_reference: bool,
+}
+
+/// Supplemental enhancement information message
+#[derive(Debug, Clone, PartialEq)]
+pub enum H264SeiMessage {
+    /// Random access point without an IDR (type 6)
+    RecoveryPoint {
+        recovery_frame_cnt: u32,
+        exact_match: bool,
+        broken_link: bool,
+    },
+    /// SMPTE ST 2086 mastering display (type 137)
+    /// Primaries/white point in 0.00002 units, luminance in 0.0001 cd/m²
+    MasteringDisplayColourVolume {
+        display_primaries: [(u16, u16); 3],
+        white_point: (u16, u16),
+        max_luminance: u32,
+        min_luminance: u32,
+    },
+    /// Content light level in cd/m² (type 144)
+    ContentLightLevel {
+        max_content_light_level: u16,
+        max_pic_average_light_level: u16,
+    },
+    /// Encoder-specific data (type 5)
+    UserDataUnregistered {
+        uuid: [u8; 16],
+        data: Vec<u8>,
+    },
+    Other {
+        payload_type: u32,
+        payload: Vec<u8>,
+    },
+}
+
+/// Stream properties derived from the active SPS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct H264StreamFormat {
+    pub width: u32,
+    pub height: u32,
+    pub profile_idc: u8,
+    pub level_idc: u8,
+    pub bit_depth: u8,
+    pub chroma_format_idc: u8,
+    pub color_range: ColorRange,
+    pub color_space: ColorSpace,
+    pub transfer_function: TransferFunction,
+    pub max_num_ref_frames: u32,
+    /// None when the stream doesn't signal its reorder depth
+    pub max_num_reorder_frames: Option<u32>,
+}
+
+impl H264StreamFormat {
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self.transfer_function,
+            TransferFunction::PQ | TransferFunction::HLG
+        )
+    }
+
+    /// Whether common hardware decoders (NVDEC, VA-API, D3D11, VideoToolbox)
+    /// can decode this stream: 8-bit 4:2:0 or monochrome
+    pub fn hardware_decodable(&self) -> bool {
+        self.bit_depth == 8 && self.chroma_format_idc <= 1
+    }
+
+    /// Human readable profile name
+    pub fn profile_name(&self) -> &'static str {
+        match self.profile_idc {
+            66 => "Baseline",
+            77 => "Main",
+            88 => "Extended",
+            100 => "High",
+            110 => "High 10",
+            122 => "High 4:2:2",
+            244 => "High 4:4:4",
+            _ => "Unknown",
+        }
+    }
+
+    fn from_sps(sps: &H264Sps) -> Self {
+        let height = sps.height();
+        Self {
+            width: sps.width(),
+            height,
+            profile_idc: sps.profile_idc,
+            level_idc: sps.level_idc,
+            bit_depth: sps.bit_depth_luma,
+            chroma_format_idc: sps.chroma_format_idc,
+            color_range: sps.color_range(),
+            // Unsignalled matrix: BT.601 for SD, BT.709 otherwise (common practice)
+            color_space: sps.color_space().unwrap_or(if height <= 576 {
+                ColorSpace::BT601
+            } else {
+                ColorSpace::BT709
+            }),
+            transfer_function: sps.transfer_function(),
+            max_num_ref_frames: sps.max_num_ref_frames,
+            max_num_reorder_frames: sps.vui.as_ref().and_then(|v| v.max_num_reorder_frames),
+        }
+    }
+}
+
+/// H.264 Bitstream Parser
+pub struct H264Parser {
+    /// Current SPS (up to 32)
+    pub sps: Vec<Option<H264Sps>>,
+    /// Current PPS (up to 256)
+    pub pps: Vec<Option<H264Pps>>,
+    /// Format of the most recently activated SPS
+    format: Option<H264StreamFormat>,
+}
+
+impl Default for H264Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl H264Parser {
+    pub fn new() -> Self {
+        Self {
+            sps: vec![None; 32],
+            pps: vec![None; 256],
+            format: None,
+        }
+    }
+
+    /// Find all NAL units in a bitstream (Annex B format with start codes)
+    pub fn find_nal_units(&self, data: &[u8]) -> Vec<H264NalUnit> {
+        split_annexb(data)
+            .into_iter()
+            .filter_map(H264NalUnit::new)
+            .inspect(|nal| {
+                trace!(
+                    "Found NAL: type={:?}, size={}",
+                    nal.nal_type,
+                    nal.data.len()
+                )
+            })
+            .collect()
+    }
+
+    /// Parse SPS NAL unit
+    pub fn parse_sps(&mut self, nal: &H264NalUnit) -> Result<u8> {
+        let rbsp = nal.rbsp();
+        let mut reader = BitReader::new(&rbsp);
+
+        let profile_idc = reader.read_bits(8)? as u8;
+        let constraint_flags = reader.read_bits(8)? as u8;
+        let level_idc = reader.read_bits(8)? as u8;
+        let sps_id = reader.read_ue()?;
+        if sps_id >= 32 {
+            return Err(anyhow!("Invalid SPS id {}", sps_id));
+        }
+
+        let mut chroma_format_idc = 1;
+        let mut separate_colour_plane = false;
+        let mut bit_depth_luma = 8;
+        let mut bit_depth_chroma = 8;
+        if matches!(
+            profile_idc,
+            100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+        ) {
+            chroma_format_idc = read_ue_max(&mut reader, 3, "chroma_format_idc")? as u8;
+            if chroma_format_idc == 3 {
+                separate_colour_plane = reader.read_bit()? != 0;
+            }
+            bit_depth_luma = read_ue_max(&mut reader, 6, "bit_depth_luma_minus8")? as u8 + 8;
+            bit_depth_chroma = read_ue_max(&mut reader, 6, "bit_depth_chroma_minus8")? as u8 + 8;
+            reader.skip_bits(1)?; // qpprime_y_zero_transform_bypass_flag
+            let seq_scaling_matrix_present = reader.read_bit()? != 0;
+            if seq_scaling_matrix_present {
+                let count = if chroma_format_idc != 3 { 8 } else { 12 };
+                Self::skip_scaling_lists(&mut reader, count)?;
+            }
+        }
+
+        let log2_max_frame_num =
+            read_ue_max(&mut reader, 12, "log2_max_frame_num_minus4")? as u8 + 4;
+        let pic_order_cnt_type = read_ue_max(&mut reader, 2, "pic_order_cnt_type")? as u8;
+        let mut log2_max_poc_lsb = 0;
+        let mut delta_pic_order_always_zero = false;
+        match pic_order_cnt_type {
+            0 => {
+                log2_max_poc_lsb =
+                    read_ue_max(&mut reader, 12, "log2_max_pic_order_cnt_lsb_minus4")? as u8 + 4
+            }
+            1 => {
+                delta_pic_order_always_zero = reader.read_bit()? != 0;
+                reader.read_se()?; // offset_for_non_ref_pic
+                reader.read_se()?; // offset_for_top_to_bottom_field
+                let cycle = read_ue_max(&mut reader, 255, "num_ref_frames_in_pic_order_cnt_cycle")?;
+                for _ in 0..cycle {
+                    reader.read_se()?; // offset_for_ref_frame
+                }
+            }
+            _ => {}
+        }
+
+        let max_num_ref_frames = read_ue_max(&mut reader, 16, "max_num_ref_frames")?;
+        let gaps_in_frame_num_allowed = reader.read_bit()? != 0;
+        let pic_width_in_mbs = read_ue_max(
+            &mut reader,
+            MAX_FRAME_SIZE_IN_MBS - 1,
+            "pic_width_in_mbs_minus1",
+        )? + 1;
+        let pic_height_in_map_units = read_ue_max(
+            &mut reader,
+            MAX_FRAME_SIZE_IN_MBS - 1,
+            "pic_height_in_map_units_minus1",
+        )? + 1;
+        let frame_mbs_only = reader.read_bit()? != 0;
+        let mb_adaptive_frame_field = if !frame_mbs_only {
+            reader.read_bit()? != 0
+        } else {
+            false
+        };
+        let direct_8x8_inference = reader.read_bit()? != 0;
+
+        let frame_cropping = reader.read_bit()? != 0;
+        let frame_crop = if frame_cropping {
+            (
+                reader.read_ue()?,
+                reader.read_ue()?,
+                reader.read_ue()?,
+                reader.read_ue()?,
+            )
+        } else {
+            (0, 0, 0, 0)
+        };
+
+        // A truncated or unusual VUI shouldn't discard the SPS
+        let vui_present = reader.read_bit()? != 0;
+        let vui = if vui_present {
+            match Self::parse_vui(&mut reader) {
+                Ok(vui) => Some(vui),
+                Err(e) => {
+                    debug!("SPS {}: failed to parse VUI: {}", sps_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let sps = H264Sps {
+            sps_id: sps_id as u8,
+            profile_idc,
+            constraint_flags,
+            level_idc,
+            chroma_format_idc,
+            separate_colour_plane,
+            bit_depth_luma,
+            bit_depth_chroma,
+            log2_max_frame_num,
+            pic_order_cnt_type,
+            log2_max_poc_lsb,
+            delta_pic_order_always_zero,
+            max_num_ref_frames,
+            gaps_in_frame_num_allowed,
+            pic_width_in_mbs,
+            pic_height_in_map_units,
+            frame_mbs_only,
+            mb_adaptive_frame_field,
+            direct_8x8_inference,
+            frame_crop,
+            vui,
+            raw_data: nal.data.clone(),
+        };
+        sps.check_frame_size()?;
+
+        debug!(
+            "Parsed SPS {}: {}x{}, profile={}, level={}, bit_depth={}, refs={}",
+            sps_id,
+            sps.width(),
+            sps.height(),
+            profile_idc,
+            level_idc,
+            bit_depth_luma,
+            max_num_ref_frames
+        );
+        self.sps[sps_id as usize] = Some(sps);
+        Ok(sps_id as u8)
+    }
+
+    fn parse_vui(reader: &mut BitReader) -> Result<H264Vui> {
+        let mut vui = H264Vui {
+            video_format: 5,
+            colour_primaries: 2,
+            transfer_characteristics: 2,
+            matrix_coefficients: 2,
+            ..Default::default()
+        };
+
+        let aspect_ratio_info_present = reader.read_bit()? != 0;
+        if aspect_ratio_info_present {
+            let aspect_ratio_idc = reader.read_bits(8)? as u8;
+            vui.sar = match aspect_ratio_idc {
+                // Extended_SAR
+                255 => (reader.read_bits(16)? as u16, reader.read_bits(16)? as u16),
+                1 => (1, 1),
+                _ => (0, 0),
+            };
+        }
+
+        let overscan_info_present = reader.read_bit()? != 0;
+        if overscan_info_present {
+            reader.skip_bits(1)?; // overscan_appropriate_flag
+        }
+
+        let video_signal_type_present = reader.read_bit()? != 0;
+        if video_signal_type_present {
+            vui.video_format = reader.read_bits(3)? as u8;
+            vui.video_full_range = reader.read_bit()? != 0;
+            let colour_description_present = reader.read_bit()? != 0;
+            if colour_description_present {
+                vui.colour_primaries = reader.read_bits(8)? as u8;
+                vui.transfer_characteristics = reader.read_bits(8)? as u8;
+                vui.matrix_coefficients = reader.read_bits(8)? as u8;
+            }
+        }
+
+        let chroma_loc_info_present = reader.read_bit()? != 0;
+        if chroma_loc_info_present {
+            reader.read_ue()?; // chroma_sample_loc_type_top_field
+            reader.read_ue()?; // chroma_sample_loc_type_bottom_field
+        }
+
+        let timing_info_present = reader.read_bit()? != 0;
+        if timing_info_present {
+            vui.num_units_in_tick = reader.read_bits(32)?;
+            vui.time_scale = reader.read_bits(32)?;
+            vui.fixed_frame_rate = reader.read_bit()? != 0;
+        }
+
+        let nal_hrd_present = reader.read_bit()? != 0;
+        if nal_hrd_present {
+            Self::skip_hrd_parameters(reader)?;
+        }
+        let vcl_hrd_present = reader.read_bit()? != 0;
+        if vcl_hrd_present {
+            Self::skip_hrd_parameters(reader)?;
+        }
+        if nal_hrd_present || vcl_hrd_present {
+            reader.skip_bits(1)?; // low_delay_hrd_flag
+        }
+        reader.skip_bits(1)?; // pic_struct_present_flag
+
+        let bitstream_restriction = reader.read_bit()? != 0;
+        if bitstream_restriction {
+            reader.skip_bits(1)?; // motion_vectors_over_pic_boundaries_flag
+            reader.read_ue()?; // max_bytes_per_pic_denom
+            reader.read_ue()?; // max_bits_per_mb_denom
+            reader.read_ue()?; // log2_max_mv_length_horizontal
+            reader.read_ue()?; // log2_max_mv_length_vertical
+            vui.max_num_reorder_frames = Some(reader.read_ue()?);
+            vui.max_dec_frame_buffering = Some(reader.read_ue()?);
+        }
+
+        Ok(vui)
+    }
+
+    /// Parse PPS NAL unit
+    pub fn parse_pps(&mut self, nal: &H264NalUnit) -> Result<u8> {
+        let rbsp = nal.rbsp();
+        let mut reader = BitReader::new(&rbsp);
+
+        let pps_id = reader.read_ue()?;
+        let sps_id = reader.read_ue()?;
+        if pps_id >= 256 || sps_id >= 32 {
+            return Err(anyhow!("Invalid PPS/SPS id {}/{}", pps_id, sps_id));
+        }
+        let chroma_format_idc = self.sps[sps_id as usize]
+            .as_ref()
+            .map(|sps| sps.chroma_format_idc)
+            .unwrap_or(1);
+
+        let entropy_coding_mode = reader.read_bit()? != 0;
+        let bottom_field_pic_order_in_frame_present = reader.read_bit()? != 0;
+
+        let num_slice_groups = read_ue_max(&mut reader, 7, "num_slice_groups_minus1")? + 1;
+        if num_slice_groups > 1 {
+            Self::skip_slice_group_map(&mut reader, num_slice_groups)?;
+        }
+
+        let num_ref_idx_l0_default_active =
+            read_ue_max(&mut reader, 31, "num_ref_idx_l0_default_active_minus1")? as u8 + 1;
+        let num_ref_idx_l1_default_active =
+            read_ue_max(&mut reader, 31, "num_ref_idx_l1_default_active_minus1")? as u8 + 1;
+        let weighted_pred = reader.read_bit()? != 0;
+        let weighted_bipred_idc = reader.read_bits(2)? as u8;
+        let pic_init_qp = (26 + read_se_range(&mut reader, -62, 25, "pic_init_qp_minus26")?) as i8;
+        let pic_init_qs = (26 + read_se_range(&mut reader, -26, 25, "pic_init_qs_minus26")?) as i8;
+        let chroma_qp_index_offset =
+            read_se_range(&mut reader, -12, 12, "chroma_qp_index_offset")? as i8;
+        let deblocking_filter_control_present = reader.read_bit()? != 0;
+        let constrained_intra_pred = reader.read_bit()? != 0;
+        let redundant_pic_cnt_present = reader.read_bit()? != 0;
+
+        // High profile extension, present only if more RBSP data follows
+        let mut transform_8x8_mode = false;
+        let mut pic_scaling_matrix_present = false;
+        let mut second_chroma_qp_index_offset = chroma_qp_index_offset;
+        if reader.position() < rbsp_stop_bit(&rbsp) {
+            transform_8x8_mode = reader.read_bit()? != 0;
+            pic_scaling_matrix_present = reader.read_bit()? != 0;
+            if pic_scaling_matrix_present {
+                let extra = if chroma_format_idc != 3 { 2 } else { 6 };
+                let count = 6 + if transform_8x8_mode { extra } else { 0 };
+                Self::skip_scaling_lists(&mut reader, count)?;
+            }
+            second_chroma_qp_index_offset =
+                read_se_range(&mut reader, -12, 12, "second_chroma_qp_index_offset")? as i8;
+        }
+
+        let pps = H264Pps {
+            pps_id: pps_id as u8,
+            sps_id: sps_id as u8,
+            entropy_coding_mode,
+            bottom_field_pic_order_in_frame_present,
+            num_slice_groups,
+            num_ref_idx_l0_default_active,
+            num_ref_idx_l1_default_active,
+            weighted_pred,
+            weighted_bipred_idc,
+            pic_init_qp,
+            pic_init_qs,
+            chroma_qp_index_offset,
+            deblocking_filter_control_present,
+            constrained_intra_pred,
+            redundant_pic_cnt_present,
+            transform_8x8_mode,
+            pic_scaling_matrix_present,
+            second_chroma_qp_index_offset,
+            raw_data: nal.data.clone(),
+        };
+
+        debug!(
+            "Parsed PPS {}: sps={}, cabac={}, 8x8={}",
+            pps_id, sps_id, entropy_coding_mode, transform_8x8_mode
+        );
+        self.pps[pps_id as usize] = Some(pps);
+        Ok(pps_id as u8)
+    }
+
+    /// Parse slice header (up to num_ref_idx_active)
+    pub fn parse_slice_header(&self, nal: &H264NalUnit) -> Result<H264SliceHeader> {
+        let rbsp = nal.rbsp();
+        let mut reader = BitReader::new(&rbsp);
+
+        let first_mb_in_slice = reader.read_ue()?;
+        let slice_type = H264SliceType::from_ue(reader.read_ue()?);
+        let pps_id = reader.read_ue()?;
+        let pps = self
+            .pps
+            .get(pps_id as usize)
+            .and_then(|p| p.as_ref())
+            .ok_or_else(|| anyhow!("PPS {} not found", pps_id))?;
+        let sps = self.sps[pps.sps_id as usize]
+            .as_ref()
+            .ok_or_else(|| anyhow!("SPS {} not found", pps.sps_id))?;
+
+        let mut header = H264SliceHeader {
+            first_mb_in_slice,
+            slice_type,
+            pps_id: pps_id as u8,
+            is_reference: nal.nal_ref_idc != 0,
+            ..Default::default()
+        };
+
+        if sps.separate_colour_plane {
+            header.colour_plane_id = reader.read_bits(2)? as u8;
+        }
+        header.frame_num = reader.read_bits(sps.log2_max_frame_num)?;
+
+        if !sps.frame_mbs_only {
+            header.field_pic = reader.read_bit()? != 0;
+            if header.field_pic {
+                header.bottom_field = reader.read_bit()? != 0;
+            }
+        }
+
+        if nal.nal_type.is_idr() {
+            header.idr_pic_id = Some(reader.read_ue()?);
+        }
+
+        match sps.pic_order_cnt_type {
+            0 => {
+                header.pic_order_cnt_lsb = reader.read_bits(sps.log2_max_poc_lsb)?;
+                if pps.bottom_field_pic_order_in_frame_present && !header.field_pic {
+                    header.delta_pic_order_cnt_bottom = reader.read_se()?;
+                }
+            }
+            1 if !sps.delta_pic_order_always_zero => {
+                reader.read_se()?; // delta_pic_order_cnt[0]
+                if pps.bottom_field_pic_order_in_frame_present && !header.field_pic {
+                    reader.read_se()?; // delta_pic_order_cnt[1]
+                }
+            }
+            _ => {}
+        }
+
+        if pps.redundant_pic_cnt_present {
+            header.redundant_pic_cnt = reader.read_ue()?;
+        }
+
+        if slice_type == H264SliceType::B {
+            header.direct_spatial_mv_pred = reader.read_bit()? != 0;
+        }
+
+        header.num_ref_idx_l0_active = pps.num_ref_idx_l0_default_active;
+        header.num_ref_idx_l1_active = pps.num_ref_idx_l1_default_active;
+        if matches!(
+            slice_type,
+            H264SliceType::P | H264SliceType::SP | H264SliceType::B
+        ) {
+            let override_flag = reader.read_bit()? != 0;
+            if override_flag {
+                header.num_ref_idx_l0_active =
+                    read_ue_max(&mut reader, 31, "num_ref_idx_l0_active_minus1")? as u8 + 1;
+                if slice_type == H264SliceType::B {
+                    header.num_ref_idx_l1_active =
+                        read_ue_max(&mut reader, 31, "num_ref_idx_l1_active_minus1")? as u8 + 1;
+                }
+            }
+        }
+        if slice_type.is_intra() {
+            header.num_ref_idx_l0_active = 0;
+            header.num_ref_idx_l1_active = 0;
+        } else if slice_type != H264SliceType::B {
+            header.num_ref_idx_l1_active = 0;
+        }
+
+        Ok(header)
+    }
+
+    /// Parse all messages in an SEI NAL unit
+    pub fn parse_sei(&self, nal: &H264NalUnit) -> Result<Vec<H264SeiMessage>> {
+        let rbsp = nal.rbsp();
+        let mut messages = Vec::new();
+        let mut pos = 0;
+
+        // Stop at the rbsp_trailing_bits byte
+        while pos < rbsp.len() && rbsp[pos] != 0x80 {
+            let payload_type = read_sei_value(&rbsp, &mut pos)?;
+            let payload_size = read_sei_value(&rbsp, &mut pos)? as usize;
+            let payload = rbsp
+                .get(pos..pos + payload_size)
+                .ok_or_else(|| anyhow!("SEI payload truncated"))?;
+            pos += payload_size;
+
+            messages.push(Self::parse_sei_message(payload_type, payload)?);
+        }
+
+        Ok(messages)
+    }
+
+    fn parse_sei_message(payload_type: u32, payload: &[u8]) -> Result<H264SeiMessage> {
+        let mut reader = BitReader::new(payload);
+        let message = match payload_type {
+            5 if payload.len() >= 16 => {
+                let mut uuid = [0u8; 16];
+                uuid.copy_from_slice(&payload[..16]);
+                H264SeiMessage::UserDataUnregistered {
+                    uuid,
+                    data: payload[16..].to_vec(),
+                }
+            }
+            6 => H264SeiMessage::RecoveryPoint {
+                recovery_frame_cnt: reader.read_ue()?,
+                exact_match: reader.read_bit()? != 0,
+                broken_link: reader.read_bit()? != 0,
+            },
+            137 if payload.len() >= 24 => {
+                let mut display_primaries = [(0u16, 0u16); 3];
+                for primary in &mut display_primaries {
+                    *primary = (reader.read_bits(16)? as u16, reader.read_bits(16)? as u16);
+                }
+                H264SeiMessage::MasteringDisplayColourVolume {
+                    display_primaries,
+                    white_point: (reader.read_bits(16)? as u16, reader.read_bits(16)? as u16),
+                    max_luminance: reader.read_bits(32)?,
+                    min_luminance: reader.read_bits(32)?,
+                }
+            }
+            144 if payload.len() >= 4 => H264SeiMessage::ContentLightLevel {
+                max_content_light_level: reader.read_bits(16)? as u16,
+                max_pic_average_light_level: reader.read_bits(16)? as u16,
+            },
+            _ => H264SeiMessage::Other {
+                payload_type,
+                payload: payload.to_vec(),
+            },
+        };
+        Ok(message)
+    }
+
+    /// Process a NAL unit (parse if parameter set)
+    pub fn process_nal(&mut self, nal: &H264NalUnit) -> Result<()> {
+        match nal.nal_type {
+            H264NalType::Sps => {
+                self.parse_sps(nal)?;
+            }
+            H264NalType::Pps => {
+                self.parse_pps(nal)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Parse the parameter sets of an access unit
+    ///
+    /// Returns the new stream format when an SPS changes resolution, colour
+    /// or reference structure, so decoders and the renderer can be
+    /// reconfigured before the frame is decoded.
+    pub fn process_access_unit(&mut self, data: &[u8]) -> Option<H264StreamFormat> {
+        let mut active_sps = None;
+        for nal in self.find_nal_units(data) {
+            match nal.nal_type {
+                H264NalType::Sps => match self.parse_sps(&nal) {
+                    Ok(sps_id) => active_sps = Some(sps_id),
+                    Err(e) => debug!("Failed to parse SPS: {}", e),
+                },
+                H264NalType::Pps => {
+                    if let Err(e) = self.parse_pps(&nal) {
+                        debug!("Failed to parse PPS: {}", e);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let sps = self.sps[active_sps? as usize].as_ref()?;
+        let format = H264StreamFormat::from_sps(sps);
+        if self.format == Some(format) {
+            return None;
+        }
+        self.format = Some(format);
+        Some(format)
+    }
+
+    /// Format of the most recently parsed SPS
+    pub fn format(&self) -> Option<H264StreamFormat> {
+        self.format
+    }
+
+    /// Get active SPS for a PPS
+    pub fn get_sps_for_pps(&self, pps_id: u8) -> Option<&H264Sps> {
+        let pps = self.pps[pps_id as usize].as_ref()?;
+        self.sps[pps.sps_id as usize].as_ref()
+    }
+
+    // Helper functions for skipping complex structures
+
+    fn skip_scaling_lists(reader: &mut BitReader, count: usize) -> Result<()> {
+        for i in 0..count {
+            let present = reader.read_bit()? != 0;
+            if !present {
+                continue;
+            }
+            let size = if i < 6 { 16 } else { 64 };
+            let mut last_scale = 8i32;
+            let mut next_scale = 8i32;
+            for _ in 0..size {
+                if next_scale != 0 {
+                    let delta = read_se_range(reader, -128, 127, "delta_scale")?;
+                    next_scale = (last_scale + delta + 256) % 256;
+                }
+                if next_scale != 0 {
+                    last_scale = next_scale;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_hrd_parameters(reader: &mut BitReader) -> Result<()> {
+        let cpb_cnt = read_ue_max(reader, 31, "cpb_cnt_minus1")? + 1;
+        reader.skip_bits(8)?; // bit_rate_scale, cpb_size_scale
+        for _ in 0..cpb_cnt {
+            reader.read_ue()?; // bit_rate_value_minus1
+            reader.read_ue()?; // cpb_size_value_minus1
+            reader.skip_bits(1)?; // cbr_flag
+        }
+        // initial_cpb_removal_delay_length_minus1, cpb_removal_delay_length_minus1,
+        // dpb_output_delay_length_minus1, time_offset_length
+        reader.skip_bits(20)?;
+        Ok(())
+    }
+
+    fn skip_slice_group_map(reader: &mut BitReader, num_slice_groups: u32) -> Result<()> {
+        let map_type = reader.read_ue()?;
+        match map_type {
+            0 => {
+                for _ in 0..num_slice_groups {
+                    reader.read_ue()?; // run_length_minus1
+                }
+            }
+            2 => {
+                for _ in 0..num_slice_groups - 1 {
+                    reader.read_ue()?; // top_left
+                    reader.read_ue()?; // bottom_right
+                }
+            }
+            3..=5 => {
+                reader.skip_bits(1)?; // slice_group_change_direction_flag
+                reader.read_ue()?; // slice_group_change_rate_minus1
+            }
+            6 => {
+                let pic_size_in_map_units = read_ue_max(
+                    reader,
+                    MAX_FRAME_SIZE_IN_MBS - 1,
+                    "pic_size_in_map_units_minus1",
+                )? + 1;
+                let bits = 32 - (num_slice_groups - 1).leading_zeros();
+                reader.skip_bits(pic_size_in_map_units * bits)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// SEI payload type/size: sum of 0xFF bytes plus the final byte
+fn read_sei_value(data: &[u8], pos: &mut usize) -> Result<u32> {
+    let mut value = 0u32;
+    loop {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| anyhow!("SEI header truncated"))?;
+        *pos += 1;
+        value = value
+            .checked_add(byte as u32)
+            .ok_or_else(|| anyhow!("SEI header too large"))?;
+        if byte != 0xFF {
+            return Ok(value);
+        }
+    }
+}
+
+/// Exp-Golomb value, rejected above the syntax element's maximum
+fn read_ue_max(reader: &mut BitReader, max: u32, name: &str) -> Result<u32> {
+    let value = reader.read_ue()?;
+    if value > max {
+        return Err(anyhow!("{} out of range: {}", name, value));
+    }
+    Ok(value)
+}
+
+/// Signed Exp-Golomb value, rejected outside the syntax element's range
+fn read_se_range(reader: &mut BitReader, min: i32, max: i32, name: &str) -> Result<i32> {
+    let value = reader.read_se()?;
+    if !(min..=max).contains(&value) {
+        return Err(anyhow!("{} out of range: {}", name, value));
+    }
+    Ok(value)
+}
+
+/// Bit position of the rbsp_stop_one_bit (end of syntax elements)
+fn rbsp_stop_bit(rbsp: &[u8]) -> usize {
+    rbsp.iter()
+        .rposition(|&b| b != 0)
+        .map(|i| i * 8 + 7 - rbsp[i].trailing_zeros() as usize)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// x264 1080p High profile SPS (1920x1088 coded, cropped to 1080)
+    const SPS_1080P_HIGH: &[u8] = &[
+        0x67, 0x64, 0x00, 0x28, 0xAC, 0xD9, 0x40, 0x78, 0x02, 0x27, 0xE5, 0xC0, 0x44, 0x00, 0x00,
+        0x03, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xF0, 0x3C, 0x60, 0xC6, 0x58,
+    ];
+    /// x264 High profile PPS (CABAC, weighted bipred, 8x8 transform)
+    const PPS_HIGH: &[u8] = &[0x68, 0xEB, 0xE3, 0xCB, 0x22, 0xC0];
+    /// Camera 640x480 Baseline SPS with full-range BT.709 colour description
+    const SPS_480P_FULL_RANGE: &[u8] = &[
+        0x67, 0x42, 0x00, 0x29, 0xE2, 0x90, 0x14, 0x07, 0xB6, 0x02, 0xDC, 0x04, 0x04, 0x06, 0x90,
+        0x78, 0x91, 0x15,
+    ];
+    /// Baseline PPS (CAVLC)
+    const PPS_BASELINE: &[u8] = &[0x68, 0xCE, 0x3C, 0x80];
+
+    fn nal(data: &[u8]) -> H264NalUnit {
+        H264NalUnit::new(data).unwrap()
+    }
+
+    fn annexb(nals: &[&[u8]]) -> Vec<u8> {
+        nals.iter()
+            .flat_map(|n| [&[0u8, 0, 0, 1][..], n].concat())
+            .collect()
+    }
+
+    /// NAL unit from syntax elements: fixed-width values and Exp-Golomb codes
+    enum Syntax {
+        Bits(u8, u32),
+        Ue(u32),
+    }
+
+    fn build_nal(header: u8, syntax: &[Syntax]) -> Vec<u8> {
+        let mut bits = Vec::new();
+        for element in syntax {
+            match *element {
+                Syntax::Bits(n, value) => bits.extend((0..n).rev().map(|i| (value >> i) & 1)),
+                Syntax::Ue(value) => {
+                    let code = value as u64 + 1;
+                    let len = 64 - code.leading_zeros();
+                    bits.extend((1..len).map(|_| 0));
+                    bits.extend((0..len).rev().map(|i| (code >> i) as u32 & 1));
+                }
+            }
+        }
+        bits.push(1); // rbsp_stop_one_bit
+        let mut data = vec![header];
+        data.extend(bits.chunks(8).map(|byte| {
+            byte.iter()
+                .chain(std::iter::repeat(&0))
+                .take(8)
+                .fold(0u8, |acc, &bit| acc << 1 | bit as u8)
+        }));
+        data
+    }
+
+    /// Baseline SPS 0 with the given size and cropping
+    fn baseline_sps(width_mbs_minus1: u32, height_mbs_minus1: u32, crop: [u32; 4]) -> Vec<u8> {
+        use Syntax::*;
+        let mut syntax = vec![
+            Bits(8, 66), // profile_idc
+            Bits(8, 0),  // constraint flags
+            Bits(8, 40), // level_idc
+            Ue(0),       // sps_id
+            Ue(0),       // log2_max_frame_num_minus4
+            Ue(2),       // pic_order_cnt_type
+            Ue(1),       // max_num_ref_frames
+            Bits(1, 0),  // gaps_in_frame_num_allowed
+            Ue(width_mbs_minus1),
+            Ue(height_mbs_minus1),
+            Bits(1, 1), // frame_mbs_only
+            Bits(1, 1), // direct_8x8_inference
+            Bits(1, 1), // frame_cropping
+        ];
+        syntax.extend(crop.map(Ue));
+        syntax.push(Bits(1, 0)); // vui_parameters_present
+        build_nal(0x67, &syntax)
+    }
+
+    #[test]
+    fn test_nal_type_parsing() {
+        assert_eq!(H264NalType::from(5), H264NalType::IdrSlice);
+        assert_eq!(H264NalType::from(7), H264NalType::Sps);
+        assert_eq!(H264NalType::from(24), H264NalType::Unknown);
+        assert!(H264NalType::IdrSlice.is_idr());
+        assert!(H264NalType::Slice.is_vcl());
+        assert!(!H264NalType::Sei.is_vcl());
+
+        let unit = nal(&[0x65, 0x88]);
+        assert_eq!(unit.nal_type, H264NalType::IdrSlice);
+        assert_eq!(unit.nal_ref_idc, 3);
+    }
+
+    #[test]
+    fn test_parse_sps_high_with_cropping() {
+        let mut parser = H264Parser::new();
+        let id = parser.parse_sps(&nal(SPS_1080P_HIGH)).unwrap();
+        let sps = parser.sps[id as usize].as_ref().unwrap();
+
+        assert_eq!(sps.profile_idc, 100);
+        assert_eq!(sps.level_idc, 40);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!(sps.bit_depth_luma, 8);
+        assert_eq!(sps.max_num_ref_frames, 4);
+        assert_eq!(sps.frame_crop, (0, 0, 0, 4));
+        assert_eq!((sps.width(), sps.height()), (1920, 1080));
+
+        let vui = sps.vui.as_ref().unwrap();
+        assert_eq!((vui.num_units_in_tick, vui.time_scale), (1, 60));
+        assert_eq!(vui.max_num_reorder_frames, Some(2));
+        assert_eq!(vui.max_dec_frame_buffering, Some(4));
+        // No colour description: limited range, unspecified matrix
+        assert_eq!(sps.color_range(), ColorRange::Limited);
+        assert_eq!(sps.color_space(), None);
+    }
+
+    #[test]
+    fn test_parse_sps_vui_colour() {
+        let mut parser = H264Parser::new();
+        let id = parser.parse_sps(&nal(SPS_480P_FULL_RANGE)).unwrap();
+        let sps = parser.sps[id as usize].as_ref().unwrap();
+
+        assert_eq!(sps.profile_idc, 66);
+        assert_eq!((sps.width(), sps.height()), (640, 480));
+        let vui = sps.vui.as_ref().unwrap();
+        assert!(vui.video_full_range);
+        assert_eq!(
+            (
+                vui.colour_primaries,
+                vui.transfer_characteristics,
+                vui.matrix_coefficients
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(sps.color_range(), ColorRange::Full);
+        assert_eq!(sps.color_space(), Some(ColorSpace::BT709));
+        assert_eq!(sps.transfer_function(), TransferFunction::SDR);
+        assert_eq!(vui.max_num_reorder_frames, Some(0));
+    }
+
+    #[test]
+    fn test_parse_pps() {
+        let mut parser = H264Parser::new();
+        parser.parse_sps(&nal(SPS_1080P_HIGH)).unwrap();
+        let id = parser.parse_pps(&nal(PPS_HIGH)).unwrap();
+        let pps = parser.pps[id as usize].as_ref().unwrap();
+
+        assert!(pps.entropy_coding_mode);
+        assert_eq!(pps.num_ref_idx_l0_default_active, 3);
+        assert!(pps.weighted_pred);
+        assert_eq!(pps.weighted_bipred_idc, 2);
+        assert_eq!(pps.pic_init_qp, 23);
+        assert_eq!(pps.chroma_qp_index_offset, -2);
+        assert!(pps.transform_8x8_mode);
+        assert_eq!(pps.second_chroma_qp_index_offset, -2);
+
+        let id = parser.parse_pps(&nal(PPS_BASELINE)).unwrap();
+        let pps = parser.pps[id as usize].as_ref().unwrap();
+        assert!(!pps.entropy_coding_mode);
+        assert!(!pps.transform_8x8_mode);
+    }
+
+    #[test]
+    fn test_malformed_parameter_sets_are_errors() {
+        let error = |result: Result<u8>| result.unwrap_err().to_string();
+        let mut parser = H264Parser::new();
+
+        let id = parser
+            .parse_sps(&nal(&baseline_sps(79, 44, [0, 0, 0, 0])))
+            .unwrap();
+        let sps = parser.sps[id as usize].as_ref().unwrap();
+        assert_eq!((sps.width(), sps.height()), (1280, 720));
+
+        let sps = baseline_sps(u32::MAX - 1, 44, [0, 0, 0, 0]);
+        assert!(error(parser.parse_sps(&nal(&sps))).contains("pic_width_in_mbs_minus1"));
+        let sps = baseline_sps(4095, 4095, [0, 0, 0, 0]);
+        assert!(error(parser.parse_sps(&nal(&sps))).contains("frame too large"));
+        let sps = baseline_sps(79, 44, [u32::MAX - 1, 1, 0, 0]);
+        assert!(error(parser.parse_sps(&nal(&sps))).contains("cropping"));
+        let sps = baseline_sps(79, 44, [0, 0, 360, 0]);
+        assert!(error(parser.parse_sps(&nal(&sps))).contains("cropping"));
+
+        use Syntax::*;
+        let high_sps = build_nal(
+            0x67,
+            &[Bits(8, 100), Bits(8, 0), Bits(8, 40), Ue(0), Ue(1), Ue(250)],
+        );
+        assert!(error(parser.parse_sps(&nal(&high_sps))).contains("bit_depth_luma_minus8"));
+
+        let pps = build_nal(0x68, &[Ue(0), Ue(0), Bits(2, 0), Ue(0), Ue(255)]);
+        assert!(
+            error(parser.parse_pps(&nal(&pps))).contains("num_ref_idx_l0_default_active_minus1")
+        );
+    }
+
+    #[test]
+    fn test_parse_slice_headers() {
+        let mut parser = H264Parser::new();
+        parser.parse_sps(&nal(SPS_1080P_HIGH)).unwrap();
+        parser.parse_pps(&nal(PPS_HIGH)).unwrap();
+
+        // IDR: first_mb=0, slice_type=7 (I), pps=0, frame_num=0, idr_pic_id=0
+        let idr = parser
+            .parse_slice_header(&nal(&[0x65, 0x88, 0x84, 0x00, 0x33, 0xFF]))
+            .unwrap();
+        assert_eq!(idr.slice_type, H264SliceType::I);
+        assert_eq!(idr.frame_num, 0);
+        assert_eq!(idr.idr_pic_id, Some(0));
+        assert!(idr.is_reference);
+        assert_eq!(idr.num_ref_idx_l0_active, 0);
+
+        // Non-reference B slice (nal_ref_idc=0): slice_type=6, frame_num=2,
+        // poc_lsb=4, direct_spatial, override to 2/1 refs
+        let b = parser
+            .parse_slice_header(&nal(&[0x01, 0x9E, 0x42, 0x6B]))
+            .unwrap();
+        assert_eq!(b.slice_type, H264SliceType::B);
+        assert!(!b.is_reference);
+        assert_eq!(b.frame_num, 2);
+        assert_eq!(b.pic_order_cnt_lsb, 4);
+        assert!(b.direct_spatial_mv_pred);
+        assert_eq!((b.num_ref_idx_l0_active, b.num_ref_idx_l1_active), (2, 1));
+    }
+
+    #[test]
+    fn test_parse_sei() {
+        let parser = H264Parser::new();
+
+        // x264 intra-refresh recovery point
+        let messages = parser
+            .parse_sei(&nal(&[0x06, 0x06, 0x01, 0xC4, 0x80]))
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![H264SeiMessage::RecoveryPoint {
+                recovery_frame_cnt: 0,
+                exact_match: true,
+                broken_link: false,
+            }]
+        );
+
+        // Content light level: 1000 / 400 nits
+        let messages = parser
+            .parse_sei(&nal(&[0x06, 0x90, 0x04, 0x03, 0xE8, 0x01, 0x90, 0x80]))
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![H264SeiMessage::ContentLightLevel {
+                max_content_light_level: 1000,
+                max_pic_average_light_level: 400,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_process_access_unit_detects_format_changes() {
+        let mut parser = H264Parser::new();
+        let idr = [0x65, 0x88, 0x84, 0x00, 0x33, 0xFF];
+        let au = annexb(&[SPS_1080P_HIGH, PPS_HIGH, &idr]);
+
+        let format = parser.process_access_unit(&au).unwrap();
+        assert_eq!((format.width, format.height), (1920, 1080));
+        assert_eq!(format.color_space, ColorSpace::BT709);
+        assert_eq!(format.max_num_reorder_frames, Some(2));
+        assert_eq!(format.profile_name(), "High");
+        assert!(format.hardware_decodable());
+        assert!(!format.is_hdr());
+
+        // Same SPS repeated with the next IDR: no change
+        assert!(parser.process_access_unit(&au).is_none());
+
+        let au = annexb(&[SPS_480P_FULL_RANGE, PPS_BASELINE, &idr]);
+        let format = parser.process_access_unit(&au).unwrap();
+        assert_eq!((format.width, format.height), (640, 480));
+        assert_eq!(format.color_range, ColorRange::Full);
+
+        // 10-bit and 4:4:4 streams need a software decoder
+        let high10 = H264StreamFormat {
+            profile_idc: 110,
+            bit_depth: 10,
+            ..format
+        };
+        assert_eq!(high10.profile_name(), "High 10");
+        assert!(!high10.hardware_decodable());
+        let high444 = H264StreamFormat {
+            profile_idc: 244,
+            chroma_format_idc: 3,
+            ..format
+        };
+        assert!(!high444.hardware_decodable());
+    }
+}
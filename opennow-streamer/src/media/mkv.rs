@@ -0,0 +1,827 @@
+//! Matroska (MKV) Muxer
+//!
+//! Minimal EBML/Matroska writer used to remux the compressed stream into a
+//! local file without re-encoding. Supports H.264, H.265 and AV1 video with
+//! an optional Opus audio track.
+//!
+//! Clusters are buffered in memory and written with a known size. Every
+//! cluster starting with a video keyframe gets a cue point; the Cues are
+//! written when the writer is finished, along with the segment size,
+//! duration and the SeekHead entry that lets players find the Cues.
+
+use std::io::{self, Seek, SeekFrom, Write};
+
+use super::av1_parser::{parse_obus, Av1ObuType, Av1Parser};
+use super::hevc_parser::{HevcNalType, HevcParser};
+use super::rtp::DepacketizerCodec;
+
+// EBML / Matroska element IDs (IDs include their length marker bits)
+const EBML: u32 = 0x1A45_DFA3;
+const EBML_VERSION: u32 = 0x4286;
+const EBML_READ_VERSION: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+const DOC_TYPE: u32 = 0x4282;
+const DOC_TYPE_VERSION: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+const SEGMENT: u32 = 0x1853_8067;
+const SEEK_HEAD: u32 = 0x114D_9B74;
+const SEEK: u32 = 0x4DBB;
+const SEEK_ID: u32 = 0x53AB;
+const SEEK_POSITION: u32 = 0x53AC;
+const VOID: u32 = 0xEC;
+const INFO: u32 = 0x1549_A966;
+const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const DURATION: u32 = 0x4489;
+const MUXING_APP: u32 = 0x4D80;
+const WRITING_APP: u32 = 0x5741;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const FLAG_LACING: u32 = 0x9C;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const CODEC_DELAY: u32 = 0x56AA;
+const SEEK_PRE_ROLL: u32 = 0x56BB;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const CHANNELS: u32 = 0x9F;
+const CLUSTER: u32 = 0x1F43_B675;
+const TIMESTAMP: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const CUES: u32 = 0x1C53_BB6B;
+const CUE_POINT: u32 = 0xBB;
+const CUE_TIME: u32 = 0xB3;
+const CUE_TRACK_POSITIONS: u32 = 0xB7;
+const CUE_TRACK: u32 = 0xF7;
+const CUE_CLUSTER_POSITION: u32 = 0xF1;
+
+/// Track number of the video track
+const VIDEO_TRACK: u64 = 1;
+/// Track number of the audio track
+const AUDIO_TRACK: u64 = 2;
+
+/// Opus seek pre-roll recommended by the Matroska Opus mapping (80ms)
+const OPUS_SEEK_PRE_ROLL_NS: u64 = 80_000_000;
+
+/// Video track description
+#[derive(Debug, Clone)]
+pub struct MkvVideoTrack {
+    pub codec: DepacketizerCodec,
+    pub width: u32,
+    pub height: u32,
+    /// avcC / hvcC / av1C record (see [`video_codec_private`])
+    pub codec_private: Vec<u8>,
+}
+
+/// Audio track description (always Opus)
+#[derive(Debug, Clone)]
+pub struct MkvAudioTrack {
+    pub sample_rate: u32,
+    pub channels: u8,
+}
+
+/// Streaming Matroska writer
+///
+/// Timestamps passed to the writer are in milliseconds (TimestampScale = 1ms).
+pub struct MkvWriter<W: Write + Seek> {
+    out: W,
+    /// Offset of the 8-byte segment size field
+    segment_size_pos: u64,
+    /// Offset of the first byte of segment data
+    segment_data_start: u64,
+    /// Offset of the 8-byte Duration float
+    duration_pos: u64,
+    /// Offset and length of the SeekHead reserved for the Cues position
+    seek_head_pos: u64,
+    seek_head_len: usize,
+    /// Buffered SimpleBlocks of the current cluster
+    cluster: Vec<u8>,
+    cluster_timestamp: Option<u64>,
+    /// The current cluster starts with a video keyframe
+    cluster_keyframe: bool,
+    /// (timestamp, cluster offset in the segment) of each keyframe cluster
+    cues: Vec<(u64, u64)>,
+    last_timestamp: u64,
+}
+
+impl<W: Write + Seek> MkvWriter<W> {
+    /// Write the file header and track descriptions
+    pub fn new(
+        mut out: W,
+        video: &MkvVideoTrack,
+        audio: Option<&MkvAudioTrack>,
+    ) -> io::Result<Self> {
+        // EBML header
+        let mut header = Vec::new();
+        put_uint(&mut header, EBML_VERSION, 1);
+        put_uint(&mut header, EBML_READ_VERSION, 1);
+        put_uint(&mut header, EBML_MAX_ID_LENGTH, 4);
+        put_uint(&mut header, EBML_MAX_SIZE_LENGTH, 8);
+        put_string(&mut header, DOC_TYPE, "matroska");
+        put_uint(&mut header, DOC_TYPE_VERSION, 4);
+        put_uint(&mut header, DOC_TYPE_READ_VERSION, 2);
+        let mut buf = Vec::new();
+        put_master(&mut buf, EBML, &header);
+        out.write_all(&buf)?;
+
+        // Segment with unknown size, patched in finish()
+        let mut buf = Vec::new();
+        put_id(&mut buf, SEGMENT);
+        out.write_all(&buf)?;
+        let segment_size_pos = out.stream_position()?;
+        out.write_all(&[0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])?;
+        let segment_data_start = out.stream_position()?;
+
+        // SeekHead pointing at the Cues, position patched in finish()
+        let seek_head = seek_head(0);
+        let seek_head_pos = out.stream_position()?;
+        out.write_all(&seek_head)?;
+
+        // Segment info
+        let app = concat!("OpenNOW ", env!("CARGO_PKG_VERSION"));
+        let mut info = Vec::new();
+        put_uint(&mut info, TIMESTAMP_SCALE, 1_000_000);
+        put_id(&mut info, DURATION);
+        put_size(&mut info, 8);
+        let duration_offset = info.len();
+        info.extend_from_slice(&0f64.to_be_bytes());
+        put_string(&mut info, MUXING_APP, app);
+        put_string(&mut info, WRITING_APP, app);
+        let mut buf = Vec::new();
+        put_master(&mut buf, INFO, &info);
+        // Master header is ID (4 bytes) + size vint; locate the payload start
+        let info_header_len = buf.len() - info.len();
+        let duration_pos = out.stream_position()? + (info_header_len + duration_offset) as u64;
+        out.write_all(&buf)?;
+
+        // Tracks
+        let mut tracks = Vec::new();
+        put_master(&mut tracks, TRACK_ENTRY, &video_track_entry(video));
+        if let Some(audio) = audio {
+            put_master(&mut tracks, TRACK_ENTRY, &audio_track_entry(audio));
+        }
+        let mut buf = Vec::new();
+        put_master(&mut buf, TRACKS, &tracks);
+        out.write_all(&buf)?;
+
+        Ok(Self {
+            out,
+            segment_size_pos,
+            segment_data_start,
+            duration_pos,
+            seek_head_pos,
+            seek_head_len: seek_head.len(),
+            cluster: Vec::new(),
+            cluster_timestamp: None,
+            cluster_keyframe: false,
+            cues: Vec::new(),
+            last_timestamp: 0,
+        })
+    }
+
+    /// Write one video access unit (already converted with [`video_block_data`])
+    pub fn write_video(
+        &mut self,
+        timestamp_ms: u64,
+        data: &[u8],
+        keyframe: bool,
+    ) -> io::Result<()> {
+        // Start a new cluster on every keyframe so files can be cut and seeked
+        if keyframe && !self.cluster.is_empty() {
+            self.flush_cluster()?;
+        }
+        self.write_block(VIDEO_TRACK, timestamp_ms, data, keyframe)
+    }
+
+    /// Write one Opus packet
+    pub fn write_audio(&mut self, timestamp_ms: u64, data: &[u8]) -> io::Result<()> {
+        self.write_block(AUDIO_TRACK, timestamp_ms, data, true)
+    }
+
+    /// Flush buffered data, write the Cues, patch segment size, duration and
+    /// SeekHead, and return the output
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush_cluster()?;
+
+        // Cues need at least one point; without any the SeekHead becomes Void
+        let seek_head = if self.cues.is_empty() {
+            void(self.seek_head_len)
+        } else {
+            let cues_pos = self.out.stream_position()? - self.segment_data_start;
+            let mut cues = Vec::new();
+            for &(timestamp, cluster_pos) in &self.cues {
+                let mut positions = Vec::new();
+                put_uint(&mut positions, CUE_TRACK, VIDEO_TRACK);
+                put_uint(&mut positions, CUE_CLUSTER_POSITION, cluster_pos);
+                let mut point = Vec::new();
+                put_uint(&mut point, CUE_TIME, timestamp);
+                put_master(&mut point, CUE_TRACK_POSITIONS, &positions);
+                put_master(&mut cues, CUE_POINT, &point);
+            }
+            let mut buf = Vec::new();
+            put_master(&mut buf, CUES, &cues);
+            self.out.write_all(&buf)?;
+            seek_head(cues_pos)
+        };
+
+        let end = self.out.stream_position()?;
+        let segment_size = end - self.segment_data_start;
+        self.out.seek(SeekFrom::Start(self.segment_size_pos))?;
+        self.out
+            .write_all(&((0x01u64 << 56) | segment_size).to_be_bytes())?;
+        self.out.seek(SeekFrom::Start(self.duration_pos))?;
+        self.out
+            .write_all(&(self.last_timestamp as f64).to_be_bytes())?;
+        self.out.seek(SeekFrom::Start(self.seek_head_pos))?;
+        self.out.write_all(&seek_head)?;
+        self.out.seek(SeekFrom::Start(end))?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Timestamp of the most recent block in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.last_timestamp
+    }
+
+    fn write_block(
+        &mut self,
+        track: u64,
+        timestamp_ms: u64,
+        data: &[u8],
+        keyframe: bool,
+    ) -> io::Result<()> {
+        // Block timestamps are signed 16-bit offsets from the cluster timestamp
+        if let Some(base) = self.cluster_timestamp {
+            let relative = timestamp_ms as i64 - base as i64;
+            if i16::try_from(relative).is_err() {
+                self.flush_cluster()?;
+            }
+        }
+        if self.cluster_timestamp.is_none() {
+            self.cluster_keyframe = track == VIDEO_TRACK && keyframe;
+        }
+        let base = *self.cluster_timestamp.get_or_insert(timestamp_ms);
+        let relative = (timestamp_ms as i64 - base as i64) as i16;
+
+        let mut block = Vec::with_capacity(data.len() + 4);
+        put_size(&mut block, track);
+        block.extend_from_slice(&relative.to_be_bytes());
+        block.push(if keyframe { 0x80 } else { 0x00 });
+        block.extend_from_slice(data);
+        put_binary(&mut self.cluster, SIMPLE_BLOCK, &block);
+
+        self.last_timestamp = self.last_timestamp.max(timestamp_ms);
+        Ok(())
+    }
+
+    fn flush_cluster(&mut self) -> io::Result<()> {
+        let Some(base) = self.cluster_timestamp.take() else {
+            return Ok(());
+        };
+        if self.cluster_keyframe {
+            let position = self.out.stream_position()? - self.segment_data_start;
+            self.cues.push((base, position));
+        }
+        let mut payload = Vec::with_capacity(self.cluster.len() + 10);
+        put_uint(&mut payload, TIMESTAMP, base);
+        payload.append(&mut self.cluster);
+
+        let mut buf = Vec::with_capacity(payload.len() + 12);
+        put_master(&mut buf, CLUSTER, &payload);
+        self.out.write_all(&buf)
+    }
+}
+
+/// SeekHead with a single entry for the Cues at `cues_pos`, written with a
+/// fixed-width position so it can be patched in place
+fn seek_head(cues_pos: u64) -> Vec<u8> {
+    let mut seek = Vec::new();
+    put_binary(&mut seek, SEEK_ID, &CUES.to_be_bytes());
+    put_id(&mut seek, SEEK_POSITION);
+    put_size(&mut seek, 8);
+    seek.extend_from_slice(&cues_pos.to_be_bytes());
+    let mut entries = Vec::new();
+    put_master(&mut entries, SEEK, &seek);
+    let mut buf = Vec::new();
+    put_master(&mut buf, SEEK_HEAD, &entries);
+    buf
+}
+
+/// Void element filling exactly `len` bytes (len >= 9)
+fn void(len: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(len);
+    put_id(&mut buf, VOID);
+    // Size written as an 8-byte vint so the header length doesn't depend on it
+    let payload = len - 9;
+    buf.extend_from_slice(&((0x01u64 << 56) | payload as u64).to_be_bytes());
+    buf.resize(len, 0);
+    buf
+}
+
+fn video_track_entry(video: &MkvVideoTrack) -> Vec<u8> {
+    let codec_id = match video.codec {
+        DepacketizerCodec::H264 => "V_MPEG4/ISO/AVC",
+        DepacketizerCodec::H265 => "V_MPEGH/ISO/HEVC",
+        DepacketizerCodec::AV1 => "V_AV1",
+    };
+
+    let mut entry = Vec::new();
+    put_uint(&mut entry, TRACK_NUMBER, VIDEO_TRACK);
+    put_uint(&mut entry, TRACK_UID, VIDEO_TRACK);
+    put_uint(&mut entry, TRACK_TYPE, 1);
+    put_uint(&mut entry, FLAG_LACING, 0);
+    put_string(&mut entry, CODEC_ID, codec_id);
+    if !video.codec_private.is_empty() {
+        put_binary(&mut entry, CODEC_PRIVATE, &video.codec_private);
+    }
+    let mut settings = Vec::new();
+    put_uint(&mut settings, PIXEL_WIDTH, video.width as u64);
+    put_uint(&mut settings, PIXEL_HEIGHT, video.height as u64);
+    put_master(&mut entry, VIDEO, &settings);
+    entry
+}
+
+fn audio_track_entry(audio: &MkvAudioTrack) -> Vec<u8> {
+    let mut entry = Vec::new();
+    put_uint(&mut entry, TRACK_NUMBER, AUDIO_TRACK);
+    put_uint(&mut entry, TRACK_UID, AUDIO_TRACK);
+    put_uint(&mut entry, TRACK_TYPE, 2);
+    put_uint(&mut entry, FLAG_LACING, 0);
+    put_string(&mut entry, CODEC_ID, "A_OPUS");
+    put_binary(&mut entry, CODEC_PRIVATE, &opus_head(audio));
+    put_uint(&mut entry, CODEC_DELAY, 0);
+    put_uint(&mut entry, SEEK_PRE_ROLL, OPUS_SEEK_PRE_ROLL_NS);
+    let mut settings = Vec::new();
+    put_float(&mut settings, SAMPLING_FREQUENCY, audio.sample_rate as f64);
+    put_uint(&mut settings, CHANNELS, audio.channels as u64);
+    put_master(&mut entry, AUDIO, &settings);
+    entry
+}
+
+/// OpusHead identification header (RFC 7845 section 5.1)
+fn opus_head(audio: &MkvAudioTrack) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(audio.channels);
+    head.extend_from_slice(&0u16.to_le_bytes()); // pre-skip
+    head.extend_from_slice(&audio.sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family (mono/stereo)
+    head
+}
+
+// ============================================================================
+// EBML primitives
+// ============================================================================
+
+fn put_id(buf: &mut Vec<u8>, id: u32) {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    buf.extend_from_slice(&bytes[skip..]);
+}
+
+/// Encode an element data size as the shortest EBML variable-length integer
+fn put_size(buf: &mut Vec<u8>, size: u64) {
+    let mut len = 1;
+    // All-ones values are reserved for "unknown size"
+    while len < 8 && size >= (1u64 << (7 * len)) - 1 {
+        len += 1;
+    }
+    let value = size | (1u64 << (7 * len));
+    buf.extend_from_slice(&value.to_be_bytes()[8 - len..]);
+}
+
+fn put_uint(buf: &mut Vec<u8>, id: u32, value: u64) {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    put_id(buf, id);
+    put_size(buf, (8 - skip) as u64);
+    buf.extend_from_slice(&bytes[skip..]);
+}
+
+fn put_float(buf: &mut Vec<u8>, id: u32, value: f64) {
+    put_id(buf, id);
+    put_size(buf, 8);
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_string(buf: &mut Vec<u8>, id: u32, value: &str) {
+    put_binary(buf, id, value.as_bytes());
+}
+
+fn put_binary(buf: &mut Vec<u8>, id: u32, data: &[u8]) {
+    put_id(buf, id);
+    put_size(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+fn put_master(buf: &mut Vec<u8>, id: u32, payload: &[u8]) {
+    put_binary(buf, id, payload);
+}
+
+// ============================================================================
+// Bitstream helpers
+// ============================================================================
+
+/// Split an Annex B byte stream into NAL units (without start codes)
+pub fn split_annexb(data: &[u8]) -> Vec<&[u8]> {
+    let mut nals = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+
+    while i + 3 <= data.len() {
+        if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1 {
+            if let Some(s) = start {
+                // Trailing zero of a 4-byte start code belongs to the next start code
+                let mut end = i;
+                while end > s && data[end - 1] == 0 {
+                    end -= 1;
+                }
+                nals.push(&data[s..end]);
+            }
+            i += 3;
+            start = Some(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    if let Some(s) = start {
+        if s < data.len() {
+            nals.push(&data[s..]);
+        }
+    }
+
+    nals
+}
+
+/// Remove emulation prevention bytes (0x000003 -> 0x0000) from a NAL payload
+pub fn remove_emulation_prevention(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if i + 2 < data.len() && data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 3 {
+            result.extend_from_slice(&[0, 0]);
+            i += 3;
+        } else {
+            result.push(data[i]);
+            i += 1;
+        }
+    }
+    result
+}
+
+/// Check whether an access unit from the depacketizer is a random access point
+pub fn is_keyframe(codec: DepacketizerCodec, data: &[u8]) -> bool {
+    match codec {
+        DepacketizerCodec::H264 => split_annexb(data)
+            .iter()
+            .any(|nal| !nal.is_empty() && nal[0] & 0x1F == 5),
+        DepacketizerCodec::H265 => split_annexb(data)
+            .iter()
+            .any(|nal| !nal.is_empty() && HevcNalType::from((nal[0] >> 1) & 0x3F).is_rap()),
+        DepacketizerCodec::AV1 => av1_is_keyframe(data),
+    }
+}
+
+/// Convert an access unit into Matroska block payload
+///
+/// H.264/H.265 use 4-byte length-prefixed NAL units; AV1 keeps its
+/// low-overhead OBU format with temporal delimiters removed.
+pub fn video_block_data(codec: DepacketizerCodec, data: &[u8]) -> Vec<u8> {
+    match codec {
+        DepacketizerCodec::H264 | DepacketizerCodec::H265 => {
+            let mut out = Vec::with_capacity(data.len() + 16);
+            for nal in split_annexb(data) {
+                out.extend_from_slice(&(nal.len() as u32).to_be_bytes());
+                out.extend_from_slice(nal);
+            }
+            out
+        }
+        DepacketizerCodec::AV1 => {
+            let mut out = Vec::with_capacity(data.len());
+            for obu in parse_obus(data) {
+                if obu.obu_type != Av1ObuType::TemporalDelimiter {
+                    out.extend_from_slice(obu.data);
+                }
+            }
+            out
+        }
+    }
+}
+
+/// Build the CodecPrivate record for a keyframe access unit
+///
+/// Returns None if the required parameter sets are missing from `data`.
+pub fn video_codec_private(codec: DepacketizerCodec, data: &[u8]) -> Option<Vec<u8>> {
+    match codec {
+        DepacketizerCodec::H264 => avc_decoder_config(data),
+        DepacketizerCodec::H265 => hevc_decoder_config(data),
+        DepacketizerCodec::AV1 => av1_codec_config(data),
+    }
+}
+
+/// AVCDecoderConfigurationRecord (ISO/IEC 14496-15 5.3.3.1)
+fn avc_decoder_config(data: &[u8]) -> Option<Vec<u8>> {
+    let nals = split_annexb(data);
+    let sps = nals.iter().find(|n| !n.is_empty() && n[0] & 0x1F == 7)?;
+    let pps = nals.iter().find(|n| !n.is_empty() && n[0] & 0x1F == 8)?;
+    if sps.len() < 4 {
+        return None;
+    }
+
+    let mut out = vec![
+        1,      // configurationVersion
+        sps[1], // AVCProfileIndication
+        sps[2], // profile_compatibility
+        sps[3], // AVCLevelIndication
+        0xFF,   // lengthSizeMinusOne = 3
+        0xE1,   // numOfSequenceParameterSets = 1
+    ];
+    out.extend_from_slice(&(sps.len() as u16).to_be_bytes());
+    out.extend_from_slice(sps);
+    out.push(1); // numOfPictureParameterSets
+    out.extend_from_slice(&(pps.len() as u16).to_be_bytes());
+    out.extend_from_slice(pps);
+    Some(out)
+}
+
+/// HEVCDecoderConfigurationRecord (ISO/IEC 14496-15 8.3.3.1)
+fn hevc_decoder_config(data: &[u8]) -> Option<Vec<u8>> {
+    let mut parser = HevcParser::new();
+    let units = parser.find_nal_units(data);
+
+    let find = |nal_type: HevcNalType| units.iter().find(|n| n.nal_type == nal_type);
+    let vps = find(HevcNalType::VpsNut)?;
+    let sps = find(HevcNalType::SpsNut)?;
+    let pps = find(HevcNalType::PpsNut)?;
+
+    let sps_id = parser.parse_sps(sps).ok()?;
+    let parsed = parser.sps[sps_id as usize & 0x0F].as_ref()?;
+
+    // general_profile_tier_level (12 bytes) follows the first SPS byte in the RBSP
+    let rbsp = remove_emulation_prevention(&sps.data[2..]);
+    if rbsp.len() < 13 {
+        return None;
+    }
+    let ptl = &rbsp[1..13];
+
+    let mut out = Vec::with_capacity(64 + vps.data.len() + sps.data.len() + pps.data.len());
+    out.push(1); // configurationVersion
+    out.extend_from_slice(ptl); // profile_space..general_level_idc
+    out.extend_from_slice(&[0xF0, 0x00]); // min_spatial_segmentation_idc
+    out.push(0xFC); // parallelismType
+    out.push(0xFC | (parsed.chroma_format_idc & 0x03));
+    out.push(0xF8 | (parsed.bit_depth_luma.saturating_sub(8) & 0x07));
+    out.push(0xF8 | (parsed.bit_depth_chroma.saturating_sub(8) & 0x07));
+    out.extend_from_slice(&[0x00, 0x00]); // avgFrameRate (unspecified)
+
+    // constantFrameRate=0, numTemporalLayers, temporalIdNested=1, lengthSizeMinusOne=3
+    out.push(((parsed.max_sub_layers & 0x07) << 3) | 0x04 | 0x03);
+
+    out.push(3); // numOfArrays
+    for nal in [vps, sps, pps] {
+        out.push(0x80 | nal.nal_type as u8); // array_completeness=1
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&(nal.data.len() as u16).to_be_bytes());
+        out.extend_from_slice(&nal.data);
+    }
+    Some(out)
+}
+
+/// AV1CodecConfigurationRecord (AV1 ISOBMFF binding, section 2.3)
+fn av1_codec_config(data: &[u8]) -> Option<Vec<u8>> {
+    let obu = parse_obus(data)
+        .into_iter()
+        .find(|obu| obu.obu_type == Av1ObuType::SequenceHeader)?;
+    let mut parser = Av1Parser::new();
+    let seq = parser.parse_sequence_header(&obu).ok()?;
+    Some(seq.codec_config_record())
+}
+
+/// Detect an AV1 key frame from the first frame header in a temporal unit
+fn av1_is_keyframe(data: &[u8]) -> bool {
+    let mut parser = Av1Parser::new();
+    parser.process_temporal_unit(data);
+    parser
+        .parse_frame_header(data)
+        .is_some_and(|header| header.is_keyframe())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ebml_size_encoding() {
+        let mut buf = Vec::new();
+        put_size(&mut buf, 5);
+        assert_eq!(buf, [0x85]);
+
+        // 127 is reserved in 1-byte form, must use 2 bytes
+        let mut buf = Vec::new();
+        put_size(&mut buf, 127);
+        assert_eq!(buf, [0x40, 0x7F]);
+
+        let mut buf = Vec::new();
+        put_size(&mut buf, 300);
+        assert_eq!(buf, [0x41, 0x2C]);
+    }
+
+    #[test]
+    fn test_split_annexb() {
+        let data = [
+            0, 0, 0, 1, 0x67, 1, 2, 0, 0, 1, 0x68, 3, 0, 0, 0, 1, 0x65, 4,
+        ];
+        let nals = split_annexb(&data);
+        assert_eq!(
+            nals,
+            vec![&[0x67, 1, 2][..], &[0x68, 3][..], &[0x65, 4][..]]
+        );
+    }
+
+    #[test]
+    fn test_h264_keyframe_and_config() {
+        let idr = [
+            0, 0, 0, 1, 0x67, 0x64, 0x00, 0x1F, 0xAC, 0, 0, 0, 1, 0x68, 0xEE, 0x3C, 0x80, 0, 0, 0,
+            1, 0x65, 0x88, 0x84,
+        ];
+        assert!(is_keyframe(DepacketizerCodec::H264, &idr));
+        assert!(!is_keyframe(
+            DepacketizerCodec::H264,
+            &[0, 0, 0, 1, 0x41, 0x9A]
+        ));
+
+        let avcc = video_codec_private(DepacketizerCodec::H264, &idr).unwrap();
+        assert_eq!(&avcc[..6], &[1, 0x64, 0x00, 0x1F, 0xFF, 0xE1]);
+
+        let block = video_block_data(DepacketizerCodec::H264, &idr);
+        assert_eq!(&block[..4], &[0, 0, 0, 5]);
+    }
+
+    #[test]
+    fn test_av1_keyframe() {
+        // Temporal delimiter followed by an OBU_FRAME with frame_type = KEY_FRAME
+        let key = [0x12, 0x00, 0x32, 0x02, 0x10, 0x00];
+        assert!(av1_is_keyframe(&key));
+        // frame_type = INTER_FRAME
+        let inter = [0x12, 0x00, 0x32, 0x02, 0x30, 0x00];
+        assert!(!av1_is_keyframe(&inter));
+
+        // Temporal delimiter is stripped from block data
+        assert_eq!(video_block_data(DepacketizerCodec::AV1, &key), &key[2..]);
+    }
+
+    #[test]
+    fn test_writer_patches_segment() {
+        let video = MkvVideoTrack {
+            codec: DepacketizerCodec::H264,
+            width: 1920,
+            height: 1080,
+            codec_private: vec![1, 2, 3],
+        };
+        let audio = MkvAudioTrack {
+            sample_rate: 48000,
+            channels: 2,
+        };
+
+        let mut writer = MkvWriter::new(Cursor::new(Vec::new()), &video, Some(&audio)).unwrap();
+        writer.write_video(0, &[0, 0, 0, 1, 0x65], true).unwrap();
+        writer.write_audio(10, &[0xFC, 0xFF]).unwrap();
+        writer.write_video(16, &[0, 0, 0, 1, 0x41], false).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        assert_eq!(&data[..4], &[0x1A, 0x45, 0xDF, 0xA3]);
+        let segment = data
+            .windows(4)
+            .position(|w| w == [0x18, 0x53, 0x80, 0x67])
+            .unwrap();
+        let size = u64::from_be_bytes(data[segment + 4..segment + 12].try_into().unwrap());
+        assert_eq!(size & !(0x01 << 56), (data.len() - segment - 12) as u64);
+
+        // Walk the Info children to the Duration float
+        let info = data
+            .windows(4)
+            .position(|w| w == INFO.to_be_bytes())
+            .unwrap();
+        let (info_size, size_len) = read_vint(&data[info + 4..]);
+        let mut pos = info + 4 + size_len;
+        let info_end = pos + info_size as usize;
+        let mut duration = None;
+        while pos < info_end {
+            let (id, id_len) = read_vint(&data[pos..]);
+            let id = id | (1 << (7 * id_len));
+            let (len, len_len) = read_vint(&data[pos + id_len..]);
+            let payload = pos + id_len + len_len;
+            if id == DURATION as u64 {
+                let bytes = data[payload..payload + len as usize].try_into().unwrap();
+                duration = Some(f64::from_be_bytes(bytes));
+            }
+            pos = payload + len as usize;
+        }
+        assert_eq!(duration, Some(16.0));
+    }
+
+    /// EBML variable-length integer: (value without the length marker, length)
+    fn read_vint(data: &[u8]) -> (u64, usize) {
+        let len = data[0].leading_zeros() as usize + 1;
+        let value = data[1..len]
+            .iter()
+            .fold((data[0] & (0xFF >> len)) as u64, |acc, &b| {
+                (acc << 8) | b as u64
+            });
+        (value, len)
+    }
+
+    #[test]
+    fn test_writer_cues_point_at_keyframe_clusters() {
+        let video = MkvVideoTrack {
+            codec: DepacketizerCodec::H264,
+            width: 1280,
+            height: 720,
+            codec_private: vec![1, 2, 3],
+        };
+        let mut writer = MkvWriter::new(Cursor::new(Vec::new()), &video, None).unwrap();
+        writer.write_video(0, &[0, 0, 0, 1, 0x65], true).unwrap();
+        writer.write_video(16, &[0, 0, 0, 1, 0x41], false).unwrap();
+        writer.write_video(1000, &[0, 0, 0, 1, 0x65], true).unwrap();
+        // Too far from the cluster start: new cluster without a cue
+        writer
+            .write_video(40_000, &[0, 0, 0, 1, 0x41], false)
+            .unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let find = |pattern: &[u8]| {
+            data.windows(pattern.len())
+                .position(|w| w == pattern)
+                .unwrap()
+        };
+        let segment_data = find(&[0x18, 0x53, 0x80, 0x67]) + 12;
+
+        // SeekHead leads the segment and points at the Cues
+        assert_eq!(
+            &data[segment_data..segment_data + 4],
+            &[0x11, 0x4D, 0x9B, 0x74]
+        );
+        let position = find(&[0x53, 0xAC, 0x88]) + 3;
+        let cues_pos = u64::from_be_bytes(data[position..position + 8].try_into().unwrap());
+        let cues = segment_data + cues_pos as usize;
+        assert_eq!(&data[cues..cues + 4], &[0x1C, 0x53, 0xBB, 0x6B]);
+
+        // Two cue points, each at a cluster
+        let cue_points: Vec<usize> = data[cues..]
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| *w == [0xF1, 0x81] || *w == [0xF1, 0x82])
+            .map(|(i, _)| cues + i)
+            .collect();
+        assert_eq!(cue_points.len(), 2);
+        for i in cue_points {
+            let len = (data[i + 1] & 0x7F) as usize;
+            let cluster_pos = data[i + 2..i + 2 + len]
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            let cluster = segment_data + cluster_pos;
+            assert_eq!(&data[cluster..cluster + 4], &[0x1F, 0x43, 0xB6, 0x75]);
+        }
+    }
+
+    #[test]
+    fn test_writer_without_keyframes_voids_seek_head() {
+        let video = MkvVideoTrack {
+            codec: DepacketizerCodec::H264,
+            width: 1280,
+            height: 720,
+            codec_private: vec![1, 2, 3],
+        };
+        let audio = MkvAudioTrack {
+            sample_rate: 48000,
+            channels: 2,
+        };
+        let mut writer = MkvWriter::new(Cursor::new(Vec::new()), &video, Some(&audio)).unwrap();
+        writer.write_audio(0, &[0xFC, 0xFF]).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let segment_data = data
+            .windows(4)
+            .position(|w| w == [0x18, 0x53, 0x80, 0x67])
+            .unwrap()
+            + 12;
+        assert_eq!(data[segment_data], 0xEC);
+        let void_size =
+            u64::from_be_bytes(data[segment_data + 1..segment_data + 9].try_into().unwrap());
+        // Segment info follows right after the Void
+        let info = segment_data + 9 + (void_size & !(0x01 << 56)) as usize;
+        assert_eq!(&data[info..info + 4], &[0x15, 0x49, 0xA9, 0x66]);
+        assert!(!data.windows(4).any(|w| w == [0x1C, 0x53, 0xBB, 0x6B]));
+    }
+}
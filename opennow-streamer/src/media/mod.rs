@@ -5,7 +5,20 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 
 mod audio;
+pub mod av1_parser;
+mod decoder_probe;
+pub mod frame_pacer;
+pub mod h264_parser;
+pub mod hevc_parser;
+pub mod mkv;
+mod recorder;
+#[cfg(feature = "builtin-opus")]
+mod opus;
+mod replay;
 mod rtp;
+mod screenshot;
+mod session_log;
+pub mod stats_history;
 mod video;
 
 /// Global frame ID counter for unique frame identification
@@ -26,9 +39,6 @@ pub mod d3d11;
 #[cfg(target_os = "windows")]
 pub mod dxva_decoder;
 
-#[cfg(target_os = "windows")]
-pub mod hevc_parser;
-
 #[cfg(target_os = "windows")]
 pub mod native_video;
 
@@ -44,7 +54,16 @@ pub mod v4l2;
 pub mod gstreamer_decoder;
 
 pub use audio::*;
-pub use rtp::{DepacketizerCodec, RtpDepacketizer};
+pub use decoder_probe::{
+    decoder_capabilities, fallback_chain, is_probe_running, start_decoder_probe,
+    DecoderCapabilities, DecoderWatchdog, PROBE_CODECS,
+};
+pub use frame_pacer::{FramePacer, PacingStats};
+pub use recorder::{capture_file_path, RecordingStreamInfo, StreamRecorder};
+pub use rtp::{DepacketizerCodec, JitterEstimator, RtpDepacketizer};
+pub use screenshot::ScreenshotImage;
+pub use session_log::{SessionEvent, SessionLog};
+pub use stats_history::{MetricGraph, StatsHistory, StatsMetric};
 pub use video::{get_supported_decoder_backends, DecodeStats, UnifiedVideoDecoder, VideoDecoder};
 
 #[cfg(target_os = "macos")]
@@ -322,10 +341,28 @@ pub struct StreamStats {
     pub input_rate: f32,
     /// Frame delivery latency (RTP arrival to decode complete) in ms
     pub frame_delivery_ms: f32,
+    /// Per-frame decode times since the previous update (graphs)
+    pub decode_samples_ms: Vec<f32>,
+    /// Per-frame delivery latencies since the previous update (graphs)
+    pub delivery_samples_ms: Vec<f32>,
     /// Estimated end-to-end latency in ms (decode_time + estimated network)
     pub estimated_e2e_ms: f32,
     /// Audio buffer level in ms
     pub audio_buffer_ms: f32,
+    /// Total video packets lost (RTP sequence gaps)
+    pub video_packets_lost: u64,
+    /// Total audio packets lost (RTP sequence gaps)
+    pub audio_packets_lost: u64,
+    /// Total audio frames concealed by PLC/FEC
+    pub audio_frames_concealed: u64,
+    /// Frame pacing active (counters below are only filled in then)
+    pub frame_pacing: bool,
+    /// Refreshes that showed a new frame
+    pub frames_presented: u64,
+    /// Refreshes that repeated the previous frame
+    pub frames_repeated: u64,
+    /// Frames replaced before they were shown
+    pub frames_skipped: u64,
     /// HDR mode (true = HDR/PQ, false = SDR)
     pub is_hdr: bool,
     /// Color space (e.g., "BT.709", "BT.2020")
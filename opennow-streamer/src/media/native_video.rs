@@ -24,8 +24,10 @@ use crate::app::{SharedFrame, VideoCodec};
 /// Stats from the native decoder thread
 #[derive(Debug, Clone)]
 pub struct NativeDecodeStats {
-    /// Time from packet receive to decode complete (ms)
+    /// Time the decoder spent on the packet (ms)
     pub decode_time_ms: f32,
+    /// Time from packet receive to decode complete (ms)
+    pub delivery_time_ms: f32,
     /// Whether a frame was produced
     pub frame_produced: bool,
     /// Whether a keyframe is needed
@@ -38,6 +40,7 @@ enum NativeDecoderCommand {
     DecodeAsync {
         data: Vec<u8>,
         receive_time: std::time::Instant,
+        timestamp_us: u64,
     },
     /// Update decoder configuration (resolution change)
     Configure {
@@ -118,7 +121,11 @@ impl NativeVideoDecoder {
 
             while let Ok(cmd) = cmd_rx.recv() {
                 match cmd {
-                    NativeDecoderCommand::DecodeAsync { data, receive_time } => {
+                    NativeDecoderCommand::DecodeAsync {
+                        data,
+                        receive_time,
+                        timestamp_us,
+                    } => {
                         // Parse HEVC NAL units to extract SPS for dimensions
                         let nals = hevc_parser.find_nal_units(&data);
                         for nal in &nals {
@@ -156,9 +163,11 @@ impl NativeVideoDecoder {
                                     }
                                     Err(e) => {
                                         warn!("Failed to create DXVA decoder: {:?}", e);
+                                        let delivery_time_ms =
+                                            receive_time.elapsed().as_secs_f32() * 1000.0;
                                         let _ = stats_tx.try_send(NativeDecodeStats {
-                                            decode_time_ms: receive_time.elapsed().as_secs_f32()
-                                                * 1000.0,
+                                            decode_time_ms: 0.0,
+                                            delivery_time_ms,
                                             frame_produced: false,
                                             needs_keyframe: true,
                                         });
@@ -172,6 +181,7 @@ impl NativeVideoDecoder {
                         let mut frame_produced = false;
                         let mut needs_keyframe = false;
 
+                        let decode_start = std::time::Instant::now();
                         if let Some(ref mut dec) = decoder {
                             // Decode HEVC frame
                             match dec.decode_frame(&data, &mut hevc_parser) {
@@ -183,7 +193,8 @@ impl NativeVideoDecoder {
                                     // Convert to VideoFrame and write to SharedFrame
                                     // Zero-copy: GPU texture passed directly to renderer
                                     let video_frame = Self::convert_decoded_frame(&decoded, is_hdr);
-                                    if let Some(frame) = video_frame {
+                                    if let Some(mut frame) = video_frame {
+                                        frame.timestamp_us = timestamp_us;
                                         shared_frame.write(frame);
                                     }
                                 }
@@ -209,7 +220,8 @@ impl NativeVideoDecoder {
 
                         // Send stats
                         let _ = stats_tx.try_send(NativeDecodeStats {
-                            decode_time_ms: receive_time.elapsed().as_secs_f32() * 1000.0,
+                            decode_time_ms: decode_start.elapsed().as_secs_f32() * 1000.0,
+                            delivery_time_ms: receive_time.elapsed().as_secs_f32() * 1000.0,
                             frame_produced,
                             needs_keyframe,
                         });
@@ -326,10 +338,12 @@ impl NativeVideoDecoder {
     }
 
     /// Send a packet for async decoding
-    pub fn decode_async(&self, data: Vec<u8>, receive_time: std::time::Instant) {
-        let _ = self
-            .cmd_tx
-            .send(NativeDecoderCommand::DecodeAsync { data, receive_time });
+    pub fn decode_async(&self, data: Vec<u8>, receive_time: std::time::Instant, timestamp_us: u64) {
+        let _ = self.cmd_tx.send(NativeDecoderCommand::DecodeAsync {
+            data,
+            receive_time,
+            timestamp_us,
+        });
     }
 
     /// Get frames decoded count
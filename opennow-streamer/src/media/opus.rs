@@ -0,0 +1,261 @@
+//! Built-in Opus Decoder
+//!
+//! In-process Opus decoding with libopus, independent of GStreamer/FFmpeg
+//! plugins. Lost packets are concealed with libopus PLC, and the last lost
+//! frame is recovered from the following packet's in-band FEC when present.
+
+use anyhow::{anyhow, Result};
+
+use super::audio::opus_packet_duration;
+
+/// Longest Opus packet (120ms)
+const MAX_PACKET_MS: usize = 120;
+
+/// Opus decoder producing interleaved i16 samples
+pub struct OpusDecoder {
+    decoder: opus::Decoder,
+    sample_rate: u32,
+    channels: usize,
+    /// Samples per channel of the last decoded packet (sizes PLC output)
+    last_frame_samples: usize,
+}
+
+impl OpusDecoder {
+    pub fn new(sample_rate: u32, channels: u32) -> Result<Self> {
+        let layout = match channels {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            n => return Err(anyhow!("Unsupported Opus channel count: {}", n)),
+        };
+        let decoder = opus::Decoder::new(sample_rate, layout)
+            .map_err(|e| anyhow!("Failed to create Opus decoder: {}", e))?;
+
+        Ok(Self {
+            decoder,
+            sample_rate,
+            channels: channels as usize,
+            // 20ms until the first packet tells us otherwise
+            last_frame_samples: sample_rate as usize / 50,
+        })
+    }
+
+    /// Decode one packet
+    pub fn decode(&mut self, packet: &[u8]) -> Result<Vec<i16>> {
+        let max_samples = self.sample_rate as usize * MAX_PACKET_MS / 1000;
+        let samples = self.decode_frame(packet, max_samples, false)?;
+        self.last_frame_samples = samples.len() / self.channels;
+        Ok(samples)
+    }
+
+    /// Synthesize audio for `lost` missing packets
+    ///
+    /// Pass the packet that followed the gap as `next_packet` to recover the
+    /// last lost frame from its FEC data; it still has to be decoded normally
+    /// afterwards.
+    pub fn conceal(&mut self, lost: u16, next_packet: Option<&[u8]>) -> Result<Vec<i16>> {
+        let frame_samples = next_packet
+            .and_then(opus_packet_duration)
+            .map(|d| (d.as_micros() as usize * self.sample_rate as usize) / 1_000_000)
+            .unwrap_or(self.last_frame_samples);
+
+        let mut samples = Vec::with_capacity(frame_samples * self.channels * lost as usize);
+        for i in 0..lost {
+            let frame = match next_packet {
+                // libopus falls back to PLC if the packet carries no FEC
+                Some(packet) if i + 1 == lost => self.decode_frame(packet, frame_samples, true)?,
+                _ => self.decode_frame(&[], frame_samples, false)?,
+            };
+            samples.extend_from_slice(&frame);
+        }
+        Ok(samples)
+    }
+
+    /// Range coder state after the last decode (RFC 6716 conformance check)
+    pub fn final_range(&mut self) -> Result<u32> {
+        self.decoder
+            .get_final_range()
+            .map_err(|e| anyhow!("Failed to read Opus final range: {}", e))
+    }
+
+    fn decode_frame(&mut self, input: &[u8], frame_samples: usize, fec: bool) -> Result<Vec<i16>> {
+        let mut pcm = vec![0i16; frame_samples * self.channels];
+        let decoded = self
+            .decoder
+            .decode(input, &mut pcm, fec)
+            .map_err(|e| anyhow!("Opus decode failed: {}", e))?;
+        pcm.truncate(decoded * self.channels);
+        Ok(pcm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SAMPLE_RATE: u32 = 48000;
+    const FRAME_SAMPLES: usize = 960; // 20ms
+
+    /// Stereo 1kHz sine, 20ms frames
+    fn sine_frames(count: usize) -> Vec<Vec<i16>> {
+        (0..count)
+            .map(|f| {
+                (0..FRAME_SAMPLES)
+                    .flat_map(|i| {
+                        let t = (f * FRAME_SAMPLES + i) as f32 / SAMPLE_RATE as f32;
+                        let s = ((t * 1000.0 * std::f32::consts::TAU).sin() * 8000.0) as i16;
+                        [s, s]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn encode(frames: &[Vec<i16>]) -> Vec<Vec<u8>> {
+        let mut encoder = opus::Encoder::new(
+            SAMPLE_RATE,
+            opus::Channels::Stereo,
+            opus::Application::Audio,
+        )
+        .unwrap();
+        encoder.set_inband_fec(true).unwrap();
+        encoder.set_packet_loss_perc(20).unwrap();
+        frames
+            .iter()
+            .map(|pcm| {
+                let mut packet = vec![0u8; 4000];
+                let len = encoder.encode(pcm, &mut packet).unwrap();
+                packet.truncate(len);
+                packet
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[i16]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        (sum / samples.len().max(1) as f64).sqrt()
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let frames = sine_frames(50);
+        let packets = encode(&frames);
+        let mut decoder = OpusDecoder::new(SAMPLE_RATE, 2).unwrap();
+
+        let decoded: Vec<i16> = packets
+            .iter()
+            .flat_map(|p| decoder.decode(p).unwrap())
+            .collect();
+        assert_eq!(decoded.len(), 50 * FRAME_SAMPLES * 2);
+
+        // Skip encoder lookahead, then the level should match the source
+        let source: Vec<i16> = frames.concat();
+        let skip = 10 * FRAME_SAMPLES * 2;
+        let ratio = rms(&decoded[skip..]) / rms(&source[skip..]);
+        assert!((0.9..1.1).contains(&ratio), "level ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_conceal_keeps_timeline() {
+        let packets = encode(&sine_frames(20));
+        let mut decoder = OpusDecoder::new(SAMPLE_RATE, 2).unwrap();
+
+        let mut total = 0;
+        for (i, packet) in packets.iter().enumerate() {
+            match i {
+                // Packets 8 and 9 are lost
+                8 | 9 => continue,
+                10 => total += decoder.conceal(2, Some(packet)).unwrap().len(),
+                _ => {}
+            }
+            total += decoder.decode(packet).unwrap().len();
+        }
+        assert_eq!(total, 20 * FRAME_SAMPLES * 2);
+
+        // Without a following packet, PLC uses the last frame size
+        assert_eq!(decoder.conceal(1, None).unwrap().len(), FRAME_SAMPLES * 2);
+    }
+
+    /// Parse an opus_demo bitstream: per packet a big-endian length and
+    /// encoder final range, followed by the payload
+    fn read_bitstream(data: &[u8]) -> Vec<(Vec<u8>, u32)> {
+        let mut packets = Vec::new();
+        let mut pos = 0;
+        while pos + 8 <= data.len() {
+            let len = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+            let range = u32::from_be_bytes(data[pos + 4..pos + 8].try_into().unwrap());
+            pos += 8;
+            packets.push((data[pos..pos + len].to_vec(), range));
+            pos += len;
+        }
+        packets
+    }
+
+    /// Decode an opus_demo bitstream, checking the range coder state after
+    /// every packet against the encoder's (bit-exact) and the output against
+    /// the reference PCM (float and fixed-point builds differ by rounding)
+    fn check_vector(name: &str, bitstream: &[u8], reference: &[u8]) {
+        let reference: Vec<i16> = reference
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+
+        let mut decoder = OpusDecoder::new(SAMPLE_RATE, 2).unwrap();
+        let mut decoded = Vec::with_capacity(reference.len());
+        for (i, (packet, range)) in read_bitstream(bitstream).iter().enumerate() {
+            if packet.is_empty() {
+                decoded.extend(decoder.conceal(1, None).unwrap());
+                continue;
+            }
+            decoded.extend(decoder.decode(packet).unwrap());
+            assert_eq!(
+                decoder.final_range().unwrap(),
+                *range,
+                "{}: range mismatch at packet {}",
+                name,
+                i
+            );
+        }
+
+        assert_eq!(decoded.len(), reference.len(), "{}: length", name);
+        let noise: f64 = decoded
+            .iter()
+            .zip(&reference)
+            .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+            .sum();
+        let signal: f64 = reference.iter().map(|&s| (s as f64).powi(2)).sum();
+        let snr_db = 10.0 * (signal / noise.max(1.0)).log10();
+        assert!(snr_db > 40.0, "{}: SNR {:.1} dB", name, snr_db);
+    }
+
+    /// Reference vector: 240ms of stereo chirp/tones encoded at 64 kb/s with
+    /// libopus' opus_demo (`-e audio 48000 2 64000 -framesize 20`) and
+    /// decoded with `opus_demo -d 48000 2`
+    #[test]
+    fn test_reference_vector() {
+        check_vector(
+            "vector",
+            include_bytes!("../../tests/fixtures/opus/vector.bit"),
+            include_bytes!("../../tests/fixtures/opus/vector.dec"),
+        );
+    }
+
+    /// The full RFC 8251 test vector set (too large to commit)
+    ///
+    /// Download opus_testvectors-rfc8251.tar.gz from opus-codec.org, extract
+    /// it and run with OPUS_TESTVECTORS_DIR pointing at the extracted folder.
+    #[test]
+    #[ignore = "requires OPUS_TESTVECTORS_DIR"]
+    fn test_rfc8251_vectors() {
+        let dir = PathBuf::from(
+            std::env::var("OPUS_TESTVECTORS_DIR").expect("OPUS_TESTVECTORS_DIR not set"),
+        );
+
+        for n in 1..=12 {
+            let name = format!("testvector{:02}", n);
+            let bitstream = std::fs::read(dir.join(format!("{}.bit", name))).unwrap();
+            let reference = std::fs::read(dir.join(format!("{}.dec", name))).unwrap();
+            check_vector(&name, &bitstream, &reference);
+        }
+    }
+}
@@ -0,0 +1,611 @@
+//! Session Recorder
+//!
+//! Records the stream to a local Matroska file without re-encoding.
+//! The streaming loop taps depacketized video access units and Opus packets
+//! (before decoding); a writer thread remuxes them with timestamps derived
+//! from the RTP clocks so audio and video stay in sync.
+//!
+//! The same taps feed the instant replay buffer, which can be saved to disk
+//! independently of an explicit recording.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use log::{error, info, warn};
+use parking_lot::Mutex;
+
+use super::mkv::{self, MkvAudioTrack, MkvVideoTrack, MkvWriter};
+use super::replay::ReplayBuffer;
+use super::rtp::DepacketizerCodec;
+
+/// RTP clock rate of the video track
+pub const VIDEO_CLOCK_RATE: u32 = 90_000;
+/// RTP clock rate of the Opus audio track
+pub const AUDIO_CLOCK_RATE: u32 = 48_000;
+
+/// Max queued packets before the writer is considered stalled (~10s at 120fps + audio)
+const WRITER_QUEUE_SIZE: usize = 4096;
+
+/// Properties of the current stream needed to write file headers
+#[derive(Debug, Clone, Copy)]
+pub struct RecordingStreamInfo {
+    pub codec: DepacketizerCodec,
+    pub width: u32,
+    pub height: u32,
+    pub audio_sample_rate: u32,
+    pub audio_channels: u8,
+}
+
+/// Packet captured from the stream, queued for the writer thread
+#[derive(Clone)]
+pub enum CapturedPacket {
+    Video {
+        data: Vec<u8>,
+        rtp_timestamp: u32,
+        arrival: Instant,
+    },
+    Audio {
+        data: Vec<u8>,
+        rtp_timestamp: u32,
+        arrival: Instant,
+    },
+}
+
+enum RecorderCommand {
+    Packet(CapturedPacket),
+    Stop,
+}
+
+/// Active recording state
+struct ActiveRecording {
+    tx: mpsc::SyncSender<RecorderCommand>,
+    thread: Option<JoinHandle<()>>,
+    path: PathBuf,
+    started: Instant,
+    codec: DepacketizerCodec,
+    /// Packets dropped since the writer last kept up
+    dropped: u64,
+    /// A video packet was dropped; later ones would reference it, so video
+    /// is skipped until the next keyframe
+    awaiting_keyframe: bool,
+}
+
+impl ActiveRecording {
+    fn new(
+        tx: mpsc::SyncSender<RecorderCommand>,
+        thread: Option<JoinHandle<()>>,
+        path: PathBuf,
+        codec: DepacketizerCodec,
+    ) -> Self {
+        Self {
+            tx,
+            thread,
+            path,
+            started: Instant::now(),
+            codec,
+            dropped: 0,
+            awaiting_keyframe: false,
+        }
+    }
+
+    /// Queue a packet for the writer without blocking.
+    /// Returns true if a keyframe is needed to resume video.
+    fn send(&mut self, packet: CapturedPacket) -> bool {
+        let is_video = match &packet {
+            CapturedPacket::Video { data, .. } => {
+                if self.awaiting_keyframe && !mkv::is_keyframe(self.codec, data) {
+                    self.dropped += 1;
+                    return false;
+                }
+                true
+            }
+            CapturedPacket::Audio { .. } => false,
+        };
+
+        match self.tx.try_send(RecorderCommand::Packet(packet)) {
+            Ok(()) => {
+                if is_video {
+                    self.awaiting_keyframe = false;
+                }
+                if self.dropped > 0 && !self.awaiting_keyframe {
+                    warn!(
+                        "Recorder dropped {} packets while the writer was behind",
+                        self.dropped
+                    );
+                    self.dropped = 0;
+                }
+                false
+            }
+            Err(_) => {
+                if self.dropped == 0 {
+                    warn!("Recorder queue full, dropping packets until the writer catches up");
+                }
+                self.dropped += 1;
+                let resync = is_video && !self.awaiting_keyframe;
+                self.awaiting_keyframe |= is_video;
+                resync
+            }
+        }
+    }
+}
+
+/// Records the active stream to disk
+///
+/// Shared between the UI (start/stop) and the streaming loop (packet taps).
+/// Packet taps are a single atomic load when not recording.
+pub struct StreamRecorder {
+    recording: AtomicBool,
+    stream_info: Mutex<Option<RecordingStreamInfo>>,
+    active: Mutex<Option<ActiveRecording>>,
+    replay_active: AtomicBool,
+    replay: Mutex<Option<ReplayBuffer>>,
+    /// Replay length and max bitrate, applied whenever a stream starts
+    replay_config: Mutex<Option<(Duration, u32)>>,
+}
+
+impl StreamRecorder {
+    pub fn new() -> Self {
+        Self {
+            recording: AtomicBool::new(false),
+            stream_info: Mutex::new(None),
+            active: Mutex::new(None),
+            replay_active: AtomicBool::new(false),
+            replay: Mutex::new(None),
+            replay_config: Mutex::new(None),
+        }
+    }
+
+    /// Set codec and resolution of the current stream (called by the streaming loop)
+    pub fn set_stream_info(&self, info: RecordingStreamInfo) {
+        *self.stream_info.lock() = Some(info);
+        self.reset_replay_buffer();
+    }
+
+    /// Forget the current stream and drop buffered replay data
+    pub fn clear_stream(&self) {
+        *self.stream_info.lock() = None;
+        self.reset_replay_buffer();
+    }
+
+    /// Whether a recording is in progress
+    pub fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+    }
+
+    /// Time since the recording was started
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.active.lock().as_ref().map(|a| a.started.elapsed())
+    }
+
+    /// Start recording to `path`
+    ///
+    /// The file is created when the first keyframe arrives; callers should
+    /// request a keyframe so the recording starts promptly.
+    pub fn start(&self, path: PathBuf) -> Result<()> {
+        let mut active = self.active.lock();
+        if active.is_some() {
+            return Err(anyhow::anyhow!("Recording already in progress"));
+        }
+        let info = (*self.stream_info.lock()).context("No active stream to record")?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let (tx, rx) = mpsc::sync_channel(WRITER_QUEUE_SIZE);
+        let thread_path = path.clone();
+        let thread = std::thread::Builder::new()
+            .name("stream-recorder".to_string())
+            .spawn(move || run_writer(rx, thread_path, info))
+            .context("Failed to spawn recorder thread")?;
+
+        info!("Recording started: {}", path.display());
+        *active = Some(ActiveRecording::new(tx, Some(thread), path, info.codec));
+        self.recording.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Stop recording and finalize the file, returning its path
+    pub fn stop(&self) -> Option<PathBuf> {
+        self.recording.store(false, Ordering::Relaxed);
+        let mut recording = self.active.lock().take()?;
+
+        let _ = recording.tx.send(RecorderCommand::Stop);
+        if let Some(thread) = recording.thread.take() {
+            let _ = thread.join();
+        }
+        if recording.dropped > 0 {
+            warn!(
+                "Recorder dropped {} packets before stopping",
+                recording.dropped
+            );
+        }
+        info!("Recording saved: {}", recording.path.display());
+        Some(recording.path)
+    }
+
+    /// Enable the instant replay buffer for the current stream (None disables it)
+    pub fn set_replay_buffer(&self, duration: Option<Duration>, max_bitrate_mbps: u32) {
+        *self.replay_config.lock() = duration.map(|d| (d, max_bitrate_mbps));
+        self.reset_replay_buffer();
+    }
+
+    fn reset_replay_buffer(&self) {
+        let codec = self.stream_info.lock().map(|info| info.codec);
+        let config = *self.replay_config.lock();
+        let buffer = match (config, codec) {
+            (Some((duration, max_bitrate_mbps)), Some(codec)) => {
+                info!("Instant replay enabled: last {}s", duration.as_secs());
+                Some(ReplayBuffer::new(codec, duration, max_bitrate_mbps))
+            }
+            _ => None,
+        };
+        self.replay_active
+            .store(buffer.is_some(), Ordering::Relaxed);
+        *self.replay.lock() = buffer;
+    }
+
+    /// Whether the instant replay buffer is running
+    pub fn is_replay_active(&self) -> bool {
+        self.replay_active.load(Ordering::Relaxed)
+    }
+
+    /// Write the contents of the replay buffer to `path` on a background thread
+    pub fn save_replay(&self, path: PathBuf) -> Result<()> {
+        let info = (*self.stream_info.lock()).context("No active stream")?;
+        let packets = self
+            .replay
+            .lock()
+            .as_ref()
+            .map(|buffer| buffer.snapshot())
+            .context("Instant replay is not enabled")?;
+        if packets.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Replay buffer is empty (waiting for keyframe)"
+            ));
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        std::thread::Builder::new()
+            .name("replay-writer".to_string())
+            .spawn(move || {
+                let mut muxer = PacketMuxer::new(path.clone(), info);
+                let result = packets
+                    .iter()
+                    .try_for_each(|packet| muxer.write(packet))
+                    .and_then(|()| muxer.finish());
+                match result {
+                    Ok(Some(duration)) => info!(
+                        "Replay saved: {} ({:.1}s)",
+                        path.display(),
+                        duration as f64 / 1000.0
+                    ),
+                    Ok(None) => warn!("Replay buffer had no keyframe, nothing written"),
+                    Err(e) => error!("Failed to save replay: {:#}", e),
+                }
+            })
+            .context("Failed to spawn replay writer thread")?;
+        Ok(())
+    }
+
+    /// Tap a complete video access unit (Annex B for H.264/H.265, OBUs for AV1)
+    ///
+    /// Returns true if a keyframe should be requested, for the replay buffer
+    /// or to resume a recording after the writer fell behind.
+    pub fn push_video(&self, data: &[u8], rtp_timestamp: u32) -> bool {
+        let recording = self.is_recording();
+        let replay = self.is_replay_active();
+        if !recording && !replay {
+            return false;
+        }
+
+        let packet = CapturedPacket::Video {
+            data: data.to_vec(),
+            rtp_timestamp,
+            arrival: Instant::now(),
+        };
+        self.dispatch(packet, recording, replay)
+    }
+
+    /// Tap an Opus packet (RTP payload)
+    pub fn push_audio(&self, data: &[u8], rtp_timestamp: u32) {
+        let recording = self.is_recording();
+        let replay = self.is_replay_active();
+        if !recording && !replay {
+            return;
+        }
+
+        let packet = CapturedPacket::Audio {
+            data: data.to_vec(),
+            rtp_timestamp,
+            arrival: Instant::now(),
+        };
+        self.dispatch(packet, recording, replay);
+    }
+
+    fn dispatch(&self, packet: CapturedPacket, recording: bool, replay: bool) -> bool {
+        let mut wants_keyframe = false;
+        if recording {
+            if replay {
+                wants_keyframe = self.send(packet.clone());
+            } else {
+                return self.send(packet);
+            }
+        }
+        let replay_wants_keyframe = self
+            .replay
+            .lock()
+            .as_mut()
+            .is_some_and(|buffer| buffer.push(packet));
+        wants_keyframe || replay_wants_keyframe
+    }
+
+    fn send(&self, packet: CapturedPacket) -> bool {
+        // Never block the streaming loop on disk I/O
+        self.active
+            .lock()
+            .as_mut()
+            .is_some_and(|active| active.send(packet))
+    }
+}
+
+impl Default for StreamRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for StreamRecorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Converts 32-bit RTP timestamps into a monotonic millisecond timeline
+///
+/// The first packet is anchored at its arrival offset so tracks with
+/// unrelated RTP bases line up; later packets follow the RTP clock.
+pub struct RtpTimeline {
+    clock_rate: u32,
+    last_rtp: u32,
+    extended: i64,
+    base: Option<(i64, f64)>,
+}
+
+impl RtpTimeline {
+    pub fn new(clock_rate: u32) -> Self {
+        Self {
+            clock_rate,
+            last_rtp: 0,
+            extended: 0,
+            base: None,
+        }
+    }
+
+    /// Timestamp in ms for `rtp_timestamp`, given the packet's arrival offset
+    /// (ms since the recording origin) used to anchor the first packet
+    pub fn timestamp_ms(&mut self, rtp_timestamp: u32, arrival_offset_ms: f64) -> u64 {
+        let Some((base_rtp, base_ms)) = self.base else {
+            self.last_rtp = rtp_timestamp;
+            self.extended = rtp_timestamp as i64;
+            self.base = Some((self.extended, arrival_offset_ms));
+            return arrival_offset_ms.max(0.0) as u64;
+        };
+
+        // Signed difference handles both wraparound and reordering
+        self.extended += rtp_timestamp.wrapping_sub(self.last_rtp) as i32 as i64;
+        self.last_rtp = rtp_timestamp;
+
+        let ms = base_ms + (self.extended - base_rtp) as f64 * 1000.0 / self.clock_rate as f64;
+        ms.max(0.0) as u64
+    }
+}
+
+/// Remuxes captured packets into a Matroska file
+///
+/// Shared by recordings and replay saves. Output starts at the first video keyframe; audio before it is discarded.
+pub struct PacketMuxer {
+    info: RecordingStreamInfo,
+    writer: Option<MkvWriter<BufWriter<File>>>,
+    path: PathBuf,
+    origin: Option<Instant>,
+    video_timeline: RtpTimeline,
+    audio_timeline: RtpTimeline,
+}
+
+impl PacketMuxer {
+    pub fn new(path: PathBuf, info: RecordingStreamInfo) -> Self {
+        Self {
+            info,
+            writer: None,
+            path,
+            origin: None,
+            video_timeline: RtpTimeline::new(VIDEO_CLOCK_RATE),
+            audio_timeline: RtpTimeline::new(AUDIO_CLOCK_RATE),
+        }
+    }
+
+    /// Whether the file has been created (a keyframe was seen)
+    pub fn is_started(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    pub fn write(&mut self, packet: &CapturedPacket) -> Result<()> {
+        match packet {
+            CapturedPacket::Video {
+                data,
+                rtp_timestamp,
+                arrival,
+            } => {
+                let keyframe = mkv::is_keyframe(self.info.codec, data);
+                if self.writer.is_none() {
+                    if !keyframe {
+                        return Ok(());
+                    }
+                    self.open(data)?;
+                    self.origin = Some(*arrival);
+                }
+                let offset = self.arrival_offset_ms(*arrival);
+                let timestamp = self.video_timeline.timestamp_ms(*rtp_timestamp, offset);
+                let block = mkv::video_block_data(self.info.codec, data);
+                if let Some(writer) = self.writer.as_mut() {
+                    writer.write_video(timestamp, &block, keyframe)?;
+                }
+            }
+            CapturedPacket::Audio {
+                data,
+                rtp_timestamp,
+                arrival,
+            } => {
+                if self.writer.is_none() {
+                    return Ok(());
+                }
+                let offset = self.arrival_offset_ms(*arrival);
+                let timestamp = self.audio_timeline.timestamp_ms(*rtp_timestamp, offset);
+                if let Some(writer) = self.writer.as_mut() {
+                    writer.write_audio(timestamp, data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Finalize the file; returns the duration written in ms
+    pub fn finish(self) -> Result<Option<u64>> {
+        let Some(writer) = self.writer else {
+            return Ok(None);
+        };
+        let duration = writer.duration_ms();
+        writer.finish()?;
+        Ok(Some(duration))
+    }
+
+    fn open(&mut self, keyframe: &[u8]) -> Result<()> {
+        let codec_private = mkv::video_codec_private(self.info.codec, keyframe)
+            .context("Keyframe is missing codec parameter sets")?;
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+
+        let video = MkvVideoTrack {
+            codec: self.info.codec,
+            width: self.info.width,
+            height: self.info.height,
+            codec_private,
+        };
+        let audio = MkvAudioTrack {
+            sample_rate: self.info.audio_sample_rate,
+            channels: self.info.audio_channels,
+        };
+        self.writer = Some(MkvWriter::new(BufWriter::new(file), &video, Some(&audio))?);
+        Ok(())
+    }
+
+    fn arrival_offset_ms(&self, arrival: Instant) -> f64 {
+        match self.origin {
+            Some(origin) => arrival.saturating_duration_since(origin).as_secs_f64() * 1000.0,
+            None => 0.0,
+        }
+    }
+}
+
+fn run_writer(rx: mpsc::Receiver<RecorderCommand>, path: PathBuf, info: RecordingStreamInfo) {
+    let mut muxer = PacketMuxer::new(path.clone(), info);
+
+    while let Ok(RecorderCommand::Packet(packet)) = rx.recv() {
+        if let Err(e) = muxer.write(&packet) {
+            error!("Recording write failed: {:#}", e);
+            break;
+        }
+    }
+
+    match muxer.finish() {
+        Ok(Some(duration)) => info!(
+            "Recording finalized: {} ({:.1}s)",
+            path.display(),
+            duration as f64 / 1000.0
+        ),
+        Ok(None) => warn!("Recording stopped before the first keyframe, nothing written"),
+        Err(e) => error!("Failed to finalize recording: {:#}", e),
+    }
+}
+
+/// Build a capture file path: `<dir>/<game>_<YYYY-MM-DD_HH-MM-SS>.<ext>`
+pub fn capture_file_path(dir: &Path, game_title: Option<&str>, extension: &str) -> PathBuf {
+    let title: String = game_title
+        .unwrap_or("OpenNOW")
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' => Some(c),
+            ' ' | '_' | '.' => Some('_'),
+            _ => None,
+        })
+        .collect();
+    let title = if title.is_empty() {
+        "OpenNOW".to_string()
+    } else {
+        title
+    };
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    dir.join(format!("{}_{}.{}", title, timestamp, extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rtp_timeline_wraparound() {
+        let mut timeline = RtpTimeline::new(VIDEO_CLOCK_RATE);
+        assert_eq!(timeline.timestamp_ms(u32::MAX - 899, 100.0), 100);
+        // 1800 ticks later across the 32-bit boundary = 20ms
+        assert_eq!(timeline.timestamp_ms(900, 0.0), 120);
+    }
+
+    #[test]
+    fn test_full_queue_skips_video_until_keyframe() {
+        let video = |nal: &[u8]| CapturedPacket::Video {
+            data: [&[0, 0, 0, 1], nal].concat(),
+            rtp_timestamp: 0,
+            arrival: Instant::now(),
+        };
+        let (tx, rx) = mpsc::sync_channel(1);
+        let mut active =
+            ActiveRecording::new(tx, None, PathBuf::from("test.mkv"), DepacketizerCodec::H264);
+
+        assert!(!active.send(video(&[0x41, 0x9A])));
+        // Queue full: the first lost frame asks for a keyframe, once
+        assert!(active.send(video(&[0x41, 0x9A])));
+        assert!(!active.send(video(&[0x41, 0x9A])));
+        assert!(active.awaiting_keyframe);
+
+        // Writer caught up, but inter frames still reference the lost one
+        rx.try_recv().unwrap();
+        assert!(!active.send(video(&[0x41, 0x9A])));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(active.dropped, 3);
+
+        assert!(!active.send(video(&[0x65, 0x88])));
+        assert!(!active.awaiting_keyframe);
+        assert_eq!(active.dropped, 0);
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_capture_file_path_sanitizes_title() {
+        let path = capture_file_path(Path::new("out"), Some("Cyberpunk 2077: Phantom"), "mkv");
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("Cyberpunk_2077_Phantom_"));
+        assert!(name.ends_with(".mkv"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Instant Replay Buffer
+//!
+//! Rolling in-memory buffer of the last N seconds of compressed video access
+//! units and Opus packets. Packets are grouped by keyframe (GOP) so the
+//! buffer always starts at a keyframe and can be saved at any moment.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::mkv;
+use super::recorder::CapturedPacket;
+use super::rtp::DepacketizerCodec;
+
+/// Headroom over the nominal bitrate (keyframes, bitrate spikes, audio)
+const BITRATE_HEADROOM: f64 = 1.5;
+
+/// Packets from one keyframe up to (not including) the next
+struct Gop {
+    start: Instant,
+    packets: Vec<CapturedPacket>,
+    bytes: usize,
+}
+
+/// Rolling buffer of the most recent stream packets
+pub struct ReplayBuffer {
+    codec: DepacketizerCodec,
+    duration: Duration,
+    max_bytes: usize,
+    gops: VecDeque<Gop>,
+    bytes: usize,
+    last_keyframe_request: Option<Instant>,
+}
+
+impl ReplayBuffer {
+    /// Create a buffer holding `duration` of stream at up to `max_bitrate_mbps`
+    pub fn new(codec: DepacketizerCodec, duration: Duration, max_bitrate_mbps: u32) -> Self {
+        // Worst case we hold two GOP windows (the one covering the start of
+        // the replay plus the one being filled)
+        let bytes_per_sec = max_bitrate_mbps as f64 * 1_000_000.0 / 8.0;
+        let max_bytes = (bytes_per_sec * duration.as_secs_f64() * 2.0 * BITRATE_HEADROOM) as usize;
+
+        Self {
+            codec,
+            duration,
+            max_bytes,
+            gops: VecDeque::new(),
+            bytes: 0,
+            last_keyframe_request: None,
+        }
+    }
+
+    /// Add a packet; returns true if a keyframe should be requested
+    ///
+    /// GFN streams rarely send keyframes on their own, so the buffer asks for
+    /// one when it has no cut point within the replay window.
+    pub fn push(&mut self, packet: CapturedPacket) -> bool {
+        let now = Instant::now();
+        let size = packet_size(&packet);
+
+        match &packet {
+            CapturedPacket::Video { data, .. } if mkv::is_keyframe(self.codec, data) => {
+                self.gops.push_back(Gop {
+                    start: now,
+                    packets: vec![packet],
+                    bytes: size,
+                });
+                self.bytes += size;
+            }
+            _ => {
+                // Nothing is kept until the first keyframe arrives
+                let Some(gop) = self.gops.back_mut() else {
+                    return matches!(packet, CapturedPacket::Video { .. })
+                        && self.should_request_keyframe(now);
+                };
+                gop.packets.push(packet);
+                gop.bytes += size;
+                self.bytes += size;
+            }
+        }
+
+        self.trim(now);
+
+        // Current GOP is older than the window: without a new keyframe the
+        // buffer can't be trimmed and would keep growing
+        let gop_too_long = self
+            .gops
+            .back()
+            .is_some_and(|gop| now.duration_since(gop.start) >= self.duration);
+        let too_big = self.bytes > self.max_bytes;
+        (gop_too_long || too_big) && self.should_request_keyframe(now)
+    }
+
+    /// Copy of all buffered packets in arrival order
+    pub fn snapshot(&self) -> Vec<CapturedPacket> {
+        self.gops
+            .iter()
+            .flat_map(|gop| gop.packets.iter().cloned())
+            .collect()
+    }
+
+    /// Drop old GOPs while the next one still covers the replay window
+    fn trim(&mut self, now: Instant) {
+        while self.gops.len() > 1 {
+            let next_start = self.gops[1].start;
+            let covers_window = now.duration_since(next_start) >= self.duration;
+            if !covers_window && self.bytes <= self.max_bytes {
+                break;
+            }
+            if let Some(gop) = self.gops.pop_front() {
+                self.bytes -= gop.bytes;
+            }
+        }
+    }
+
+    fn should_request_keyframe(&mut self, now: Instant) -> bool {
+        // At most one request per second
+        if self
+            .last_keyframe_request
+            .is_some_and(|last| now.duration_since(last) < Duration::from_secs(1))
+        {
+            return false;
+        }
+        self.last_keyframe_request = Some(now);
+        true
+    }
+}
+
+fn packet_size(packet: &CapturedPacket) -> usize {
+    match packet {
+        CapturedPacket::Video { data, .. } | CapturedPacket::Audio { data, .. } => data.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDR: &[u8] = &[0, 0, 0, 1, 0x65, 0x88];
+    const P_FRAME: &[u8] = &[0, 0, 0, 1, 0x41, 0x9A];
+
+    fn video(data: &[u8]) -> CapturedPacket {
+        CapturedPacket::Video {
+            data: data.to_vec(),
+            rtp_timestamp: 0,
+            arrival: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_waits_for_keyframe() {
+        let mut buffer = ReplayBuffer::new(DepacketizerCodec::H264, Duration::from_secs(30), 50);
+        assert!(buffer.push(video(P_FRAME)));
+        assert!(buffer.snapshot().is_empty());
+
+        buffer.push(video(IDR));
+        buffer.push(video(P_FRAME));
+        assert_eq!(buffer.snapshot().len(), 2);
+    }
+
+    #[test]
+    fn test_memory_bound_drops_oldest_gop() {
+        // 0 Mbps budget: anything beyond the newest GOP is over the limit
+        let mut buffer = ReplayBuffer::new(DepacketizerCodec::H264, Duration::from_secs(30), 0);
+        buffer.push(video(IDR));
+        buffer.push(video(P_FRAME));
+        buffer.push(video(IDR));
+
+        assert_eq!(buffer.snapshot().len(), 1);
+        assert_eq!(buffer.bytes, IDR.len());
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Depacketizes RTP payloads for H.264, H.265/HEVC, and AV1 video codecs.
 
+use std::time::Instant;
+
 use log::{debug, warn};
 
+use super::av1_parser::{self, Av1ObuType};
+
 /// Codec type for depacketizer
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DepacketizerCodec {
@@ -136,7 +140,9 @@ impl RtpDepacketizer {
             }
 
             let obu_size = if w_field > 0 && i < obu_count - 1 {
-                let (size, bytes_read) = Self::read_leb128(&payload[offset..]);
+                let Some((size, bytes_read)) = av1_parser::read_leb128(&payload[offset..]) else {
+                    break;
+                };
                 offset += bytes_read;
                 size as usize
             } else {
@@ -246,82 +252,22 @@ impl RtpDepacketizer {
     /// Check if an AV1 frame contains actual picture data (TILE_GROUP or FRAME OBU)
     /// Frames with only SEQUENCE_HEADER, FRAME_HEADER, etc. are not decodable
     fn av1_frame_has_picture_data(data: &[u8]) -> bool {
-        Self::av1_find_obu_types(data).iter().any(|&t| t == 4 || t == 6)
+        av1_parser::parse_obus(data)
+            .iter()
+            .any(|obu| obu.obu_type.has_picture_data())
     }
 
     /// Check if an AV1 frame contains a SEQUENCE_HEADER OBU
     fn av1_frame_has_sequence_header(data: &[u8]) -> bool {
-        Self::av1_find_obu_types(data).contains(&1)
-    }
-
-    /// Find all OBU types in an AV1 bitstream
-    fn av1_find_obu_types(data: &[u8]) -> Vec<u8> {
-        let mut types = Vec::new();
-        let mut offset = 0;
-
-        while offset < data.len() {
-            // Parse OBU header
-            let header = data[offset];
-            let obu_type = (header >> 3) & 0x0F;
-            let has_extension = (header & 0x04) != 0;
-            let has_size = (header & 0x02) != 0;
-
-            types.push(obu_type);
-
-            // Move to next OBU
-            let header_size = if has_extension { 2 } else { 1 };
-            offset += header_size;
-
-            if has_size && offset < data.len() {
-                let (size, bytes_read) = Self::read_leb128(&data[offset..]);
-                offset += bytes_read + size as usize;
-            } else {
-                // No size field - OBU extends to end of data
-                break;
-            }
-        }
-        types
+        Self::extract_sequence_header(data).is_some()
     }
 
     /// Extract the SEQUENCE_HEADER OBU from an AV1 bitstream
     fn extract_sequence_header(data: &[u8]) -> Option<Vec<u8>> {
-        let mut offset = 0;
-
-        while offset < data.len() {
-            let start_offset = offset;
-
-            // Parse OBU header
-            let header = data[offset];
-            let obu_type = (header >> 3) & 0x0F;
-            let has_extension = (header & 0x04) != 0;
-            let has_size = (header & 0x02) != 0;
-
-            // Move past header
-            let header_size = if has_extension { 2 } else { 1 };
-            offset += header_size;
-
-            if has_size && offset < data.len() {
-                let (size, bytes_read) = Self::read_leb128(&data[offset..]);
-                offset += bytes_read;
-
-                // If this is SEQUENCE_HEADER (type 1), extract it
-                if obu_type == 1 {
-                    let end_offset = offset + size as usize;
-                    if end_offset <= data.len() {
-                        return Some(data[start_offset..end_offset].to_vec());
-                    }
-                }
-
-                offset += size as usize;
-            } else {
-                // No size field - OBU extends to end of data
-                if obu_type == 1 {
-                    return Some(data[start_offset..].to_vec());
-                }
-                break;
-            }
-        }
-        None
+        av1_parser::parse_obus(data)
+            .into_iter()
+            .find(|obu| obu.obu_type == Av1ObuType::SequenceHeader)
+            .map(|obu| obu.data.to_vec())
     }
 
     /// Process an RTP payload and return complete NAL units
@@ -600,48 +546,95 @@ impl RtpDepacketizer {
         }
 
         // Write payload size as LEB128
-        Self::write_leb128(&mut new_obu, payload_size as u64);
+        av1_parser::write_leb128(&mut new_obu, payload_size as u64);
 
         // Copy payload
         new_obu.extend_from_slice(&obu_data[header_size..]);
 
         Some(new_obu)
     }
+}
 
-    /// Read LEB128 encoded unsigned integer
-    fn read_leb128(data: &[u8]) -> (u64, usize) {
-        let mut value: u64 = 0;
-        let mut bytes_read = 0;
+impl Default for RtpDepacketizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        for (i, &byte) in data.iter().enumerate().take(8) {
-            value |= ((byte & 0x7F) as u64) << (i * 7);
-            bytes_read = i + 1;
-            if (byte & 0x80) == 0 {
-                break;
-            }
-        }
+/// RTP interarrival jitter estimator (RFC 3550 section 6.4.1)
+pub struct JitterEstimator {
+    clock_rate: u32,
+    /// Arrival time and RTP timestamp of the previous packet
+    last: Option<(Instant, u32)>,
+    /// Smoothed jitter in RTP timestamp units
+    jitter: f64,
+}
 
-        (value, bytes_read)
+impl JitterEstimator {
+    pub fn new(clock_rate: u32) -> Self {
+        Self {
+            clock_rate,
+            last: None,
+            jitter: 0.0,
+        }
     }
 
-    /// Write LEB128 encoded unsigned integer
-    fn write_leb128(output: &mut Vec<u8>, mut value: u64) {
-        loop {
-            let mut byte = (value & 0x7F) as u8;
-            value >>= 7;
-            if value != 0 {
-                byte |= 0x80; // More bytes follow
-            }
-            output.push(byte);
-            if value == 0 {
-                break;
-            }
+    /// Update with a received packet
+    pub fn update(&mut self, rtp_timestamp: u32, arrival: Instant) {
+        if let Some((last_arrival, last_timestamp)) = self.last {
+            let arrival_delta = arrival
+                .saturating_duration_since(last_arrival)
+                .as_secs_f64()
+                * self.clock_rate as f64;
+            let timestamp_delta = rtp_timestamp.wrapping_sub(last_timestamp) as i32 as f64;
+            let d = (arrival_delta - timestamp_delta).abs();
+            self.jitter += (d - self.jitter) / 16.0;
         }
+        self.last = Some((arrival, rtp_timestamp));
+    }
+
+    /// Current jitter in milliseconds
+    pub fn jitter_ms(&self) -> f32 {
+        (self.jitter * 1000.0 / self.clock_rate as f64) as f32
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.jitter = 0.0;
     }
 }
 
-impl Default for RtpDepacketizer {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_jitter_zero_for_steady_stream() {
+        let start = Instant::now();
+        let mut jitter = JitterEstimator::new(90_000);
+        // Starts just below u32::MAX so the timestamp wraps mid-stream
+        let base = u32::MAX - 3000;
+        for i in 0..120u32 {
+            let arrival = start + Duration::from_micros(i as u64 * 16_667);
+            jitter.update(base.wrapping_add(i * 1500), arrival);
+        }
+        assert!(jitter.jitter_ms() < 0.05);
+    }
+
+    #[test]
+    fn test_jitter_tracks_arrival_variation() {
+        let start = Instant::now();
+        let mut jitter = JitterEstimator::new(90_000);
+        // Frames every 16.67ms, arriving alternately 4ms early and late
+        for i in 0..500u32 {
+            let offset_ms = if i % 2 == 0 { 0.0 } else { 8.0 };
+            let arrival_ms = i as f64 * 16.667 + offset_ms;
+            let arrival = start + Duration::from_secs_f64(arrival_ms / 1000.0);
+            jitter.update(i * 1500, arrival);
+        }
+        // Every transit difference is 8ms, so jitter converges there
+        let jitter_ms = jitter.jitter_ms();
+        assert!((jitter_ms - 8.0).abs() < 0.2, "jitter {jitter_ms}");
     }
 }
@@ -0,0 +1,465 @@
+//! Screenshot Capture
+//!
+//! Converts a decoded `VideoFrame` to a PNG using the frame's own colour
+//! metadata. HDR frames are tone-mapped to SDR (BT.709/sRGB), or optionally
+//! written as 16-bit BT.2020 PQ/HLG PNGs tagged with a cICP chunk.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+
+use super::{ColorRange, ColorSpace, PixelFormat, TransferFunction, VideoFrame};
+
+/// Luminance that maps to SDR white when tone-mapping (ITU-R BT.2408)
+const REFERENCE_WHITE_NITS: f32 = 203.0;
+/// Assumed peak brightness of HDR content without mastering metadata
+const HDR_PEAK_NITS: f32 = 1000.0;
+
+/// CPU copy of a decoded frame, detached from the decoder
+pub struct ScreenshotImage {
+    pub width: u32,
+    pub height: u32,
+    format: PixelFormat,
+    y_plane: Vec<u8>,
+    y_stride: usize,
+    u_plane: Vec<u8>,
+    u_stride: usize,
+    v_plane: Vec<u8>,
+    v_stride: usize,
+    color_range: ColorRange,
+    color_space: ColorSpace,
+    transfer_function: TransferFunction,
+}
+
+impl ScreenshotImage {
+    /// Copy the frame planes, reading back GPU surfaces for zero-copy frames
+    pub fn from_frame(frame: &VideoFrame) -> Result<Self> {
+        let mut image = Self {
+            width: frame.width,
+            height: frame.height,
+            format: frame.format,
+            y_plane: frame.y_plane.clone(),
+            y_stride: frame.y_stride as usize,
+            u_plane: frame.u_plane.clone(),
+            u_stride: frame.u_stride as usize,
+            v_plane: frame.v_plane.clone(),
+            v_stride: frame.v_stride as usize,
+            color_range: frame.color_range,
+            color_space: frame.color_space,
+            transfer_function: frame.transfer_function,
+        };
+
+        if image.y_plane.is_empty() {
+            image.read_back(frame)?;
+        }
+        if image.y_plane.is_empty() || image.width == 0 || image.height == 0 {
+            return Err(anyhow::anyhow!("Frame has no pixel data"));
+        }
+        Ok(image)
+    }
+
+    /// Map the zero-copy GPU surface and copy its NV12/P010 planes
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    fn read_back(&mut self, frame: &VideoFrame) -> Result<()> {
+        let gpu = frame
+            .gpu_frame
+            .as_ref()
+            .context("Frame has neither CPU planes nor a GPU surface")?;
+        let planes = gpu.lock_and_get_planes()?;
+
+        #[cfg(target_os = "linux")]
+        let is_10bit = gpu.is_10bit();
+        #[cfg(target_os = "windows")]
+        let is_10bit = gpu.is_10bit;
+
+        self.format = if is_10bit || frame.format == PixelFormat::P010 {
+            PixelFormat::P010
+        } else {
+            PixelFormat::NV12
+        };
+        self.width = planes.width;
+        self.height = planes.height;
+        self.y_plane = planes.y_plane;
+        self.y_stride = planes.y_stride as usize;
+        self.u_plane = planes.uv_plane;
+        self.u_stride = planes.uv_stride as usize;
+        Ok(())
+    }
+
+    /// Lock the CVPixelBuffer and copy its NV12/P010 planes
+    #[cfg(target_os = "macos")]
+    fn read_back(&mut self, frame: &VideoFrame) -> Result<()> {
+        let gpu = frame
+            .gpu_frame
+            .as_ref()
+            .context("Frame has neither CPU planes nor a GPU surface")?;
+        let planes = gpu
+            .lock_and_get_planes()
+            .context("Failed to lock pixel buffer")?;
+
+        self.format = if frame.format == PixelFormat::P010 {
+            PixelFormat::P010
+        } else {
+            PixelFormat::NV12
+        };
+        self.y_plane = planes.y_data.to_vec();
+        self.y_stride = planes.y_stride as usize;
+        self.u_plane = planes.uv_data.to_vec();
+        self.u_stride = planes.uv_stride as usize;
+        Ok(())
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        self.transfer_function != TransferFunction::SDR
+    }
+
+    /// Convert and write the image as PNG
+    ///
+    /// With `hdr_16bit`, HDR frames keep their PQ/HLG signal in a 16-bit
+    /// BT.2020 PNG; otherwise they are tone-mapped to 8-bit sRGB.
+    pub fn save_png(&self, path: &Path, hdr_16bit: bool) -> Result<()> {
+        let (width, height) = (self.width, self.height);
+        let pixel_count = (width * height) as usize;
+
+        let png = if hdr_16bit && self.is_hdr() {
+            let mut samples = Vec::with_capacity(pixel_count * 6);
+            self.for_each_pixel(|rgb| {
+                for c in rgb {
+                    let v = (c.clamp(0.0, 1.0) * 65535.0 + 0.5) as u16;
+                    samples.extend_from_slice(&v.to_ne_bytes());
+                }
+            });
+            let png = encode_png(&samples, width, height, ExtendedColorType::Rgb16)?;
+            let transfer = match self.transfer_function {
+                TransferFunction::HLG => CICP_TRANSFER_HLG,
+                _ => CICP_TRANSFER_PQ,
+            };
+            insert_cicp_chunk(png, CICP_PRIMARIES_BT2020, transfer)
+        } else {
+            let mut samples = Vec::with_capacity(pixel_count * 3);
+            let transfer = self.transfer_function;
+            let primaries = self.color_space;
+            self.for_each_pixel(|rgb| {
+                let rgb = match transfer {
+                    TransferFunction::SDR => rgb,
+                    _ => tone_map_to_srgb(rgb, transfer, primaries),
+                };
+                for c in rgb {
+                    samples.push((c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8);
+                }
+            });
+            encode_png(&samples, width, height, ExtendedColorType::Rgb8)?
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(path, png).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Visit every pixel as non-linear R'G'B' in [0, 1]
+    fn for_each_pixel(&self, mut f: impl FnMut([f32; 3])) {
+        let (kr, kb) = match self.color_space {
+            ColorSpace::BT601 => (0.299, 0.114),
+            ColorSpace::BT709 => (0.2126, 0.0722),
+            ColorSpace::BT2020 => (0.2627, 0.0593),
+        };
+        let kg = 1.0 - kr - kb;
+
+        let bit_depth = if self.format == PixelFormat::P010 {
+            10
+        } else {
+            8
+        };
+        let scale = (1u32 << (bit_depth - 8)) as f32;
+        let max = ((1u32 << bit_depth) - 1) as f32;
+        let (y_offset, y_range, c_range) = match self.color_range {
+            ColorRange::Limited => (16.0 * scale, 219.0 * scale, 224.0 * scale),
+            ColorRange::Full => (0.0, max, max),
+        };
+        let c_offset = 128.0 * scale;
+
+        for row in 0..self.height as usize {
+            for col in 0..self.width as usize {
+                let (y, cb, cr) = self.sample(col, row);
+                let y = (y - y_offset) / y_range;
+                let cb = (cb - c_offset) / c_range;
+                let cr = (cr - c_offset) / c_range;
+
+                let r = y + 2.0 * (1.0 - kr) * cr;
+                let b = y + 2.0 * (1.0 - kb) * cb;
+                let g = (y - kr * r - kb * b) / kg;
+                f([r, g, b]);
+            }
+        }
+    }
+
+    /// Raw (Y, Cb, Cr) code values at a pixel
+    fn sample(&self, x: usize, y: usize) -> (f32, f32, f32) {
+        let byte = |plane: &[u8], i: usize| plane.get(i).copied().unwrap_or(0) as f32;
+        let word = |plane: &[u8], i: usize| {
+            let lo = plane.get(i).copied().unwrap_or(0) as u16;
+            let hi = plane.get(i + 1).copied().unwrap_or(0) as u16;
+            // P010 stores 10-bit samples in the high bits of 16-bit words
+            ((hi << 8 | lo) >> 6) as f32
+        };
+
+        match self.format {
+            PixelFormat::YUV420P => (
+                byte(&self.y_plane, y * self.y_stride + x),
+                byte(&self.u_plane, (y / 2) * self.u_stride + x / 2),
+                byte(&self.v_plane, (y / 2) * self.v_stride + x / 2),
+            ),
+            PixelFormat::NV12 => {
+                let uv = (y / 2) * self.u_stride + (x / 2) * 2;
+                (
+                    byte(&self.y_plane, y * self.y_stride + x),
+                    byte(&self.u_plane, uv),
+                    byte(&self.u_plane, uv + 1),
+                )
+            }
+            PixelFormat::P010 => {
+                let uv = (y / 2) * self.u_stride + (x / 2) * 4;
+                (
+                    word(&self.y_plane, y * self.y_stride + x * 2),
+                    word(&self.u_plane, uv),
+                    word(&self.u_plane, uv + 2),
+                )
+            }
+        }
+    }
+}
+
+// ============================================================================
+// HDR -> SDR tone mapping
+// ============================================================================
+
+/// SMPTE ST 2084 (PQ) EOTF: signal -> nits
+pub fn pq_to_nits(signal: f32) -> f32 {
+    const M1: f32 = 2610.0 / 16384.0;
+    const M2: f32 = 2523.0 / 4096.0 * 128.0;
+    const C1: f32 = 3424.0 / 4096.0;
+    const C2: f32 = 2413.0 / 4096.0 * 32.0;
+    const C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+    let p = signal.clamp(0.0, 1.0).powf(1.0 / M2);
+    let num = (p - C1).max(0.0);
+    let den = C2 - C3 * p;
+    10000.0 * (num / den).powf(1.0 / M1)
+}
+
+/// ARIB STD-B67 (HLG) inverse OETF: signal -> scene linear [0, 1]
+pub fn hlg_to_scene_linear(signal: f32) -> f32 {
+    const A: f32 = 0.178_832_77;
+    const B: f32 = 0.284_668_92;
+    const C: f32 = 0.559_910_7;
+
+    let e = signal.clamp(0.0, 1.0);
+    if e <= 0.5 {
+        e * e / 3.0
+    } else {
+        (((e - C) / A).exp() + B) / 12.0
+    }
+}
+
+/// sRGB OETF: linear [0, 1] -> signal
+pub fn linear_to_srgb(linear: f32) -> f32 {
+    let l = linear.clamp(0.0, 1.0);
+    if l <= 0.003_130_8 {
+        l * 12.92
+    } else {
+        1.055 * l.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Linear BT.2020 -> linear BT.709 primaries (ITU-R BT.2087)
+pub fn bt2020_to_bt709(rgb: [f32; 3]) -> [f32; 3] {
+    [
+        1.6605 * rgb[0] - 0.5876 * rgb[1] - 0.0728 * rgb[2],
+        -0.1246 * rgb[0] + 1.1329 * rgb[1] - 0.0083 * rgb[2],
+        -0.0182 * rgb[0] - 0.1006 * rgb[1] + 1.1187 * rgb[2],
+    ]
+}
+
+/// Map an HDR R'G'B' pixel to SDR sRGB R'G'B'
+///
+/// Uses extended Reinhard on luminance so hues are preserved, with
+/// reference white at 203 nits and highlights rolling off to 1000 nits.
+pub fn tone_map_to_srgb(
+    rgb: [f32; 3],
+    transfer: TransferFunction,
+    primaries: ColorSpace,
+) -> [f32; 3] {
+    // Display-linear nits
+    let nits = match transfer {
+        TransferFunction::PQ => rgb.map(pq_to_nits),
+        TransferFunction::HLG => {
+            // HLG OOTF with system gamma 1.2 at 1000 nits peak
+            let scene = rgb.map(hlg_to_scene_linear);
+            let ys = 0.2627 * scene[0] + 0.6780 * scene[1] + 0.0593 * scene[2];
+            let gain = HDR_PEAK_NITS * ys.max(1e-6).powf(0.2);
+            scene.map(|c| c * gain)
+        }
+        TransferFunction::SDR => return rgb,
+    };
+
+    let linear = nits.map(|c| c / REFERENCE_WHITE_NITS);
+    let linear = match primaries {
+        ColorSpace::BT2020 => bt2020_to_bt709(linear),
+        _ => linear,
+    };
+
+    let luma = 0.2126 * linear[0] + 0.7152 * linear[1] + 0.0722 * linear[2];
+    if luma <= 0.0 {
+        return [0.0; 3];
+    }
+    let white = HDR_PEAK_NITS / REFERENCE_WHITE_NITS;
+    let mapped = luma * (1.0 + luma / (white * white)) / (1.0 + luma);
+    let scale = mapped / luma;
+
+    linear.map(|c| linear_to_srgb(c.max(0.0) * scale))
+}
+
+// ============================================================================
+// PNG output
+// ============================================================================
+
+/// cICP colour primaries: BT.2020
+const CICP_PRIMARIES_BT2020: u8 = 9;
+/// cICP transfer characteristics: SMPTE ST 2084
+const CICP_TRANSFER_PQ: u8 = 16;
+/// cICP transfer characteristics: ARIB STD-B67
+const CICP_TRANSFER_HLG: u8 = 18;
+
+fn encode_png(
+    samples: &[u8],
+    width: u32,
+    height: u32,
+    color: ExtendedColorType,
+) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(samples, width, height, color)
+        .context("Failed to encode PNG")?;
+    Ok(png)
+}
+
+/// Insert a cICP chunk (PNG 3rd edition) after IHDR so HDR-aware viewers
+/// interpret the 16-bit samples as BT.2020 PQ/HLG
+fn insert_cicp_chunk(png: Vec<u8>, primaries: u8, transfer: u8) -> Vec<u8> {
+    // 8-byte signature + IHDR (length, type, 13 data bytes, CRC)
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.len() < IHDR_END {
+        return png;
+    }
+
+    // matrix_coefficients = 0 (RGB), video_full_range_flag = 1
+    let mut chunk = Vec::with_capacity(16);
+    chunk.extend_from_slice(&4u32.to_be_bytes());
+    chunk.extend_from_slice(b"cICP");
+    chunk.extend_from_slice(&[primaries, transfer, 0, 1]);
+    let crc = crc32(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+
+    let mut out = Vec::with_capacity(png.len() + chunk.len());
+    out.extend_from_slice(&png[..IHDR_END]);
+    out.extend_from_slice(&chunk);
+    out.extend_from_slice(&png[IHDR_END..]);
+    out
+}
+
+/// CRC-32 (ISO-HDLC) as used by PNG chunks
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nv12_image(y: u8, u: u8, v: u8, range: ColorRange) -> ScreenshotImage {
+        ScreenshotImage {
+            width: 2,
+            height: 2,
+            format: PixelFormat::NV12,
+            y_plane: vec![y; 4],
+            y_stride: 2,
+            u_plane: vec![u, v],
+            u_stride: 2,
+            v_plane: Vec::new(),
+            v_stride: 0,
+            color_range: range,
+            color_space: ColorSpace::BT709,
+            transfer_function: TransferFunction::SDR,
+        }
+    }
+
+    fn first_pixel(image: &ScreenshotImage) -> [f32; 3] {
+        let mut first = None;
+        image.for_each_pixel(|rgb| {
+            first.get_or_insert(rgb);
+        });
+        first.unwrap()
+    }
+
+    #[test]
+    fn test_limited_range_white_and_black() {
+        let white = first_pixel(&nv12_image(235, 128, 128, ColorRange::Limited));
+        assert!(white.iter().all(|c| (c - 1.0).abs() < 1e-3));
+
+        let black = first_pixel(&nv12_image(16, 128, 128, ColorRange::Limited));
+        assert!(black.iter().all(|c| c.abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_bt709_red() {
+        // BT.709 limited-range pure red: Y=63, Cb=102, Cr=240
+        let red = first_pixel(&nv12_image(63, 102, 240, ColorRange::Limited));
+        assert!((red[0] - 1.0).abs() < 0.02);
+        assert!(red[1].abs() < 0.02);
+        assert!(red[2].abs() < 0.02);
+    }
+
+    #[test]
+    fn test_pq_reference_points() {
+        assert!(pq_to_nits(0.0) < 1e-3);
+        assert!((pq_to_nits(1.0) - 10000.0).abs() < 1.0);
+        // 0.58 PQ is roughly 203 nits
+        assert!((pq_to_nits(0.58) - 203.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_tone_map_reference_white() {
+        // PQ-encoded 203 nit white should land near SDR white, not clip
+        let signal = 0.5807;
+        let rgb = tone_map_to_srgb([signal; 3], TransferFunction::PQ, ColorSpace::BT2020);
+        assert!(rgb.iter().all(|c| *c > 0.7 && *c < 1.0));
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn test_cicp_chunk_after_ihdr() {
+        let png = encode_png(&[0u8; 12], 2, 2, ExtendedColorType::Rgb8).unwrap();
+        let out = insert_cicp_chunk(png, CICP_PRIMARIES_BT2020, CICP_TRANSFER_PQ);
+        assert_eq!(&out[37..41], b"cICP");
+        assert_eq!(&out[41..45], &[9, 16, 0, 1]);
+    }
+}
@@ -0,0 +1,437 @@
+//! Session Performance Log
+//!
+//! Opt-in local timeline of a streaming session: every stats sample plus
+//! notable events, written as CSV or JSON Lines. Nothing is uploaded.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::StreamStats;
+use crate::app::config::SessionLogFormat;
+
+/// Audio packets lost within one stats interval that count as a loss burst
+const LOSS_BURST_PACKETS: u64 = 5;
+
+/// Notable events on the session timeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// Log opened, with what is being streamed and how
+    SessionStart {
+        game: String,
+        zone: String,
+        codec: String,
+        resolution: String,
+        fps: u32,
+        decoder: String,
+    },
+    KeyframeRequest {
+        reason: &'static str,
+    },
+    SsrcChange {
+        stall_ms: u64,
+    },
+    ResolutionChange {
+        from: String,
+        to: String,
+    },
+    DecoderFallback {
+        from: String,
+        to: String,
+        reason: &'static str,
+    },
+    PacketLossBurst {
+        video: u64,
+        audio: u64,
+    },
+}
+
+impl SessionEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionEvent::SessionStart { .. } => "session_start",
+            SessionEvent::KeyframeRequest { .. } => "keyframe_request",
+            SessionEvent::SsrcChange { .. } => "ssrc_change",
+            SessionEvent::ResolutionChange { .. } => "resolution_change",
+            SessionEvent::DecoderFallback { .. } => "decoder_fallback",
+            SessionEvent::PacketLossBurst { .. } => "packet_loss_burst",
+        }
+    }
+
+    /// Human-readable details (CSV `detail` column and JSON `detail` field)
+    pub fn detail(&self) -> String {
+        match self {
+            SessionEvent::SessionStart {
+                game,
+                zone,
+                codec,
+                resolution,
+                fps,
+                decoder,
+            } => format!(
+                "{} on {} ({} {}@{}, decoder {})",
+                game, zone, codec, resolution, fps, decoder
+            ),
+            SessionEvent::KeyframeRequest { reason } => reason.to_string(),
+            SessionEvent::SsrcChange { stall_ms } => format!("stalled {}ms", stall_ms),
+            SessionEvent::ResolutionChange { from, to } => format!("{} -> {}", from, to),
+            SessionEvent::DecoderFallback { from, to, reason } => {
+                format!("{} -> {} ({})", from, to, reason)
+            }
+            SessionEvent::PacketLossBurst { video, audio } => match (video, audio) {
+                (0, audio) => format!("{} audio packets lost", audio),
+                (video, 0) => format!("{} video packets lost", video),
+                (video, audio) => format!("{} video and {} audio packets lost", video, audio),
+            },
+        }
+    }
+}
+
+/// Per-second stats columns, in file order
+fn stats_fields(stats: &StreamStats) -> Vec<(&'static str, Value)> {
+    let round = |v: f32| (v as f64 * 100.0).round() / 100.0;
+    let samples = &stats.decode_samples_ms;
+    let (decode_avg, decode_max) = if samples.is_empty() {
+        (stats.decode_time_ms, stats.decode_time_ms)
+    } else {
+        (
+            samples.iter().sum::<f32>() / samples.len() as f32,
+            samples.iter().copied().fold(0.0, f32::max),
+        )
+    };
+
+    vec![
+        ("resolution", json!(stats.resolution)),
+        ("codec", json!(stats.codec)),
+        ("fps", json!(round(stats.fps))),
+        ("render_fps", json!(round(stats.render_fps))),
+        ("target_fps", json!(stats.target_fps)),
+        ("bitrate_mbps", json!(round(stats.bitrate_mbps))),
+        ("rtt_ms", json!(round(stats.rtt_ms))),
+        ("jitter_ms", json!(round(stats.jitter_ms))),
+        ("decode_ms", json!(round(decode_avg))),
+        ("decode_max_ms", json!(round(decode_max))),
+        ("frame_delivery_ms", json!(round(stats.frame_delivery_ms))),
+        ("render_ms", json!(round(stats.render_time_ms))),
+        ("e2e_ms", json!(round(stats.estimated_e2e_ms))),
+        ("audio_buffer_ms", json!(round(stats.audio_buffer_ms))),
+        ("input_rate", json!(round(stats.input_rate))),
+        ("input_latency_ms", json!(round(stats.input_latency_ms))),
+        ("frames_received", json!(stats.frames_received)),
+        ("frames_decoded", json!(stats.frames_decoded)),
+        ("frames_dropped", json!(stats.frames_dropped)),
+        ("video_packets_lost", json!(stats.video_packets_lost)),
+        ("audio_packets_lost", json!(stats.audio_packets_lost)),
+        (
+            "audio_frames_concealed",
+            json!(stats.audio_frames_concealed),
+        ),
+    ]
+}
+
+/// Quote a CSV field if needed
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+struct LogState {
+    /// None after a write error (logging stops, the stream carries on)
+    writer: Option<BufWriter<File>>,
+    last_resolution: String,
+    last_video_lost: u64,
+    last_audio_lost: u64,
+}
+
+/// Session telemetry log, shared between the app and the streaming loop
+pub struct SessionLog {
+    path: PathBuf,
+    format: SessionLogFormat,
+    started: Instant,
+    state: Mutex<LogState>,
+}
+
+impl SessionLog {
+    /// Create the log file (and its directory)
+    pub fn create(path: PathBuf, format: SessionLogFormat) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let file =
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        if format == SessionLogFormat::Csv {
+            let mut header = vec!["elapsed_ms", "timestamp_ms", "type", "event", "detail"];
+            header.extend(
+                stats_fields(&StreamStats::default())
+                    .iter()
+                    .map(|(name, _)| *name),
+            );
+            writeln!(writer, "{}", header.join(","))?;
+        }
+
+        info!("Session log: {}", path.display());
+        Ok(Self {
+            path,
+            format,
+            started: Instant::now(),
+            state: Mutex::new(LogState {
+                writer: Some(writer),
+                last_resolution: String::new(),
+                last_video_lost: 0,
+                last_audio_lost: 0,
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an event
+    pub fn event(&self, event: SessionEvent) {
+        let mut state = self.state.lock();
+        self.write_event(&mut state, &event);
+    }
+
+    /// Append a stats sample, plus any resolution change or loss burst it shows
+    pub fn record_stats(&self, stats: &StreamStats) {
+        let mut state = self.state.lock();
+
+        if !stats.resolution.is_empty() && stats.resolution != state.last_resolution {
+            if !state.last_resolution.is_empty() {
+                let event = SessionEvent::ResolutionChange {
+                    from: state.last_resolution.clone(),
+                    to: stats.resolution.clone(),
+                };
+                self.write_event(&mut state, &event);
+            }
+            state.last_resolution = stats.resolution.clone();
+        }
+
+        // Counters restart after an SSRC reconnect
+        let video = stats
+            .video_packets_lost
+            .saturating_sub(state.last_video_lost);
+        let audio = stats
+            .audio_packets_lost
+            .saturating_sub(state.last_audio_lost);
+        state.last_video_lost = stats.video_packets_lost;
+        state.last_audio_lost = stats.audio_packets_lost;
+        if video + audio >= LOSS_BURST_PACKETS {
+            self.write_event(&mut state, &SessionEvent::PacketLossBurst { video, audio });
+        }
+
+        let fields = stats_fields(stats);
+        let line = match self.format {
+            SessionLogFormat::Csv => {
+                let mut columns = vec![self.elapsed_ms().to_string(), now_ms().to_string()];
+                columns.extend(["stats".to_string(), String::new(), String::new()]);
+                columns.extend(fields.iter().map(|(_, value)| csv_field(value)));
+                columns.join(",")
+            }
+            SessionLogFormat::JsonLines => {
+                let mut record = self.json_record("stats");
+                record.extend(
+                    fields
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value)),
+                );
+                Value::Object(record).to_string()
+            }
+        };
+        self.write_line(&mut state, &line);
+
+        // Once per second: keep the file current in case of a crash
+        if let Some(writer) = state.writer.as_mut() {
+            let _ = writer.flush();
+        }
+    }
+
+    fn json_record(&self, kind: &str) -> Map<String, Value> {
+        let mut record = Map::new();
+        record.insert("elapsed_ms".to_string(), json!(self.elapsed_ms()));
+        record.insert("timestamp_ms".to_string(), json!(now_ms()));
+        record.insert("type".to_string(), json!(kind));
+        record
+    }
+
+    fn write_event(&self, state: &mut LogState, event: &SessionEvent) {
+        let line = match self.format {
+            SessionLogFormat::Csv => {
+                let empty_stats = ",".repeat(stats_fields(&StreamStats::default()).len());
+                format!(
+                    "{},{},event,{},{}{}",
+                    self.elapsed_ms(),
+                    now_ms(),
+                    event.name(),
+                    csv_field(&json!(event.detail())),
+                    empty_stats
+                )
+            }
+            SessionLogFormat::JsonLines => {
+                let mut record = self.json_record("event");
+                record.insert("event".to_string(), json!(event.name()));
+                record.insert("detail".to_string(), json!(event.detail()));
+                Value::Object(record).to_string()
+            }
+        };
+        self.write_line(state, &line);
+    }
+
+    fn write_line(&self, state: &mut LogState, line: &str) {
+        if let Some(writer) = state.writer.as_mut() {
+            if let Err(e) = writeln!(writer, "{}", line) {
+                warn!("Session log write failed, logging stopped: {}", e);
+                state.writer = None;
+            }
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
+impl Drop for SessionLog {
+    fn drop(&mut self) {
+        if let Some(writer) = self.state.get_mut().writer.as_mut() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("opennow-session-log-{}", std::process::id()))
+            .join(name)
+    }
+
+    /// Column count, ignoring commas inside quotes
+    fn csv_columns(line: &str) -> usize {
+        let mut quoted = false;
+        1 + line
+            .chars()
+            .filter(|&c| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c == ',' && !quoted
+            })
+            .count()
+    }
+
+    fn sample(resolution: &str, audio_lost: u64) -> StreamStats {
+        StreamStats {
+            resolution: resolution.to_string(),
+            codec: "H265".to_string(),
+            fps: 59.94,
+            decode_samples_ms: vec![2.0, 4.0, 9.0],
+            audio_packets_lost: audio_lost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_line_up_with_header() {
+        let path = temp_path("log.csv");
+        let log = SessionLog::create(path.clone(), SessionLogFormat::Csv).unwrap();
+        log.event(SessionEvent::KeyframeRequest {
+            reason: "decoder errors, retrying",
+        });
+        log.record_stats(&sample("1920x1080", 0));
+        log.record_stats(&sample("2560x1440", 8));
+        drop(log);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        // Header, keyframe, stats, resolution change, loss burst, stats
+        assert_eq!(lines.len(), 6);
+        let columns = csv_columns(lines[0]);
+        assert!(lines.iter().all(|l| csv_columns(l) == columns));
+        assert!(lines[1].contains(",event,keyframe_request,\"decoder errors, retrying\","));
+        assert!(lines[2].contains(",stats,,,1920x1080,H265,59.94,"));
+        assert!(lines[3].contains("resolution_change,1920x1080 -> 2560x1440"));
+        assert!(lines[4].contains("packet_loss_burst,8 audio packets lost"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_records() {
+        let path = temp_path("log.jsonl");
+        let log = SessionLog::create(path.clone(), SessionLogFormat::JsonLines).unwrap();
+        log.event(SessionEvent::DecoderFallback {
+            from: "Vaapi".to_string(),
+            to: "Software".to_string(),
+            reason: "stalled",
+        });
+        log.record_stats(&sample("1920x1080", 2));
+        drop(log);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["event"], "decoder_fallback");
+        assert_eq!(records[0]["detail"], "Vaapi -> Software (stalled)");
+        assert_eq!(records[1]["type"], "stats");
+        assert_eq!(records[1]["decode_ms"], 5.0);
+        assert_eq!(records[1]["decode_max_ms"], 9.0);
+        // Small losses aren't a burst
+        assert_eq!(records[1]["audio_packets_lost"], 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_video_loss_counts_towards_bursts() {
+        let path = temp_path("video_loss.jsonl");
+        let log = SessionLog::create(path.clone(), SessionLogFormat::JsonLines).unwrap();
+        let mut stats = sample("1920x1080", 1);
+        stats.video_packets_lost = 3;
+        log.record_stats(&stats);
+        stats.video_packets_lost = 40;
+        log.record_stats(&stats);
+        drop(log);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        // First second: 3 video + 1 audio is under the threshold
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["video_packets_lost"], 3);
+        assert_eq!(records[1]["event"], "packet_loss_burst");
+        assert_eq!(records[1]["detail"], "37 video packets lost");
+        assert_eq!(
+            SessionEvent::PacketLossBurst { video: 6, audio: 2 }.detail(),
+            "6 video and 2 audio packets lost"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
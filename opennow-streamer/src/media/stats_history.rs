@@ -0,0 +1,387 @@
+//! Stats History
+//!
+//! Rolling sample windows behind the stats overlay graphs. Each metric keeps
+//! the last few seconds of samples so percentiles and spikes show stutter
+//! that the once-per-second averages hide.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::StreamStats;
+
+/// How much history the graphs show
+pub const HISTORY_WINDOW: Duration = Duration::from_secs(10);
+
+/// Points per graph after downsampling (bucket maxima, so spikes survive)
+const GRAPH_POINTS: usize = 200;
+
+/// Upper bound on samples per metric (10s at 360fps)
+const MAX_SAMPLES: usize = 3600;
+
+/// Graphed metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsMetric {
+    DecodeTime,
+    RenderTime,
+    FrameDelivery,
+    Rtt,
+    Jitter,
+    Bitrate,
+    AudioBuffer,
+}
+
+impl StatsMetric {
+    pub const ALL: [StatsMetric; 7] = [
+        StatsMetric::DecodeTime,
+        StatsMetric::RenderTime,
+        StatsMetric::FrameDelivery,
+        StatsMetric::Rtt,
+        StatsMetric::Jitter,
+        StatsMetric::Bitrate,
+        StatsMetric::AudioBuffer,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatsMetric::DecodeTime => "Decode",
+            StatsMetric::RenderTime => "Render",
+            StatsMetric::FrameDelivery => "Delivery",
+            StatsMetric::Rtt => "RTT",
+            StatsMetric::Jitter => "Jitter",
+            StatsMetric::Bitrate => "Bitrate",
+            StatsMetric::AudioBuffer => "Audio buf",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            StatsMetric::Bitrate => "Mbps",
+            _ => "ms",
+        }
+    }
+
+    /// Minimum rise over the median that counts as a spike, so noise on
+    /// near-zero values isn't flagged
+    fn spike_floor(&self) -> f32 {
+        match self {
+            StatsMetric::DecodeTime | StatsMetric::RenderTime => 4.0,
+            StatsMetric::FrameDelivery | StatsMetric::Jitter => 5.0,
+            StatsMetric::Rtt | StatsMetric::AudioBuffer => 20.0,
+            StatsMetric::Bitrate => 10.0,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Percentile summary of a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl Percentiles {
+    /// Nearest-rank percentiles; None for an empty window
+    pub fn from_values(values: impl Iterator<Item = f32>) -> Option<Self> {
+        let mut sorted: Vec<f32> = values.collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = |p: f32| {
+            let index = ((p / 100.0) * sorted.len() as f32).ceil() as usize;
+            sorted[index.clamp(1, sorted.len()) - 1]
+        };
+        Some(Self {
+            p50: rank(50.0),
+            p95: rank(95.0),
+            p99: rank(99.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Timestamped samples within a rolling window
+#[derive(Debug, Clone)]
+pub struct RollingSeries {
+    samples: VecDeque<(Instant, f32)>,
+    window: Duration,
+}
+
+impl RollingSeries {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            window,
+        }
+    }
+
+    pub fn push(&mut self, time: Instant, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        self.samples.push_back((time, value));
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.prune(time);
+    }
+
+    /// Drop samples older than the window
+    pub fn prune(&mut self, now: Instant) {
+        while let Some(&(time, _)) = self.samples.front() {
+            if now.saturating_duration_since(time) <= self.window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<f32> {
+        self.samples.back().map(|&(_, v)| v)
+    }
+
+    pub fn percentiles(&self) -> Option<Percentiles> {
+        Percentiles::from_values(self.samples.iter().map(|&(_, v)| v))
+    }
+
+    /// Graph snapshot with x = seconds relative to `now` (negative = past)
+    pub fn graph(&self, metric: StatsMetric, now: Instant) -> Option<MetricGraph> {
+        let percentiles = self.percentiles()?;
+        let age = |t: Instant| -(now.saturating_duration_since(t).as_secs_f32());
+
+        // Spikes: well above the median, judged on raw samples
+        let threshold = (percentiles.p50 * 2.0).max(percentiles.p50 + metric.spike_floor());
+        let spikes = self
+            .samples
+            .iter()
+            .filter(|&&(_, v)| v > threshold)
+            .map(|&(t, v)| [age(t), v])
+            .collect();
+
+        // Downsample by time bucket, keeping each bucket's maximum
+        let window = self.window.as_secs_f32();
+        let mut buckets: Vec<Option<[f32; 2]>> = vec![None; GRAPH_POINTS];
+        for &(t, v) in &self.samples {
+            let x = age(t);
+            let index = (((x + window) / window) * (GRAPH_POINTS - 1) as f32).round() as usize;
+            let bucket = &mut buckets[index.min(GRAPH_POINTS - 1)];
+            if bucket.is_none_or(|[_, max]| v > max) {
+                *bucket = Some([x, v]);
+            }
+        }
+
+        Some(MetricGraph {
+            metric,
+            points: buckets.into_iter().flatten().collect(),
+            spikes,
+            percentiles,
+            latest: self.latest().unwrap_or_default(),
+        })
+    }
+}
+
+/// Downsampled graph of one metric, ready to draw
+#[derive(Debug, Clone)]
+pub struct MetricGraph {
+    pub metric: StatsMetric,
+    /// [seconds ago (negative), value], oldest first
+    pub points: Vec<[f32; 2]>,
+    /// Samples flagged as spikes, same coordinates as `points`
+    pub spikes: Vec<[f32; 2]>,
+    pub percentiles: Percentiles,
+    pub latest: f32,
+}
+
+/// Rolling history for every graphed metric
+#[derive(Debug, Clone)]
+pub struct StatsHistory {
+    series: Vec<RollingSeries>,
+    last_stats_update: Option<Instant>,
+}
+
+impl StatsHistory {
+    pub fn new() -> Self {
+        Self {
+            series: StatsMetric::ALL
+                .iter()
+                .map(|_| RollingSeries::new(HISTORY_WINDOW))
+                .collect(),
+            last_stats_update: None,
+        }
+    }
+
+    pub fn record(&mut self, metric: StatsMetric, time: Instant, value: f32) {
+        self.series[metric.index()].push(time, value);
+    }
+
+    /// Record samples taken evenly over `span` ending at `end`
+    pub fn record_batch(
+        &mut self,
+        metric: StatsMetric,
+        end: Instant,
+        span: Duration,
+        values: &[f32],
+    ) {
+        let step = span / values.len().max(1) as u32;
+        for (i, &value) in values.iter().enumerate() {
+            let offset = step * (values.len() - 1 - i) as u32;
+            self.record(metric, end.checked_sub(offset).unwrap_or(end), value);
+        }
+    }
+
+    /// Record a stats update from the streaming loop
+    pub fn record_stats(&mut self, stats: &StreamStats, now: Instant) {
+        let span = self
+            .last_stats_update
+            .map(|t| now.saturating_duration_since(t))
+            .unwrap_or(Duration::from_secs(1));
+        self.last_stats_update = Some(now);
+
+        self.record_batch(StatsMetric::DecodeTime, now, span, &stats.decode_samples_ms);
+        self.record_batch(
+            StatsMetric::FrameDelivery,
+            now,
+            span,
+            &stats.delivery_samples_ms,
+        );
+        if stats.rtt_ms > 0.0 {
+            self.record(StatsMetric::Rtt, now, stats.rtt_ms);
+        }
+        self.record(StatsMetric::Jitter, now, stats.jitter_ms);
+        self.record(StatsMetric::Bitrate, now, stats.bitrate_mbps);
+        self.record(StatsMetric::AudioBuffer, now, stats.audio_buffer_ms);
+    }
+
+    pub fn series(&self, metric: StatsMetric) -> &RollingSeries {
+        &self.series[metric.index()]
+    }
+
+    /// Graphs for every metric with data
+    pub fn graphs(&self, now: Instant) -> Vec<MetricGraph> {
+        StatsMetric::ALL
+            .iter()
+            .filter_map(|&metric| self.series(metric).graph(metric, now))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for StatsHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        let p = Percentiles::from_values((1..=100).map(|v| v as f32)).unwrap();
+        assert_eq!((p.p50, p.p95, p.p99, p.max), (50.0, 95.0, 99.0, 100.0));
+
+        let p = Percentiles::from_values([3.0].into_iter()).unwrap();
+        assert_eq!((p.p50, p.p99), (3.0, 3.0));
+        assert!(Percentiles::from_values(std::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn test_window_drops_old_samples() {
+        let start = Instant::now();
+        let mut series = RollingSeries::new(Duration::from_secs(10));
+        for i in 0..20 {
+            series.push(start + Duration::from_secs(i), i as f32);
+        }
+        // Samples at 9..=19 are within 10s of the last one
+        assert_eq!(series.len(), 11);
+        assert_eq!(series.percentiles().unwrap().p50, 14.0);
+    }
+
+    #[test]
+    fn test_spikes_stand_out_from_average() {
+        let start = Instant::now();
+        let mut history = StatsHistory::new();
+        // 600 frames at ~5ms decode with two 40ms hitches
+        for i in 0..600 {
+            let value = if i == 200 || i == 450 {
+                40.0
+            } else {
+                5.0 + (i % 3) as f32 * 0.2
+            };
+            history.record(
+                StatsMetric::DecodeTime,
+                start + Duration::from_millis(i * 16),
+                value,
+            );
+        }
+        let now = start + Duration::from_millis(600 * 16);
+        let graph = history
+            .series(StatsMetric::DecodeTime)
+            .graph(StatsMetric::DecodeTime, now)
+            .unwrap();
+
+        assert_eq!(graph.spikes.len(), 2);
+        assert!(graph.percentiles.p50 < 6.0);
+        assert_eq!(graph.percentiles.max, 40.0);
+        // Downsampling keeps the spikes visible
+        assert!(graph.points.len() <= GRAPH_POINTS);
+        assert_eq!(graph.points.iter().filter(|p| p[1] == 40.0).count(), 2);
+        assert!(graph.points.windows(2).all(|w| w[0][0] <= w[1][0]));
+    }
+
+    #[test]
+    fn test_small_wobble_is_not_a_spike() {
+        let start = Instant::now();
+        let mut series = RollingSeries::new(HISTORY_WINDOW);
+        for i in 0..100 {
+            series.push(
+                start + Duration::from_millis(i * 10),
+                if i % 10 == 0 { 2.5 } else { 1.0 },
+            );
+        }
+        let graph = series
+            .graph(StatsMetric::DecodeTime, start + Duration::from_secs(1))
+            .unwrap();
+        assert!(graph.spikes.is_empty());
+    }
+
+    #[test]
+    fn test_stats_batches_spread_over_interval() {
+        let start = Instant::now();
+        let mut history = StatsHistory::new();
+        let mut stats = StreamStats {
+            decode_samples_ms: vec![1.0; 60],
+            bitrate_mbps: 30.0,
+            ..Default::default()
+        };
+        history.record_stats(&stats, start);
+        stats.decode_samples_ms = vec![2.0; 60];
+        history.record_stats(&stats, start + Duration::from_secs(1));
+
+        let decode = history.series(StatsMetric::DecodeTime);
+        assert_eq!(decode.len(), 120);
+        assert_eq!(decode.latest(), Some(2.0));
+        // Nothing recorded without an RTT measurement
+        assert!(history.series(StatsMetric::Rtt).is_empty());
+        // Decode, jitter, bitrate and audio buffer have data
+        assert_eq!(history.graphs(start + Duration::from_secs(1)).len(), 4);
+    }
+}
@@ -53,6 +53,24 @@ pub enum GpuVendor {
 
 /// Cached GPU vendor
 static GPU_VENDOR: std::sync::OnceLock<GpuVendor> = std::sync::OnceLock::new();
+/// Name and driver of the adapter the vendor was detected from
+static GPU_DRIVER: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+fn describe_adapter(info: &wgpu::AdapterInfo) -> String {
+    format!(
+        "{} ({:?}) {} {}",
+        info.name, info.backend, info.driver, info.driver_info
+    )
+}
+
+/// Get the selected GPU and its driver version (decoder probe cache key)
+pub fn gpu_driver_description() -> String {
+    detect_gpu_vendor();
+    GPU_DRIVER
+        .get()
+        .cloned()
+        .unwrap_or_else(|| "unknown".to_string())
+}
 
 /// Detect the primary GPU vendor using wgpu, prioritizing discrete GPUs
 pub fn detect_gpu_vendor() -> GpuVendor {
@@ -67,6 +85,7 @@ pub fn detect_gpu_vendor() -> GpuVendor {
 
             let mut best_score = -1;
             let mut best_vendor = GpuVendor::Unknown;
+            let mut best_driver = String::new();
 
             info!("Available GPU adapters:");
 
@@ -125,11 +144,13 @@ pub fn detect_gpu_vendor() -> GpuVendor {
                 if score > best_score {
                     best_score = score;
                     best_vendor = vendor;
+                    best_driver = describe_adapter(&info);
                 }
             }
 
             if best_vendor != GpuVendor::Unknown {
                 info!("Selected best GPU vendor: {:?}", best_vendor);
+                let _ = GPU_DRIVER.set(best_driver);
                 best_vendor
             } else {
                 // Fallback to default request if enumeration fails
@@ -147,6 +168,7 @@ pub fn detect_gpu_vendor() -> GpuVendor {
                 if let Ok(adapter) = adapter_result {
                     let info = adapter.get_info();
                     let name = info.name.to_lowercase();
+                    let _ = GPU_DRIVER.set(describe_adapter(&info));
 
                     if name.contains("nvidia") {
                         GpuVendor::Nvidia
@@ -342,12 +364,33 @@ fn is_qsv_supported_for_codec(codec_id: ffmpeg::codec::Id) -> bool {
     }
 }
 
-/// Cached supported decoder backends
+/// Cached decoder backend candidates
 static SUPPORTED_BACKENDS: std::sync::OnceLock<Vec<VideoDecoderBackend>> =
     std::sync::OnceLock::new();
 
 /// Get list of supported decoder backends for the current system
+///
+/// Once the decoder probe has run, backends that failed to decode any of
+/// the probe clips are left out.
 pub fn get_supported_decoder_backends() -> Vec<VideoDecoderBackend> {
+    let candidates = decoder_backend_candidates();
+    match super::decoder_probe::decoder_capabilities() {
+        Some(caps) => {
+            let working = caps.working_backends();
+            candidates
+                .into_iter()
+                .filter(|b| {
+                    matches!(b, VideoDecoderBackend::Auto | VideoDecoderBackend::Software)
+                        || working.contains(b)
+                })
+                .collect()
+        }
+        None => candidates,
+    }
+}
+
+/// Get the decoder backends that may work on this system (GPU/OS heuristics)
+pub fn decoder_backend_candidates() -> Vec<VideoDecoderBackend> {
     SUPPORTED_BACKENDS
         .get_or_init(|| {
             let mut backends = vec![VideoDecoderBackend::Auto];
@@ -419,6 +462,8 @@ enum DecoderCommand {
     DecodeAsync {
         data: Vec<u8>,
         receive_time: std::time::Instant,
+        /// Presentation time from RTP (frame pacing)
+        timestamp_us: u64,
     },
     Stop,
 }
@@ -426,8 +471,10 @@ enum DecoderCommand {
 /// Stats from the decoder thread
 #[derive(Debug, Clone)]
 pub struct DecodeStats {
-    /// Time from packet receive to decode complete (ms)
+    /// Time the decoder spent on the packet (ms)
     pub decode_time_ms: f32,
+    /// Time from packet receive to decode complete (ms)
+    pub delivery_time_ms: f32,
     /// Whether a frame was produced
     pub frame_produced: bool,
     /// Whether a keyframe is needed (too many consecutive decode failures)
@@ -535,6 +582,7 @@ impl VideoDecoder {
                     width: 1920,
                     height: 1080,
                     low_latency: true, // Enable low latency for streaming
+                    backend,
                 };
 
                 let gst_decoder = super::gstreamer_decoder::GStreamerDecoder::new(config)
@@ -562,9 +610,15 @@ impl VideoDecoder {
                                 let result = decoder.decode(&data);
                                 let _ = frame_tx.send(result.ok().flatten());
                             }
-                            DecoderCommand::DecodeAsync { data, receive_time } => {
+                            DecoderCommand::DecodeAsync {
+                                data,
+                                receive_time,
+                                timestamp_us,
+                            } => {
+                                let decode_start = std::time::Instant::now();
                                 let result = decoder.decode(&data);
-                                let decode_time_ms = receive_time.elapsed().as_secs_f32() * 1000.0;
+                                let decode_time_ms = decode_start.elapsed().as_secs_f32() * 1000.0;
+                                let delivery_time_ms = receive_time.elapsed().as_secs_f32() * 1000.0;
 
                                 let frame_produced = matches!(&result, Ok(Some(_)));
 
@@ -576,15 +630,17 @@ impl VideoDecoder {
                                     consecutive_failures == KEYFRAME_REQUEST_THRESHOLD
                                 };
 
-                                if let Ok(Some(frame)) = result {
+                                if let Ok(Some(mut frame)) = result {
                                     frames_decoded += 1;
                                     if frames_decoded > FRAMES_TO_SKIP {
+                                        frame.timestamp_us = timestamp_us;
                                         shared_frame_clone.write(frame);
                                     }
                                 }
 
                                 let _ = stats_tx.try_send(DecodeStats {
                                     decode_time_ms,
+                                    delivery_time_ms,
                                     frame_produced,
                                     needs_keyframe,
                                 });
@@ -724,13 +780,18 @@ impl VideoDecoder {
                         );
                         let _ = frame_tx.send(result);
                     }
-                    DecoderCommand::DecodeAsync { data, receive_time } => {
+                    DecoderCommand::DecodeAsync {
+                        data,
+                        receive_time,
+                        timestamp_us,
+                    } => {
                         packets_received += 1;
 
                         // Check if we're in recovery mode (waiting for keyframe)
                         let in_recovery = consecutive_failures >= KEYFRAME_REQUEST_THRESHOLD;
 
                         // Non-blocking mode - write directly to SharedFrame
+                        let decode_start = std::time::Instant::now();
                         let result = Self::decode_frame(
                             &mut decoder,
                             &mut scaler,
@@ -742,7 +803,8 @@ impl VideoDecoder {
                             in_recovery,
                         );
 
-                        let decode_time_ms = receive_time.elapsed().as_secs_f32() * 1000.0;
+                        let decode_time_ms = decode_start.elapsed().as_secs_f32() * 1000.0;
+                        let delivery_time_ms = receive_time.elapsed().as_secs_f32() * 1000.0;
                         let frame_produced = result.is_some();
 
                         // Track consecutive decode failures for PLI request
@@ -786,9 +848,10 @@ impl VideoDecoder {
                         // Write frame directly to SharedFrame (zero-copy handoff)
                         // Skip first few frames to let decoder settle with proper reference frames
                         // This prevents green/corrupted frames during stream startup
-                        if let Some(frame) = result {
+                        if let Some(mut frame) = result {
                             if frames_decoded > FRAMES_TO_SKIP {
                                 if let Some(ref sf) = shared_frame {
+                                    frame.timestamp_us = timestamp_us;
                                     sf.write(frame);
                                 }
                             } else {
@@ -803,6 +866,7 @@ impl VideoDecoder {
                         if let Some(ref tx) = stats_tx {
                             let _ = tx.try_send(DecodeStats {
                                 decode_time_ms,
+                                delivery_time_ms,
                                 frame_produced,
                                 needs_keyframe,
                             });
@@ -2246,11 +2310,17 @@ impl VideoDecoder {
     ///
     /// This method NEVER blocks the calling thread, making it ideal for
     /// the main streaming loop where input responsiveness is critical.
-    pub fn decode_async(&mut self, data: &[u8], receive_time: std::time::Instant) -> Result<()> {
+    pub fn decode_async(
+        &mut self,
+        data: &[u8],
+        receive_time: std::time::Instant,
+        timestamp_us: u64,
+    ) -> Result<()> {
         self.cmd_tx
             .send(DecoderCommand::DecodeAsync {
                 data: data.to_vec(),
                 receive_time,
+                timestamp_us,
             })
             .map_err(|_| anyhow!("Decoder thread closed"))?;
 
@@ -2363,6 +2433,7 @@ impl UnifiedVideoDecoder {
                     while let Some(native_stats) = native_rx.recv().await {
                         let stats = DecodeStats {
                             decode_time_ms: native_stats.decode_time_ms,
+                            delivery_time_ms: native_stats.delivery_time_ms,
                             frame_produced: native_stats.frame_produced,
                             needs_keyframe: native_stats.needs_keyframe,
                         };
@@ -2397,6 +2468,7 @@ impl UnifiedVideoDecoder {
                 width: 1920,
                 height: 1080,
                 low_latency: true,
+                backend,
             };
 
             let gst_decoder = super::gstreamer_decoder::GStreamerDecoder::new(gst_config)
@@ -2445,6 +2517,7 @@ impl UnifiedVideoDecoder {
                 while let Some(native_stats) = native_rx.recv().await {
                     let stats = DecodeStats {
                         decode_time_ms: native_stats.decode_time_ms,
+                        delivery_time_ms: native_stats.delivery_time_ms,
                         frame_produced: native_stats.frame_produced,
                         needs_keyframe: native_stats.needs_keyframe,
                     };
@@ -2466,18 +2539,28 @@ impl UnifiedVideoDecoder {
     }
 
     /// Decode a frame asynchronously
-    pub fn decode_async(&mut self, data: &[u8], receive_time: std::time::Instant) -> Result<()> {
+    ///
+    /// `timestamp_us` is the frame's presentation time (from RTP) and is
+    /// carried over to the decoded frame for pacing.
+    pub fn decode_async(
+        &mut self,
+        data: &[u8],
+        receive_time: std::time::Instant,
+        timestamp_us: u64,
+    ) -> Result<()> {
         match self {
             #[cfg(not(windows))]
-            UnifiedVideoDecoder::Ffmpeg(decoder) => decoder.decode_async(data, receive_time),
+            UnifiedVideoDecoder::Ffmpeg(decoder) => {
+                decoder.decode_async(data, receive_time, timestamp_us)
+            }
             #[cfg(windows)]
             UnifiedVideoDecoder::Native(decoder) => {
-                decoder.decode_async(data.to_vec(), receive_time);
+                decoder.decode_async(data.to_vec(), receive_time, timestamp_us);
                 Ok(())
             }
             #[cfg(all(windows, target_arch = "x86_64"))]
             UnifiedVideoDecoder::GStreamer(wrapper) => {
-                wrapper.decode_async(data, receive_time);
+                wrapper.decode_async(data, receive_time, timestamp_us);
                 Ok(())
             }
         }
@@ -2514,13 +2597,19 @@ impl GStreamerDecoderWrapper {
     const KEYFRAME_REQUEST_THRESHOLD: u32 = 3;
 
     /// Decode a frame asynchronously and write to SharedFrame
-    pub fn decode_async(&mut self, data: &[u8], receive_time: std::time::Instant) {
+    pub fn decode_async(
+        &mut self,
+        data: &[u8],
+        receive_time: std::time::Instant,
+        timestamp_us: u64,
+    ) {
         let decode_start = std::time::Instant::now();
 
         match self.decoder.decode(data) {
-            Ok(Some(frame)) => {
+            Ok(Some(mut frame)) => {
                 self.frames_decoded += 1;
                 self.consecutive_failures = 0;
+                frame.timestamp_us = timestamp_us;
                 self.shared_frame.write(frame);
 
                 // Measure decode time from when we started pushing data
@@ -2537,6 +2626,7 @@ impl GStreamerDecoderWrapper {
 
                 let _ = self.stats_tx.try_send(DecodeStats {
                     decode_time_ms,
+                    delivery_time_ms: receive_time.elapsed().as_secs_f32() * 1000.0,
                     frame_produced: true,
                     needs_keyframe: false,
                 });
@@ -2567,6 +2657,7 @@ impl GStreamerDecoderWrapper {
                 let decode_time_ms = decode_start.elapsed().as_secs_f32() * 1000.0;
                 let _ = self.stats_tx.try_send(DecodeStats {
                     decode_time_ms,
+                    delivery_time_ms: receive_time.elapsed().as_secs_f32() * 1000.0,
                     frame_produced: false,
                     needs_keyframe,
                 });
@@ -2578,6 +2669,7 @@ impl GStreamerDecoderWrapper {
                 let decode_time_ms = decode_start.elapsed().as_secs_f32() * 1000.0;
                 let _ = self.stats_tx.try_send(DecodeStats {
                     decode_time_ms,
+                    delivery_time_ms: receive_time.elapsed().as_secs_f32() * 1000.0,
                     frame_produced: false,
                     needs_keyframe: self.consecutive_failures >= Self::KEYFRAME_REQUEST_THRESHOLD,
                 });
@@ -3,9 +3,13 @@
 //! Common utilities used throughout the application.
 
 mod logging;
+mod notify;
+mod probe;
 mod time;
 
 pub use logging::*;
+pub use notify::desktop_notification;
+pub use probe::{probe_hosts, LatencyStats};
 pub use time::*;
 
 use std::path::PathBuf;
@@ -0,0 +1,100 @@
+//! Desktop Notifications
+//!
+//! Fire-and-forget system notifications (queue alerts). Uses the
+//! freedesktop notification service over D-Bus on Linux and Notification
+//! Center on macOS. Windows only gets the optional sound; the taskbar flash
+//! is requested separately through the window.
+
+use log::{debug, warn};
+
+/// Show a desktop notification without blocking the caller
+pub fn desktop_notification(title: &str, body: &str, sound: bool) {
+    let title = title.to_string();
+    let body = body.to_string();
+    let spawned = std::thread::Builder::new()
+        .name("notification".to_string())
+        .spawn(move || {
+            if let Err(e) = show(&title, &body, sound) {
+                warn!("Desktop notification failed: {}", e);
+            } else {
+                debug!("Notification shown: {}", title);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn notification thread: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn show(title: &str, body: &str, sound: bool) -> anyhow::Result<()> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let connection = zbus::blocking::Connection::session()?;
+
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    hints.insert("desktop-entry", Value::from("opennow-streamer"));
+    // Normal urgency
+    hints.insert("urgency", Value::U8(1));
+    if sound {
+        hints.insert("sound-name", Value::from("message-new-instant"));
+    }
+
+    // org.freedesktop.Notifications.Notify(app_name, replaces_id, app_icon,
+    // summary, body, actions, hints, expire_timeout)
+    connection.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "Notify",
+        &(
+            "OpenNOW",
+            0u32,
+            "",
+            title,
+            body,
+            Vec::<&str>::new(),
+            hints,
+            -1i32,
+        ),
+    )?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn show(title: &str, body: &str, sound: bool) -> anyhow::Result<()> {
+    // AppleScript string literal
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut script = format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(title)
+    );
+    if sound {
+        script.push_str(" sound name \"Glass\"");
+    }
+
+    let status = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("osascript exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn show(_title: &str, _body: &str, sound: bool) -> anyhow::Result<()> {
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBeep, MB_ICONASTERISK};
+
+    if sound {
+        unsafe { MessageBeep(MB_ICONASTERISK)? };
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn show(_title: &str, _body: &str, _sound: bool) -> anyhow::Result<()> {
+    Ok(())
+}
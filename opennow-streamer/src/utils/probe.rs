@@ -0,0 +1,444 @@
+//! Latency Probing
+//!
+//! Measures the round trip to a server with several samples and reports
+//! min / average / 95th percentile RTT, jitter and loss. Three methods:
+//! - TCP connect to port 443 (works everywhere, one handshake per sample)
+//! - ICMP echo over an unprivileged datagram socket (Linux with
+//!   `net.ipv4.ping_group_range` allowing it, macOS). Falls back to TCP
+//!   where that isn't available.
+//! - STUN binding requests over UDP, the same transport the stream uses
+
+use futures_util::stream::{self, StreamExt};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+use crate::app::config::PingMode;
+
+/// Per-sample timeout (counts as lost)
+const SAMPLE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Pause between samples to the same host
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Hosts probed at once
+pub const MAX_CONCURRENT_PROBES: usize = 8;
+
+const TCP_PORT: u16 = 443;
+
+/// STUN port of the GFN media servers
+pub const STUN_PORT: u16 = 19308;
+
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+
+static ICMP_FALLBACK_WARNING: Once = Once::new();
+
+/// Result of probing one host
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub min_ms: f32,
+    pub avg_ms: f32,
+    pub p95_ms: f32,
+    /// Mean difference between consecutive RTTs
+    pub jitter_ms: f32,
+    /// Percent of samples without a reply
+    pub loss_pct: f32,
+    /// Samples sent
+    pub samples: u32,
+    /// Method actually used (ICMP may have fallen back to TCP)
+    pub mode: PingMode,
+}
+
+impl LatencyStats {
+    /// Stats from per-sample RTTs in ms (None = lost). None if nothing came back.
+    pub fn from_samples(samples: &[Option<f32>], mode: PingMode) -> Option<Self> {
+        let received: Vec<f32> = samples.iter().flatten().copied().collect();
+        if received.is_empty() {
+            return None;
+        }
+
+        let mut sorted = received.clone();
+        sorted.sort_by(f32::total_cmp);
+        // Nearest rank
+        let rank = ((sorted.len() as f32 * 0.95).ceil() as usize).clamp(1, sorted.len());
+
+        let jitter_ms = if received.len() > 1 {
+            let diffs: f32 = received.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+            diffs / (received.len() - 1) as f32
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            min_ms: sorted[0],
+            avg_ms: received.iter().sum::<f32>() / received.len() as f32,
+            p95_ms: sorted[rank - 1],
+            jitter_ms,
+            loss_pct: (samples.len() - received.len()) as f32 * 100.0 / samples.len() as f32,
+            samples: samples.len() as u32,
+            mode,
+        })
+    }
+
+    /// Single ping figure for lists and scoring
+    pub fn ping_ms(&self) -> u32 {
+        self.avg_ms.round() as u32
+    }
+
+    /// e.g. "min 12 / avg 14 / p95 19 ms, jitter 1.2 ms, 0% loss (TCP connect)"
+    pub fn summary(&self) -> String {
+        format!(
+            "min {:.0} / avg {:.0} / p95 {:.0} ms, jitter {:.1} ms, {:.0}% loss ({})",
+            self.min_ms,
+            self.avg_ms,
+            self.p95_ms,
+            self.jitter_ms,
+            self.loss_pct,
+            self.mode.as_str()
+        )
+    }
+}
+
+/// Probe several hosts (`(id, hostname)`), at most [`MAX_CONCURRENT_PROBES`]
+/// at a time. Results come back in input order.
+pub async fn probe_hosts(
+    targets: Vec<(String, String)>,
+    mode: PingMode,
+    samples: u32,
+) -> Vec<(String, Option<LatencyStats>)> {
+    let mut results: Vec<(usize, String, Option<LatencyStats>)> =
+        stream::iter(targets.into_iter().enumerate())
+            .map(|(i, (id, host))| async move {
+                let stats = probe_host(&host, mode, samples).await;
+                (i, id, stats)
+            })
+            .buffer_unordered(MAX_CONCURRENT_PROBES)
+            .collect()
+            .await;
+    results.sort_by_key(|(i, _, _)| *i);
+    results
+        .into_iter()
+        .map(|(_, id, stats)| (id, stats))
+        .collect()
+}
+
+/// Probe one host. None if it can't be resolved or never answered.
+pub async fn probe_host(host: &str, mode: PingMode, samples: u32) -> Option<LatencyStats> {
+    let samples = samples.max(1);
+    let port = match mode {
+        PingMode::Tcp | PingMode::Icmp => TCP_PORT,
+        PingMode::Udp => STUN_PORT,
+    };
+    let addrs: Vec<SocketAddr> = match lookup_host((host, port)).await {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => {
+            debug!("Probe: can't resolve {}: {}", host, e);
+            return None;
+        }
+    };
+    // ICMP is IPv4 only here
+    let addr = match mode {
+        PingMode::Icmp => addrs.iter().find(|a| a.is_ipv4()).or(addrs.first()),
+        _ => addrs.first(),
+    }
+    .copied()?;
+
+    let (rtts, used) = match mode {
+        PingMode::Tcp => (tcp_samples(addr, samples).await, PingMode::Tcp),
+        PingMode::Udp => match stun_samples(addr, samples).await {
+            Ok(rtts) => (rtts, PingMode::Udp),
+            Err(e) => {
+                debug!("Probe: UDP socket for {} failed: {}", host, e);
+                return None;
+            }
+        },
+        PingMode::Icmp => match icmp_samples(addr.ip(), samples).await {
+            Ok(rtts) => (rtts, PingMode::Icmp),
+            Err(e) => {
+                ICMP_FALLBACK_WARNING.call_once(|| {
+                    warn!("Unprivileged ICMP unavailable ({}), using TCP connect", e);
+                });
+                (tcp_samples(addr, samples).await, PingMode::Tcp)
+            }
+        },
+    };
+
+    let stats = LatencyStats::from_samples(&rtts, used);
+    if let Some(stats) = &stats {
+        debug!("Probe {}: {}", host, stats.summary());
+    }
+    stats
+}
+
+fn elapsed_ms(start: Instant) -> f32 {
+    start.elapsed().as_secs_f32() * 1000.0
+}
+
+async fn tcp_samples(addr: SocketAddr, samples: u32) -> Vec<Option<f32>> {
+    let mut rtts = Vec::with_capacity(samples as usize);
+    for i in 0..samples {
+        if i > 0 {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+        let start = Instant::now();
+        let rtt = match timeout(SAMPLE_TIMEOUT, TcpStream::connect(addr)).await {
+            Ok(Ok(_stream)) => Some(elapsed_ms(start)),
+            _ => None,
+        };
+        rtts.push(rtt);
+    }
+    rtts
+}
+
+/// STUN binding request with the given transaction ID
+fn stun_request(transaction_id: &[u8; 12]) -> [u8; 20] {
+    let mut packet = [0u8; 20];
+    packet[0..2].copy_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    // Message length 0 (no attributes)
+    packet[4..8].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    packet[8..20].copy_from_slice(transaction_id);
+    packet
+}
+
+/// Whether `packet` is the binding response to `transaction_id`
+fn is_stun_response(packet: &[u8], transaction_id: &[u8; 12]) -> bool {
+    packet.len() >= 20
+        && u16::from_be_bytes([packet[0], packet[1]]) == STUN_BINDING_RESPONSE
+        && packet[4..8] == STUN_MAGIC_COOKIE.to_be_bytes()
+        && packet[8..20] == transaction_id[..]
+}
+
+async fn stun_samples(addr: SocketAddr, samples: u32) -> std::io::Result<Vec<Option<f32>>> {
+    let bind: SocketAddr = if addr.is_ipv4() {
+        "0.0.0.0:0".parse().unwrap()
+    } else {
+        "[::]:0".parse().unwrap()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect(addr).await?;
+
+    let mut rtts = Vec::with_capacity(samples as usize);
+    let mut buf = [0u8; 1500];
+    for i in 0..samples {
+        if i > 0 {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+        let transaction_id: [u8; 12] = rand::random();
+        let start = Instant::now();
+        socket.send(&stun_request(&transaction_id)).await?;
+        let reply = timeout(SAMPLE_TIMEOUT, async {
+            loop {
+                let len = socket.recv(&mut buf).await?;
+                // Late replies to earlier samples are skipped
+                if is_stun_response(&buf[..len], &transaction_id) {
+                    return Ok::<_, std::io::Error>(elapsed_ms(start));
+                }
+            }
+        })
+        .await;
+        rtts.push(match reply {
+            Ok(Ok(rtt)) => Some(rtt),
+            _ => None,
+        });
+    }
+    Ok(rtts)
+}
+
+/// ICMP echo request (header + payload) with the checksum filled in
+fn icmp_echo_request(identifier: u16, sequence: u16, payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![ICMP_ECHO_REQUEST, 0, 0, 0];
+    packet.extend_from_slice(&identifier.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(payload);
+    let checksum = internet_checksum(&packet);
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    packet
+}
+
+/// RFC 1071 ones' complement checksum
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Sequence number of an echo reply. macOS hands datagram ICMP sockets the
+/// IPv4 header too, Linux doesn't.
+fn icmp_echo_reply_sequence(packet: &[u8]) -> Option<u16> {
+    let icmp = match packet.first() {
+        Some(first) if first >> 4 == 4 => packet.get(((first & 0x0f) as usize) * 4..)?,
+        _ => packet,
+    };
+    if icmp.len() < 8 || icmp[0] != ICMP_ECHO_REPLY {
+        return None;
+    }
+    Some(u16::from_be_bytes([icmp[6], icmp[7]]))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn icmp_socket() -> std::io::Result<UdpSocket> {
+    use std::os::fd::FromRawFd;
+
+    // SOCK_DGRAM + IPPROTO_ICMP: echo only, no raw socket privileges needed
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, libc::IPPROTO_ICMP) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // The fd is a datagram socket we own; sendto/recvfrom work as for UDP
+    let socket = unsafe { std::net::UdpSocket::from_raw_fd(fd) };
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn icmp_socket() -> std::io::Result<UdpSocket> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "no unprivileged ICMP sockets on this platform",
+    ))
+}
+
+async fn icmp_samples(ip: IpAddr, samples: u32) -> std::io::Result<Vec<Option<f32>>> {
+    if !ip.is_ipv4() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "ICMP probing is IPv4 only",
+        ));
+    }
+    let socket = icmp_socket()?;
+    let target = SocketAddr::new(ip, 0);
+    // Linux replaces the identifier with the socket's own; replies are
+    // matched on the sequence number
+    let identifier: u16 = rand::random();
+    let first_sequence: u16 = rand::random();
+
+    let mut rtts = Vec::with_capacity(samples as usize);
+    let mut buf = [0u8; 1500];
+    for i in 0..samples {
+        if i > 0 {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+        let sequence = first_sequence.wrapping_add(i as u16);
+        let start = Instant::now();
+        socket
+            .send_to(&icmp_echo_request(identifier, sequence, b"opennow"), target)
+            .await?;
+        let reply = timeout(SAMPLE_TIMEOUT, async {
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await?;
+                if from.ip() == ip && icmp_echo_reply_sequence(&buf[..len]) == Some(sequence) {
+                    return Ok::<_, std::io::Error>(elapsed_ms(start));
+                }
+            }
+        })
+        .await;
+        rtts.push(match reply {
+            Ok(Ok(rtt)) => Some(rtt),
+            _ => None,
+        });
+    }
+    Ok(rtts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_from_samples() {
+        let samples = [Some(10.0), Some(14.0), None, Some(12.0), Some(20.0)];
+        let stats = LatencyStats::from_samples(&samples, PingMode::Tcp).unwrap();
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.avg_ms, 14.0);
+        assert_eq!(stats.p95_ms, 20.0);
+        // |14-10| + |12-14| + |20-12| over 3 gaps
+        assert!((stats.jitter_ms - 14.0 / 3.0).abs() < 1e-4);
+        assert_eq!(stats.loss_pct, 20.0);
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.ping_ms(), 14);
+
+        assert!(LatencyStats::from_samples(&[None, None], PingMode::Udp).is_none());
+    }
+
+    #[test]
+    fn test_icmp_echo_packets() {
+        let request = icmp_echo_request(0x1234, 7, b"abc");
+        assert_eq!(request[0], ICMP_ECHO_REQUEST);
+        // A packet with its checksum in place sums to zero
+        assert_eq!(internet_checksum(&request), 0);
+
+        let mut reply = request.clone();
+        reply[0] = ICMP_ECHO_REPLY;
+        assert_eq!(icmp_echo_reply_sequence(&reply), Some(7));
+        // With a 20-byte IPv4 header in front (macOS)
+        let mut with_ip = vec![0x45];
+        with_ip.extend_from_slice(&[0; 19]);
+        with_ip.extend_from_slice(&reply);
+        assert_eq!(icmp_echo_reply_sequence(&with_ip), Some(7));
+        // Our own request isn't a reply
+        assert_eq!(icmp_echo_reply_sequence(&request), None);
+    }
+
+    #[tokio::test]
+    async fn test_stun_probe_against_stand_in() {
+        // Answers binding requests, ignores everything else
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1500];
+            while let Ok((len, from)) = server.recv_from(&mut buf).await {
+                if len < 20 || u16::from_be_bytes([buf[0], buf[1]]) != STUN_BINDING_REQUEST {
+                    continue;
+                }
+                let mut response = buf[..20].to_vec();
+                response[0..2].copy_from_slice(&STUN_BINDING_RESPONSE.to_be_bytes());
+                let _ = server.send_to(&response, from).await;
+            }
+        });
+
+        let rtts = stun_samples(addr, 3).await.unwrap();
+        assert_eq!(rtts.len(), 3);
+        assert!(rtts.iter().all(Option::is_some));
+
+        let stats = LatencyStats::from_samples(&rtts, PingMode::Udp).unwrap();
+        assert_eq!(stats.loss_pct, 0.0);
+        assert!(stats.min_ms <= stats.p95_ms);
+    }
+
+    #[tokio::test]
+    async fn test_probe_hosts_keeps_order() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+
+        let open = tcp_samples(SocketAddr::from(([127, 0, 0, 1], port)), 2).await;
+        assert!(open.iter().all(Option::is_some));
+
+        let results = probe_hosts(
+            vec![
+                ("bad".to_string(), "invalid.invalid".to_string()),
+                ("local".to_string(), "localhost".to_string()),
+            ],
+            PingMode::Tcp,
+            1,
+        )
+        .await;
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["bad", "local"]);
+        assert!(results[0].1.is_none());
+    }
+}
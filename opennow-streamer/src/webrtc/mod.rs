@@ -31,8 +31,14 @@ pub enum StreamingResult {
     SsrcChangeDetected { stall_duration_ms: u64 },
 }
 use crate::input::{ControllerManager, FfbEffectType, G29FfbManager, InputHandler, WheelManager};
+use crate::app::config::VideoDecoderBackend;
+use crate::media::av1_parser::Av1Parser;
+use crate::media::frame_pacer::RtpClock;
+use crate::media::h264_parser::H264Parser;
 use crate::media::{
-    AudioDecoder, AudioPlayer, DepacketizerCodec, RtpDepacketizer, StreamStats, UnifiedVideoDecoder,
+    fallback_chain, AudioDecoder, AudioPlayer, ColorSpace, DecoderWatchdog, DepacketizerCodec,
+    JitterEstimator, RecordingStreamInfo, RtpDepacketizer, SessionEvent, SessionLog,
+    StreamRecorder, StreamStats, UnifiedVideoDecoder,
 };
 
 /// Active streaming session
@@ -277,6 +283,8 @@ pub async fn run_streaming(
     shared_frame: Arc<SharedFrame>,
     stats_tx: mpsc::Sender<StreamStats>,
     input_handler: Arc<InputHandler>,
+    recorder: Arc<StreamRecorder>,
+    session_log: Option<Arc<SessionLog>>,
 ) -> StreamingResult {
     info!(
         "Starting streaming to {} with session {}",
@@ -317,14 +325,34 @@ pub async fn run_streaming(
     // Video decoder - use async mode for non-blocking decode
     // Decoded frames are written directly to SharedFrame by the decoder thread
     // Uses UnifiedVideoDecoder to support both FFmpeg and native DXVA backends
-    let (mut video_decoder, mut decode_stats_rx) =
-        match UnifiedVideoDecoder::new_async(codec, settings.decoder_backend, shared_frame.clone())
-        {
-            Ok(decoder) => decoder,
+    // Backends that fail to start (or stall later) hand over to the next one in the chain
+    let decoder_chain = fallback_chain(codec, settings.decoder_backend);
+    info!("Video decoder fallback chain: {:?}", decoder_chain);
+    let log_event = |event: SessionEvent| {
+        if let Some(ref log) = session_log {
+            log.event(event);
+        }
+    };
+    let mut decoder_index = 0;
+    let (mut video_decoder, mut decode_stats_rx) = loop {
+        let backend = decoder_chain[decoder_index];
+        match UnifiedVideoDecoder::new_async(codec, backend, shared_frame.clone()) {
+            Ok(decoder) => break decoder,
+            Err(e) if decoder_index + 1 < decoder_chain.len() => {
+                warn!("Failed to create {:?} video decoder: {}", backend, e);
+                decoder_index += 1;
+                log_event(SessionEvent::DecoderFallback {
+                    from: format!("{:?}", backend),
+                    to: format!("{:?}", decoder_chain[decoder_index]),
+                    reason: "failed to start",
+                });
+            }
             Err(e) => {
                 return StreamingResult::Error(format!("Failed to create video decoder: {}", e))
             }
-        };
+        }
+    };
+    let mut decoder_watchdog = DecoderWatchdog::new();
 
     // Create RTP depacketizer with correct codec
     let depacketizer_codec = match codec {
@@ -335,14 +363,37 @@ pub async fn run_streaming(
     let mut rtp_depacketizer = RtpDepacketizer::with_codec(depacketizer_codec);
     info!("RTP depacketizer using {:?} mode", depacketizer_codec);
 
-    let mut audio_decoder = match AudioDecoder::new(48000, 2) {
-        Ok(decoder) => decoder,
-        Err(e) => return StreamingResult::Error(format!("Failed to create audio decoder: {}", e)),
-    };
+    // Presentation timestamps for frame pacing
+    let mut video_clock = RtpClock::new();
+    let mut video_jitter = JitterEstimator::new(90_000);
+
+    // Tracks H.264 SPS changes (resolution/colour) ahead of the decoder. The
+    // SPS picks the decoder backend (software for profiles hardware can't
+    // decode) and feeds the stats; colour and size reach the renderer on each
+    // decoded frame, since FFmpeg and GStreamer read the same SPS/VUI, so
+    // they are not pushed to the renderer separately.
+    let mut h264_parser = H264Parser::new();
+    // Tracks AV1 sequence header/HDR metadata (bit depth, profile, colour)
+    let mut av1_parser = Av1Parser::new();
+
+    // Recorder taps depacketized access units and Opus packets before decoding
+    recorder.set_stream_info(RecordingStreamInfo {
+        codec: depacketizer_codec,
+        width,
+        height,
+        audio_sample_rate: 48000,
+        audio_channels: 2,
+    });
+
+    let mut audio_decoder = AudioDecoder::new(48000, 2, settings.audio_decoder);
 
     // Get the sample receiver from the decoder for async operation
     let audio_sample_rx = audio_decoder.take_sample_receiver();
 
+    // Audio buffer level in ms (f32 bits), published by the player thread
+    let audio_buffer_level = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let player_buffer_level = audio_buffer_level.clone();
+
     // Audio player thread - receives decoded samples and plays them
     // Uses larger jitter buffer (150ms) to handle network timing variations
     std::thread::spawn(move || {
@@ -360,6 +411,10 @@ pub async fn run_streaming(
                     }
                     total_samples += samples.len() as u64;
                     audio_player.push_samples(&samples);
+                    player_buffer_level.store(
+                        audio_player.buffer_ms().to_bits(),
+                        std::sync::atomic::Ordering::Relaxed,
+                    );
 
                     // Log buffer status periodically
                     if log_interval.elapsed().as_secs() >= 5 {
@@ -385,6 +440,8 @@ pub async fn run_streaming(
     let frames_dropped: u64 = 0;
     let mut bytes_received: u64 = 0;
     let mut last_frames_decoded: u64 = 0; // For actual FPS calculation
+    let mut video_packets_lost: u64 = 0;
+    let mut audio_packets_lost: u64 = 0;
 
     // Pipeline latency tracking (receive to decode complete)
     let mut pipeline_latency_sum: f64 = 0.0;
@@ -778,18 +835,20 @@ pub async fn run_streaming(
                         warn!("WebRTC disconnected");
                         break;
                     }
-                    WebRtcEvent::VideoFrame { payload, rtp_timestamp: _, marker } => {
+                    WebRtcEvent::VideoFrame { payload, rtp_timestamp, marker, lost_packets } => {
                         frames_received += 1;
+                        video_packets_lost += lost_packets as u64;
                         bytes_received += payload.len() as u64;
                         let packet_receive_time = std::time::Instant::now();
+                        video_jitter.update(rtp_timestamp, packet_receive_time);
 
                         // Only log first packet
                         if frames_received == 1 {
                             info!("First video RTP packet received: {} bytes", payload.len());
                         }
 
-                        // Handle codec-specific depacketization
-                        match depacketizer_codec {
+                        // Handle codec-specific depacketization, giving a complete frame on the marker bit
+                        let frame = match depacketizer_codec {
                             DepacketizerCodec::AV1 => {
                                 // AV1: Use specialized OBU accumulation
                                 rtp_depacketizer.process_av1_raw(&payload);
@@ -797,11 +856,9 @@ pub async fn run_streaming(
                                 // On marker bit, flush pending OBU and get complete frame
                                 if marker {
                                     rtp_depacketizer.flush_pending_obu();
-                                    if let Some(frame_data) = rtp_depacketizer.take_accumulated_frame() {
-                                        if let Err(e) = video_decoder.decode_async(&frame_data, packet_receive_time) {
-                                            warn!("AV1 decode async failed: {}", e);
-                                        }
-                                    }
+                                    rtp_depacketizer.take_accumulated_frame()
+                                } else {
+                                    None
                                 }
                             }
                             DepacketizerCodec::H264 | DepacketizerCodec::H265 => {
@@ -816,16 +873,103 @@ pub async fn run_streaming(
 
                                 // On marker bit, we have a complete Access Unit - send to decoder
                                 if marker {
-                                    if let Some(frame_data) = rtp_depacketizer.take_nal_frame() {
-                                        if let Err(e) = video_decoder.decode_async(&frame_data, packet_receive_time) {
-                                            warn!("Decode async failed: {}", e);
+                                    rtp_depacketizer.take_nal_frame()
+                                } else {
+                                    None
+                                }
+                            }
+                        };
+
+                        if let Some(frame_data) = frame {
+                            // Stream format from the sequence header / SPS, when it changes:
+                            // (is HDR, matrix, profile name if hardware decoders can't handle it)
+                            let format = match depacketizer_codec {
+                                DepacketizerCodec::AV1 => av1_parser.process_temporal_unit(&frame_data).map(|format| {
+                                    info!(
+                                        "AV1 stream format: {} profile, level {}, {}-bit, {}x{} max, {:?} {:?} {:?}, CLL {:?}",
+                                        format.profile_name(),
+                                        format.seq_level_idx,
+                                        format.bit_depth,
+                                        format.max_width,
+                                        format.max_height,
+                                        format.color_space,
+                                        format.color_range,
+                                        format.transfer_function,
+                                        format.content_light_level
+                                    );
+                                    let software_only = (!format.hardware_decodable())
+                                        .then(|| format!("AV1 {}", format.profile_name()));
+                                    (format.is_hdr(), format.color_space, software_only)
+                                }),
+                                DepacketizerCodec::H264 => h264_parser.process_access_unit(&frame_data).map(|format| {
+                                    info!(
+                                        "H.264 stream format: {}x{}, {} profile, level={}, {}-bit, {:?} {:?} {:?}, refs={}, reorder={:?}",
+                                        format.width,
+                                        format.height,
+                                        format.profile_name(),
+                                        format.level_idc,
+                                        format.bit_depth,
+                                        format.color_space,
+                                        format.color_range,
+                                        format.transfer_function,
+                                        format.max_num_ref_frames,
+                                        format.max_num_reorder_frames
+                                    );
+                                    stats.resolution = format!("{}x{}", format.width, format.height);
+                                    let software_only = (!format.hardware_decodable())
+                                        .then(|| format!("H.264 {}", format.profile_name()));
+                                    (format.is_hdr(), format.color_space, software_only)
+                                }),
+                                DepacketizerCodec::H265 => None,
+                            };
+
+                            if let Some((is_hdr, color_space, software_only)) = format {
+                                stats.is_hdr = is_hdr;
+                                stats.color_space = match color_space {
+                                    ColorSpace::BT2020 => "BT.2020".to_string(),
+                                    ColorSpace::BT709 => "BT.709".to_string(),
+                                    ColorSpace::BT601 => "BT.601".to_string(),
+                                };
+
+                                // Hardware decoders only handle 8/10-bit 4:2:0 AV1 Main and 8-bit 4:2:0 H.264
+                                if let Some(profile) = software_only.filter(|_| video_decoder.is_hw_accelerated()) {
+                                    if cfg!(windows) {
+                                        warn!("{} profile is not supported by the D3D11 decoder", profile);
+                                    } else {
+                                        warn!("{} profile needs software decoding, switching decoder", profile);
+                                        match UnifiedVideoDecoder::new_async(codec, VideoDecoderBackend::Software, shared_frame.clone()) {
+                                            Ok((decoder, stats_rx)) => {
+                                                video_decoder = decoder;
+                                                decode_stats_rx = stats_rx;
+                                                decoder_watchdog.reset();
+                                                log_event(SessionEvent::DecoderFallback {
+                                                    from: format!("{:?}", decoder_chain[decoder_index]),
+                                                    to: "Software".to_string(),
+                                                    reason: "profile not hardware decodable",
+                                                });
+                                                // No hardware fallbacks for this stream
+                                                decoder_index = decoder_chain.len() - 1;
+                                            }
+                                            Err(e) => error!("Failed to create software {:?} decoder: {}", codec, e),
                                         }
                                     }
                                 }
                             }
+
+                            let wants_keyframe = recorder.push_video(&frame_data, rtp_timestamp);
+                            let timestamp_us = video_clock.timestamp_us(rtp_timestamp);
+                            if let Err(e) = video_decoder.decode_async(&frame_data, packet_receive_time, timestamp_us) {
+                                warn!("{:?} decode async failed: {}", codec, e);
+                            }
+                            decoder_watchdog.frame_submitted();
+                            // Replay buffer needs a fresh cut point, or the recording lost frames
+                            if wants_keyframe {
+                                log_event(SessionEvent::KeyframeRequest { reason: "recorder cut point" });
+                                request_keyframe().await;
+                            }
                         }
                     }
-                    WebRtcEvent::AudioFrame(rtp_data) => {
+                    WebRtcEvent::AudioFrame { payload: rtp_data, rtp_timestamp, lost_packets } => {
                         // Async decode - non-blocking, samples go directly to audio player
                         static AUDIO_PACKET_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
                         let count = AUDIO_PACKET_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -834,7 +978,13 @@ pub async fn run_streaming(
                         } else if count % 500 == 0 {
                             debug!("Audio packets received: {}", count);
                         }
-                        audio_decoder.decode_async(&rtp_data);
+                        recorder.push_audio(&rtp_data, rtp_timestamp);
+                        // Conceal the gap before decoding the packet that follows it
+                        if lost_packets > 0 {
+                            audio_packets_lost += lost_packets as u64;
+                            audio_decoder.conceal(lost_packets);
+                        }
+                        audio_decoder.decode_async(&rtp_data, rtp_timestamp);
                     }
                     WebRtcEvent::DataChannelOpen(label) => {
                         info!("Data channel opened: {}", label);
@@ -959,6 +1109,7 @@ pub async fn run_streaming(
                             "SSRC change detected after {}ms stall. Initiating auto-reconnect...",
                             stall_duration_ms
                         );
+                        log_event(SessionEvent::SsrcChange { stall_ms: stall_duration_ms });
 
                         // Stop input managers before returning
                         controller_manager.stop();
@@ -978,16 +1129,19 @@ pub async fn run_streaming(
             Some(decode_stat) = decode_stats_rx.recv() => {
                 if decode_stat.frame_produced {
                     frames_decoded += 1;
+                    decoder_watchdog.frame_decoded();
 
                     // Track decode latency
                     stats.decode_time_ms = decode_stat.decode_time_ms;
-                    pipeline_latency_sum += decode_stat.decode_time_ms as f64;
+                    stats.decode_samples_ms.push(decode_stat.decode_time_ms);
+                    stats.delivery_samples_ms.push(decode_stat.delivery_time_ms);
+                    pipeline_latency_sum += decode_stat.delivery_time_ms as f64;
                     pipeline_latency_count += 1;
                     stats.latency_ms = (pipeline_latency_sum / pipeline_latency_count as f64) as f32;
 
                     // Log first decoded frame
                     if frames_decoded == 1 {
-                        info!("First frame decoded (async) in {:.1}ms", decode_stat.decode_time_ms);
+                        info!("First frame decoded (async) in {:.1}ms", decode_stat.delivery_time_ms);
                     }
                 }
 
@@ -996,11 +1150,44 @@ pub async fn run_streaming(
                     // Reset depacketizer state to clear any corrupted fragment state
                     // This is critical for recovering from packet loss/corruption
                     rtp_depacketizer.reset_state();
+                    log_event(SessionEvent::KeyframeRequest { reason: "decoder errors" });
                     request_keyframe().await;
                 }
             }
             // Update stats periodically (interval persists across loop iterations)
             _ = stats_interval.tick() => {
+                // Decoder keeps failing despite keyframe requests: move down the fallback chain
+                if decoder_watchdog.is_stalled() {
+                    decoder_watchdog.reset();
+                    let mut switched = false;
+                    while !switched && decoder_index + 1 < decoder_chain.len() {
+                        let stalled = decoder_chain[decoder_index];
+                        decoder_index += 1;
+                        let backend = decoder_chain[decoder_index];
+                        warn!("Video decoder stalled, falling back to {:?}", backend);
+                        log_event(SessionEvent::DecoderFallback {
+                            from: format!("{:?}", stalled),
+                            to: format!("{:?}", backend),
+                            reason: "stalled",
+                        });
+                        match UnifiedVideoDecoder::new_async(codec, backend, shared_frame.clone()) {
+                            Ok((decoder, stats_rx)) => {
+                                video_decoder = decoder;
+                                decode_stats_rx = stats_rx;
+                                switched = true;
+                            }
+                            Err(e) => warn!("Failed to create {:?} video decoder: {}", backend, e),
+                        }
+                    }
+                    if switched {
+                        rtp_depacketizer.reset_state();
+                        log_event(SessionEvent::KeyframeRequest { reason: "decoder fallback" });
+                        request_keyframe().await;
+                    } else {
+                        warn!("Video decoder stalled and no fallback decoders are left");
+                    }
+                }
+
                 let now = std::time::Instant::now();
                 let elapsed = now.duration_since(last_stats_time).as_secs_f64();
 
@@ -1014,6 +1201,11 @@ pub async fn run_streaming(
                 stats.frames_received = frames_received;
                 stats.frames_decoded = frames_decoded;
                 stats.frames_dropped = frames_dropped;
+                stats.video_packets_lost = video_packets_lost;
+                stats.audio_packets_lost = audio_packets_lost;
+                stats.audio_frames_concealed = audio_decoder.concealed_frames();
+                stats.audio_buffer_ms = f32::from_bits(audio_buffer_level.load(std::sync::atomic::Ordering::Relaxed));
+                stats.jitter_ms = video_jitter.jitter_ms();
 
                 // Calculate average input latency (microseconds to milliseconds)
                 if input_latency_count > 0 {
@@ -1042,7 +1234,7 @@ pub async fn run_streaming(
 
                 // Estimate end-to-end latency:
                 // E2E = network_rtt/2 (input to server) + server_processing (~16ms at 60fps)
-                //     + network_rtt/2 (video back) + decode_time + render_time
+                //     + network_rtt/2 (video back) + frame_delivery + render_time
                 // If RTT is 0 (ice-lite), estimate based on typical values
                 let (estimated_network_oneway, rtt_source) = if stats.rtt_ms > 0.0 {
                     (stats.rtt_ms / 2.0, "measured")
@@ -1056,14 +1248,14 @@ pub async fn run_streaming(
                 stats.estimated_e2e_ms = estimated_network_oneway * 2.0 // network round trip
                     + server_frame_time // server processing (1 frame)
                     + server_encode_time // server encode
-                    + stats.decode_time_ms
+                    + stats.frame_delivery_ms
                     + stats.render_time_ms;
 
                 // Log latency breakdown once
                 static LOGGED_LATENCY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
-                if !LOGGED_LATENCY.swap(true, std::sync::atomic::Ordering::Relaxed) && stats.decode_time_ms > 0.0 {
-                    info!("Latency breakdown ({}): network={:.0}ms x2, server_frame={:.0}ms, encode=8ms, decode={:.1}ms, render={:.1}ms = ~{:.0}ms E2E",
-                        rtt_source, estimated_network_oneway, server_frame_time, stats.decode_time_ms, stats.render_time_ms, stats.estimated_e2e_ms);
+                if !LOGGED_LATENCY.swap(true, std::sync::atomic::Ordering::Relaxed) && stats.frame_delivery_ms > 0.0 {
+                    info!("Latency breakdown ({}): network={:.0}ms x2, server_frame={:.0}ms, encode=8ms, delivery={:.1}ms, render={:.1}ms = ~{:.0}ms E2E",
+                        rtt_source, estimated_network_oneway, server_frame_time, stats.frame_delivery_ms, stats.render_time_ms, stats.estimated_e2e_ms);
                     info!("Note: If actual latency is higher, check server distance or try a closer region");
                 }
 
@@ -1079,8 +1271,14 @@ pub async fn run_streaming(
                 bytes_received = 0;
                 last_stats_time = now;
 
-                // Send stats update
+                if let Some(ref log) = session_log {
+                    log.record_stats(&stats);
+                }
+
+                // Send stats update (per-frame samples go out once)
                 let _ = stats_tx.try_send(stats.clone());
+                stats.decode_samples_ms.clear();
+                stats.delivery_samples_ms.clear();
             }
         }
     }
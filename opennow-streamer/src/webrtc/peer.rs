@@ -32,15 +32,19 @@ const MIME_TYPE_AV1: &str = "video/AV1";
 
 use super::InputEncoder;
 use super::sdp::is_ice_lite;
+use crate::media::RtpSequenceTracker;
 
 /// Events from WebRTC connection
 #[derive(Debug)]
 pub enum WebRtcEvent {
     Connected,
     Disconnected,
-    /// Video frame with RTP timestamp (90kHz clock) and marker bit
-    VideoFrame { payload: Vec<u8>, rtp_timestamp: u32, marker: bool },
-    AudioFrame(Vec<u8>),
+    /// Video frame with RTP timestamp (90kHz clock), marker bit and the
+    /// number of packets lost just before it (from RTP sequence gaps)
+    VideoFrame { payload: Vec<u8>, rtp_timestamp: u32, marker: bool, lost_packets: u16 },
+    /// Opus packet with RTP timestamp (48kHz clock) and the number of
+    /// packets lost just before it (from RTP sequence gaps)
+    AudioFrame { payload: Vec<u8>, rtp_timestamp: u32, lost_packets: u16 },
     DataChannelOpen(String),
     DataChannelMessage(String, Vec<u8>),
     IceCandidate(String, Option<String>, Option<u16>),
@@ -308,6 +312,9 @@ impl WebRtcPeer {
                 let mut packet_count: u64 = 0;
                 let mut last_packet_time = std::time::Instant::now();
                 let mut stall_warning_sent = false;
+                // Loss detection for both tracks: video reports losses for the stats,
+                // audio also drops late packets whose slot was already concealed
+                let mut sequence_tracker = RtpSequenceTracker::new();
                 const STALL_TIMEOUT_MS: u64 = 2000; // 2 seconds without packets = stall
 
                 info!("=== Starting track read loop for {} ({}) ===",
@@ -350,16 +357,35 @@ impl WebRtcPeer {
                             }
 
                             if track_kind == webrtc::rtp_transceiver::rtp_codec::RTPCodecType::Video {
+                                // Late packets are still passed on; the depacketizer deals with them
+                                let lost_packets = sequence_tracker.update(rtp_packet.header.sequence_number).unwrap_or(0);
+                                if lost_packets > 0 {
+                                    debug!("[{}] {} video packet(s) lost before seq {}",
+                                        track_id_clone, lost_packets, rtp_packet.header.sequence_number);
+                                }
                                 if let Err(e) = tx_clone.send(WebRtcEvent::VideoFrame {
                                     payload: rtp_packet.payload.to_vec(),
                                     rtp_timestamp: rtp_packet.header.timestamp,
                                     marker: rtp_packet.header.marker,
+                                    lost_packets,
                                 }).await {
                                     warn!("Failed to send video frame event: {:?}", e);
                                     break;
                                 }
                             } else {
-                                if let Err(e) = tx_clone.send(WebRtcEvent::AudioFrame(rtp_packet.payload.to_vec())).await {
+                                // Late or duplicate packets were already concealed
+                                let Some(lost_packets) = sequence_tracker.update(rtp_packet.header.sequence_number) else {
+                                    continue;
+                                };
+                                if lost_packets > 0 {
+                                    debug!("[{}] {} audio packet(s) lost before seq {}",
+                                        track_id_clone, lost_packets, rtp_packet.header.sequence_number);
+                                }
+                                if let Err(e) = tx_clone.send(WebRtcEvent::AudioFrame {
+                                    payload: rtp_packet.payload.to_vec(),
+                                    rtp_timestamp: rtp_packet.header.timestamp,
+                                    lost_packets,
+                                }).await {
                                     warn!("Failed to send audio frame event: {:?}", e);
                                     break;
                                 }
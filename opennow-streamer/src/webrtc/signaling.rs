@@ -120,14 +120,12 @@ impl GfnSignaling {
             .build()
             .context("Failed to build TLS connector")?;
 
-        // Connect TCP first
+        // Connect TCP first (through the configured proxy, if any)
         let host = self.server_ip.split(':').next().unwrap_or(&self.server_ip);
         let port = 443;
-        let addr = format!("{}:{}", host, port);
 
-        info!("Connecting TCP to: {}", addr);
-        let tcp_stream = tokio::net::TcpStream::connect(&addr).await
-            .context("TCP connection failed")?;
+        info!("Connecting TCP to: {}:{}", host, port);
+        let tcp_stream = crate::api::http::connect_tunnel(host, port).await?;
 
         info!("TCP connected, starting TLS handshake...");
         let tls_stream = tokio_native_tls::TlsConnector::from(tls_connector)